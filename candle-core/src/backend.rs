@@ -1,6 +1,6 @@
 //! Traits to Define Backend Behavior
 //!
-use crate::op::{BinaryOpT, CmpOp, ReduceOp, UnaryOpT};
+use crate::op::{BinaryOpT, BitwiseOp, CmpOp, ReduceOp, Resample, ScanOp, UnaryOpT};
 use crate::{CpuStorage, DType, Layout, Result, Shape};
 
 pub trait BackendStorage: Sized {
@@ -23,8 +23,14 @@ pub trait BackendStorage: Sized {
 
     fn reduce_op(&self, _: ReduceOp, _: &Layout, _: &[usize]) -> Result<Self>;
 
+    fn scan_op(&self, _: ScanOp, _: &Layout, _: usize, _: bool) -> Result<Self>;
+
+    fn resample(&self, _: &Layout, _: &Resample) -> Result<Self>;
+
     fn cmp(&self, _: CmpOp, _: &Self, _: &Layout, _: &Layout) -> Result<Self>;
 
+    fn bitwise(&self, _: BitwiseOp, _: &Self, _: &Layout, _: &Layout) -> Result<Self>;
+
     fn to_dtype(&self, _: &Layout, _: DType) -> Result<Self>;
 
     fn unary_impl<B: UnaryOpT>(&self, _: &Layout) -> Result<Self>;
@@ -65,6 +71,22 @@ pub trait BackendStorage: Sized {
         _params: &crate::conv::ParamsConvTranspose2D,
     ) -> Result<Self>;
 
+    fn conv3d(
+        &self,
+        _l: &Layout,
+        _kernel: &Self,
+        _kernel_l: &Layout,
+        _params: &crate::conv::ParamsConv3D,
+    ) -> Result<Self>;
+
+    fn conv_transpose3d(
+        &self,
+        _l: &Layout,
+        _kernel: &Self,
+        _kernel_l: &Layout,
+        _params: &crate::conv::ParamsConvTranspose3D,
+    ) -> Result<Self>;
+
     fn avg_pool2d(&self, _: &Layout, _: (usize, usize), _: (usize, usize)) -> Result<Self>;
     fn max_pool2d(&self, _: &Layout, _: (usize, usize), _: (usize, usize)) -> Result<Self>;
     fn upsample_nearest1d(&self, _: &Layout, _: usize) -> Result<Self>;
@@ -80,6 +102,27 @@ pub trait BackendStorage: Sized {
         _: &Layout,
         _: usize,
     ) -> Result<Self>;
+    fn scatter(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: usize,
+    ) -> Result<Self>;
+    #[allow(clippy::too_many_arguments)]
+    fn scatter_reduce(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: usize,
+        _: crate::op::ScatterReduceOp,
+        _: bool,
+    ) -> Result<Self>;
     fn index_select(&self, _: &Self, _: &Layout, _: &Layout, _: usize) -> Result<Self>;
     fn index_add(
         &self,
@@ -1,5 +1,5 @@
 //! Methods for backpropagation of gradients.
-use crate::op::{BinaryOp, Op, ReduceOp, UnaryOp};
+use crate::op::{BinaryOp, Op, ReduceOp, ScanOp, ScatterReduceOp, UnaryOp};
 use crate::{Error, Result, Tensor, TensorId};
 use std::collections::HashMap;
 
@@ -16,6 +16,166 @@ fn broadcast_back(arg: &Tensor, node: &Tensor, reduced_dims: &[usize]) -> Result
     }
 }
 
+// Returns the gradient of `arg` where `node` is the result of the scan.
+fn scan_backward(
+    arg: &Tensor,
+    node: &Tensor,
+    grad: &Tensor,
+    dim: usize,
+    op: ScanOp,
+    reverse: bool,
+) -> Result<Tensor> {
+    match op {
+        // The gradient of a scan flows to all the values that were combined into it, hence the
+        // scans going in the opposite direction.
+        ScanOp::Sum => grad.scan(dim, ScanOp::Sum, !reverse),
+        ScanOp::Prod => {
+            // Before the first zero of a lane, the gradient is sum_{j >= i} grad_j * node_j / arg_i.
+            // At the first zero it uses the products where this zero is skipped and after the
+            // first zero it is 0.
+            let zero_count =
+                arg.eq(0f64)?
+                    .to_dtype(grad.dtype())?
+                    .scan(dim, ScanOp::Sum, reverse)?;
+            let before_zero = zero_count.eq(0f64)?;
+            let first_zero = (arg.eq(0f64)? * zero_count.eq(1f64)?)?;
+            let ones = arg.ones_like()?;
+            let before_grad = (grad * node)?
+                .scan(dim, ScanOp::Sum, !reverse)?
+                .div(&before_zero.where_cond(arg, &ones)?)?;
+            let skip_zero = first_zero
+                .where_cond(&ones, arg)?
+                .scan(dim, ScanOp::Prod, reverse)?;
+            let first_zero_grad = (grad * skip_zero)?.scan(dim, ScanOp::Sum, !reverse)?;
+            let zeros = arg.zeros_like()?;
+            before_zero.where_cond(
+                &before_grad,
+                &first_zero.where_cond(&first_zero_grad, &zeros)?,
+            )
+        }
+        ScanOp::Min | ScanOp::Max => {
+            let arg_op = if op == ScanOp::Min {
+                ScanOp::ArgMin
+            } else {
+                ScanOp::ArgMax
+            };
+            let indexes = arg.scan(dim, arg_op, reverse)?;
+            arg.zeros_like()?
+                .scatter_add(&indexes, &grad.contiguous()?, dim)
+        }
+        ScanOp::LogSumExp => {
+            // The gradient is sum_{j >= i} grad_j * exp(arg_i - node_j), the positive and negative
+            // parts of grad are accumulated separately in log space to avoid overflows.
+            let acc = |grad: Tensor| {
+                (grad.log()? - node)?
+                    .scan(dim, ScanOp::LogSumExp, !reverse)?
+                    .add(arg)?
+                    .exp()
+            };
+            acc(grad.relu()?)? - acc(grad.neg()?.relu()?)?
+        }
+        ScanOp::ArgMin | ScanOp::ArgMax => Err(Error::BackwardNotSupported { op: op.name() }),
+    }
+}
+
+// Returns the gradients for `init` and `src` where `node` is the result of the scatter-reduce.
+#[allow(clippy::too_many_arguments)]
+fn scatter_reduce_backward(
+    init: &Tensor,
+    indexes: &Tensor,
+    src: &Tensor,
+    node: &Tensor,
+    grad: &Tensor,
+    dim: usize,
+    reduce: ScatterReduceOp,
+    include_self: bool,
+) -> Result<(Tensor, Tensor)> {
+    // 1 for the positions of init that received no value from src.
+    let untouched = init
+        .ones_like()?
+        .scatter(indexes, &src.zeros_like()?, dim)?;
+    match reduce {
+        ScatterReduceOp::Sum | ScatterReduceOp::Mean => {
+            let grad = if reduce == ScatterReduceOp::Mean {
+                let count = init.ones_like()?.scatter_reduce(
+                    indexes,
+                    &src.ones_like()?,
+                    dim,
+                    ScatterReduceOp::Sum,
+                    include_self,
+                )?;
+                (grad / count)?
+            } else {
+                grad.clone()
+            };
+            let init_grad = if include_self {
+                grad.clone()
+            } else {
+                (&grad * untouched)?
+            };
+            Ok((init_grad, grad.gather(indexes, dim)?))
+        }
+        ScatterReduceOp::Amax | ScatterReduceOp::Amin => {
+            // The gradient is evenly distributed between the values that are equal to the
+            // result.
+            let init_eq = if include_self {
+                init.eq(node)?.to_dtype(grad.dtype())?
+            } else {
+                untouched
+            };
+            let src_eq = src
+                .eq(&node.gather(indexes, dim)?)?
+                .to_dtype(grad.dtype())?;
+            let count = init_eq.scatter_add(indexes, &src_eq, dim)?;
+            let grad = (grad / count)?;
+            let src_grad = (grad.gather(indexes, dim)? * src_eq)?;
+            Ok(((grad * init_eq)?, src_grad))
+        }
+        ScatterReduceOp::Prod => {
+            // The gradient for a value is the product of the other values of its reduction,
+            // this is computed without dividing by zero by tracking the zeros separately.
+            let init_zero = init.eq(0f64)?;
+            let src_zero = src.eq(0f64)?;
+            let zero_count = if include_self {
+                init_zero.to_dtype(grad.dtype())?
+            } else {
+                init.zeros_like()?
+            };
+            let zero_count =
+                zero_count.scatter_add(indexes, &src_zero.to_dtype(grad.dtype())?, dim)?;
+            let prod_nonzero = init_zero
+                .where_cond(&init.ones_like()?, init)?
+                .scatter_reduce(
+                    indexes,
+                    &src_zero.where_cond(&src.ones_like()?, src)?,
+                    dim,
+                    ScatterReduceOp::Prod,
+                    include_self,
+                )?;
+            let prod_others =
+                |xs: &Tensor, is_zero: &Tensor, zero_count: &Tensor, prod: &Tensor| {
+                    let zeros = xs.zeros_like()?;
+                    let if_zero = zero_count.eq(1f64)?.where_cond(prod, &zeros)?;
+                    let if_nonzero = zero_count.eq(0f64)?.where_cond(&(prod / xs)?, &zeros)?;
+                    is_zero.where_cond(&if_zero, &if_nonzero)
+                };
+            let init_grad = if include_self {
+                (grad * prod_others(init, &init_zero, &zero_count, &prod_nonzero)?)?
+            } else {
+                (grad * untouched)?
+            };
+            let src_grad = prod_others(
+                src,
+                &src_zero,
+                &zero_count.gather(indexes, dim)?,
+                &prod_nonzero.gather(indexes, dim)?,
+            )?;
+            let src_grad = (grad.gather(indexes, dim)? * src_grad)?;
+            Ok((init_grad, src_grad))
+        }
+    }
+}
+
 thread_local! {
     static CANDLE_GRAD_DO_NOT_DETACH: bool = {
         match std::env::var("CANDLE_GRAD_DO_NOT_DETACH") {
@@ -48,12 +208,14 @@ impl Tensor {
                 // Do not call recursively on the "leaf" nodes.
                 track_grad = true;
                 nodes
-            } else if node.dtype().is_int() {
+            } else if !node.dtype().is_float() {
                 nodes
             } else if let Some(op) = node.op() {
                 match op {
                     Op::IndexAdd(t1, t2, t3, _)
                     | Op::ScatterAdd(t1, t2, t3, _)
+                    | Op::Scatter(t1, t2, t3, _)
+                    | Op::ScatterReduce(t1, t2, t3, ..)
                     | Op::CustomOp3(t1, t2, t3, _)
                     | Op::WhereCond(t1, t2, t3) => {
                         let (tg, nodes) = walk(t1, nodes, already_seen);
@@ -84,6 +246,16 @@ impl Tensor {
                         kernel: rhs,
                         ..
                     }
+                    | Op::Conv3D {
+                        arg: lhs,
+                        kernel: rhs,
+                        ..
+                    }
+                    | Op::ConvTranspose3D {
+                        arg: lhs,
+                        kernel: rhs,
+                        ..
+                    }
                     | Op::CustomOp2(lhs, rhs, _)
                     | Op::Binary(lhs, rhs, _)
                     | Op::Gather(lhs, rhs, _)
@@ -123,10 +295,13 @@ impl Tensor {
                     | Op::Broadcast(node)
                     | Op::Cmp(node, _)
                     | Op::Reduce(node, ReduceOp::Min | ReduceOp::Sum | ReduceOp::Max, _)
+                    | Op::Scan(node, _, _, _)
+                    | Op::Resample(node, _)
                     | Op::ToDevice(node)
                     | Op::Transpose(node, _, _)
                     | Op::Permute(node, _)
                     | Op::Narrow(node, _, _, _)
+                    | Op::Unfold(node, _, _, _)
                     | Op::Unary(node, _)
                     | Op::Elu(node, _)
                     | Op::Powf(node, _)
@@ -208,6 +383,15 @@ impl Tensor {
                         let rhs_sum_grad = grads.or_insert(rhs)?;
                         *rhs_sum_grad = rhs_sum_grad.sub(&rhs_grad)?;
                     }
+                    Op::Binary(lhs, rhs, BinaryOp::Remainder)
+                    | Op::Binary(lhs, rhs, BinaryOp::Fmod) => {
+                        let lhs_sum_grad = grads.or_insert(lhs)?;
+                        *lhs_sum_grad = lhs_sum_grad.add(&grad)?;
+                        // (lhs - node) / rhs is the rounded quotient.
+                        let rhs_grad = grad.mul(&lhs.sub(node)?.div(rhs)?)?;
+                        let rhs_sum_grad = grads.or_insert(rhs)?;
+                        *rhs_sum_grad = rhs_sum_grad.sub(&rhs_grad)?;
+                    }
                     Op::Binary(lhs, rhs, BinaryOp::Minimum)
                     | Op::Binary(lhs, rhs, BinaryOp::Maximum) => {
                         let mask_lhs = node.eq(lhs)?.to_dtype(grad.dtype())?;
@@ -338,16 +522,105 @@ impl Tensor {
                         };
                         *sum_grad = sum_grad.add(&grad_kernel)?;
                     }
+                    Op::Conv3D {
+                        arg,
+                        kernel,
+                        padding,
+                        stride,
+                        dilation,
+                    } => {
+                        // The output size for conv_transpose3d along each spatial dim is:
+                        // (i_d - 1) * stride - 2 * padding + dilation * (k_d - 1) + out_padding + 1
+                        // The out_padding can differ between the dims, the values do not depend
+                        // on it so the largest one is used and the result is narrowed to the
+                        // shape of arg.
+                        let mut out_padding = 0;
+                        for dim in 2..5 {
+                            let out_size = (grad.dim(dim)? - 1) * stride
+                                + dilation * (kernel.dim(dim)? - 1)
+                                + 1
+                                - 2 * padding;
+                            out_padding = usize::max(out_padding, arg.dim(dim)? - out_size);
+                        }
+                        let grad_arg = grad
+                            .conv_transpose3d(kernel, *padding, out_padding, *stride, *dilation)?
+                            .narrow(2, 0, arg.dim(2)?)?
+                            .narrow(3, 0, arg.dim(3)?)?
+                            .narrow(4, 0, arg.dim(4)?)?;
+                        let sum_grad = grads.or_insert(arg)?;
+                        *sum_grad = sum_grad.add(&grad_arg)?;
+
+                        let grad_kernel = arg
+                            .transpose(0, 1)?
+                            .conv3d(&grad.transpose(0, 1)?, *padding, *dilation, *stride, 1)?
+                            .transpose(0, 1)?;
+                        let sum_grad = grads.or_insert(kernel)?;
+                        let (_, _, k0, k1, k2) = kernel.dims5()?;
+                        let (_, _, g_k0, g_k1, g_k2) = grad_kernel.dims5()?;
+                        let grad_kernel = if g_k0 != k0 || g_k1 != k1 || g_k2 != k2 {
+                            grad_kernel
+                                .narrow(2, 0, k0)?
+                                .narrow(3, 0, k1)?
+                                .narrow(4, 0, k2)?
+                        } else {
+                            grad_kernel
+                        };
+                        *sum_grad = sum_grad.add(&grad_kernel)?;
+                    }
+                    Op::ConvTranspose3D {
+                        arg,
+                        kernel,
+                        padding,
+                        stride,
+                        dilation,
+                        output_padding: _output_padding,
+                    } => {
+                        let grad_arg = grad.conv3d(kernel, *padding, *stride, *dilation, 1)?;
+                        let sum_grad = grads.or_insert(arg)?;
+                        *sum_grad = sum_grad.add(&grad_arg)?;
+
+                        let grad_kernel = grad
+                            .transpose(0, 1)?
+                            .conv3d(&arg.transpose(0, 1)?, *padding, *dilation, *stride, 1)?
+                            .transpose(0, 1)?;
+                        let sum_grad = grads.or_insert(kernel)?;
+                        let (_, _, k0, k1, k2) = kernel.dims5()?;
+                        let (_, _, g_k0, g_k1, g_k2) = grad_kernel.dims5()?;
+                        let grad_kernel = if g_k0 != k0 || g_k1 != k1 || g_k2 != k2 {
+                            grad_kernel
+                                .narrow(2, 0, k0)?
+                                .narrow(3, 0, k1)?
+                                .narrow(4, 0, k2)?
+                        } else {
+                            grad_kernel
+                        };
+                        *sum_grad = sum_grad.add(&grad_kernel)?;
+                    }
                     Op::AvgPool2D {
                         arg,
                         kernel_size,
                         stride,
                     } => {
                         if kernel_size != stride {
-                            crate::bail!("backward not supported for avgpool2d if ksize {kernel_size:?} != stride {stride:?}")
+                            let grad_arg = crate::pool::avg_pool2d_backward(
+                                arg,
+                                &grad,
+                                *kernel_size,
+                                *stride,
+                            )?;
+                            let sum_grad = grads.or_insert(arg)?;
+                            *sum_grad = sum_grad.add(&grad_arg)?;
+                            continue;
                         }
                         let (_n, _c, h, w) = arg.dims4()?;
-                        let grad_arg = grad.upsample_nearest2d(h, w)?;
+                        let (_n, _c, h_out, w_out) = grad.dims4()?;
+                        // The trailing elements that are not covered by any window get no
+                        // gradient.
+                        let (h_in, w_in) = (h_out * kernel_size.0, w_out * kernel_size.1);
+                        let grad_arg = grad
+                            .upsample_nearest2d(h_in, w_in)?
+                            .pad_with_zeros(2, 0, h - h_in)?
+                            .pad_with_zeros(3, 0, w - w_in)?;
                         let grad_arg =
                             (grad_arg * (1f64 / (kernel_size.0 * kernel_size.1) as f64))?;
                         let sum_grad = grads.or_insert(arg)?;
@@ -359,17 +632,40 @@ impl Tensor {
                         stride,
                     } => {
                         if kernel_size != stride {
-                            crate::bail!("backward not supported for maxpool2d if ksize {kernel_size:?} != stride {stride:?}")
+                            // With overlapping windows, the gradient of each window goes to the
+                            // position of its maximum.
+                            let config = crate::pool::Pool2dConfig::default();
+                            let (_max, indexes) =
+                                arg.max_pool2d_with_indices(*kernel_size, *stride, &config)?;
+                            let (n, c, h, w) = arg.dims4()?;
+                            let grad_arg =
+                                Tensor::zeros((n, c, h * w), grad.dtype(), grad.device())?
+                                    .scatter_add(
+                                        &indexes.flatten_from(2)?,
+                                        &grad.flatten_from(2)?,
+                                        2,
+                                    )?
+                                    .reshape((n, c, h, w))?;
+                            let sum_grad = grads.or_insert(arg)?;
+                            *sum_grad = sum_grad.add(&grad_arg)?;
+                            continue;
                         }
                         let (_n, _c, h, w) = arg.dims4()?;
+                        let (_n, _c, h_out, w_out) = node.dims4()?;
+                        let (h_in, w_in) = (h_out * kernel_size.0, w_out * kernel_size.1);
                         // For computing the max-pool gradient, we compute a mask where a 1 means
                         // that the element is the maximum, then we apply this mask to the
                         // upsampled gradient (taking into account that multiple max may exist so
-                        // we scale the gradient for this case).
-                        let node_upsampled = node.upsample_nearest2d(h, w)?;
-                        let mask = arg.eq(&node_upsampled)?.to_dtype(arg.dtype())?;
-                        let avg = mask.avg_pool2d_with_stride(*kernel_size, *stride)?;
-                        let grad_arg = ((grad * avg)?.upsample_nearest2d(h, w)? * mask)?;
+                        // we scale the gradient for this case). The trailing elements that are
+                        // not covered by any window get no gradient.
+                        let node_upsampled = node.upsample_nearest2d(h_in, w_in)?;
+                        let arg_in = arg.narrow(2, 0, h_in)?.narrow(3, 0, w_in)?;
+                        let mask = arg_in.eq(&node_upsampled)?.to_dtype(arg.dtype())?;
+                        let count = (mask.avg_pool2d_with_stride(*kernel_size, *stride)?
+                            * (kernel_size.0 * kernel_size.1) as f64)?;
+                        let grad_arg = ((grad / count)?.upsample_nearest2d(h_in, w_in)? * mask)?
+                            .pad_with_zeros(2, 0, h - h_in)?
+                            .pad_with_zeros(3, 0, w - w_in)?;
                         let sum_grad = grads.or_insert(arg)?;
                         *sum_grad = sum_grad.add(&grad_arg)?;
                     }
@@ -427,6 +723,34 @@ impl Tensor {
                         let src_sum_grad = grads.or_insert(src)?;
                         *src_sum_grad = src_sum_grad.add(&src_grad)?;
                     }
+                    Op::Scatter(init, indexes, src, dim) => {
+                        // The overwritten values of init get no gradient.
+                        let mask = init
+                            .ones_like()?
+                            .scatter(indexes, &src.zeros_like()?, *dim)?;
+                        let init_sum_grad = grads.or_insert(init)?;
+                        *init_sum_grad = init_sum_grad.add(&(&grad * mask)?)?;
+
+                        let src_grad = grad.gather(indexes, *dim)?;
+                        let src_sum_grad = grads.or_insert(src)?;
+                        *src_sum_grad = src_sum_grad.add(&src_grad)?;
+                    }
+                    Op::ScatterReduce(init, indexes, src, dim, reduce, include_self) => {
+                        let (init_grad, src_grad) = scatter_reduce_backward(
+                            init,
+                            indexes,
+                            src,
+                            node,
+                            &grad,
+                            *dim,
+                            *reduce,
+                            *include_self,
+                        )?;
+                        let init_sum_grad = grads.or_insert(init)?;
+                        *init_sum_grad = init_sum_grad.add(&init_grad)?;
+                        let src_sum_grad = grads.or_insert(src)?;
+                        *src_sum_grad = src_sum_grad.add(&src_grad)?;
+                    }
                     Op::IndexAdd(init, indexes, src, dim) => {
                         let init_sum_grad = grads.or_insert(init)?;
                         *init_sum_grad = init_sum_grad.add(&grad)?;
@@ -503,6 +827,16 @@ impl Tensor {
                         let sum_grad = grads.or_insert(arg)?;
                         *sum_grad = sum_grad.add(&grad.broadcast_as(sum_grad.dims())?)?;
                     }
+                    Op::Scan(arg, op, dim, reverse) => {
+                        let arg_grad = scan_backward(arg, node, &grad, *dim, *op, *reverse)?;
+                        let sum_grad = grads.or_insert(arg)?;
+                        *sum_grad = sum_grad.add(&arg_grad)?
+                    }
+                    Op::Resample(arg, resample) => {
+                        let arg_grad = grad.resample(&resample.transposed())?;
+                        let sum_grad = grads.or_insert(arg)?;
+                        *sum_grad = sum_grad.add(&arg_grad)?
+                    }
                     Op::ToDType(arg) => {
                         let sum_grad = grads.or_insert(arg)?;
                         *sum_grad = sum_grad.add(&grad.to_dtype(arg.dtype())?)?
@@ -552,6 +886,24 @@ impl Tensor {
                         let grad = (grad / arg.sqr()?)?;
                         *sum_grad = sum_grad.sub(&grad)?
                     }
+                    &Op::Unfold(ref arg, dim, size, step) => {
+                        // Move the window elements next to the windows so that each gradient
+                        // value can be added back to its input position in a single index_add.
+                        let rank = arg.rank();
+                        let windows = grad.dim(dim)?;
+                        let mut perm: Vec<_> = (0..rank).collect();
+                        perm.insert(dim + 1, rank);
+                        let mut dims = arg.dims().to_vec();
+                        dims[dim] = windows * size;
+                        let grad = grad.permute(perm)?.reshape(dims)?;
+                        let indexes: Vec<u32> = (0..windows)
+                            .flat_map(|i| (0..size).map(move |k| (i * step + k) as u32))
+                            .collect();
+                        let indexes = Tensor::new(indexes, grad.device())?;
+                        let arg_grad = arg.zeros_like()?.index_add(&indexes, &grad, dim)?;
+                        let sum_grad = grads.or_insert(arg)?;
+                        *sum_grad = sum_grad.add(&arg_grad)?;
+                    }
                     &Op::Narrow(ref arg, dim, start_idx, len) => {
                         let arg_dims = arg.dims();
                         let left_pad = if start_idx == 0 {
@@ -580,6 +932,7 @@ impl Tensor {
                     }
                     Op::Unary(_, UnaryOp::Floor)
                     | Op::Unary(_, UnaryOp::Round)
+                    | Op::Binary(_, _, BinaryOp::FloorDiv)
                     | Op::Reduce(_, ReduceOp::ArgMin, _)
                     | Op::Reduce(_, ReduceOp::ArgMax, _)
                     | Op::Unary(_, UnaryOp::Sign)
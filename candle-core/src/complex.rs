@@ -0,0 +1,249 @@
+//! Operations specific to the complex dtypes.
+//!
+//! `C64` and `C128` tensors store their real and imaginary parts with `f32` and `f64` precision
+//! respectively. Element-wise arithmetic and matmul go through the usual tensor ops, the
+//! conversions between real and complex tensors are only available on the cpu.
+//!
+//! There is no autograd support for complex values: the conversions between real and complex
+//! tensors do not track gradients and the backward passes of the other ops are only defined for
+//! real tensors.
+use crate::backend::BackendStorage;
+use crate::cpu_backend::{binary_map, unary_map};
+use crate::{bail, CpuStorage, CustomOp1, CustomOp2, DType, Layout, Result, Shape, Tensor};
+use num_complex::{Complex32, Complex64};
+
+fn complex_dtype(dtype: DType) -> Result<DType> {
+    match dtype {
+        DType::F32 => Ok(DType::C64),
+        DType::F64 => Ok(DType::C128),
+        dtype => bail!("no complex dtype with {dtype:?} parts, use f32 or f64"),
+    }
+}
+
+struct FromParts;
+
+impl CustomOp2 for FromParts {
+    fn name(&self) -> &'static str {
+        "complex"
+    }
+
+    fn cpu_fwd(
+        &self,
+        s1: &CpuStorage,
+        l1: &Layout,
+        s2: &CpuStorage,
+        l2: &Layout,
+    ) -> Result<(CpuStorage, Shape)> {
+        let storage = match (s1, s2) {
+            (CpuStorage::F32(re), CpuStorage::F32(im)) => {
+                CpuStorage::C64(binary_map(l1, l2, re, im, Complex32::new))
+            }
+            (CpuStorage::F64(re), CpuStorage::F64(im)) => {
+                CpuStorage::C128(binary_map(l1, l2, re, im, Complex64::new))
+            }
+            _ => Err(crate::Error::DTypeMismatchBinaryOp {
+                lhs: s1.dtype(),
+                rhs: s2.dtype(),
+                op: self.name(),
+            }
+            .bt())?,
+        };
+        Ok((storage, l1.shape().clone()))
+    }
+}
+
+/// The element-wise ops that map a complex value to another value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Real,
+    Imag,
+    Abs,
+    Angle,
+    Conj,
+}
+
+impl CustomOp1 for Part {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Real => "real",
+            Self::Imag => "imag",
+            Self::Abs => "abs",
+            Self::Angle => "angle",
+            Self::Conj => "conj",
+        }
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        macro_rules! part {
+            ($vs:expr, $out:ident) => {
+                match self {
+                    Self::Real => CpuStorage::$out(unary_map($vs, layout, |v| v.re)),
+                    Self::Imag => CpuStorage::$out(unary_map($vs, layout, |v| v.im)),
+                    Self::Abs => CpuStorage::$out(unary_map($vs, layout, |v| v.norm())),
+                    Self::Angle => CpuStorage::$out(unary_map($vs, layout, |v| v.arg())),
+                    Self::Conj => bail!("conj is handled by the complex variants"),
+                }
+            };
+        }
+        let storage = match (self, storage) {
+            (Self::Conj, CpuStorage::C64(vs)) => {
+                CpuStorage::C64(unary_map(vs, layout, |v| v.conj()))
+            }
+            (Self::Conj, CpuStorage::C128(vs)) => {
+                CpuStorage::C128(unary_map(vs, layout, |v| v.conj()))
+            }
+            (_, CpuStorage::C64(vs)) => part!(vs, F32),
+            (_, CpuStorage::C128(vs)) => part!(vs, F64),
+            (_, storage) => {
+                Err(crate::Error::UnsupportedDTypeForOp(storage.dtype(), self.name()).bt())?
+            }
+        };
+        Ok((storage, layout.shape().clone()))
+    }
+}
+
+struct ViewAsReal;
+
+impl CustomOp1 for ViewAsReal {
+    fn name(&self) -> &'static str {
+        "view-as-real"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let storage = match storage {
+            CpuStorage::C64(vs) => {
+                let vs = unary_map(vs, layout, |v| [v.re, v.im]);
+                CpuStorage::F32(vs.into_iter().flatten().collect())
+            }
+            CpuStorage::C128(vs) => {
+                let vs = unary_map(vs, layout, |v| [v.re, v.im]);
+                CpuStorage::F64(vs.into_iter().flatten().collect())
+            }
+            storage => Err(crate::Error::UnsupportedDTypeForOp(storage.dtype(), self.name()).bt())?,
+        };
+        let mut dims = layout.dims().to_vec();
+        dims.push(2);
+        Ok((storage, Shape::from(dims)))
+    }
+}
+
+struct ViewAsComplex;
+
+impl CustomOp1 for ViewAsComplex {
+    fn name(&self) -> &'static str {
+        "view-as-complex"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (start, end) = match layout.contiguous_offsets() {
+            Some(offsets) => offsets,
+            None => bail!("{}: input has to be contiguous", self.name()),
+        };
+        let storage = match storage {
+            CpuStorage::F32(vs) => CpuStorage::C64(
+                vs[start..end]
+                    .chunks_exact(2)
+                    .map(|v| Complex32::new(v[0], v[1]))
+                    .collect(),
+            ),
+            CpuStorage::F64(vs) => CpuStorage::C128(
+                vs[start..end]
+                    .chunks_exact(2)
+                    .map(|v| Complex64::new(v[0], v[1]))
+                    .collect(),
+            ),
+            storage => Err(crate::Error::UnsupportedDTypeForOp(storage.dtype(), self.name()).bt())?,
+        };
+        let dims = layout.dims();
+        Ok((storage, Shape::from(&dims[..dims.len() - 1])))
+    }
+}
+
+impl Tensor {
+    /// Creates a complex tensor from its real and imaginary parts. The parts must have the same
+    /// shape, `f32` parts result in a `C64` tensor and `f64` parts in a `C128` tensor.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device, DType};
+    /// let re = Tensor::new(&[1f32, 2.], &Device::Cpu)?;
+    /// let im = Tensor::new(&[3f32, -4.], &Device::Cpu)?;
+    /// let c = Tensor::complex(&re, &im)?;
+    /// assert_eq!(c.dtype(), DType::C64);
+    /// assert_eq!(c.abs()?.to_vec1::<f32>()?, &[10f32.sqrt(), 20f32.sqrt()]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn complex(re: &Self, im: &Self) -> Result<Self> {
+        re.same_shape_binary_op(im, "complex")?;
+        complex_dtype(re.dtype())?;
+        re.apply_op2_no_bwd(im, &FromParts)
+    }
+
+    /// The real part of a complex tensor, real tensors are returned unchanged.
+    pub fn real(&self) -> Result<Self> {
+        if self.dtype().is_complex() {
+            self.apply_op1_no_bwd(&Part::Real)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// The imaginary part of a complex tensor, this is zero for real tensors.
+    pub fn imag(&self) -> Result<Self> {
+        if self.dtype().is_complex() {
+            self.apply_op1_no_bwd(&Part::Imag)
+        } else {
+            self.zeros_like()
+        }
+    }
+
+    /// The argument of the complex values in radians, between `-pi` and `pi`.
+    pub fn angle(&self) -> Result<Self> {
+        if !self.dtype().is_complex() {
+            bail!("angle expects a complex tensor, got {:?}", self.dtype())
+        }
+        self.apply_op1_no_bwd(&Part::Angle)
+    }
+
+    /// The complex conjugate, real tensors are returned unchanged.
+    pub fn conj(&self) -> Result<Self> {
+        if self.dtype().is_complex() {
+            self.apply_op1_no_bwd(&Part::Conj)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// The modulus of the complex values as a real tensor.
+    pub(crate) fn complex_abs(&self) -> Result<Self> {
+        self.apply_op1_no_bwd(&Part::Abs)
+    }
+
+    /// Returns a real tensor with an additional trailing dimension of size 2 holding the real and
+    /// imaginary parts of a complex tensor. Contrary to PyTorch, the values are copied.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let re = Tensor::new(&[1f64, 2.], &Device::Cpu)?;
+    /// let im = Tensor::new(&[3f64, 4.], &Device::Cpu)?;
+    /// let c = Tensor::complex(&re, &im)?;
+    /// assert_eq!(c.view_as_real()?.to_vec2::<f64>()?, &[[1., 3.], [2., 4.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn view_as_real(&self) -> Result<Self> {
+        self.apply_op1_no_bwd(&ViewAsReal)
+    }
+
+    /// The inverse of [`Tensor::view_as_real`], the last dimension of the real tensor must have
+    /// size 2.
+    pub fn view_as_complex(&self) -> Result<Self> {
+        complex_dtype(self.dtype())?;
+        match self.dims().last() {
+            Some(2) => {}
+            _ => bail!(
+                "view_as_complex expects a trailing dimension of size 2, got {:?}",
+                self.shape()
+            ),
+        }
+        self.contiguous()?.apply_op1_no_bwd(&ViewAsComplex)
+    }
+}
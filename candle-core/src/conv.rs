@@ -1,4 +1,4 @@
-//! 1D and 2D Convolutions
+//! 1D, 2D and 3D Convolutions
 //!
 use crate::{op::BackpropOp, op::Op, Error, Result, Tensor};
 
@@ -127,6 +127,90 @@ impl ParamsConvTranspose2D {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsConv3D {
+    pub(crate) b_size: usize,
+    pub(crate) i_d: usize,
+    pub(crate) i_h: usize,
+    pub(crate) i_w: usize,
+    pub(crate) k_d: usize,
+    pub(crate) k_h: usize,
+    pub(crate) k_w: usize,
+    pub(crate) c_out: usize,
+    pub(crate) c_in: usize,
+    pub(crate) padding: usize,
+    pub(crate) stride: usize,
+    pub(crate) dilation: usize,
+}
+
+impl ParamsConv3D {
+    pub(crate) fn out_d(&self) -> usize {
+        (self.i_d + 2 * self.padding - self.dilation * (self.k_d - 1) - 1) / self.stride + 1
+    }
+
+    pub(crate) fn out_h(&self) -> usize {
+        (self.i_h + 2 * self.padding - self.dilation * (self.k_h - 1) - 1) / self.stride + 1
+    }
+
+    pub(crate) fn out_w(&self) -> usize {
+        (self.i_w + 2 * self.padding - self.dilation * (self.k_w - 1) - 1) / self.stride + 1
+    }
+
+    pub(crate) fn out_dims(&self) -> Vec<usize> {
+        vec![
+            self.b_size,
+            self.c_out,
+            self.out_d(),
+            self.out_h(),
+            self.out_w(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsConvTranspose3D {
+    pub(crate) b_size: usize,
+    pub(crate) i_d: usize,
+    pub(crate) i_h: usize,
+    pub(crate) i_w: usize,
+    pub(crate) k_d: usize,
+    pub(crate) k_h: usize,
+    pub(crate) k_w: usize,
+    pub(crate) c_out: usize,
+    pub(crate) c_in: usize,
+    pub(crate) padding: usize,
+    pub(crate) output_padding: usize,
+    pub(crate) stride: usize,
+    pub(crate) dilation: usize,
+}
+
+impl ParamsConvTranspose3D {
+    pub(crate) fn out_d(&self) -> usize {
+        (self.i_d - 1) * self.stride + self.dilation * (self.k_d - 1) + self.output_padding + 1
+            - 2 * self.padding
+    }
+
+    pub(crate) fn out_h(&self) -> usize {
+        (self.i_h - 1) * self.stride + self.dilation * (self.k_h - 1) + self.output_padding + 1
+            - 2 * self.padding
+    }
+
+    pub(crate) fn out_w(&self) -> usize {
+        (self.i_w - 1) * self.stride + self.dilation * (self.k_w - 1) + self.output_padding + 1
+            - 2 * self.padding
+    }
+
+    pub(crate) fn out_dims(&self) -> Vec<usize> {
+        vec![
+            self.b_size,
+            self.c_out,
+            self.out_d(),
+            self.out_h(),
+            self.out_w(),
+        ]
+    }
+}
+
 impl Tensor {
     fn conv1d_single_group(&self, kernel: &Self, params: &ParamsConv1D) -> Result<Self> {
         let storage =
@@ -357,4 +441,257 @@ impl Tensor {
         let out_dims = params.out_dims();
         Ok(crate::tensor::from_storage(storage, out_dims, op, false))
     }
+
+    fn conv3d_single_group(&self, kernel: &Self, params: &ParamsConv3D) -> Result<Self> {
+        let storage =
+            self.storage()
+                .conv3d(self.layout(), &kernel.storage(), kernel.layout(), params)?;
+        let op = BackpropOp::new2(self, kernel, |arg, kernel| Op::Conv3D {
+            arg,
+            kernel,
+            padding: params.padding,
+            stride: params.stride,
+            dilation: params.dilation,
+        });
+        let out_dims = params.out_dims();
+        Ok(crate::tensor::from_storage(storage, out_dims, op, false))
+    }
+
+    /// Applies a 3D convolution over the input tensor.
+    pub fn conv3d(
+        &self,
+        kernel: &Self,
+        padding: usize,
+        stride: usize,
+        dilation: usize,
+        groups: usize,
+    ) -> Result<Self> {
+        let (b_size, c_in, i_d, i_h, i_w) = self.dims5()?;
+        let (c_out, c_in_k, k_d, k_h, k_w) = kernel.dims5()?;
+        if c_in != c_in_k * groups {
+            crate::bail!(
+                "in_channel mismatch between input ({c_in}, groups {groups}) and kernel ({c_in_k})"
+            )
+        }
+        let params = ParamsConv3D {
+            b_size,
+            i_d,
+            i_h,
+            i_w,
+            k_d,
+            k_h,
+            k_w,
+            c_out: c_out / groups,
+            c_in: c_in / groups,
+            padding,
+            stride,
+            dilation,
+        };
+        if groups == 1 {
+            self.conv3d_single_group(kernel, &params)
+        } else {
+            let blocks = self.chunk(groups, 1)?;
+            let kernel = kernel.chunk(groups, 0)?;
+            let blocks = blocks
+                .iter()
+                .zip(&kernel)
+                .map(|(block, kernel)| block.conv3d_single_group(kernel, &params))
+                .collect::<Result<Vec<_>>>()?;
+            Tensor::cat(&blocks, 1)
+        }
+    }
+
+    /// Applies a 3D transposed convolution over the input tensor.
+    pub fn conv_transpose3d(
+        &self,
+        kernel: &Self,
+        padding: usize,
+        output_padding: usize,
+        stride: usize,
+        dilation: usize,
+    ) -> Result<Self> {
+        let (b_size, c_in, i_d, i_h, i_w) = self.dims5()?;
+        let (c_in_k, c_out, k_d, k_h, k_w) = kernel.dims5()?;
+        if c_in != c_in_k {
+            crate::bail!("in_channel mismatch between input ({c_in}) and kernel ({c_in_k})")
+        }
+        let params = ParamsConvTranspose3D {
+            b_size,
+            i_d,
+            i_h,
+            i_w,
+            k_d,
+            k_h,
+            k_w,
+            c_out,
+            c_in,
+            padding,
+            output_padding,
+            stride,
+            dilation,
+        };
+        let storage = self.storage().conv_transpose3d(
+            self.layout(),
+            &kernel.storage(),
+            kernel.layout(),
+            &params,
+        )?;
+        let op = BackpropOp::new2(self, kernel, |arg, kernel| Op::ConvTranspose3D {
+            arg,
+            kernel,
+            padding: params.padding,
+            output_padding: params.output_padding,
+            stride: params.stride,
+            dilation: params.dilation,
+        });
+        let out_dims = params.out_dims();
+        Ok(crate::tensor::from_storage(storage, out_dims, op, false))
+    }
+
+    /// Extracts the sliding blocks of a `(batch, channels, h, w)` tensor, also known as im2col.
+    ///
+    /// The result has shape `(batch, channels * kernel_h * kernel_w, l)` where `l` is the number
+    /// of blocks, each column holding the values of one block with the channel as the slowest
+    /// varying index. This matches PyTorch `unfold`.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::arange(0f32, 16., &Device::Cpu)?.reshape((1, 1, 4, 4))?;
+    /// let a = a.unfold2d(2, 1, 0, 2)?.squeeze(0)?;
+    /// assert_eq!(
+    ///     a.to_vec2::<f32>()?,
+    ///     &[[0., 2., 8., 10.], [1., 3., 9., 11.], [4., 6., 12., 14.], [5., 7., 13., 15.]]
+    /// );
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn unfold2d<T: crate::ToUsize2>(
+        &self,
+        kernel_size: T,
+        dilation: T,
+        padding: T,
+        stride: T,
+    ) -> Result<Self> {
+        let (b_size, c, h, w) = self.dims4()?;
+        let blocks = Blocks2D::new((h, w), kernel_size, dilation, padding, stride)?;
+        let (ph, pw) = blocks.padding;
+        let xs = self
+            .pad_with_zeros(2, ph, ph)?
+            .pad_with_zeros(3, pw, pw)?
+            .reshape((b_size, c, blocks.padded_len()))?;
+        let indexes = Tensor::new(blocks.indexes(), self.device())?;
+        xs.index_select(&indexes, 2)?
+            .reshape((b_size, c * blocks.kernel_len(), blocks.len()))
+    }
+
+    /// Combines an array of sliding blocks into a `(batch, channels, h, w)` tensor, also known as
+    /// col2im. This is the adjoint of [`Tensor::unfold2d`]: the values of overlapping blocks are
+    /// summed up.
+    ///
+    /// The input tensor should have shape `(batch, channels * kernel_h * kernel_w, l)` where `l`
+    /// is the number of blocks for an input of size `output_size`.
+    pub fn fold2d<T: crate::ToUsize2>(
+        &self,
+        output_size: T,
+        kernel_size: T,
+        dilation: T,
+        padding: T,
+        stride: T,
+    ) -> Result<Self> {
+        let (b_size, ckk, l) = self.dims3()?;
+        let (h, w) = output_size.to_usize2();
+        let blocks = Blocks2D::new((h, w), kernel_size, dilation, padding, stride)?;
+        if ckk % blocks.kernel_len() != 0 || l != blocks.len() {
+            crate::bail!(
+                "fold2d: expected an input of shape (_, channels * {}, {}), got {:?}",
+                blocks.kernel_len(),
+                blocks.len(),
+                self.shape()
+            )
+        }
+        let c = ckk / blocks.kernel_len();
+        let (ph, pw) = blocks.padding;
+        let (hp, wp) = (h + 2 * ph, w + 2 * pw);
+        let xs = self.reshape((b_size, c, blocks.kernel_len() * l))?;
+        let indexes = Tensor::new(blocks.indexes(), self.device())?;
+        Tensor::zeros((b_size, c, hp * wp), self.dtype(), self.device())?
+            .index_add(&indexes, &xs, 2)?
+            .reshape((b_size, c, hp, wp))?
+            .narrow(2, ph, h)?
+            .narrow(3, pw, w)
+    }
+}
+
+/// The sliding blocks used by `unfold2d` and `fold2d`.
+struct Blocks2D {
+    kernel_size: (usize, usize),
+    dilation: (usize, usize),
+    padding: (usize, usize),
+    stride: (usize, usize),
+    padded_size: (usize, usize),
+    out_size: (usize, usize),
+}
+
+impl Blocks2D {
+    fn new<T: crate::ToUsize2>(
+        (h, w): (usize, usize),
+        kernel_size: T,
+        dilation: T,
+        padding: T,
+        stride: T,
+    ) -> Result<Self> {
+        let (kh, kw) = kernel_size.to_usize2();
+        let (dh, dw) = dilation.to_usize2();
+        let (ph, pw) = padding.to_usize2();
+        let (sh, sw) = stride.to_usize2();
+        if kh == 0 || kw == 0 || dh == 0 || dw == 0 || sh == 0 || sw == 0 {
+            crate::bail!("kernel size, dilation and stride should be positive")
+        }
+        let (hp, wp) = (h + 2 * ph, w + 2 * pw);
+        let (eh, ew) = (dh * (kh - 1) + 1, dw * (kw - 1) + 1);
+        if eh > hp || ew > wp {
+            crate::bail!(
+                "the dilated kernel ({eh}, {ew}) is larger than the padded input ({hp}, {wp})"
+            )
+        }
+        Ok(Self {
+            kernel_size: (kh, kw),
+            dilation: (dh, dw),
+            padding: (ph, pw),
+            stride: (sh, sw),
+            padded_size: (hp, wp),
+            out_size: ((hp - eh) / sh + 1, (wp - ew) / sw + 1),
+        })
+    }
+
+    fn kernel_len(&self) -> usize {
+        self.kernel_size.0 * self.kernel_size.1
+    }
+
+    fn len(&self) -> usize {
+        self.out_size.0 * self.out_size.1
+    }
+
+    fn padded_len(&self) -> usize {
+        self.padded_size.0 * self.padded_size.1
+    }
+
+    /// The flat index in the padded input of each kernel position for each block.
+    fn indexes(&self) -> Vec<u32> {
+        let (kh, kw) = self.kernel_size;
+        let (dh, dw) = self.dilation;
+        let (sh, sw) = self.stride;
+        let (lh, lw) = self.out_size;
+        let wp = self.padded_size.1;
+        let mut indexes = Vec::with_capacity(self.kernel_len() * self.len());
+        for i in 0..kh {
+            for j in 0..kw {
+                for y in 0..lh {
+                    for x in 0..lw {
+                        indexes.push(((i * dh + y * sh) * wp + j * dw + x * sw) as u32)
+                    }
+                }
+            }
+        }
+        indexes
+    }
 }
@@ -93,7 +93,11 @@ from_tensor!(f32);
 from_tensor!(f16);
 from_tensor!(bf16);
 from_tensor!(i64);
+from_tensor!(i32);
+from_tensor!(i16);
+from_tensor!(i8);
 from_tensor!(u32);
+from_tensor!(u16);
 from_tensor!(u8);
 
 impl Tensor {
@@ -135,10 +139,49 @@ impl Tensor {
                     f.write_i64::<LittleEndian>(v)?
                 }
             }
+            DType::U16 => {
+                for v in vs.to_vec1::<u16>()? {
+                    f.write_u16::<LittleEndian>(v)?
+                }
+            }
+            DType::I8 => {
+                for v in vs.to_vec1::<i8>()? {
+                    f.write_i8(v)?
+                }
+            }
+            DType::I16 => {
+                for v in vs.to_vec1::<i16>()? {
+                    f.write_i16::<LittleEndian>(v)?
+                }
+            }
+            DType::I32 => {
+                for v in vs.to_vec1::<i32>()? {
+                    f.write_i32::<LittleEndian>(v)?
+                }
+            }
             DType::U8 => {
                 let vs = vs.to_vec1::<u8>()?;
                 f.write_all(&vs)?;
             }
+            DType::Bool => {
+                let vs: Vec<u8> = vs.to_vec1::<bool>()?.into_iter().map(u8::from).collect();
+                f.write_all(&vs)?;
+            }
+            DType::F8E4M3 | DType::F8E5M2 => {
+                let vs = crate::safetensors::convert_back_fp8(&vs)?;
+                f.write_all(&vs)?;
+            }
+            // The real and imaginary parts are interleaved.
+            DType::C64 => {
+                for v in vs.view_as_real()?.flatten_all()?.to_vec1::<f32>()? {
+                    f.write_f32::<LittleEndian>(v)?
+                }
+            }
+            DType::C128 => {
+                for v in vs.view_as_real()?.flatten_all()?.to_vec1::<f64>()? {
+                    f.write_f64::<LittleEndian>(v)?
+                }
+            }
         }
         Ok(())
     }
@@ -0,0 +1,88 @@
+//! Conversions between `f32` and the 8 bits floating-point formats.
+//!
+//! The fp8 values are stored as raw bytes. `E4M3FN` has no infinities and a single NaN
+//! representation per sign, its largest finite value is 448. `E5M2` follows the IEEE conventions
+//! and is the upper byte of an `f16`. Conversions from `f32` round to the nearest value with ties
+//! to even, values that are too large become NaN for `E4M3FN` and infinity for `E5M2` as in
+//! PyTorch.
+
+const E4M3_MAX_FINITE: u32 = 0x7e;
+const E5M2_MAX_FINITE: u32 = 0x7b;
+const E5M2_INF: u8 = 0x7c;
+const NAN: u8 = 0x7f;
+
+/// Rounds the absolute value of `v` to `man_bits` mantissa bits with an exponent bias of `bias`
+/// and returns the resulting bits without the sign. Values that are too large for the format
+/// result in bits above the largest finite value and have to be handled by the caller.
+fn round_abs_bits(v: f32, man_bits: u32, bias: i32) -> u32 {
+    let bits = v.to_bits() & 0x7fff_ffff;
+    let exp = (bits >> 23) as i32;
+    // Zero and the f32 subnormals are far below the smallest fp8 value.
+    if exp == 0 {
+        return 0;
+    }
+    let exp = exp - 127;
+    let mantissa = (bits & 0x7f_ffff) | 0x80_0000;
+    let min_exp = 1 - bias;
+    // Below the smallest normal value, the mantissa is shifted further to get a subnormal.
+    let shift = (23 - man_bits as i32 + (min_exp - exp).max(0)) as u32;
+    if shift > 25 {
+        return 0;
+    }
+    let mut q = mantissa >> shift;
+    let rem = mantissa & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if rem > half || (rem == half && q & 1 == 1) {
+        q += 1
+    }
+    if exp >= min_exp {
+        // `q` includes the implicit leading bit, a carry from the rounding moves to the exponent.
+        (((exp + bias - 1) as u32) << man_bits) + q
+    } else {
+        q
+    }
+}
+
+fn sign_bit(v: f32) -> u8 {
+    (v.to_bits() >> 24) as u8 & 0x80
+}
+
+pub fn f8e4m3_to_f32(v: u8) -> f32 {
+    let sign = if v & 0x80 == 0 { 1f32 } else { -1f32 };
+    let exp = ((v >> 3) & 0xf) as i32;
+    let mantissa = (v & 0x7) as f32;
+    let abs = match exp {
+        15 if mantissa == 7. => f32::NAN,
+        0 => mantissa * 2f32.powi(-9),
+        _ => (8. + mantissa) * 2f32.powi(exp - 10),
+    };
+    sign * abs
+}
+
+pub fn f32_to_f8e4m3(v: f32) -> u8 {
+    if v.is_nan() {
+        return NAN;
+    }
+    let bits = round_abs_bits(v, 3, 7);
+    if bits > E4M3_MAX_FINITE {
+        sign_bit(v) | NAN
+    } else {
+        sign_bit(v) | bits as u8
+    }
+}
+
+pub fn f8e5m2_to_f32(v: u8) -> f32 {
+    half::f16::from_bits((v as u16) << 8).to_f32()
+}
+
+pub fn f32_to_f8e5m2(v: f32) -> u8 {
+    if v.is_nan() {
+        return NAN;
+    }
+    let bits = round_abs_bits(v, 2, 15);
+    if bits > E5M2_MAX_FINITE {
+        sign_bit(v) | E5M2_INF
+    } else {
+        sign_bit(v) | bits as u8
+    }
+}
@@ -1,6 +1,7 @@
 //! Traits and methods for CPU-backed Tensors
 
 pub mod erf;
+pub mod fp8;
 pub mod kernels;
 
 #[allow(unused)]
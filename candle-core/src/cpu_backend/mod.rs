@@ -1,13 +1,18 @@
 //! Implementation of Backend Fns for CPU
 use crate::backend::{BackendDevice, BackendStorage};
-use crate::op::{BinaryOpT, CmpOp, ReduceOp, UnaryOpT};
-use crate::{DType, Error, IntDType, Layout, Result, Shape, WithDType};
+use crate::cpu::fp8;
+use crate::op::{
+    BinaryOpT, BitwiseOp, CmpOp, ReduceOp, Resample, ScanOp, ScatterReduceOp, UnaryOpT,
+};
+use crate::{DType, Error, IntDType, Layout, NumDType, Result, Shape, WithDType};
 use half::{bf16, f16};
+use num_complex::{Complex32, Complex64};
 use rayon::prelude::*;
 
 mod utils;
+pub(crate) use utils::bool_as_u8;
 pub use utils::{
-    binary_map, binary_map_vec, unary_map, unary_map_vec, Map1, Map1Any, Map2, Map2U8,
+    binary_map, binary_map_vec, unary_map, unary_map_vec, Map1, Map1Any, Map2, Map2Bool,
 };
 
 const USE_IM2COL_CONV1D: bool = true;
@@ -19,57 +24,121 @@ const USE_IM2COL_CONV2D: bool = true;
 #[derive(Debug, Clone)]
 pub enum CpuStorage {
     U8(Vec<u8>),
+    U16(Vec<u16>),
     U32(Vec<u32>),
+    I8(Vec<i8>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
     I64(Vec<i64>),
+    Bool(Vec<bool>),
+    // The raw fp8 bits, see `crate::cpu::fp8`.
+    F8E4M3(Vec<u8>),
+    F8E5M2(Vec<u8>),
     BF16(Vec<bf16>),
     F16(Vec<f16>),
     F32(Vec<f32>),
     F64(Vec<f64>),
+    C64(Vec<Complex32>),
+    C128(Vec<Complex64>),
 }
 
 #[derive(Debug, Clone)]
 pub enum CpuStorageRef<'a> {
     U8(&'a [u8]),
+    U16(&'a [u16]),
     U32(&'a [u32]),
+    I8(&'a [i8]),
+    I16(&'a [i16]),
+    I32(&'a [i32]),
     I64(&'a [i64]),
+    Bool(&'a [bool]),
+    F8E4M3(&'a [u8]),
+    F8E5M2(&'a [u8]),
     BF16(&'a [bf16]),
     F16(&'a [f16]),
     F32(&'a [f32]),
     F64(&'a [f64]),
+    C64(&'a [Complex32]),
+    C128(&'a [Complex64]),
 }
 
 #[derive(Debug, Clone)]
 pub struct CpuDevice;
 
 struct Cmp(CmpOp);
-impl Map2U8 for Cmp {
+impl Map2Bool for Cmp {
     const OP: &'static str = "cmp";
     #[inline(always)]
-    fn f<T: WithDType>(
+    fn f<T: NumDType>(
         &self,
         lhs: &[T],
         lhs_l: &Layout,
         rhs: &[T],
         rhs_l: &Layout,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<Vec<bool>> {
         let dst = match self.0 {
-            CmpOp::Eq => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| u8::from(x == y)),
-            CmpOp::Ne => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| u8::from(x != y)),
-            CmpOp::Lt => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| u8::from(x < y)),
-            CmpOp::Le => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| u8::from(x <= y)),
-            CmpOp::Gt => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| u8::from(x > y)),
-            CmpOp::Ge => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| u8::from(x >= y)),
+            CmpOp::Eq => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| x == y),
+            CmpOp::Ne => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| x != y),
+            CmpOp::Lt => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| x < y),
+            CmpOp::Le => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| x <= y),
+            CmpOp::Gt => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| x > y),
+            CmpOp::Ge => binary_map(lhs_l, rhs_l, lhs, rhs, |x, y| x >= y),
         };
         Ok(dst)
     }
 }
 
+/// Casts going through `f64`, used for the dtype pairs that have no dedicated conversion.
+struct Cast(DType);
+impl Map1Any for Cast {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType, W: Fn(Vec<T>) -> CpuStorage>(
+        &self,
+        vs: &[T],
+        layout: &Layout,
+        _wrap: W,
+    ) -> Result<CpuStorage> {
+        fn cast<T: NumDType, U: NumDType>(vs: &[T], layout: &Layout) -> Vec<U> {
+            unary_map(vs, layout, |v| U::from_f64(v.to_f64()))
+        }
+        let storage = match self.0 {
+            DType::U8 => CpuStorage::U8(cast(vs, layout)),
+            DType::U16 => CpuStorage::U16(cast(vs, layout)),
+            DType::U32 => CpuStorage::U32(cast(vs, layout)),
+            DType::I8 => CpuStorage::I8(cast(vs, layout)),
+            DType::I16 => CpuStorage::I16(cast(vs, layout)),
+            DType::I32 => CpuStorage::I32(cast(vs, layout)),
+            DType::I64 => CpuStorage::I64(cast(vs, layout)),
+            DType::Bool => CpuStorage::Bool(unary_map(vs, layout, |v| v != T::zero())),
+            DType::F8E4M3 => CpuStorage::F8E4M3(unary_map(vs, layout, |v| {
+                fp8::f32_to_f8e4m3(v.to_f64() as f32)
+            })),
+            DType::F8E5M2 => CpuStorage::F8E5M2(unary_map(vs, layout, |v| {
+                fp8::f32_to_f8e5m2(v.to_f64() as f32)
+            })),
+            DType::BF16 => CpuStorage::BF16(cast(vs, layout)),
+            DType::F16 => CpuStorage::F16(cast(vs, layout)),
+            DType::F32 => CpuStorage::F32(cast(vs, layout)),
+            DType::F64 => CpuStorage::F64(cast(vs, layout)),
+            DType::C64 => CpuStorage::C64(unary_map(vs, layout, |v| {
+                Complex32::new(v.to_f64() as f32, 0.)
+            })),
+            DType::C128 => {
+                CpuStorage::C128(unary_map(vs, layout, |v| Complex64::new(v.to_f64(), 0.)))
+            }
+        };
+        Ok(storage)
+    }
+}
+
 struct WCond<'a, T: IntDType>(&'a [T], &'a Layout);
 
 impl<I: IntDType> Map2 for WCond<'_, I> {
     const OP: &'static str = "where";
+    const BOOL: bool = true;
     #[inline(always)]
-    fn f<T: WithDType>(&self, t: &[T], t_l: &Layout, f: &[T], f_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, t: &[T], t_l: &Layout, f: &[T], f_l: &Layout) -> Result<Vec<T>> {
         let vs = match (
             self.1.contiguous_offsets(),
             t_l.contiguous_offsets(),
@@ -186,8 +255,10 @@ impl ReduceIndex {
 }
 
 impl Map1Any for ReduceIndex {
+    const BOOL: bool = true;
+
     #[inline(always)]
-    fn f<T: WithDType, W: Fn(Vec<T>) -> CpuStorage>(
+    fn f<T: NumDType, W: Fn(Vec<T>) -> CpuStorage>(
         &self,
         src: &[T],
         src_l: &Layout,
@@ -210,6 +281,227 @@ impl Map1Any for ReduceIndex {
     }
 }
 
+struct Bitwise(BitwiseOp);
+
+impl Bitwise {
+    fn f<T: IntDType>(&self, lhs: &[T], lhs_l: &Layout, rhs: &[T], rhs_l: &Layout) -> Vec<T> {
+        match self.0 {
+            BitwiseOp::And => binary_map(lhs_l, rhs_l, lhs, rhs, |v1, v2| v1 & v2),
+            BitwiseOp::Or => binary_map(lhs_l, rhs_l, lhs, rhs, |v1, v2| v1 | v2),
+            BitwiseOp::Xor => binary_map(lhs_l, rhs_l, lhs, rhs, |v1, v2| v1 ^ v2),
+            BitwiseOp::Shl => binary_map(lhs_l, rhs_l, lhs, rhs, |v1, v2| v1.shift_left(v2)),
+            BitwiseOp::Shr => binary_map(lhs_l, rhs_l, lhs, rhs, |v1, v2| v1.shift_right(v2)),
+        }
+    }
+
+    fn map(
+        &self,
+        lhs: &CpuStorage,
+        lhs_l: &Layout,
+        rhs: &CpuStorage,
+        rhs_l: &Layout,
+    ) -> Result<CpuStorage> {
+        match (lhs, rhs) {
+            (CpuStorage::U8(lhs), CpuStorage::U8(rhs)) => {
+                Ok(CpuStorage::U8(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            (CpuStorage::U32(lhs), CpuStorage::U32(rhs)) => {
+                Ok(CpuStorage::U32(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            (CpuStorage::U16(lhs), CpuStorage::U16(rhs)) => {
+                Ok(CpuStorage::U16(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            (CpuStorage::I8(lhs), CpuStorage::I8(rhs)) => {
+                Ok(CpuStorage::I8(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            (CpuStorage::I16(lhs), CpuStorage::I16(rhs)) => {
+                Ok(CpuStorage::I16(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            (CpuStorage::I32(lhs), CpuStorage::I32(rhs)) => {
+                Ok(CpuStorage::I32(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            (CpuStorage::I64(lhs), CpuStorage::I64(rhs)) => {
+                Ok(CpuStorage::I64(self.f(lhs, lhs_l, rhs, rhs_l)))
+            }
+            // Shifting bool values is not supported.
+            (CpuStorage::Bool(lhs), CpuStorage::Bool(rhs))
+                if !matches!(self.0, BitwiseOp::Shl | BitwiseOp::Shr) =>
+            {
+                let f = match self.0 {
+                    BitwiseOp::And => |v1, v2| v1 & v2,
+                    BitwiseOp::Or => |v1, v2| v1 | v2,
+                    _ => |v1, v2| v1 ^ v2,
+                };
+                Ok(CpuStorage::Bool(binary_map(lhs_l, rhs_l, lhs, rhs, f)))
+            }
+            (lhs, rhs) if lhs.dtype() != rhs.dtype() => Err(Error::DTypeMismatchBinaryOp {
+                lhs: lhs.dtype(),
+                rhs: rhs.dtype(),
+                op: self.0.name(),
+            }
+            .bt()),
+            (lhs, _) => Err(Error::UnsupportedDTypeForOp(lhs.dtype(), self.0.name()).bt()),
+        }
+    }
+}
+
+struct Scan {
+    dim: usize,
+    op: ScanOp,
+    reverse: bool,
+}
+
+impl Scan {
+    // Runs a scan over each lane along `dim`, the accumulator is created from the first element of
+    // the lane with `init`, updated with `step` and each output value is computed with `out`.
+    #[inline(always)]
+    fn scan_impl<T, U, A, I, S, O>(
+        &self,
+        src: &[T],
+        src_l: &Layout,
+        init: I,
+        step: S,
+        out: O,
+    ) -> Result<Vec<U>>
+    where
+        T: Copy,
+        U: NumDType,
+        I: Fn(T, usize) -> A,
+        S: Fn(A, T, usize) -> A,
+        O: Fn(&A) -> U,
+    {
+        let el_count = src_l.shape().elem_count();
+        let mut dst = vec![U::zero(); el_count];
+        if el_count == 0 {
+            return Ok(dst);
+        }
+        let dims = src_l.dims();
+        let dim_size = dims[self.dim];
+        let dim_stride = src_l.stride()[self.dim];
+        let inner_size = dims[self.dim + 1..].iter().product::<usize>();
+        let lanes = src_l.narrow(self.dim, 0, 1)?;
+        for (lane_i, src_i) in lanes.strided_index().enumerate() {
+            let dst_i = (lane_i / inner_size) * dim_size * inner_size + lane_i % inner_size;
+            let mut acc = None;
+            for i in 0..dim_size {
+                let i = if self.reverse { dim_size - 1 - i } else { i };
+                let v = src[src_i + i * dim_stride];
+                let a = match acc {
+                    None => init(v, i),
+                    Some(a) => step(a, v, i),
+                };
+                dst[dst_i + i * inner_size] = out(&a);
+                acc = Some(a)
+            }
+        }
+        Ok(dst)
+    }
+}
+
+impl Map1Any for Scan {
+    fn f<T: NumDType, W: Fn(Vec<T>) -> CpuStorage>(
+        &self,
+        src: &[T],
+        src_l: &Layout,
+        wrap: W,
+    ) -> Result<CpuStorage> {
+        let dst = match self.op {
+            ScanOp::Sum => wrap(self.scan_impl(src, src_l, |v, _| v, |a, v, _| a + v, |&a| a)?),
+            ScanOp::Prod => wrap(self.scan_impl(src, src_l, |v, _| v, |a, v, _| a * v, |&a| a)?),
+            ScanOp::Min => wrap(self.scan_impl(
+                src,
+                src_l,
+                |v, _| v,
+                |a, v, _| if v < a { v } else { a },
+                |&a| a,
+            )?),
+            ScanOp::Max => wrap(self.scan_impl(
+                src,
+                src_l,
+                |v, _| v,
+                |a, v, _| if v > a { v } else { a },
+                |&a| a,
+            )?),
+            ScanOp::ArgMin => CpuStorage::U32(self.scan_impl(
+                src,
+                src_l,
+                |v, i| (v, i),
+                |(a, j), v, i| if v <= a { (v, i) } else { (a, j) },
+                |&(_, i)| i as u32,
+            )?),
+            ScanOp::ArgMax => CpuStorage::U32(self.scan_impl(
+                src,
+                src_l,
+                |v, i| (v, i),
+                |(a, j), v, i| if v >= a { (v, i) } else { (a, j) },
+                |&(_, i)| i as u32,
+            )?),
+            ScanOp::LogSumExp => {
+                // The accumulation is done in f64 so that long sequences do not lose precision.
+                let log_add_exp = |a: f64, v: T| {
+                    let v = v.to_f64();
+                    let max = a.max(v);
+                    if max == f64::NEG_INFINITY {
+                        max
+                    } else {
+                        max + ((a - max).exp() + (v - max).exp()).ln()
+                    }
+                };
+                wrap(self.scan_impl(
+                    src,
+                    src_l,
+                    |v, _| v.to_f64(),
+                    |a, v, _| log_add_exp(a, v),
+                    |&a| T::from_f64(a),
+                )?)
+            }
+        };
+        Ok(dst)
+    }
+}
+
+struct ResampleMap<'a>(&'a Resample);
+
+impl Map1 for ResampleMap<'_> {
+    fn f<T: NumDType>(&self, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
+        let r = self.0;
+        let (src_size, dst_size) = r.in_out_sizes();
+        let dims = src_l.dims();
+        if dims.get(r.dim) != Some(&src_size) {
+            crate::bail!(
+                "resample: unexpected shape {dims:?} for dim {} of size {src_size}",
+                r.dim
+            )
+        }
+        let inner_size = dims[r.dim + 1..].iter().product::<usize>();
+        let outer_size = dims[..r.dim].iter().product::<usize>();
+        // The accumulation is done in f64 for all the dtypes.
+        let mut dst = vec![0f64; outer_size * dst_size * inner_size];
+        if dst.is_empty() || src_size == 0 {
+            return Ok(dst.into_iter().map(T::from_f64).collect());
+        }
+        let dim_stride = src_l.stride()[r.dim];
+        let lanes = src_l.narrow(r.dim, 0, 1)?;
+        for (lane_i, src_i) in lanes.strided_index().enumerate() {
+            let dst_i = (lane_i / inner_size) * dst_size * inner_size + lane_i % inner_size;
+            let taps = r
+                .indexes
+                .chunks_exact(r.taps)
+                .zip(r.weights.chunks_exact(r.taps));
+            for (o, (indexes, weights)) in taps.enumerate() {
+                for (&i, &w) in indexes.iter().zip(weights.iter()) {
+                    if r.transpose {
+                        dst[dst_i + i * inner_size] += w * src[src_i + o * dim_stride].to_f64()
+                    } else {
+                        dst[dst_i + o * inner_size] += w * src[src_i + i * dim_stride].to_f64()
+                    }
+                }
+            }
+        }
+        Ok(dst.into_iter().map(T::from_f64).collect())
+    }
+}
+
 struct ReduceSum<'a> {
     dst_shape: &'a Shape,
     reduce_dims: &'a [usize],
@@ -220,7 +512,7 @@ impl ReduceSum<'_> {
     #[inline(always)]
     fn fold_impl<T>(&self, src: &[T], src_l: &Layout, start_elt: T) -> Result<Vec<T>>
     where
-        T: WithDType,
+        T: NumDType,
     {
         let mut dst = vec![start_elt; self.dst_shape.elem_count()];
         match src_l.contiguous_offsets() {
@@ -283,7 +575,7 @@ impl ReduceSum<'_> {
 
 impl Map1 for ReduceSum<'_> {
     #[inline(always)]
-    fn f<T: WithDType>(&self, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
         self.fold_impl(src, src_l, T::zero())
     }
 }
@@ -291,7 +583,7 @@ impl Map1 for ReduceSum<'_> {
 struct Affine(f64, f64);
 
 impl Map1 for Affine {
-    fn f<T: WithDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>> {
         let mul = T::from_f64(self.0);
         let add = T::from_f64(self.1);
         Ok(unary_map(vs, layout, |v| v * mul + add))
@@ -301,7 +593,7 @@ impl Map1 for Affine {
 struct AvgPool2D((usize, usize), (usize, usize));
 
 impl Map1 for AvgPool2D {
-    fn f<T: WithDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
         // https://pytorch.org/docs/stable/generated/torch.nn.AvgPool2d.html
         let (k_h, k_w) = self.0;
         let (s_h, s_w) = self.1;
@@ -342,7 +634,9 @@ impl Map1 for AvgPool2D {
 struct MaxPool2D((usize, usize), (usize, usize));
 
 impl Map1 for MaxPool2D {
-    fn f<T: WithDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
         // https://pytorch.org/docs/stable/generated/torch.nn.MaxPool2d.html
         let (k_h, k_w) = self.0;
         let (s_h, s_w) = self.1;
@@ -384,7 +678,9 @@ impl Map1 for MaxPool2D {
 struct UpsampleNearest1D(usize);
 
 impl Map1 for UpsampleNearest1D {
-    fn f<T: WithDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
         // TODO: Specialized implementation for the case 2*sz?
         let dst_sz = self.0;
         let (b_sz, c, src_sz) = layout.shape().dims3()?;
@@ -414,7 +710,9 @@ impl Map1 for UpsampleNearest1D {
 struct UpsampleNearest2D(usize, usize);
 
 impl Map1 for UpsampleNearest2D {
-    fn f<T: WithDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
         // TODO: Specialized implementation for the case 2*h, 2*w?
         let (dst_h, dst_w) = (self.0, self.1);
         let (b_sz, c, src_h, src_w) = layout.shape().dims4()?;
@@ -455,7 +753,9 @@ struct Gather<'a, I: IntDType> {
 }
 
 impl<I: IntDType> Map1 for Gather<'_, I> {
-    fn f<T: WithDType>(&self, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType>(&self, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
         let ids = match self.ids_l.contiguous_offsets() {
             Some((a, b)) => &self.ids[a..b],
             None => Err(Error::RequiresContiguous { op: "gather" }.bt())?,
@@ -508,7 +808,9 @@ struct IndexSelect<'a, T: IntDType> {
 }
 
 impl<I: IntDType> Map1 for IndexSelect<'_, I> {
-    fn f<T: WithDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType>(&self, src: &[T], layout: &Layout) -> Result<Vec<T>> {
         let src = match layout.contiguous_offsets() {
             Some((a, b)) => &src[a..b],
             None => Err(Error::RequiresContiguous { op: "index-select" }.bt())?,
@@ -562,7 +864,7 @@ struct ScatterAdd<'a, I: IntDType> {
 
 impl<I: IntDType> Map2 for ScatterAdd<'_, I> {
     const OP: &'static str = "scatter-add";
-    fn f<T: WithDType>(&self, v1: &[T], l1: &Layout, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, v1: &[T], l1: &Layout, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
         let dst_len = l1.shape().elem_count();
         let mut dst = vec![T::zero(); dst_len];
         copy_strided_src_(v1, &mut dst, 0, l1);
@@ -611,6 +913,102 @@ impl<I: IntDType> Map2 for ScatterAdd<'_, I> {
     }
 }
 
+struct Scatter<'a, I: IntDType> {
+    ids: &'a [I],
+    ids_l: &'a Layout,
+    dim: usize,
+    // `None` overwrites the destination values, otherwise this is the reduction and whether the
+    // initial destination values are included in it.
+    reduce: Option<(ScatterReduceOp, bool)>,
+}
+
+impl<I: IntDType> Map2 for Scatter<'_, I> {
+    const OP: &'static str = "scatter";
+    fn f<T: NumDType>(&self, v1: &[T], l1: &Layout, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
+        let dst_len = l1.shape().elem_count();
+        let mut dst = vec![T::zero(); dst_len];
+        copy_strided_src_(v1, &mut dst, 0, l1);
+        let src = match src_l.contiguous_offsets() {
+            None => Err(Error::RequiresContiguous { op: "scatter" }.bt())?,
+            Some((o1, o2)) => &src[o1..o2],
+        };
+
+        let dim = self.dim;
+        let ids_dims = self.ids_l.dims();
+        let dst_dims = l1.dims();
+        let dst_dim_len = dst_dims[dim];
+        let dst_right_len: usize = dst_dims[dim + 1..].iter().product();
+
+        let ids_left_len: usize = ids_dims[..dim].iter().product();
+        let ids_dim_len = ids_dims[dim];
+        let ids_right_len: usize = ids_dims[dim + 1..].iter().product();
+
+        let ids = match self.ids_l.contiguous_offsets() {
+            Some((a, b)) => &self.ids[a..b],
+            None => Err(Error::RequiresContiguous { op: "scatter" }.bt())?,
+        };
+        // The number of values scattered to each destination position.
+        let mut counts = vec![0usize; if self.reduce.is_some() { dst_len } else { 0 }];
+        for left_i in 0..ids_left_len {
+            let start_ids_idx = left_i * ids_right_len * ids_dim_len;
+            let start_dst_idx = left_i * dst_right_len * dst_dim_len;
+            for i in 0..ids_dim_len {
+                let start_ids_idx = start_ids_idx + i * ids_right_len;
+                for right_i in 0..dst_right_len {
+                    let ids_idx = start_ids_idx + right_i;
+                    let index = ids[ids_idx].as_usize();
+                    if index >= dst_dim_len {
+                        Err(Error::InvalidIndex {
+                            index,
+                            size: dst_dim_len,
+                            op: "scatter",
+                        }
+                        .bt())?
+                    }
+                    let dst_idx = start_dst_idx + index * dst_right_len + right_i;
+                    let v = src[ids_idx];
+                    let (op, include_self) = match self.reduce {
+                        None => {
+                            dst[dst_idx] = v;
+                            continue;
+                        }
+                        Some(reduce) => reduce,
+                    };
+                    let d = &mut dst[dst_idx];
+                    if counts[dst_idx] == 0 && !include_self {
+                        *d = v
+                    } else {
+                        match op {
+                            ScatterReduceOp::Sum | ScatterReduceOp::Mean => *d += v,
+                            ScatterReduceOp::Prod => *d *= v,
+                            ScatterReduceOp::Amax => {
+                                if v > *d {
+                                    *d = v
+                                }
+                            }
+                            ScatterReduceOp::Amin => {
+                                if v < *d {
+                                    *d = v
+                                }
+                            }
+                        }
+                    }
+                    counts[dst_idx] += 1;
+                }
+            }
+        }
+        if let Some((ScatterReduceOp::Mean, include_self)) = self.reduce {
+            for (d, &count) in dst.iter_mut().zip(counts.iter()) {
+                if count > 0 {
+                    let count = count + include_self as usize;
+                    *d /= T::from_f64(count as f64)
+                }
+            }
+        }
+        Ok(dst)
+    }
+}
+
 struct IndexAdd<'a, I: IntDType> {
     ids: &'a [I],
     dim: usize,
@@ -620,7 +1018,7 @@ impl<I: IntDType> Map2 for IndexAdd<'_, I> {
     const OP: &'static str = "index-add";
     // https://pytorch.org/docs/stable/generated/torch.Tensor.index_add_.html#torch.Tensor.index_add_
     // v1, l1 -> self
-    fn f<T: WithDType>(&self, v1: &[T], l1: &Layout, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, v1: &[T], l1: &Layout, src: &[T], src_l: &Layout) -> Result<Vec<T>> {
         let dst_len = l1.shape().elem_count();
         let mut dst = vec![T::zero(); dst_len];
         copy_strided_src_(v1, &mut dst, 0, l1);
@@ -738,7 +1136,7 @@ struct Conv1D<'a>(&'a crate::conv::ParamsConv1D);
 
 impl Map2 for Conv1D<'_> {
     const OP: &'static str = "conv1d";
-    fn f<T: WithDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
         let p = self.0;
         let inp = &inp[inp_l.start_offset()..];
         let k = &k[k_l.start_offset()..];
@@ -810,7 +1208,7 @@ impl Im2Col1D {
 }
 
 impl Map1 for Im2Col1D {
-    fn f<T: WithDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>> {
         let &Self {
             l_k,
             stride,
@@ -872,7 +1270,7 @@ impl Im2Col {
 }
 
 impl Map1 for Im2Col {
-    fn f<T: WithDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>> {
         let &Self {
             h_k,
             w_k,
@@ -935,7 +1333,7 @@ struct Col2Im1D {
 }
 
 impl Map1 for Col2Im1D {
-    fn f<T: WithDType>(&self, col: &[T], l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, col: &[T], l: &Layout) -> Result<Vec<T>> {
         let (b_size, l_in, c_out, k_size) = l.shape().dims4()?;
         let stride = self.stride;
         let l_out = (l_in - 1) * stride + k_size;
@@ -962,7 +1360,7 @@ struct ConvTranspose1D<'a>(&'a crate::conv::ParamsConvTranspose1D);
 
 impl Map2 for ConvTranspose1D<'_> {
     const OP: &'static str = "conv_transpose1d";
-    fn f<T: WithDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
         let p = self.0;
         let inp = &inp[inp_l.start_offset()..];
         let k = &k[k_l.start_offset()..];
@@ -1031,7 +1429,7 @@ struct Conv2D<'a>(&'a crate::conv::ParamsConv2D);
 
 impl Map2 for Conv2D<'_> {
     const OP: &'static str = "conv2d";
-    fn f<T: WithDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
         let p = self.0;
         let inp = &inp[inp_l.start_offset()..];
         let (inp_s0, inp_s1, inp_s2, inp_s3) = crate::shape::dims4(inp_l.stride())?;
@@ -1119,7 +1517,7 @@ struct ConvTranspose2D<'a>(&'a crate::conv::ParamsConvTranspose2D);
 
 impl Map2 for ConvTranspose2D<'_> {
     const OP: &'static str = "conv_transpose2d";
-    fn f<T: WithDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
+    fn f<T: NumDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
         let p = self.0;
         let inp = &inp[inp_l.start_offset()..];
         let (inp_s0, inp_s1, inp_s2, inp_s3) = crate::shape::dims4(inp_l.stride())?;
@@ -1205,6 +1603,239 @@ impl Map2 for ConvTranspose2D<'_> {
     }
 }
 
+struct Conv3D<'a>(&'a crate::conv::ParamsConv3D);
+
+impl Map2 for Conv3D<'_> {
+    const OP: &'static str = "conv3d";
+    fn f<T: NumDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
+        let p = self.0;
+        let inp = &inp[inp_l.start_offset()..];
+        let (inp_s0, inp_s1, inp_s2, inp_s3, inp_s4) = crate::shape::dims5(inp_l.stride())?;
+        let k = &k[k_l.start_offset()..];
+        let (k_s0, k_s1, k_s2, k_s3, k_s4) = crate::shape::dims5(k_l.stride())?;
+        let (out_d, out_h, out_w) = (p.out_d(), p.out_h(), p.out_w());
+
+        // Output shape: [b_size, c_out, out_d, out_h, out_w].
+        let dst = vec![T::zero(); p.b_size * p.c_out * out_d * out_h * out_w];
+
+        // Make the channels the innermost dimension of the input so that the dot products below
+        // operate on contiguous memory.
+        let mut inp_cont = vec![T::zero(); p.b_size * p.c_in * p.i_d * p.i_h * p.i_w];
+        let cont_s0 = p.i_d * p.i_h * p.i_w * p.c_in;
+        let cont_s1 = p.i_h * p.i_w * p.c_in;
+        let cont_s2 = p.i_w * p.c_in;
+        let cont_s3 = p.c_in;
+        for b_idx in 0..p.b_size {
+            for d_idx in 0..p.i_d {
+                for h_idx in 0..p.i_h {
+                    for w_idx in 0..p.i_w {
+                        for c_idx in 0..p.c_in {
+                            let src_idx = b_idx * inp_s0
+                                + c_idx * inp_s1
+                                + d_idx * inp_s2
+                                + h_idx * inp_s3
+                                + w_idx * inp_s4;
+                            let dst_idx = b_idx * cont_s0
+                                + d_idx * cont_s1
+                                + h_idx * cont_s2
+                                + w_idx * cont_s3
+                                + c_idx;
+                            inp_cont[dst_idx] = inp[src_idx]
+                        }
+                    }
+                }
+            }
+        }
+
+        for offset_d in 0..p.k_d {
+            for offset_h in 0..p.k_h {
+                for offset_w in 0..p.k_w {
+                    (0..p.c_out).into_par_iter().for_each(|dst_c_idx| {
+                        let dst_idx = dst_c_idx * out_d * out_h * out_w;
+                        let k_cont = (0..p.c_in)
+                            .map(|c_in_idx| {
+                                k[dst_c_idx * k_s0
+                                    + c_in_idx * k_s1
+                                    + offset_d * k_s2
+                                    + offset_h * k_s3
+                                    + offset_w * k_s4]
+                            })
+                            .collect::<Vec<_>>();
+                        for b_idx in 0..p.b_size {
+                            let dst_idx = dst_idx + b_idx * p.c_out * out_d * out_h * out_w;
+                            for dst_d in 0..out_d {
+                                let dst_idx = dst_idx + dst_d * out_h * out_w;
+                                let src_d = p.stride * dst_d + offset_d * p.dilation;
+                                if src_d < p.padding || src_d >= p.i_d + p.padding {
+                                    continue;
+                                }
+                                let src_d = src_d - p.padding;
+                                for dst_h in 0..out_h {
+                                    let dst_idx = dst_idx + dst_h * out_w;
+                                    let src_h = p.stride * dst_h + offset_h * p.dilation;
+                                    if src_h < p.padding || src_h >= p.i_h + p.padding {
+                                        continue;
+                                    }
+                                    let src_h = src_h - p.padding;
+                                    for dst_w in 0..out_w {
+                                        let dst_idx = dst_idx + dst_w;
+                                        let src_w = p.stride * dst_w + offset_w * p.dilation;
+                                        if src_w < p.padding || src_w >= p.i_w + p.padding {
+                                            continue;
+                                        }
+                                        let src_w = src_w - p.padding;
+                                        let inp_cont = &inp_cont[b_idx * cont_s0
+                                            + src_d * cont_s1
+                                            + src_h * cont_s2
+                                            + src_w * cont_s3..];
+                                        assert!(inp_cont.len() >= p.c_in);
+                                        assert!(k_cont.len() >= p.c_in);
+                                        let mut d = T::zero();
+                                        unsafe {
+                                            T::vec_dot(
+                                                inp_cont.as_ptr(),
+                                                k_cont.as_ptr(),
+                                                &mut d,
+                                                p.c_in,
+                                            )
+                                        }
+                                        let dst_p = dst.as_ptr();
+                                        // Safety: dst_idx are uniques per dst_c_idx which is used
+                                        // to parallelise the different tasks so no two threads can
+                                        // try to write at the same location.
+                                        unsafe {
+                                            let ptr = dst_p.add(dst_idx) as *mut T;
+                                            *ptr += d
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok(dst)
+    }
+}
+
+struct ConvTranspose3D<'a>(&'a crate::conv::ParamsConvTranspose3D);
+
+impl Map2 for ConvTranspose3D<'_> {
+    const OP: &'static str = "conv_transpose3d";
+    fn f<T: NumDType>(&self, inp: &[T], inp_l: &Layout, k: &[T], k_l: &Layout) -> Result<Vec<T>> {
+        let p = self.0;
+        let inp = &inp[inp_l.start_offset()..];
+        let (inp_s0, inp_s1, inp_s2, inp_s3, inp_s4) = crate::shape::dims5(inp_l.stride())?;
+        let k = &k[k_l.start_offset()..];
+        let (k_s0, k_s1, k_s2, k_s3, k_s4) = crate::shape::dims5(k_l.stride())?;
+        let (out_d, out_h, out_w) = (p.out_d(), p.out_h(), p.out_w());
+
+        // Output shape: [b_size, c_out, out_d, out_h, out_w].
+        let dst = vec![T::zero(); p.b_size * p.c_out * out_d * out_h * out_w];
+        let dst_s0 = p.c_out * out_d * out_h * out_w;
+        let dst_s1 = out_d * out_h * out_w;
+        let dst_s2 = out_h * out_w;
+        let dst_s3 = out_w;
+
+        let mut inp_cont = vec![T::zero(); p.b_size * p.c_in * p.i_d * p.i_h * p.i_w];
+        let cont_s0 = p.i_d * p.i_h * p.i_w * p.c_in;
+        let cont_s1 = p.i_h * p.i_w * p.c_in;
+        let cont_s2 = p.i_w * p.c_in;
+        let cont_s3 = p.c_in;
+        for b_idx in 0..p.b_size {
+            for d_idx in 0..p.i_d {
+                for h_idx in 0..p.i_h {
+                    for w_idx in 0..p.i_w {
+                        for c_idx in 0..p.c_in {
+                            let src_idx = b_idx * inp_s0
+                                + c_idx * inp_s1
+                                + d_idx * inp_s2
+                                + h_idx * inp_s3
+                                + w_idx * inp_s4;
+                            let dst_idx = b_idx * cont_s0
+                                + d_idx * cont_s1
+                                + h_idx * cont_s2
+                                + w_idx * cont_s3
+                                + c_idx;
+                            inp_cont[dst_idx] = inp[src_idx]
+                        }
+                    }
+                }
+            }
+        }
+
+        for k_z in 0..p.k_d {
+            for k_y in 0..p.k_h {
+                for k_x in 0..p.k_w {
+                    (0..p.c_out).into_par_iter().for_each(|dst_c_idx| {
+                        let k_cont = (0..p.c_in)
+                            .map(|c_in_idx| {
+                                k[c_in_idx * k_s0
+                                    + dst_c_idx * k_s1
+                                    + k_z * k_s2
+                                    + k_y * k_s3
+                                    + k_x * k_s4]
+                            })
+                            .collect::<Vec<_>>();
+                        for b_idx in 0..p.b_size {
+                            for inp_z in 0..p.i_d {
+                                let out_z = inp_z * p.stride + k_z * p.dilation;
+                                if out_z < p.padding || out_z - p.padding >= out_d {
+                                    continue;
+                                }
+                                let out_z = out_z - p.padding;
+                                for inp_y in 0..p.i_h {
+                                    let out_y = inp_y * p.stride + k_y * p.dilation;
+                                    if out_y < p.padding || out_y - p.padding >= out_h {
+                                        continue;
+                                    }
+                                    let out_y = out_y - p.padding;
+                                    for inp_x in 0..p.i_w {
+                                        let out_x = inp_x * p.stride + k_x * p.dilation;
+                                        if out_x < p.padding || out_x - p.padding >= out_w {
+                                            continue;
+                                        }
+                                        let out_x = out_x - p.padding;
+                                        let inp_cont = &inp_cont[b_idx * cont_s0
+                                            + inp_z * cont_s1
+                                            + inp_y * cont_s2
+                                            + inp_x * cont_s3..];
+                                        let dst_idx = b_idx * dst_s0
+                                            + dst_c_idx * dst_s1
+                                            + out_z * dst_s2
+                                            + out_y * dst_s3
+                                            + out_x;
+                                        let mut d = T::zero();
+                                        unsafe {
+                                            T::vec_dot(
+                                                inp_cont.as_ptr(),
+                                                k_cont.as_ptr(),
+                                                &mut d,
+                                                p.c_in,
+                                            )
+                                        }
+                                        let dst_p = dst.as_ptr();
+                                        // Safety: dst_idx are uniques per dst_c_idx which is used
+                                        // to parallelise the different tasks so no two threads can
+                                        // try to write at the same location.
+                                        unsafe {
+                                            let ptr = dst_p.add(dst_idx) as *mut T;
+                                            *ptr += d
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        Ok(dst)
+    }
+}
+
 struct MatMul((usize, usize, usize, usize));
 
 impl MatMul {
@@ -1241,13 +1872,10 @@ impl MatMul {
         };
         Ok((a_skip, b_skip))
     }
-}
-
-impl Map2 for MatMul {
-    const OP: &'static str = "mat_mul";
 
-    #[cfg(all(not(feature = "mkl"), not(feature = "accelerate")))]
-    fn f<T: 'static + WithDType + num_traits::Num + Copy>(
+    /// The gemm based implementation, this is also used for the complex dtypes that do not go
+    /// through `Map2`.
+    fn gemm<T: 'static + num_traits::Num + Copy>(
         &self,
         lhs: &[T],
         lhs_l: &Layout,
@@ -1256,11 +1884,6 @@ impl Map2 for MatMul {
     ) -> Result<Vec<T>> {
         use gemm::{gemm, Parallelism};
 
-        match T::DTYPE {
-            DType::F16 | DType::F32 | DType::F64 => {}
-            _ => Err(Error::UnsupportedDTypeForOp(T::DTYPE, "matmul").bt())?,
-        }
-
         let (b, m, n, k) = self.0;
         let lhs = &lhs[lhs_l.start_offset()..];
         let rhs = &rhs[rhs_l.start_offset()..];
@@ -1319,9 +1942,28 @@ impl Map2 for MatMul {
         }
         Ok(dst)
     }
+}
+
+impl Map2 for MatMul {
+    const OP: &'static str = "mat_mul";
+
+    #[cfg(all(not(feature = "mkl"), not(feature = "accelerate")))]
+    fn f<T: 'static + NumDType + num_traits::Num + Copy>(
+        &self,
+        lhs: &[T],
+        lhs_l: &Layout,
+        rhs: &[T],
+        rhs_l: &Layout,
+    ) -> Result<Vec<T>> {
+        match T::DTYPE {
+            DType::F16 | DType::F32 | DType::F64 => {}
+            _ => Err(Error::UnsupportedDTypeForOp(T::DTYPE, "matmul").bt())?,
+        }
+        self.gemm(lhs, lhs_l, rhs, rhs_l)
+    }
 
     #[cfg(feature = "accelerate")]
-    fn f<T: 'static + WithDType + num_traits::Num + Copy>(
+    fn f<T: 'static + NumDType + num_traits::Num + Copy>(
         &self,
         lhs: &[T],
         lhs_l: &Layout,
@@ -1412,7 +2054,7 @@ impl Map2 for MatMul {
     }
 
     #[cfg(feature = "mkl")]
-    fn f<T: 'static + WithDType + num_traits::Num + Copy>(
+    fn f<T: 'static + NumDType + num_traits::Num + Copy>(
         &self,
         lhs: &[T],
         lhs_l: &Layout,
@@ -1557,6 +2199,17 @@ impl CpuStorage {
                     .concat();
                 Self::U8(storages)
             }
+            Self::U16(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::U16(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::U16(storages)
+            }
             Self::U32(_) => {
                 let storages = storages
                     .iter()
@@ -1568,6 +2221,39 @@ impl CpuStorage {
                     .concat();
                 Self::U32(storages)
             }
+            Self::I8(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::I8(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::I8(storages)
+            }
+            Self::I16(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::I16(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::I16(storages)
+            }
+            Self::I32(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::I32(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::I32(storages)
+            }
             Self::I64(_) => {
                 let storages = storages
                     .iter()
@@ -1579,6 +2265,39 @@ impl CpuStorage {
                     .concat();
                 Self::I64(storages)
             }
+            Self::Bool(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::Bool(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::Bool(storages)
+            }
+            Self::F8E4M3(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::F8E4M3(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::F8E4M3(storages)
+            }
+            Self::F8E5M2(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::F8E5M2(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::F8E5M2(storages)
+            }
             Self::BF16(_) => {
                 let storages = storages
                     .iter()
@@ -1623,6 +2342,28 @@ impl CpuStorage {
                     .concat();
                 Self::F64(storages)
             }
+            Self::C64(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::C64(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::C64(storages)
+            }
+            Self::C128(_) => {
+                let storages = storages
+                    .iter()
+                    .map(|s| match s {
+                        Self::C128(s) => Ok(s.as_slice()),
+                        _ => crate::bail!("dtype mismatch"),
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .concat();
+                Self::C128(storages)
+            }
         };
         Ok(s)
     }
@@ -1634,12 +2375,21 @@ impl BackendStorage for CpuStorage {
     fn dtype(&self) -> DType {
         match self {
             Self::U8(_) => DType::U8,
+            Self::U16(_) => DType::U16,
             Self::U32(_) => DType::U32,
+            Self::I8(_) => DType::I8,
+            Self::I16(_) => DType::I16,
+            Self::I32(_) => DType::I32,
             Self::I64(_) => DType::I64,
+            Self::Bool(_) => DType::Bool,
+            Self::F8E4M3(_) => DType::F8E4M3,
+            Self::F8E5M2(_) => DType::F8E5M2,
             Self::BF16(_) => DType::BF16,
             Self::F16(_) => DType::F16,
             Self::F32(_) => DType::F32,
             Self::F64(_) => DType::F64,
+            Self::C64(_) => DType::C64,
+            Self::C128(_) => DType::C128,
         }
     }
 
@@ -1842,6 +2592,47 @@ impl BackendStorage for CpuStorage {
                 let data = unary_map(storage, layout, |v| v);
                 Ok(Self::F64(data))
             }
+            (Self::F8E4M3(storage), DType::F8E4M3) => {
+                let data = unary_map(storage, layout, |v| v);
+                Ok(Self::F8E4M3(data))
+            }
+            (Self::F8E5M2(storage), DType::F8E5M2) => {
+                let data = unary_map(storage, layout, |v| v);
+                Ok(Self::F8E5M2(data))
+            }
+            (Self::C64(storage), DType::C64) => {
+                let data = unary_map(storage, layout, |v| v);
+                Ok(Self::C64(data))
+            }
+            (Self::C64(storage), DType::C128) => {
+                let data = unary_map(storage, layout, |v| {
+                    Complex64::new(v.re as f64, v.im as f64)
+                });
+                Ok(Self::C128(data))
+            }
+            (Self::C128(storage), DType::C128) => {
+                let data = unary_map(storage, layout, |v| v);
+                Ok(Self::C128(data))
+            }
+            (Self::C128(storage), DType::C64) => {
+                let data = unary_map(storage, layout, |v| {
+                    Complex32::new(v.re as f32, v.im as f32)
+                });
+                Ok(Self::C64(data))
+            }
+            (Self::C64(_) | Self::C128(_), dtype) => {
+                crate::bail!("cannot convert complex values to {dtype:?}, use real, imag or abs")
+            }
+            // The fp8 values are converted through f32.
+            (Self::F8E4M3(storage), dtype) => {
+                let data = unary_map(storage, layout, fp8::f8e4m3_to_f32);
+                Cast(dtype).map(&Self::F32(data), &Layout::contiguous(layout.shape()))
+            }
+            (Self::F8E5M2(storage), dtype) => {
+                let data = unary_map(storage, layout, fp8::f8e5m2_to_f32);
+                Cast(dtype).map(&Self::F32(data), &Layout::contiguous(layout.shape()))
+            }
+            (storage, dtype) => Cast(dtype).map(storage, layout),
         }
     }
 
@@ -1901,12 +2692,32 @@ impl BackendStorage for CpuStorage {
         }
     }
 
+    fn scan_op(&self, op: ScanOp, layout: &Layout, dim: usize, reverse: bool) -> Result<Self> {
+        Scan { dim, op, reverse }.map(self, layout)
+    }
+
+    fn resample(&self, layout: &Layout, resample: &Resample) -> Result<Self> {
+        ResampleMap(resample).map(self, layout)
+    }
+
     fn cmp(&self, op: CmpOp, rhs: &Self, lhs_l: &Layout, rhs_l: &Layout) -> Result<Self> {
         Cmp(op).map(self, lhs_l, rhs, rhs_l)
     }
 
+    fn bitwise(&self, op: BitwiseOp, rhs: &Self, lhs_l: &Layout, rhs_l: &Layout) -> Result<Self> {
+        Bitwise(op).map(self, lhs_l, rhs, rhs_l)
+    }
+
     fn affine(&self, layout: &Layout, mul: f64, add: f64) -> Result<Self> {
-        Affine(mul, add).map(self, layout)
+        match self {
+            // The complex values are scaled and shifted by real values.
+            Self::C64(vs) => {
+                let (mul, add) = (mul as f32, add as f32);
+                Ok(Self::C64(unary_map(vs, layout, |v| v * mul + add)))
+            }
+            Self::C128(vs) => Ok(Self::C128(unary_map(vs, layout, |v| v * mul + add))),
+            _ => Affine(mul, add).map(self, layout),
+        }
     }
 
     fn avg_pool2d(
@@ -1958,6 +2769,15 @@ impl BackendStorage for CpuStorage {
             Self::U8(_) => Err(Error::UnsupportedDTypeForOp(DType::U8, "elu").bt()),
             Self::U32(_) => Err(Error::UnsupportedDTypeForOp(DType::U32, "elu").bt()),
             Self::I64(_) => Err(Error::UnsupportedDTypeForOp(DType::I64, "elu").bt()),
+            Self::U16(_)
+            | Self::I8(_)
+            | Self::I16(_)
+            | Self::I32(_)
+            | Self::Bool(_)
+            | Self::F8E4M3(_)
+            | Self::F8E5M2(_)
+            | Self::C64(_)
+            | Self::C128(_) => Err(Error::UnsupportedDTypeForOp(self.dtype(), "elu").bt()),
         }
     }
 
@@ -1983,6 +2803,15 @@ impl BackendStorage for CpuStorage {
             Self::U8(_) => Err(Error::UnsupportedDTypeForOp(DType::U8, "elu").bt()),
             Self::U32(_) => Err(Error::UnsupportedDTypeForOp(DType::U32, "elu").bt()),
             Self::I64(_) => Err(Error::UnsupportedDTypeForOp(DType::I64, "elu").bt()),
+            Self::U16(_)
+            | Self::I8(_)
+            | Self::I16(_)
+            | Self::I32(_)
+            | Self::Bool(_)
+            | Self::F8E4M3(_)
+            | Self::F8E5M2(_)
+            | Self::C64(_)
+            | Self::C128(_) => Err(Error::UnsupportedDTypeForOp(self.dtype(), "elu").bt()),
         }
     }
 
@@ -2032,10 +2861,37 @@ impl BackendStorage for CpuStorage {
                 let data = unary_map(storage, layout, B::u32);
                 Ok(Self::U32(data))
             }
+            Self::U16(storage) => {
+                let data = unary_map(storage, layout, B::u16);
+                Ok(Self::U16(data))
+            }
+            Self::I8(storage) => {
+                let data = unary_map(storage, layout, B::i8);
+                Ok(Self::I8(data))
+            }
+            Self::I16(storage) => {
+                let data = unary_map(storage, layout, B::i16);
+                Ok(Self::I16(data))
+            }
+            Self::I32(storage) => {
+                let data = unary_map(storage, layout, B::i32);
+                Ok(Self::I32(data))
+            }
             Self::I64(storage) => {
                 let data = unary_map(storage, layout, B::i64);
                 Ok(Self::I64(data))
             }
+            Self::C64(storage) if B::COMPLEX => {
+                let data = unary_map(storage, layout, B::c64);
+                Ok(Self::C64(data))
+            }
+            Self::C128(storage) if B::COMPLEX => {
+                let data = unary_map(storage, layout, B::c128);
+                Ok(Self::C128(data))
+            }
+            Self::Bool(_) | Self::F8E4M3(_) | Self::F8E5M2(_) | Self::C64(_) | Self::C128(_) => {
+                Err(Error::UnsupportedDTypeForOp(self.dtype(), B::NAME).bt())
+            }
         }
     }
 
@@ -2102,6 +2958,41 @@ impl BackendStorage for CpuStorage {
                 };
                 Ok(Self::U8(data))
             }
+            (Self::U16(lhs), Self::U16(rhs)) => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::u16);
+                Ok(Self::U16(data))
+            }
+            (Self::I8(lhs), Self::I8(rhs)) => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::i8);
+                Ok(Self::I8(data))
+            }
+            (Self::I16(lhs), Self::I16(rhs)) => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::i16);
+                Ok(Self::I16(data))
+            }
+            (Self::I32(lhs), Self::I32(rhs)) => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::i32);
+                Ok(Self::I32(data))
+            }
+            (Self::Bool(lhs), Self::Bool(rhs)) if B::BOOL => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::bool);
+                Ok(Self::Bool(data))
+            }
+            (Self::C64(lhs), Self::C64(rhs)) if B::COMPLEX => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::c64);
+                Ok(Self::C64(data))
+            }
+            (Self::C128(lhs), Self::C128(rhs)) if B::COMPLEX => {
+                let data = binary_map(lhs_l, rhs_l, lhs, rhs, B::c128);
+                Ok(Self::C128(data))
+            }
+            (Self::Bool(_), Self::Bool(_))
+            | (Self::F8E4M3(_), Self::F8E4M3(_))
+            | (Self::F8E5M2(_), Self::F8E5M2(_))
+            | (Self::C64(_), Self::C64(_))
+            | (Self::C128(_), Self::C128(_)) => {
+                Err(Error::UnsupportedDTypeForOp(self.dtype(), B::NAME).bt())
+            }
             _ => {
                 // This should be covered by the dtype check above.
                 Err(Error::DTypeMismatchBinaryOp {
@@ -2129,9 +3020,31 @@ impl BackendStorage for CpuStorage {
             (Self::U32(src), Self::U32(dst)) => {
                 copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
             }
+            (Self::U16(src), Self::U16(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
+            (Self::I8(src), Self::I8(dst)) => copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o),
+            (Self::I16(src), Self::I16(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
+            (Self::I32(src), Self::I32(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
             (Self::I64(src), Self::I64(dst)) => {
                 copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
             }
+            (Self::Bool(src), Self::Bool(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
+            (Self::F8E4M3(src), Self::F8E4M3(dst)) | (Self::F8E5M2(src), Self::F8E5M2(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
+            (Self::C64(src), Self::C64(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
+            (Self::C128(src), Self::C128(dst)) => {
+                copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
+            }
             (Self::BF16(src), Self::BF16(dst)) => {
                 copy2d_(src, dst, d1, d2, src_s, dst_s, src_o, dst_o)
             }
@@ -2160,7 +3073,17 @@ impl BackendStorage for CpuStorage {
         match (self, dst) {
             (Self::U8(src), Self::U8(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
             (Self::U32(src), Self::U32(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::U16(src), Self::U16(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::I8(src), Self::I8(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::I16(src), Self::I16(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::I32(src), Self::I32(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
             (Self::I64(src), Self::I64(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::Bool(src), Self::Bool(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::F8E4M3(src), Self::F8E4M3(dst)) | (Self::F8E5M2(src), Self::F8E5M2(dst)) => {
+                copy_strided_src_(src, dst, dst_offset, src_l)
+            }
+            (Self::C64(src), Self::C64(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
+            (Self::C128(src), Self::C128(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
             (Self::BF16(src), Self::BF16(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
             (Self::F16(src), Self::F16(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
             (Self::F32(src), Self::F32(dst)) => copy_strided_src_(src, dst, dst_offset, src_l),
@@ -2188,7 +3111,9 @@ impl BackendStorage for CpuStorage {
     ) -> Result<Self> {
         match self {
             Self::U8(pred) => WCond(pred, layout).map(t, t_l, f, f_l),
+            Self::Bool(pred) => WCond(bool_as_u8(pred), layout).map(t, t_l, f, f_l),
             Self::U32(pred) => WCond(pred, layout).map(t, t_l, f, f_l),
+            Self::I32(pred) => WCond(pred, layout).map(t, t_l, f, f_l),
             Self::I64(pred) => WCond(pred, layout).map(t, t_l, f, f_l),
             _ => Err(Error::UnsupportedDTypeForOp(self.dtype(), "where-cond")),
         }
@@ -2354,10 +3279,31 @@ impl BackendStorage for CpuStorage {
         ConvTranspose2D(params).map(self, l, kernel, kernel_l)
     }
 
+    fn conv3d(
+        &self,
+        l: &Layout,
+        kernel: &Self,
+        kernel_l: &Layout,
+        params: &crate::conv::ParamsConv3D,
+    ) -> Result<Self> {
+        Conv3D(params).map(self, l, kernel, kernel_l)
+    }
+
+    fn conv_transpose3d(
+        &self,
+        l: &Layout,
+        kernel: &Self,
+        kernel_l: &Layout,
+        params: &crate::conv::ParamsConvTranspose3D,
+    ) -> Result<Self> {
+        ConvTranspose3D(params).map(self, l, kernel, kernel_l)
+    }
+
     fn index_select(&self, ids: &Self, l: &Layout, ids_l: &Layout, dim: usize) -> Result<Self> {
         match ids {
             Self::U8(ids) => IndexSelect { ids, ids_l, dim }.map(self, l),
             Self::U32(ids) => IndexSelect { ids, ids_l, dim }.map(self, l),
+            Self::I32(ids) => IndexSelect { ids, ids_l, dim }.map(self, l),
             Self::I64(ids) => IndexSelect { ids, ids_l, dim }.map(self, l),
             _ => Err(Error::UnsupportedDTypeForOp(self.dtype(), "index-select").bt()),
         }
@@ -2367,6 +3313,7 @@ impl BackendStorage for CpuStorage {
         match ids {
             Self::U8(ids) => Gather { ids, ids_l, dim }.map(self, l),
             Self::U32(ids) => Gather { ids, ids_l, dim }.map(self, l),
+            Self::I32(ids) => Gather { ids, ids_l, dim }.map(self, l),
             Self::I64(ids) => Gather { ids, ids_l, dim }.map(self, l),
             _ => Err(Error::UnsupportedDTypeForOp(self.dtype(), "gather").bt()),
         }
@@ -2384,11 +3331,100 @@ impl BackendStorage for CpuStorage {
         match ids {
             Self::U8(ids) => ScatterAdd { ids, ids_l, dim }.map(self, l, src, src_l),
             Self::U32(ids) => ScatterAdd { ids, ids_l, dim }.map(self, l, src, src_l),
+            Self::I32(ids) => ScatterAdd { ids, ids_l, dim }.map(self, l, src, src_l),
             Self::I64(ids) => ScatterAdd { ids, ids_l, dim }.map(self, l, src, src_l),
             _ => Err(Error::UnsupportedDTypeForOp(self.dtype(), "scatter-add").bt()),
         }
     }
 
+    fn scatter(
+        &self,
+        l: &Layout,
+        ids: &Self,
+        ids_l: &Layout,
+        src: &Self,
+        src_l: &Layout,
+        dim: usize,
+    ) -> Result<Self> {
+        let reduce = None;
+        match ids {
+            Self::U8(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            Self::U32(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            Self::I32(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            Self::I64(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            _ => Err(Error::UnsupportedDTypeForOp(self.dtype(), "scatter").bt()),
+        }
+    }
+
+    fn scatter_reduce(
+        &self,
+        l: &Layout,
+        ids: &Self,
+        ids_l: &Layout,
+        src: &Self,
+        src_l: &Layout,
+        dim: usize,
+        op: ScatterReduceOp,
+        include_self: bool,
+    ) -> Result<Self> {
+        let reduce = Some((op, include_self));
+        match ids {
+            Self::U8(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            Self::U32(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            Self::I32(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            Self::I64(ids) => Scatter {
+                ids,
+                ids_l,
+                dim,
+                reduce,
+            }
+            .map(self, l, src, src_l),
+            _ => Err(Error::UnsupportedDTypeForOp(self.dtype(), "scatter-reduce").bt()),
+        }
+    }
+
     fn index_add(
         &self,
         l: &Layout,
@@ -2413,6 +3449,13 @@ impl BackendStorage for CpuStorage {
                 };
                 IndexAdd { ids, dim }.map(self, l, src, src_l)
             }
+            Self::I32(ids) => {
+                let ids = match ids_l.contiguous_offsets() {
+                    Some((a, b)) => &ids[a..b],
+                    None => Err(Error::RequiresContiguous { op: "index-add" }.bt())?,
+                };
+                IndexAdd { ids, dim }.map(self, l, src, src_l)
+            }
             Self::I64(ids) => {
                 let ids = match ids_l.contiguous_offsets() {
                     Some((a, b)) => &ids[a..b],
@@ -2431,7 +3474,15 @@ impl BackendStorage for CpuStorage {
         lhs_l: &Layout,
         rhs_l: &Layout,
     ) -> Result<Self> {
-        MatMul(bmnk).map(self, lhs_l, rhs, rhs_l)
+        match (self, rhs) {
+            (Self::C64(lhs), Self::C64(rhs)) => {
+                Ok(Self::C64(MatMul(bmnk).gemm(lhs, lhs_l, rhs, rhs_l)?))
+            }
+            (Self::C128(lhs), Self::C128(rhs)) => {
+                Ok(Self::C128(MatMul(bmnk).gemm(lhs, lhs_l, rhs, rhs_l)?))
+            }
+            _ => MatMul(bmnk).map(self, lhs_l, rhs, rhs_l),
+        }
     }
 
     fn device(&self) -> &Self::Device {
@@ -2484,9 +3535,18 @@ impl BackendDevice for CpuDevice {
         let elem_count = shape.elem_count();
         let mut rng = rand::thread_rng();
         match dtype {
-            DType::U8 | DType::U32 | DType::I64 => {
-                Err(Error::UnsupportedDTypeForOp(dtype, "rand_uniform").bt())
-            }
+            DType::U8
+            | DType::U16
+            | DType::U32
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::I64
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => Err(Error::UnsupportedDTypeForOp(dtype, "rand_uniform").bt()),
             DType::BF16 => {
                 let mut data = Vec::with_capacity(elem_count);
                 let uniform =
@@ -2530,9 +3590,18 @@ impl BackendDevice for CpuDevice {
         let elem_count = shape.elem_count();
         let mut rng = rand::thread_rng();
         match dtype {
-            DType::U8 | DType::U32 | DType::I64 => {
-                Err(Error::UnsupportedDTypeForOp(dtype, "rand_normal").bt())
-            }
+            DType::U8
+            | DType::U16
+            | DType::U32
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::I64
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => Err(Error::UnsupportedDTypeForOp(dtype, "rand_normal").bt()),
             DType::BF16 => {
                 let mut data = Vec::with_capacity(elem_count);
                 let normal = rand_distr::Normal::new(bf16::from_f64(mean), bf16::from_f64(std))
@@ -2589,11 +3658,43 @@ impl BackendDevice for CpuDevice {
                 v.set_len(elem_count);
                 CpuStorage::U32(v)
             }
+            DType::U16 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::U16(v)
+            }
+            DType::I8 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::I8(v)
+            }
+            DType::I16 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::I16(v)
+            }
+            DType::I32 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::I32(v)
+            }
             DType::I64 => {
                 let mut v = Vec::with_capacity(elem_count);
                 v.set_len(elem_count);
                 CpuStorage::I64(v)
             }
+            // Bool values have to be 0 or 1 so they are not left uninitialized.
+            DType::Bool => CpuStorage::Bool(vec![false; elem_count]),
+            DType::F8E4M3 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::F8E4M3(v)
+            }
+            DType::F8E5M2 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::F8E5M2(v)
+            }
             DType::BF16 => {
                 let mut v = Vec::with_capacity(elem_count);
                 v.set_len(elem_count);
@@ -2614,6 +3715,16 @@ impl BackendDevice for CpuDevice {
                 v.set_len(elem_count);
                 CpuStorage::F64(v)
             }
+            DType::C64 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::C64(v)
+            }
+            DType::C128 => {
+                let mut v = Vec::with_capacity(elem_count);
+                v.set_len(elem_count);
+                CpuStorage::C128(v)
+            }
         };
         Ok(storage)
     }
@@ -2623,11 +3734,20 @@ impl BackendDevice for CpuDevice {
         let storage = match dtype {
             DType::U8 => CpuStorage::U8(vec![1u8; elem_count]),
             DType::U32 => CpuStorage::U32(vec![1u32; elem_count]),
+            DType::U16 => CpuStorage::U16(vec![1u16; elem_count]),
+            DType::I8 => CpuStorage::I8(vec![1i8; elem_count]),
+            DType::I16 => CpuStorage::I16(vec![1i16; elem_count]),
+            DType::I32 => CpuStorage::I32(vec![1i32; elem_count]),
             DType::I64 => CpuStorage::I64(vec![1i64; elem_count]),
+            DType::Bool => CpuStorage::Bool(vec![true; elem_count]),
+            DType::F8E4M3 => CpuStorage::F8E4M3(vec![fp8::f32_to_f8e4m3(1.); elem_count]),
+            DType::F8E5M2 => CpuStorage::F8E5M2(vec![fp8::f32_to_f8e5m2(1.); elem_count]),
             DType::BF16 => CpuStorage::BF16(vec![bf16::ONE; elem_count]),
             DType::F16 => CpuStorage::F16(vec![f16::ONE; elem_count]),
             DType::F32 => CpuStorage::F32(vec![1f32; elem_count]),
             DType::F64 => CpuStorage::F64(vec![1f64; elem_count]),
+            DType::C64 => CpuStorage::C64(vec![Complex32::new(1., 0.); elem_count]),
+            DType::C128 => CpuStorage::C128(vec![Complex64::new(1., 0.); elem_count]),
         };
         Ok(storage)
     }
@@ -2637,11 +3757,20 @@ impl BackendDevice for CpuDevice {
         let storage = match dtype {
             DType::U8 => CpuStorage::U8(vec![0u8; elem_count]),
             DType::U32 => CpuStorage::U32(vec![0u32; elem_count]),
+            DType::U16 => CpuStorage::U16(vec![0u16; elem_count]),
+            DType::I8 => CpuStorage::I8(vec![0i8; elem_count]),
+            DType::I16 => CpuStorage::I16(vec![0i16; elem_count]),
+            DType::I32 => CpuStorage::I32(vec![0i32; elem_count]),
             DType::I64 => CpuStorage::I64(vec![0i64; elem_count]),
+            DType::Bool => CpuStorage::Bool(vec![false; elem_count]),
+            DType::F8E4M3 => CpuStorage::F8E4M3(vec![0u8; elem_count]),
+            DType::F8E5M2 => CpuStorage::F8E5M2(vec![0u8; elem_count]),
             DType::BF16 => CpuStorage::BF16(vec![bf16::ZERO; elem_count]),
             DType::F16 => CpuStorage::F16(vec![f16::ZERO; elem_count]),
             DType::F32 => CpuStorage::F32(vec![0f32; elem_count]),
             DType::F64 => CpuStorage::F64(vec![0f64; elem_count]),
+            DType::C64 => CpuStorage::C64(vec![Complex32::new(0., 0.); elem_count]),
+            DType::C128 => CpuStorage::C128(vec![Complex64::new(0., 0.); elem_count]),
         };
         Ok(storage)
     }
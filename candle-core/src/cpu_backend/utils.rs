@@ -1,16 +1,50 @@
 /// Helper functions to write CPU kernels.
 use crate::backend::BackendStorage;
-use crate::{Error, Layout, Result, WithDType};
+use crate::{DType, Error, Layout, NumDType, Result};
 
 type C = super::CpuStorage;
+
+/// Views bool values as `u8` ones so that the kernels written for the numeric types can move them
+/// around.
+pub fn bool_as_u8(vs: &[bool]) -> &[u8] {
+    // SAFETY: a bool is a single byte holding 0 or 1 so it is also a valid u8.
+    unsafe { std::slice::from_raw_parts(vs.as_ptr() as *const u8, vs.len()) }
+}
+
+/// The reverse of `bool_as_u8` for the values returned by the kernels.
+pub fn u8_to_bool(vs: Vec<u8>) -> Vec<bool> {
+    vs.into_iter().map(|v| v != 0).collect()
+}
+
+/// The name of the kernel used in the error raised on unsupported bool, fp8 or complex storage.
+fn kernel_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
 pub trait Map1 {
-    fn f<T: WithDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>>;
+    /// Whether the kernel can run on bool storage, this is only the case for the kernels that
+    /// move values around without combining them.
+    const BOOL: bool = false;
+
+    fn f<T: NumDType>(&self, vs: &[T], layout: &Layout) -> Result<Vec<T>>;
 
     fn map(&self, vs: &C, layout: &Layout) -> Result<C> {
         match vs {
             C::U8(vs) => Ok(C::U8(self.f(vs, layout)?)),
+            C::U16(vs) => Ok(C::U16(self.f(vs, layout)?)),
             C::U32(vs) => Ok(C::U32(self.f(vs, layout)?)),
+            C::I8(vs) => Ok(C::I8(self.f(vs, layout)?)),
+            C::I16(vs) => Ok(C::I16(self.f(vs, layout)?)),
+            C::I32(vs) => Ok(C::I32(self.f(vs, layout)?)),
             C::I64(vs) => Ok(C::I64(self.f(vs, layout)?)),
+            C::Bool(vs) if Self::BOOL => Ok(C::Bool(u8_to_bool(self.f(bool_as_u8(vs), layout)?))),
+            C::Bool(_) => {
+                Err(Error::UnsupportedDTypeForOp(DType::Bool, kernel_name::<Self>()).bt())
+            }
+            C::F8E4M3(_) | C::F8E5M2(_) | C::C64(_) | C::C128(_) => {
+                Err(Error::UnsupportedDTypeForOp(vs.dtype(), kernel_name::<Self>()).bt())
+            }
             C::BF16(vs) => Ok(C::BF16(self.f(vs, layout)?)),
             C::F16(vs) => Ok(C::F16(self.f(vs, layout)?)),
             C::F32(vs) => Ok(C::F32(self.f(vs, layout)?)),
@@ -20,13 +54,29 @@ pub trait Map1 {
 }
 
 pub trait Map1Any {
-    fn f<T: WithDType, W: Fn(Vec<T>) -> C>(&self, vs: &[T], layout: &Layout, wrap: W) -> Result<C>;
+    /// See `Map1::BOOL`.
+    const BOOL: bool = false;
+
+    fn f<T: NumDType, W: Fn(Vec<T>) -> C>(&self, vs: &[T], layout: &Layout, wrap: W) -> Result<C>;
 
     fn map(&self, vs: &C, layout: &Layout) -> Result<C> {
         match vs {
             C::U8(vs) => Ok(self.f(vs, layout, C::U8)?),
+            C::U16(vs) => Ok(self.f(vs, layout, C::U16)?),
             C::U32(vs) => Ok(self.f(vs, layout, C::U32)?),
+            C::I8(vs) => Ok(self.f(vs, layout, C::I8)?),
+            C::I16(vs) => Ok(self.f(vs, layout, C::I16)?),
+            C::I32(vs) => Ok(self.f(vs, layout, C::I32)?),
             C::I64(vs) => Ok(self.f(vs, layout, C::I64)?),
+            C::Bool(vs) if Self::BOOL => {
+                Ok(self.f(bool_as_u8(vs), layout, |vs| C::Bool(u8_to_bool(vs)))?)
+            }
+            C::Bool(_) => {
+                Err(Error::UnsupportedDTypeForOp(DType::Bool, kernel_name::<Self>()).bt())
+            }
+            C::F8E4M3(_) | C::F8E5M2(_) | C::C64(_) | C::C128(_) => {
+                Err(Error::UnsupportedDTypeForOp(vs.dtype(), kernel_name::<Self>()).bt())
+            }
             C::BF16(vs) => Ok(self.f(vs, layout, C::BF16)?),
             C::F16(vs) => Ok(self.f(vs, layout, C::F16)?),
             C::F32(vs) => Ok(self.f(vs, layout, C::F32)?),
@@ -37,13 +87,33 @@ pub trait Map1Any {
 
 pub trait Map2 {
     const OP: &'static str;
-    fn f<T: WithDType>(&self, v1: &[T], l1: &Layout, v2: &[T], l2: &Layout) -> Result<Vec<T>>;
+    /// See `Map1::BOOL`.
+    const BOOL: bool = false;
+
+    fn f<T: NumDType>(&self, v1: &[T], l1: &Layout, v2: &[T], l2: &Layout) -> Result<Vec<T>>;
 
     fn map(&self, v1: &C, l1: &Layout, v2: &C, l2: &Layout) -> Result<C> {
         match (v1, v2) {
             (C::U8(v1), C::U8(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
+            (C::U16(v1), C::U16(v2)) => Ok(C::U16(self.f(v1, l1, v2, l2)?)),
             (C::U32(v1), C::U32(v2)) => Ok(C::U32(self.f(v1, l1, v2, l2)?)),
+            (C::I8(v1), C::I8(v2)) => Ok(C::I8(self.f(v1, l1, v2, l2)?)),
+            (C::I16(v1), C::I16(v2)) => Ok(C::I16(self.f(v1, l1, v2, l2)?)),
+            (C::I32(v1), C::I32(v2)) => Ok(C::I32(self.f(v1, l1, v2, l2)?)),
             (C::I64(v1), C::I64(v2)) => Ok(C::I64(self.f(v1, l1, v2, l2)?)),
+            (C::Bool(v1), C::Bool(v2)) if Self::BOOL => {
+                let vs = self.f(bool_as_u8(v1), l1, bool_as_u8(v2), l2)?;
+                Ok(C::Bool(u8_to_bool(vs)))
+            }
+            (C::Bool(_), C::Bool(_)) => {
+                Err(Error::UnsupportedDTypeForOp(DType::Bool, Self::OP).bt())
+            }
+            (C::F8E4M3(_), C::F8E4M3(_))
+            | (C::F8E5M2(_), C::F8E5M2(_))
+            | (C::C64(_), C::C64(_))
+            | (C::C128(_), C::C128(_)) => {
+                Err(Error::UnsupportedDTypeForOp(v1.dtype(), Self::OP).bt())
+            }
             (C::BF16(v1), C::BF16(v2)) => Ok(C::BF16(self.f(v1, l1, v2, l2)?)),
             (C::F16(v1), C::F16(v2)) => Ok(C::F16(self.f(v1, l1, v2, l2)?)),
             (C::F32(v1), C::F32(v2)) => Ok(C::F32(self.f(v1, l1, v2, l2)?)),
@@ -58,19 +128,33 @@ pub trait Map2 {
     }
 }
 
-pub trait Map2U8 {
+/// Binary kernels returning a bool tensor, e.g. comparisons.
+pub trait Map2Bool {
     const OP: &'static str;
-    fn f<T: WithDType>(&self, v1: &[T], l1: &Layout, v2: &[T], l2: &Layout) -> Result<Vec<u8>>;
+    fn f<T: NumDType>(&self, v1: &[T], l1: &Layout, v2: &[T], l2: &Layout) -> Result<Vec<bool>>;
 
     fn map(&self, v1: &C, l1: &Layout, v2: &C, l2: &Layout) -> Result<C> {
         match (v1, v2) {
-            (C::U8(v1), C::U8(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
-            (C::U32(v1), C::U32(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
-            (C::I64(v1), C::I64(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
-            (C::BF16(v1), C::BF16(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
-            (C::F16(v1), C::F16(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
-            (C::F32(v1), C::F32(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
-            (C::F64(v1), C::F64(v2)) => Ok(C::U8(self.f(v1, l1, v2, l2)?)),
+            (C::U8(v1), C::U8(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::U16(v1), C::U16(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::U32(v1), C::U32(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::I8(v1), C::I8(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::I16(v1), C::I16(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::I32(v1), C::I32(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::I64(v1), C::I64(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::Bool(v1), C::Bool(v2)) => {
+                Ok(C::Bool(self.f(bool_as_u8(v1), l1, bool_as_u8(v2), l2)?))
+            }
+            (C::F8E4M3(_), C::F8E4M3(_))
+            | (C::F8E5M2(_), C::F8E5M2(_))
+            | (C::C64(_), C::C64(_))
+            | (C::C128(_), C::C128(_)) => {
+                Err(Error::UnsupportedDTypeForOp(v1.dtype(), Self::OP).bt())
+            }
+            (C::BF16(v1), C::BF16(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::F16(v1), C::F16(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::F32(v1), C::F32(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
+            (C::F64(v1), C::F64(v2)) => Ok(C::Bool(self.f(v1, l1, v2, l2)?)),
             _ => Err(Error::DTypeMismatchBinaryOp {
                 lhs: v1.dtype(),
                 rhs: v2.dtype(),
@@ -1,4 +1,4 @@
-use crate::backend::BackendDevice;
+use crate::backend::{BackendDevice, BackendStorage};
 use crate::{CpuStorage, CpuStorageRef, DType, Layout, Result, Shape};
 pub use candle_kernels as kernels;
 pub use cudarc;
@@ -105,6 +105,19 @@ impl CudaDevice {
                 unsafe { func.launch(cfg, params) }.w()?;
                 CudaStorageSlice::I64(data)
             }
+            DType::U16
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => Err(CudaError::UnsupportedDtype {
+                dtype,
+                op: "const_impl",
+            })
+            .w()?,
             DType::BF16 => {
                 // SAFETY: Set later by running the fill kernel.
                 let data = unsafe { self.alloc::<bf16>(elem_count) }.w()?;
@@ -228,6 +241,19 @@ impl BackendDevice for CudaDevice {
                 let data = self.alloc_zeros::<i64>(elem_count).w()?;
                 CudaStorageSlice::I64(data)
             }
+            DType::U16
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => Err(CudaError::UnsupportedDtype {
+                dtype,
+                op: "zeros_impl",
+            })
+            .w()?,
             DType::BF16 => {
                 let data = self.alloc_zeros::<bf16>(elem_count).w()?;
                 CudaStorageSlice::BF16(data)
@@ -257,13 +283,24 @@ impl BackendDevice for CudaDevice {
         let slice = match dtype {
             // TODO: Add support for F16 and BF16 though this is likely to require some upstream
             // cudarc changes.
-            DType::U8 | DType::U32 | DType::I64 | DType::F16 | DType::BF16 => {
-                Err(CudaError::UnsupportedDtype {
-                    dtype,
-                    op: "rand_uniform",
-                })
-                .w()?
-            }
+            DType::U8
+            | DType::U16
+            | DType::U32
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::I64
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128
+            | DType::F16
+            | DType::BF16 => Err(CudaError::UnsupportedDtype {
+                dtype,
+                op: "rand_uniform",
+            })
+            .w()?,
             DType::F32 => {
                 let mut data = unsafe { self.alloc::<f32>(elem_count) }.w()?;
                 curand.0.fill_with_uniform(&mut data).w()?;
@@ -301,13 +338,24 @@ impl BackendDevice for CudaDevice {
             elem_count
         };
         let slice = match dtype {
-            DType::U8 | DType::U32 | DType::I64 | DType::F16 | DType::BF16 => {
-                Err(CudaError::UnsupportedDtype {
-                    dtype,
-                    op: "rand_normal",
-                })
-                .w()?
-            }
+            DType::U8
+            | DType::U16
+            | DType::U32
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::I64
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128
+            | DType::F16
+            | DType::BF16 => Err(CudaError::UnsupportedDtype {
+                dtype,
+                op: "rand_normal",
+            })
+            .w()?,
             DType::F32 => {
                 let mut data = unsafe { self.alloc::<f32>(elem_count_round) }.w()?;
                 curand
@@ -347,6 +395,19 @@ impl BackendDevice for CudaDevice {
                 let data = self.alloc::<i64>(elem_count).w()?;
                 CudaStorageSlice::I64(data)
             }
+            DType::U16
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => Err(CudaError::UnsupportedDtype {
+                dtype,
+                op: "alloc_uninit",
+            })
+            .w()?,
             DType::BF16 => {
                 let data = self.alloc::<bf16>(elem_count).w()?;
                 CudaStorageSlice::BF16(data)
@@ -384,6 +445,22 @@ impl BackendDevice for CudaDevice {
                 let data = self.htod_sync_copy(storage).w()?;
                 CudaStorageSlice::I64(data)
             }
+            // There is no boolean storage on this backend, the values are uploaded as `u8`.
+            CpuStorageRef::Bool(storage) => {
+                return self.storage_from_slice(crate::cpu_backend::bool_as_u8(storage))
+            }
+            CpuStorageRef::U16(_)
+            | CpuStorageRef::I8(_)
+            | CpuStorageRef::I16(_)
+            | CpuStorageRef::I32(_)
+            | CpuStorageRef::F8E4M3(_)
+            | CpuStorageRef::F8E5M2(_)
+            | CpuStorageRef::C64(_)
+            | CpuStorageRef::C128(_) => Err(CudaError::UnsupportedDtype {
+                dtype: T::DTYPE,
+                op: "storage_from_slice",
+            })
+            .w()?,
             CpuStorageRef::BF16(storage) => {
                 let data = self.htod_sync_copy(storage).w()?;
                 CudaStorageSlice::BF16(data)
@@ -421,6 +498,22 @@ impl BackendDevice for CudaDevice {
                 let data = self.htod_sync_copy(storage).w()?;
                 CudaStorageSlice::I64(data)
             }
+            // There is no boolean storage on this backend, the values are uploaded as `u8`.
+            CpuStorage::Bool(storage) => {
+                return self.storage_from_slice(crate::cpu_backend::bool_as_u8(storage))
+            }
+            CpuStorage::U16(_)
+            | CpuStorage::I8(_)
+            | CpuStorage::I16(_)
+            | CpuStorage::I32(_)
+            | CpuStorage::F8E4M3(_)
+            | CpuStorage::F8E5M2(_)
+            | CpuStorage::C64(_)
+            | CpuStorage::C128(_) => Err(CudaError::UnsupportedDtype {
+                dtype: storage.dtype(),
+                op: "storage_from_cpu_storage",
+            })
+            .w()?,
             CpuStorage::BF16(storage) => {
                 let data = self.htod_sync_copy(storage).w()?;
                 CudaStorageSlice::BF16(data)
@@ -458,6 +551,22 @@ impl BackendDevice for CudaDevice {
                 let data = self.htod_copy(storage).w()?;
                 CudaStorageSlice::I64(data)
             }
+            // There is no boolean storage on this backend, the values are uploaded as `u8`.
+            CpuStorage::Bool(storage) => {
+                return self.storage_from_slice(crate::cpu_backend::bool_as_u8(storage))
+            }
+            CpuStorage::U16(_)
+            | CpuStorage::I8(_)
+            | CpuStorage::I16(_)
+            | CpuStorage::I32(_)
+            | CpuStorage::F8E4M3(_)
+            | CpuStorage::F8E5M2(_)
+            | CpuStorage::C64(_)
+            | CpuStorage::C128(_) => Err(CudaError::UnsupportedDtype {
+                dtype: storage.dtype(),
+                op: "storage_from_cpu_storage_owned",
+            })
+            .w()?,
             CpuStorage::BF16(storage) => {
                 let data = self.htod_copy(storage).w()?;
                 CudaStorageSlice::BF16(data)
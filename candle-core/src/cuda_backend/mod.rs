@@ -1,7 +1,7 @@
 //! Implementation of Backend traits for CUDA device
 //!
 use crate::backend::{BackendDevice, BackendStorage};
-use crate::op::{BinaryOpT, CmpOp, ReduceOp, UnaryOpT};
+use crate::op::{BinaryOpT, BitwiseOp, CmpOp, ReduceOp, Resample, ScanOp, UnaryOpT};
 use crate::{CpuStorage, DType, Layout, Result, Shape, WithDType};
 pub use candle_kernels as kernels;
 pub use cudarc;
@@ -1205,6 +1205,19 @@ impl BackendStorage for CudaStorage {
                 unsafe { func.launch(cfg, params) }.w()?;
                 CudaStorageSlice::I64(out)
             }
+            DType::U16
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => Err(CudaError::UnsupportedDtype {
+                dtype,
+                op: "to_dtype",
+            })
+            .w()?,
             DType::BF16 => {
                 let out = unsafe { dev.alloc::<bf16>(el) }.w()?;
                 let params = (el, dims.len(), &ds, *inp, &out);
@@ -1260,12 +1273,24 @@ impl BackendStorage for CudaStorage {
         Ok(Self { slice, device })
     }
 
+    fn scan_op(&self, op: ScanOp, _: &Layout, _: usize, _: bool) -> Result<Self> {
+        crate::bail!("{} is not supported on cuda", op.name())
+    }
+
+    fn resample(&self, _: &Layout, _: &Resample) -> Result<Self> {
+        crate::bail!("resample is not supported on cuda")
+    }
+
     fn cmp(&self, op: CmpOp, rhs: &Self, lhs_l: &Layout, rhs_l: &Layout) -> Result<Self> {
         let device = self.device().clone();
         let slice = Cmp(op).map(&self.slice, lhs_l, &rhs.slice, rhs_l, &device)?;
         Ok(Self { slice, device })
     }
 
+    fn bitwise(&self, op: BitwiseOp, _: &Self, _: &Layout, _: &Layout) -> Result<Self> {
+        crate::bail!("{} is not supported on cuda", op.name())
+    }
+
     fn unary_impl<U: UnaryOpT>(&self, layout: &Layout) -> Result<Self> {
         let device = self.device().clone();
         let slice = U::V.map(&self.slice, &device, layout)?;
@@ -1583,6 +1608,26 @@ impl BackendStorage for CudaStorage {
         Ok(Self { slice, device })
     }
 
+    fn conv3d(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &crate::conv::ParamsConv3D,
+    ) -> Result<Self> {
+        crate::bail!("conv3d is not supported on cuda")
+    }
+
+    fn conv_transpose3d(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &crate::conv::ParamsConvTranspose3D,
+    ) -> Result<Self> {
+        crate::bail!("conv_transpose3d is not supported on cuda")
+    }
+
     fn avg_pool2d(&self, l: &Layout, k: (usize, usize), stride: (usize, usize)) -> Result<Self> {
         let device = self.device().clone();
         let slice = Pool2D {
@@ -1644,6 +1689,30 @@ impl BackendStorage for CudaStorage {
         ScatterAdd(ids, ids_l, dim).map(&mut acc.slice, l.shape(), &src.slice, src_l, &device)?;
         Ok(acc)
     }
+    fn scatter(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: usize,
+    ) -> Result<Self> {
+        crate::bail!("scatter is not supported on cuda")
+    }
+    fn scatter_reduce(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: usize,
+        _: crate::op::ScatterReduceOp,
+        _: bool,
+    ) -> Result<Self> {
+        crate::bail!("scatter-reduce is not supported on cuda")
+    }
     fn index_add(
         &self,
         l: &Layout,
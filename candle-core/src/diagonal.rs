@@ -0,0 +1,162 @@
+//! Triangular parts, diagonals and traces of batched matrices.
+//!
+//! These ops are built on `where_cond`, `index_select` and `index_add` so that they work on all
+//! devices and dtypes and get their gradients from the existing ops.
+use crate::shape::Dim;
+use crate::{bail, Result, Tensor, D};
+
+/// The positions `(row, col)` of a `rows` by `cols` matrix on the diagonal with the given offset,
+/// a positive `offset` selects a diagonal above the main one.
+fn diag_positions(rows: usize, cols: usize, offset: i64) -> Vec<(usize, usize)> {
+    (0..rows)
+        .filter_map(|row| {
+            let col = row as i64 + offset;
+            (0 <= col && col < cols as i64).then_some((row, col as usize))
+        })
+        .collect()
+}
+
+impl Tensor {
+    fn triangular(&self, diagonal: i64, upper: bool) -> Result<Self> {
+        let op = if upper { "triu" } else { "tril" };
+        let (rows, cols) = match self.dims() {
+            [.., rows, cols] => (*rows, *cols),
+            _ => bail!(
+                "{op} expects at least two dimensions, got {:?}",
+                self.shape()
+            ),
+        };
+        let mask: Vec<u8> = (0..rows as i64)
+            .flat_map(|row| {
+                (0..cols as i64).map(move |col| {
+                    let keep = if upper {
+                        col - row >= diagonal
+                    } else {
+                        col - row <= diagonal
+                    };
+                    u8::from(keep)
+                })
+            })
+            .collect();
+        let mask = Tensor::from_vec(mask, (rows, cols), self.device())?;
+        mask.broadcast_as(self.shape())?
+            .where_cond(self, &self.zeros_like()?)
+    }
+
+    /// Returns the lower triangular part of the matrices formed by the last two dimensions, the
+    /// other elements are set to zero. The elements on the diagonal with the given offset and
+    /// below are kept, a positive `diagonal` selects a diagonal above the main one.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1u32, 2, 3], [4, 5, 6]], &Device::Cpu)?;
+    /// assert_eq!(a.tril(0)?.to_vec2::<u32>()?, &[[1, 0, 0], [4, 5, 0]]);
+    /// assert_eq!(a.tril(-1)?.to_vec2::<u32>()?, &[[0, 0, 0], [4, 0, 0]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn tril(&self, diagonal: i64) -> Result<Self> {
+        self.triangular(diagonal, false)
+    }
+
+    /// Returns the upper triangular part of the matrices formed by the last two dimensions, the
+    /// other elements are set to zero. The elements on the diagonal with the given offset and
+    /// above are kept.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1u32, 2, 3], [4, 5, 6]], &Device::Cpu)?;
+    /// assert_eq!(a.triu(0)?.to_vec2::<u32>()?, &[[1, 2, 3], [0, 5, 6]]);
+    /// assert_eq!(a.triu(1)?.to_vec2::<u32>()?, &[[0, 2, 3], [0, 0, 6]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn triu(&self, diagonal: i64) -> Result<Self> {
+        self.triangular(diagonal, true)
+    }
+
+    /// Returns the diagonal with the given offset of the matrices formed by `dim1` and `dim2`.
+    /// These two dimensions are removed and the diagonal is appended as the last dimension.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1u32, 2, 3], [4, 5, 6]], &Device::Cpu)?;
+    /// assert_eq!(a.diagonal(0, 0, 1)?.to_vec1::<u32>()?, &[1, 5]);
+    /// assert_eq!(a.diagonal(1, 0, 1)?.to_vec1::<u32>()?, &[2, 6]);
+    /// assert_eq!(a.diagonal(-1, 0, 1)?.to_vec1::<u32>()?, &[4]);
+    /// assert_eq!(a.diagonal(0, 1, 0)?.to_vec1::<u32>()?, &[1, 5]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn diagonal<D1: Dim, D2: Dim>(&self, offset: i64, dim1: D1, dim2: D2) -> Result<Self> {
+        let dim1 = dim1.to_index(self.shape(), "diagonal")?;
+        let dim2 = dim2.to_index(self.shape(), "diagonal")?;
+        if dim1 == dim2 {
+            bail!("diagonal: dim1 and dim2 should be different, got {dim1}")
+        }
+        let (rows, cols) = (self.dims()[dim1], self.dims()[dim2]);
+        let mut perm: Vec<usize> = (0..self.rank())
+            .filter(|&d| d != dim1 && d != dim2)
+            .collect();
+        let mut dims: Vec<usize> = perm.iter().map(|&d| self.dims()[d]).collect();
+        perm.extend([dim1, dim2]);
+        dims.push(rows * cols);
+        let indexes: Vec<u32> = diag_positions(rows, cols, offset)
+            .into_iter()
+            .map(|(row, col)| (row * cols + col) as u32)
+            .collect();
+        let indexes = Tensor::from_slice(&indexes, indexes.len(), self.device())?;
+        self.permute(perm)?
+            .reshape(dims)?
+            .index_select(&indexes, D::Minus1)
+    }
+
+    /// Creates a tensor where the last dimension is used as the diagonal with the given offset of
+    /// square matrices, all the other elements are zeros. The last dimension of size `n` is
+    /// replaced by two dimensions of size `n + offset.abs()`.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[1f32, 2.], &Device::Cpu)?;
+    /// assert_eq!(a.diag_embed(0)?.to_vec2::<f32>()?, &[[1., 0.], [0., 2.]]);
+    /// assert_eq!(
+    ///     a.diag_embed(-1)?.to_vec2::<f32>()?,
+    ///     &[[0., 0., 0.], [1., 0., 0.], [0., 2., 0.]]
+    /// );
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn diag_embed(&self, offset: i64) -> Result<Self> {
+        let n = match self.dims().last() {
+            Some(n) => *n,
+            None => bail!("diag_embed expects at least one dimension"),
+        };
+        let size = n + offset.unsigned_abs() as usize;
+        let indexes: Vec<u32> = diag_positions(size, size, offset)
+            .into_iter()
+            .map(|(row, col)| (row * size + col) as u32)
+            .collect();
+        let indexes = Tensor::from_slice(&indexes, indexes.len(), self.device())?;
+        let mut dims = self.dims().to_vec();
+        dims.pop();
+        let batch_dims = dims.clone();
+        dims.push(size * size);
+        let zeros = Tensor::zeros(dims, self.dtype(), self.device())?;
+        let res = zeros.index_add(&indexes, &self.contiguous()?, D::Minus1)?;
+        res.reshape([batch_dims.as_slice(), &[size, size]].concat())
+    }
+
+    /// Returns the sum of the main diagonal of the matrices formed by the last two dimensions.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1f32, 2., 3.], [4., 5., 6.]], &Device::Cpu)?;
+    /// assert_eq!(a.trace()?.to_scalar::<f32>()?, 6.);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn trace(&self) -> Result<Self> {
+        if self.rank() < 2 {
+            bail!(
+                "trace expects at least two dimensions, got {:?}",
+                self.shape()
+            )
+        }
+        self.diagonal(0, D::Minus2, D::Minus1)?.sum(D::Minus1)
+    }
+}
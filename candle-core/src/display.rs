@@ -20,15 +20,21 @@ impl Tensor {
             }
         };
 
+        // Storage only dtypes such as fp8 are read as f32 values.
+        let values = if self.dtype().is_storage_only() {
+            self.to_dtype(DType::F32).unwrap_or_else(|_| self.clone())
+        } else {
+            self.clone()
+        };
         write!(f, "Tensor[")?;
         match self.dims() {
             [] => {
-                if let Ok(v) = self.to_scalar::<T>() {
+                if let Ok(v) = values.to_scalar::<T>() {
                     write!(f, "{v}")?
                 }
             }
             [s] if *s < 10 => {
-                if let Ok(vs) = self.to_vec1::<T>() {
+                if let Ok(vs) = values.to_vec1::<T>() {
                     for (i, v) in vs.iter().enumerate() {
                         if i > 0 {
                             write!(f, ", ")?;
@@ -49,18 +55,52 @@ impl Tensor {
         }
         write!(f, "; {}{}]", self.dtype().as_str(), device_str)
     }
+
+    fn fmt_complex(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let parts = match self.dims() {
+            [] => self.unsqueeze(0),
+            [s] if *s < 10 => Ok(self.clone()),
+            _ => return self.fmt_dt::<f64>(f),
+        };
+        let parts = parts
+            .and_then(|t| t.view_as_real())
+            .and_then(|t| t.to_dtype(DType::F64))
+            .and_then(|t| t.to_vec2::<f64>());
+        write!(f, "Tensor[")?;
+        if let Ok(parts) = parts {
+            for (i, v) in parts.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}{:+}i", v[0], v[1])?;
+            }
+        }
+        let device_str = match self.device().location() {
+            crate::DeviceLocation::Cpu => "".to_owned(),
+            crate::DeviceLocation::Cuda { gpu_id } => format!(", cuda:{gpu_id}"),
+            crate::DeviceLocation::Metal { gpu_id } => format!(", metal:{gpu_id}"),
+        };
+        write!(f, "; {}{}]", self.dtype().as_str(), device_str)
+    }
 }
 
 impl std::fmt::Debug for Tensor {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self.dtype() {
             DType::U8 => self.fmt_dt::<u8>(f),
+            DType::Bool => self.fmt_dt::<bool>(f),
+            DType::U16 => self.fmt_dt::<u16>(f),
             DType::U32 => self.fmt_dt::<u32>(f),
+            DType::I8 => self.fmt_dt::<i8>(f),
+            DType::I16 => self.fmt_dt::<i16>(f),
+            DType::I32 => self.fmt_dt::<i32>(f),
             DType::I64 => self.fmt_dt::<i64>(f),
             DType::BF16 => self.fmt_dt::<bf16>(f),
             DType::F16 => self.fmt_dt::<f16>(f),
             DType::F32 => self.fmt_dt::<f32>(f),
             DType::F64 => self.fmt_dt::<f64>(f),
+            DType::F8E4M3 | DType::F8E5M2 => self.fmt_dt::<f32>(f),
+            DType::C64 | DType::C128 => self.fmt_complex(f),
         }
     }
 }
@@ -407,6 +447,16 @@ where
     }
 }
 
+struct BoolFormatter;
+
+impl TensorFormatter for BoolFormatter {
+    type Elem = bool;
+
+    fn fmt<T: std::fmt::Write>(&self, v: Self::Elem, max_w: usize, f: &mut T) -> std::fmt::Result {
+        write!(f, "{v:>max_w$}")
+    }
+}
+
 fn get_summarized_data(t: &Tensor, edge_items: usize) -> Result<Tensor> {
     let dims = t.dims();
     if dims.is_empty() {
@@ -458,18 +508,48 @@ impl std::fmt::Display for Tensor {
                 tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
                 writeln!(f)?;
             }
+            DType::U16 => {
+                let tf: IntFormatter<u16> = IntFormatter::new();
+                let max_w = tf.max_width(&to_display);
+                tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
+                writeln!(f)?;
+            }
             DType::U32 => {
                 let tf: IntFormatter<u32> = IntFormatter::new();
                 let max_w = tf.max_width(&to_display);
                 tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
                 writeln!(f)?;
             }
+            DType::I8 => {
+                let tf: IntFormatter<i8> = IntFormatter::new();
+                let max_w = tf.max_width(&to_display);
+                tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
+                writeln!(f)?;
+            }
+            DType::I16 => {
+                let tf: IntFormatter<i16> = IntFormatter::new();
+                let max_w = tf.max_width(&to_display);
+                tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
+                writeln!(f)?;
+            }
+            DType::I32 => {
+                let tf: IntFormatter<i32> = IntFormatter::new();
+                let max_w = tf.max_width(&to_display);
+                tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
+                writeln!(f)?;
+            }
             DType::I64 => {
                 let tf: IntFormatter<i64> = IntFormatter::new();
                 let max_w = tf.max_width(&to_display);
                 tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
                 writeln!(f)?;
             }
+            DType::Bool => {
+                let tf = BoolFormatter;
+                let max_w = tf.max_width(&to_display);
+                tf.fmt_tensor(self, 1, max_w, summarize, &po, f)?;
+                writeln!(f)?;
+            }
             DType::BF16 => {
                 if let Ok(tf) = FloatFormatter::<bf16>::new(&to_display, &po) {
                     let max_w = tf.max_width(&to_display);
@@ -498,6 +578,38 @@ impl std::fmt::Display for Tensor {
                     writeln!(f)?;
                 }
             }
+            DType::F8E4M3 | DType::F8E5M2 => {
+                // The fp8 values are printed through their f32 conversion.
+                let (t, to_display) =
+                    match (self.to_dtype(DType::F32), to_display.to_dtype(DType::F32)) {
+                        (Ok(t), Ok(to_display)) => (t, to_display),
+                        (Err(err), _) | (_, Err(err)) => return write!(f, "{err:?}"),
+                    };
+                if let Ok(tf) = FloatFormatter::<f32>::new(&to_display, &po) {
+                    let max_w = tf.max_width(&to_display);
+                    tf.fmt_tensor(&t, 1, max_w, summarize, &po, f)?;
+                    writeln!(f)?;
+                }
+            }
+            DType::C64 | DType::C128 => {
+                // The complex values are printed as (real, imaginary) pairs along an additional
+                // trailing dimension.
+                let (t, to_display) = match (self.view_as_real(), to_display.view_as_real()) {
+                    (Ok(t), Ok(to_display)) => (t, to_display),
+                    (Err(err), _) | (_, Err(err)) => return write!(f, "{err:?}"),
+                };
+                if t.dtype() == DType::F32 {
+                    if let Ok(tf) = FloatFormatter::<f32>::new(&to_display, &po) {
+                        let max_w = tf.max_width(&to_display);
+                        tf.fmt_tensor(&t, 1, max_w, summarize, &po, f)?;
+                        writeln!(f)?;
+                    }
+                } else if let Ok(tf) = FloatFormatter::<f64>::new(&to_display, &po) {
+                    let max_w = tf.max_width(&to_display);
+                    tf.fmt_tensor(&t, 1, max_w, summarize, &po, f)?;
+                    writeln!(f)?;
+                }
+            }
         };
 
         let device_str = match self.device().location() {
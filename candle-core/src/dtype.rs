@@ -8,10 +8,24 @@ use crate::{CpuStorage, CpuStorageRef, Error, Result};
 pub enum DType {
     // Unsigned 8 bits integer.
     U8,
+    // Unsigned 16 bits integer.
+    U16,
     // Unsigned 32 bits integer.
     U32,
+    // Signed 8 bits integer.
+    I8,
+    // Signed 16 bits integer.
+    I16,
+    // Signed 32 bits integer.
+    I32,
     // Signed 64 bits integer.
     I64,
+    // Boolean, the cuda and metal backends have no boolean storage and use `U8` instead.
+    Bool,
+    // 8 bits floating-point with 4 exponent bits and 3 mantissa bits, no infinities (E4M3FN).
+    F8E4M3,
+    // 8 bits floating-point with 5 exponent bits and 2 mantissa bits.
+    F8E5M2,
     // Brain floating-point using half precision (16 bits).
     BF16,
     // Floating-point using half precision (16 bits).
@@ -20,6 +34,10 @@ pub enum DType {
     F32,
     // Floating-point using double precision (64 bits).
     F64,
+    // Complex number with single precision real and imaginary parts (64 bits).
+    C64,
+    // Complex number with double precision real and imaginary parts (128 bits).
+    C128,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -38,12 +56,21 @@ impl std::str::FromStr for DType {
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s {
             "u8" => Ok(Self::U8),
+            "u16" => Ok(Self::U16),
             "u32" => Ok(Self::U32),
+            "i8" => Ok(Self::I8),
+            "i16" => Ok(Self::I16),
+            "i32" => Ok(Self::I32),
             "i64" => Ok(Self::I64),
+            "bool" => Ok(Self::Bool),
+            "f8e4m3" => Ok(Self::F8E4M3),
+            "f8e5m2" => Ok(Self::F8E5M2),
             "bf16" => Ok(Self::BF16),
             "f16" => Ok(Self::F16),
             "f32" => Ok(Self::F32),
             "f64" => Ok(Self::F64),
+            "c64" => Ok(Self::C64),
+            "c128" => Ok(Self::C128),
             _ => Err(DTypeParseError(s.to_string())),
         }
     }
@@ -54,12 +81,21 @@ impl DType {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::U8 => "u8",
+            Self::U16 => "u16",
             Self::U32 => "u32",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
             Self::I64 => "i64",
+            Self::Bool => "bool",
+            Self::F8E4M3 => "f8e4m3",
+            Self::F8E5M2 => "f8e5m2",
             Self::BF16 => "bf16",
             Self::F16 => "f16",
             Self::F32 => "f32",
             Self::F64 => "f64",
+            Self::C64 => "c64",
+            Self::C128 => "c128",
         }
     }
 
@@ -67,41 +103,70 @@ impl DType {
     pub fn size_in_bytes(&self) -> usize {
         match self {
             Self::U8 => 1,
+            Self::U16 => 2,
             Self::U32 => 4,
+            Self::I8 => 1,
+            Self::I16 => 2,
+            Self::I32 => 4,
             Self::I64 => 8,
+            Self::Bool => 1,
+            Self::F8E4M3 => 1,
+            Self::F8E5M2 => 1,
             Self::BF16 => 2,
             Self::F16 => 2,
             Self::F32 => 4,
             Self::F64 => 8,
+            Self::C64 => 8,
+            Self::C128 => 16,
         }
     }
 
     pub fn is_int(&self) -> bool {
         match self {
-            Self::U8 | Self::U32 | Self::I64 => true,
-            Self::BF16 | Self::F16 | Self::F32 | Self::F64 => false,
+            Self::U8 | Self::U16 | Self::U32 | Self::I8 | Self::I16 | Self::I32 | Self::I64 => true,
+            Self::Bool
+            | Self::F8E4M3
+            | Self::F8E5M2
+            | Self::BF16
+            | Self::F16
+            | Self::F32
+            | Self::F64
+            | Self::C64
+            | Self::C128 => false,
         }
     }
 
     pub fn is_float(&self) -> bool {
         match self {
-            Self::U8 | Self::U32 | Self::I64 => false,
-            Self::BF16 | Self::F16 | Self::F32 | Self::F64 => true,
+            Self::U8
+            | Self::U16
+            | Self::U32
+            | Self::I8
+            | Self::I16
+            | Self::I32
+            | Self::I64
+            | Self::Bool
+            | Self::C64
+            | Self::C128 => false,
+            Self::F8E4M3 | Self::F8E5M2 | Self::BF16 | Self::F16 | Self::F32 | Self::F64 => true,
         }
     }
+
+    /// Whether the values are only stored, i.e. most ops are not available and the tensor has to
+    /// be converted to another dtype first.
+    pub fn is_storage_only(&self) -> bool {
+        matches!(self, Self::F8E4M3 | Self::F8E5M2)
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self, Self::C64 | Self::C128)
+    }
 }
 
+/// The element types that can be stored in tensors. The arithmetic ops are only available for
+/// the types that also implement [`NumDType`].
 pub trait WithDType:
-    Sized
-    + Copy
-    + num_traits::NumAssign
-    + std::cmp::PartialOrd
-    + std::fmt::Display
-    + 'static
-    + Send
-    + Sync
-    + std::any::Any
-    + crate::cpu::kernels::VecOps
+    Sized + Copy + PartialEq + std::fmt::Display + 'static + Send + Sync + std::any::Any
 {
     const DTYPE: DType;
 
@@ -168,46 +233,92 @@ macro_rules! with_dtype {
 use half::{bf16, f16};
 
 with_dtype!(u8, U8, |v: f64| v as u8, |v: u8| v as f64);
+with_dtype!(u16, U16, |v: f64| v as u16, |v: u16| v as f64);
 with_dtype!(u32, U32, |v: f64| v as u32, |v: u32| v as f64);
+with_dtype!(i8, I8, |v: f64| v as i8, |v: i8| v as f64);
+with_dtype!(i16, I16, |v: f64| v as i16, |v: i16| v as f64);
+with_dtype!(i32, I32, |v: f64| v as i32, |v: i32| v as f64);
 with_dtype!(i64, I64, |v: f64| v as i64, |v: i64| v as f64);
 with_dtype!(f16, F16, f16::from_f64, f16::to_f64);
 with_dtype!(bf16, BF16, bf16::from_f64, bf16::to_f64);
 with_dtype!(f32, F32, |v: f64| v as f32, |v: f32| v as f64);
 with_dtype!(f64, F64, |v: f64| v, |v: f64| v);
+with_dtype!(bool, Bool, |v: f64| v != 0., |v: bool| v as u8 as f64);
+// The conversions from and to `f64` use the real part.
+with_dtype!(
+    num_complex::Complex32,
+    C64,
+    |v: f64| num_complex::Complex32::new(v as f32, 0.),
+    |v: num_complex::Complex32| v.re as f64
+);
+with_dtype!(
+    num_complex::Complex64,
+    C128,
+    |v: f64| num_complex::Complex64::new(v, 0.),
+    |v: num_complex::Complex64| v.re
+);
 
-pub trait IntDType: WithDType {
-    fn is_true(&self) -> bool;
-    fn as_usize(&self) -> usize;
+/// The element types with the usual arithmetic operations and an ordering, i.e. all the types
+/// except `bool` and the complex ones. The cpu kernels are written for these types.
+pub trait NumDType:
+    WithDType + num_traits::NumAssign + std::cmp::PartialOrd + crate::cpu::kernels::VecOps
+{
 }
 
-impl IntDType for i64 {
-    fn is_true(&self) -> bool {
-        *self != 0
-    }
-    fn as_usize(&self) -> usize {
-        *self as usize
-    }
+impl<T> NumDType for T where
+    T: WithDType + num_traits::NumAssign + std::cmp::PartialOrd + crate::cpu::kernels::VecOps
+{
 }
 
-impl IntDType for u32 {
-    fn is_true(&self) -> bool {
-        *self != 0
-    }
-    fn as_usize(&self) -> usize {
-        *self as usize
-    }
+pub trait IntDType:
+    NumDType
+    + std::ops::BitAnd<Output = Self>
+    + std::ops::BitOr<Output = Self>
+    + std::ops::BitXor<Output = Self>
+{
+    fn is_true(&self) -> bool;
+    fn as_usize(&self) -> usize;
+    /// Shifts the bits to the left, shifting by the bit width or more returns 0.
+    fn shift_left(self, rhs: Self) -> Self;
+    /// Shifts the bits to the right, the shift is arithmetic for signed values.
+    fn shift_right(self, rhs: Self) -> Self;
 }
 
-impl IntDType for u8 {
-    fn is_true(&self) -> bool {
-        *self != 0
-    }
-    fn as_usize(&self) -> usize {
-        *self as usize
-    }
+macro_rules! int_dtype {
+    ($ty:ty) => {
+        impl IntDType for $ty {
+            fn is_true(&self) -> bool {
+                *self != 0
+            }
+            fn as_usize(&self) -> usize {
+                *self as usize
+            }
+            fn shift_left(self, rhs: Self) -> Self {
+                u32::try_from(rhs)
+                    .ok()
+                    .and_then(|rhs| self.checked_shl(rhs))
+                    .unwrap_or(0)
+            }
+            fn shift_right(self, rhs: Self) -> Self {
+                // Large shifts fill the value with the sign bit, i.e. 0 for unsigned values.
+                u32::try_from(rhs)
+                    .ok()
+                    .and_then(|rhs| self.checked_shr(rhs))
+                    .unwrap_or(self >> (Self::BITS - 1) >> 1)
+            }
+        }
+    };
 }
 
-pub trait FloatDType: WithDType {}
+int_dtype!(u8);
+int_dtype!(u16);
+int_dtype!(u32);
+int_dtype!(i8);
+int_dtype!(i16);
+int_dtype!(i32);
+int_dtype!(i64);
+
+pub trait FloatDType: NumDType {}
 
 impl FloatDType for f16 {}
 impl FloatDType for bf16 {}
@@ -0,0 +1,369 @@
+//! Einstein summation over tensors.
+//!
+//! The equation is parsed and lowered to existing tensor operations (`permute`, `reshape`,
+//! `broadcast_mul`, `matmul`, `sum`, ...) so that the backward pass comes for free.
+use crate::{bail, Result, Tensor};
+
+// Labels are represented as integers: ascii letters use their ascii code while the dimensions
+// covered by an ellipsis use `ELLIPSIS_LABEL + i`.
+type Label = usize;
+const ELLIPSIS_LABEL: Label = 256;
+
+#[derive(Debug, Clone)]
+struct Term {
+    labels: Vec<Label>,
+    // The position of the ellipsis in `labels` if any.
+    ellipsis: Option<usize>,
+}
+
+fn parse_term(term: &str, equation: &str) -> Result<Term> {
+    let mut labels = vec![];
+    let mut ellipsis = None;
+    let mut chars = term.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            'a'..='z' | 'A'..='Z' => labels.push(c as Label),
+            '.' => {
+                if chars.next() != Some('.') || chars.next() != Some('.') {
+                    bail!("einsum: incomplete ellipsis in '{equation}'")
+                }
+                if ellipsis.is_some() {
+                    bail!("einsum: more than one ellipsis in a term of '{equation}'")
+                }
+                ellipsis = Some(labels.len())
+            }
+            c if c.is_whitespace() => {}
+            c => bail!("einsum: unexpected character '{c}' in '{equation}'"),
+        }
+    }
+    Ok(Term { labels, ellipsis })
+}
+
+impl Term {
+    // Returns the labels for a tensor of the given rank, the ellipsis being expanded to cover the
+    // dimensions that are not explicitely labeled. The ellipsis dimensions are right aligned so
+    // that they broadcast in the same way as numpy arrays.
+    fn expand(&self, rank: usize, ellipsis_len: usize) -> Result<Vec<Label>> {
+        match self.ellipsis {
+            None => {
+                if rank != self.labels.len() {
+                    bail!(
+                        "einsum: term has {} labels but the tensor has rank {rank}",
+                        self.labels.len()
+                    )
+                }
+                Ok(self.labels.clone())
+            }
+            Some(pos) => {
+                if rank < self.labels.len() {
+                    bail!(
+                        "einsum: term has {} labels but the tensor has rank {rank}",
+                        self.labels.len()
+                    )
+                }
+                let n = rank - self.labels.len();
+                let mut labels = self.labels[..pos].to_vec();
+                labels.extend((ellipsis_len - n..ellipsis_len).map(|i| ELLIPSIS_LABEL + i));
+                labels.extend_from_slice(&self.labels[pos..]);
+                Ok(labels)
+            }
+        }
+    }
+
+    fn ellipsis_len(&self, rank: usize) -> usize {
+        match self.ellipsis {
+            None => 0,
+            Some(_) => rank.saturating_sub(self.labels.len()),
+        }
+    }
+}
+
+// Takes the diagonal for all the labels that appear multiple times in the same term, e.g.
+// `ii->i`.
+fn diagonalize(mut xs: Tensor, mut labels: Vec<Label>) -> Result<(Tensor, Vec<Label>)> {
+    while let Some((i, j)) = (0..labels.len())
+        .flat_map(|i| (i + 1..labels.len()).map(move |j| (i, j)))
+        .find(|&(i, j)| labels[i] == labels[j])
+    {
+        let n = xs.dim(i)?;
+        if xs.dim(j)? != n {
+            bail!(
+                "einsum: repeated label with different sizes {n} and {} in {:?}",
+                xs.dim(j)?,
+                xs.shape()
+            )
+        }
+        let mut mask_dims = vec![1; xs.rank()];
+        mask_dims[i] = n;
+        mask_dims[j] = n;
+        let mask = Tensor::eye(n, xs.dtype(), xs.device())?.reshape(mask_dims)?;
+        xs = xs.broadcast_mul(&mask)?.sum(j)?;
+        labels.remove(j);
+    }
+    Ok((xs, labels))
+}
+
+// Sums over the dimensions whose labels are not in `keep`.
+fn sum_unused(xs: Tensor, labels: Vec<Label>, keep: &[Label]) -> Result<(Tensor, Vec<Label>)> {
+    let sum_dims: Vec<usize> = (0..labels.len())
+        .filter(|&i| !keep.contains(&labels[i]))
+        .collect();
+    if sum_dims.is_empty() {
+        return Ok((xs, labels));
+    }
+    let labels = labels.into_iter().filter(|l| keep.contains(l)).collect();
+    Ok((xs.sum(sum_dims)?, labels))
+}
+
+// Broadcasts the dimensions of size 1 in `xs` to the size used by the same label in `other`.
+fn broadcast_shared(
+    xs: &Tensor,
+    labels: &[Label],
+    other: &Tensor,
+    other_labels: &[Label],
+) -> Result<Tensor> {
+    let mut dims = xs.dims().to_vec();
+    for (i, label) in labels.iter().enumerate() {
+        if let Some(j) = other_labels.iter().position(|l| l == label) {
+            let (d1, d2) = (dims[i], other.dim(j)?);
+            if d1 == 1 && d2 != 1 {
+                dims[i] = d2
+            }
+        }
+    }
+    xs.broadcast_as(dims)
+}
+
+// Contracts two operands, `keep` contains the labels that are still used after this
+// contraction, i.e. the output labels and the labels of the remaining operands.
+fn contract(
+    (lhs, lhs_labels): (Tensor, Vec<Label>),
+    (rhs, rhs_labels): (Tensor, Vec<Label>),
+    keep: &[Label],
+) -> Result<(Tensor, Vec<Label>)> {
+    // Labels that only appear in one of the operands and are not used later can be summed
+    // right away.
+    let lhs_keep: Vec<Label> = keep.iter().chain(rhs_labels.iter()).copied().collect();
+    let (lhs, lhs_labels) = sum_unused(lhs, lhs_labels, &lhs_keep)?;
+    let rhs_keep: Vec<Label> = keep.iter().chain(lhs_labels.iter()).copied().collect();
+    let (rhs, rhs_labels) = sum_unused(rhs, rhs_labels, &rhs_keep)?;
+    let lhs = broadcast_shared(&lhs, &lhs_labels, &rhs, &rhs_labels)?;
+    let rhs = broadcast_shared(&rhs, &rhs_labels, &lhs, &lhs_labels)?;
+
+    let mut batch = vec![];
+    let mut contracted = vec![];
+    let mut lhs_free = vec![];
+    for &label in lhs_labels.iter() {
+        if rhs_labels.contains(&label) {
+            if keep.contains(&label) {
+                batch.push(label)
+            } else {
+                contracted.push(label)
+            }
+        } else {
+            lhs_free.push(label)
+        }
+    }
+    let rhs_free: Vec<Label> = rhs_labels
+        .iter()
+        .filter(|l| !lhs_labels.contains(l))
+        .copied()
+        .collect();
+
+    let pos = |labels: &[Label], l: &Label| labels.iter().position(|v| v == l).unwrap();
+    let size = |xs: &Tensor, labels: &[Label], ls: &[Label]| -> usize {
+        ls.iter().map(|l| xs.dims()[pos(labels, l)]).product()
+    };
+    let lhs_perm: Vec<usize> = batch
+        .iter()
+        .chain(lhs_free.iter())
+        .chain(contracted.iter())
+        .map(|l| pos(&lhs_labels, l))
+        .collect();
+    let rhs_perm: Vec<usize> = batch
+        .iter()
+        .chain(contracted.iter())
+        .chain(rhs_free.iter())
+        .map(|l| pos(&rhs_labels, l))
+        .collect();
+    let b_size = size(&lhs, &lhs_labels, &batch);
+    let m_size = size(&lhs, &lhs_labels, &lhs_free);
+    let k_size = size(&lhs, &lhs_labels, &contracted);
+    let n_size = size(&rhs, &rhs_labels, &rhs_free);
+    let mut dst_dims = vec![];
+    for l in batch.iter().chain(lhs_free.iter()) {
+        dst_dims.push(lhs.dims()[pos(&lhs_labels, l)])
+    }
+    for l in rhs_free.iter() {
+        dst_dims.push(rhs.dims()[pos(&rhs_labels, l)])
+    }
+
+    let lhs = lhs
+        .permute(lhs_perm)?
+        .reshape((b_size, m_size, k_size))?
+        .contiguous()?;
+    let rhs = rhs
+        .permute(rhs_perm)?
+        .reshape((b_size, k_size, n_size))?
+        .contiguous()?;
+    let dst = lhs.matmul(&rhs)?.reshape(dst_dims)?;
+    let mut dst_labels = batch;
+    dst_labels.extend(lhs_free);
+    dst_labels.extend(rhs_free);
+    Ok((dst, dst_labels))
+}
+
+impl Tensor {
+    /// Evaluates the Einstein summation convention on the operands.
+    ///
+    /// The `equation` uses one letter per dimension, the terms for the different operands being
+    /// separated by commas. The output subscripts can be specified explicitly after `->`,
+    /// otherwise the output uses the labels that only appear once, in alphabetical order. Labels
+    /// that are repeated within a term select the diagonal, and `...` can be used to represent the
+    /// dimensions that are not explicitly labeled, these broadcast together.
+    ///
+    /// The computation is lowered to `matmul`, `sum`, `permute`, etc. so the backward pass is
+    /// supported.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::arange(0f32, 6., &Device::Cpu)?.reshape((2, 3))?;
+    /// let b = Tensor::arange(0f32, 12., &Device::Cpu)?.reshape((3, 4))?;
+    /// let c = Tensor::einsum("ij,jk->ik", &[&a, &b])?;
+    /// assert_eq!(c.to_vec2::<f32>()?, a.matmul(&b)?.to_vec2::<f32>()?);
+    ///
+    /// let t = Tensor::einsum("ij->ji", &[&a])?;
+    /// assert_eq!(t.to_vec2::<f32>()?, &[[0., 3.], [1., 4.], [2., 5.]]);
+    ///
+    /// let m = Tensor::arange(0f32, 9., &Device::Cpu)?.reshape((3, 3))?;
+    /// let trace = Tensor::einsum("ii", &[&m])?;
+    /// assert_eq!(trace.to_scalar::<f32>()?, 12.);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn einsum<A: AsRef<Tensor>>(equation: &str, operands: &[A]) -> Result<Self> {
+        let (inputs, output) = match equation.split_once("->") {
+            None => (equation, None),
+            Some((inputs, output)) => (inputs, Some(output)),
+        };
+        let terms = inputs
+            .split(',')
+            .map(|term| parse_term(term, equation))
+            .collect::<Result<Vec<_>>>()?;
+        if terms.len() != operands.len() {
+            bail!(
+                "einsum: '{equation}' has {} terms but {} operands were provided",
+                terms.len(),
+                operands.len()
+            )
+        }
+        let ellipsis_len = terms
+            .iter()
+            .zip(operands.iter())
+            .map(|(term, xs)| term.ellipsis_len(xs.as_ref().rank()))
+            .max()
+            .unwrap_or(0);
+        let mut args = vec![];
+        for (term, xs) in terms.iter().zip(operands.iter()) {
+            let xs = xs.as_ref();
+            let labels = term.expand(xs.rank(), ellipsis_len)?;
+            args.push(diagonalize(xs.clone(), labels)?)
+        }
+
+        // Check that the sizes are compatible for each label. Only the dimensions from the
+        // ellipsis are allowed to broadcast.
+        let mut sizes: std::collections::HashMap<Label, usize> = Default::default();
+        for (xs, labels) in args.iter() {
+            for (&label, &d) in labels.iter().zip(xs.dims().iter()) {
+                let size = sizes.entry(label).or_insert(d);
+                if *size != d {
+                    if label >= ELLIPSIS_LABEL && (*size == 1 || d == 1) {
+                        *size = usize::max(*size, d)
+                    } else {
+                        bail!("einsum: incompatible sizes {size} and {d} in '{equation}'")
+                    }
+                }
+            }
+        }
+
+        let output_labels = match output {
+            Some(output) => {
+                let term = parse_term(output, equation)?;
+                if term.ellipsis.is_some() && terms.iter().all(|t| t.ellipsis.is_none()) {
+                    bail!("einsum: ellipsis in the output but not in the inputs of '{equation}'")
+                }
+                let rank = match term.ellipsis {
+                    None => term.labels.len(),
+                    Some(_) => term.labels.len() + ellipsis_len,
+                };
+                let labels = term.expand(rank, ellipsis_len)?;
+                for (i, label) in labels.iter().enumerate() {
+                    if labels[..i].contains(label) {
+                        bail!("einsum: repeated label in the output of '{equation}'")
+                    }
+                    if !sizes.contains_key(label) {
+                        bail!("einsum: output label not present in the inputs of '{equation}'")
+                    }
+                }
+                labels
+            }
+            None => {
+                let mut counts: std::collections::BTreeMap<Label, usize> = Default::default();
+                for term in terms.iter() {
+                    for (i, label) in term.labels.iter().enumerate() {
+                        if !term.labels[..i].contains(label) {
+                            *counts.entry(*label).or_default() += 1;
+                        } else {
+                            // Labels repeated within a term are summed over.
+                            *counts.entry(*label).or_default() += 2;
+                        }
+                    }
+                }
+                let mut labels: Vec<Label> =
+                    (0..ellipsis_len).map(|i| ELLIPSIS_LABEL + i).collect();
+                labels.extend(counts.into_iter().filter(|(_, c)| *c == 1).map(|(l, _)| l));
+                labels
+            }
+        };
+
+        let mut args = args.into_iter();
+        let mut acc = match args.next() {
+            None => bail!("einsum: no operand for '{equation}'"),
+            Some(arg) => arg,
+        };
+        let mut rest: Vec<(Tensor, Vec<Label>)> = args.collect();
+        rest.reverse();
+        while let Some(arg) = rest.pop() {
+            let mut keep = output_labels.clone();
+            for (_, labels) in rest.iter() {
+                keep.extend_from_slice(labels)
+            }
+            acc = contract(acc, arg, &keep)?;
+        }
+        let (xs, labels) = sum_unused(acc.0, acc.1, &output_labels)?;
+        let xs = broadcast_output(xs, &labels, &sizes)?;
+        let perm: Vec<usize> = output_labels
+            .iter()
+            .map(|l| labels.iter().position(|v| v == l).unwrap())
+            .collect();
+        if perm.iter().enumerate().all(|(i, &p)| i == p) {
+            Ok(xs)
+        } else {
+            xs.permute(perm)
+        }
+    }
+}
+
+// Broadcasts the ellipsis dimensions that only had a size of 1 in the operands that were used to
+// compute `xs` but a larger size in some other operand.
+fn broadcast_output(
+    xs: Tensor,
+    labels: &[Label],
+    sizes: &std::collections::HashMap<Label, usize>,
+) -> Result<Tensor> {
+    let dims: Vec<usize> = labels.iter().map(|l| sizes[l]).collect();
+    if dims == xs.dims() {
+        Ok(xs)
+    } else {
+        xs.broadcast_as(dims)
+    }
+}
@@ -0,0 +1,476 @@
+//! Discrete Fourier transforms and short-time Fourier transforms.
+//!
+//! Complex tensors are represented as real tensors with a trailing dimension of size 2 holding
+//! the real and imaginary parts, the `dim` arguments index the dimensions that come before this
+//! trailing dimension. The kernels are only available on the cpu for the `f32` and `f64` dtypes,
+//! the computations are carried out in `f64`.
+use crate::backend::BackendStorage;
+use crate::linalg::{from_f64_vec, to_f64_vec};
+use crate::shape::Dim;
+use crate::{bail, CpuStorage, CustomOp1, DType, Layout, Result, Shape, Tensor, D};
+
+type Complex = (f64, f64);
+
+fn smallest_factor(n: usize) -> usize {
+    let mut p = 2;
+    while p * p <= n {
+        if n % p == 0 {
+            return p;
+        }
+        p += 1
+    }
+    n
+}
+
+/// Mixed radix Cooley-Tukey FFT, the prime sizes use a direct DFT. The inverse transform is not
+/// normalized.
+fn fft_(xs: &[Complex], inverse: bool) -> Vec<Complex> {
+    let n = xs.len();
+    if n <= 1 {
+        return xs.to_vec();
+    }
+    let sign = if inverse { 1. } else { -1. };
+    let twiddles: Vec<Complex> = (0..n)
+        .map(|t| match (4 * t % n, 4 * t / n) {
+            // Use exact values for the quarter turns.
+            (0, 0) => (1., 0.),
+            (0, 1) => (0., sign),
+            (0, 2) => (-1., 0.),
+            (0, _) => (0., -sign),
+            _ => {
+                let angle = sign * 2. * std::f64::consts::PI * t as f64 / n as f64;
+                (angle.cos(), angle.sin())
+            }
+        })
+        .collect();
+    let p = smallest_factor(n);
+    let m = n / p;
+    let sub_ffts: Vec<Vec<Complex>> = if p == n {
+        xs.iter().map(|&x| vec![x]).collect()
+    } else {
+        (0..p)
+            .map(|r| {
+                let sub: Vec<Complex> = xs.iter().skip(r).step_by(p).copied().collect();
+                fft_(&sub, inverse)
+            })
+            .collect()
+    };
+    (0..n)
+        .map(|k| {
+            let (mut re, mut im) = (0., 0.);
+            for (r, sub) in sub_ffts.iter().enumerate() {
+                let (w_re, w_im) = twiddles[(r * k) % n];
+                let (s_re, s_im) = sub[k % m];
+                re += w_re * s_re - w_im * s_im;
+                im += w_re * s_im + w_im * s_re;
+            }
+            (re, im)
+        })
+        .collect()
+}
+
+/// Applies `f` on all the one dimensional lines along `dim`. `dims` does not include the trailing
+/// complex dimension.
+fn map_lines<F: Fn(Vec<Complex>) -> Vec<Complex>>(
+    vs: &[f64],
+    dims: &[usize],
+    dim: usize,
+    in_complex: bool,
+    out_len: usize,
+    out_complex: bool,
+    f: F,
+) -> Vec<f64> {
+    let outer = dims[..dim].iter().product::<usize>();
+    let n = dims[dim];
+    let inner = dims[dim + 1..].iter().product::<usize>();
+    let (in_w, out_w) = (1 + in_complex as usize, 1 + out_complex as usize);
+    let mut dst = vec![0f64; outer * out_len * inner * out_w];
+    for o in 0..outer {
+        for i in 0..inner {
+            let line = (0..n)
+                .map(|j| {
+                    let idx = ((o * n + j) * inner + i) * in_w;
+                    if in_complex {
+                        (vs[idx], vs[idx + 1])
+                    } else {
+                        (vs[idx], 0.)
+                    }
+                })
+                .collect();
+            for (j, (re, im)) in f(line).into_iter().enumerate() {
+                let idx = ((o * out_len + j) * inner + i) * out_w;
+                dst[idx] = re;
+                if out_complex {
+                    dst[idx + 1] = im
+                }
+            }
+        }
+    }
+    dst
+}
+
+/// Returns the dimensions of a complex tensor, i.e. without the trailing dimension of size 2.
+fn complex_dims<'a>(dims: &'a [usize], op: &'static str) -> Result<&'a [usize]> {
+    match dims.split_last() {
+        Some((2, dims)) if !dims.is_empty() => Ok(dims),
+        _ => bail!(
+            "{op}: expected a complex tensor with a trailing dimension of size 2, got {dims:?}"
+        ),
+    }
+}
+
+fn check_float(t: &Tensor, op: &'static str) -> Result<()> {
+    match t.dtype() {
+        DType::F32 | DType::F64 => Ok(()),
+        dtype => Err(crate::Error::UnsupportedDTypeForOp(dtype, op).bt()),
+    }
+}
+
+struct Fft {
+    dim: usize,
+    inverse: bool,
+}
+
+impl CustomOp1 for Fft {
+    fn name(&self) -> &'static str {
+        if self.inverse {
+            "ifft"
+        } else {
+            "fft"
+        }
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let dims = complex_dims(layout.dims(), self.name())?;
+        let vs = to_f64_vec(storage, layout, self.name())?;
+        let n = dims[self.dim];
+        let scale = if self.inverse { 1. / n as f64 } else { 1. };
+        let dst = map_lines(&vs, dims, self.dim, true, n, true, |xs| {
+            fft_(&xs, self.inverse)
+                .into_iter()
+                .map(|(re, im)| (re * scale, im * scale))
+                .collect()
+        });
+        Ok((from_f64_vec(dst, storage.dtype()), layout.shape().clone()))
+    }
+
+    fn bwd(&self, arg: &Tensor, _res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // The adjoint of the unnormalized DFT is n times the inverse DFT.
+        let n = arg.dim(self.dim)? as f64;
+        let inverse = !self.inverse;
+        let grad = grad_res.contiguous()?.apply_op1(Fft {
+            dim: self.dim,
+            inverse,
+        })?;
+        let grad = if inverse { (grad * n)? } else { (grad / n)? };
+        Ok(Some(grad))
+    }
+}
+
+struct Rfft {
+    dim: usize,
+}
+
+impl CustomOp1 for Rfft {
+    fn name(&self) -> &'static str {
+        "rfft"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let dims = layout.dims();
+        let vs = to_f64_vec(storage, layout, "rfft")?;
+        let out_len = dims[self.dim] / 2 + 1;
+        let dst = map_lines(&vs, dims, self.dim, false, out_len, true, |xs| {
+            let mut ys = fft_(&xs, false);
+            ys.truncate(out_len);
+            ys
+        });
+        let mut out_dims = dims.to_vec();
+        out_dims[self.dim] = out_len;
+        out_dims.push(2);
+        Ok((from_f64_vec(dst, storage.dtype()), Shape::from(out_dims)))
+    }
+
+    fn bwd(&self, arg: &Tensor, res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // Zero pad the gradient to the full spectrum and apply the adjoint of the DFT.
+        let n = arg.dim(self.dim)?;
+        let m = res.dim(self.dim)?;
+        let grad = grad_res.pad_with_zeros(self.dim, 0, n - m)?.contiguous()?;
+        let grad = grad.apply_op1(Fft {
+            dim: self.dim,
+            inverse: true,
+        })?;
+        let grad = (grad * n as f64)?
+            .narrow(D::Minus1, 0, 1)?
+            .squeeze(D::Minus1)?;
+        Ok(Some(grad))
+    }
+}
+
+struct Irfft {
+    dim: usize,
+    n: usize,
+}
+
+impl CustomOp1 for Irfft {
+    fn name(&self) -> &'static str {
+        "irfft"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let dims = complex_dims(layout.dims(), "irfft")?;
+        let vs = to_f64_vec(storage, layout, "irfft")?;
+        let n = self.n;
+        let dst = map_lines(&vs, dims, self.dim, true, n, false, |xs| {
+            // Rebuild the full hermitian spectrum, only the real part of the result is used so
+            // the imaginary parts of the zero and nyquist frequencies get ignored.
+            let get = |k: usize| xs.get(k).copied().unwrap_or((0., 0.));
+            let full: Vec<Complex> = (0..n)
+                .map(|k| {
+                    if k <= n / 2 {
+                        get(k)
+                    } else {
+                        let (re, im) = get(n - k);
+                        (re, -im)
+                    }
+                })
+                .collect();
+            fft_(&full, true)
+                .into_iter()
+                .map(|(re, _)| (re / n as f64, 0.))
+                .collect()
+        });
+        let mut out_dims = dims.to_vec();
+        out_dims[self.dim] = n;
+        Ok((from_f64_vec(dst, storage.dtype()), Shape::from(out_dims)))
+    }
+
+    fn bwd(&self, arg: &Tensor, _res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // The non-redundant frequencies contribute twice to the output except for the zero
+        // and nyquist ones.
+        let n = self.n;
+        let m = arg.dim(self.dim)?;
+        let grad = grad_res.contiguous()?.apply_op1(Rfft { dim: self.dim })?;
+        let h = n / 2 + 1;
+        let weights: Vec<f64> = (0..h)
+            .map(|k| {
+                if k == 0 || 2 * k == n {
+                    1. / n as f64
+                } else {
+                    2. / n as f64
+                }
+            })
+            .collect();
+        let mut shape = vec![1; grad.rank()];
+        shape[self.dim] = h;
+        let weights = Tensor::from_vec(weights, shape, grad.device())?.to_dtype(grad.dtype())?;
+        let grad = grad.broadcast_mul(&weights)?;
+        let grad = if m > h {
+            grad.pad_with_zeros(self.dim, 0, m - h)?
+        } else {
+            grad.narrow(self.dim, 0, m)?
+        };
+        Ok(Some(grad))
+    }
+}
+
+impl Tensor {
+    fn complex_dim<D: Dim>(&self, dim: D, op: &'static str) -> Result<usize> {
+        let dims = complex_dims(self.dims(), op)?;
+        dim.to_index(&Shape::from(dims), op)
+    }
+
+    /// Computes the one dimensional discrete Fourier transform of a complex tensor along the
+    /// given dimension. The transform is not normalized.
+    pub fn fft<D: Dim>(&self, dim: D) -> Result<Self> {
+        check_float(self, "fft")?;
+        let dim = self.complex_dim(dim, "fft")?;
+        let fft = Fft {
+            dim,
+            inverse: false,
+        };
+        self.contiguous()?.apply_op1(fft)
+    }
+
+    /// Computes the one dimensional inverse discrete Fourier transform of a complex tensor along
+    /// the given dimension. The result is scaled by `1/n` so that `ifft` is the inverse of `fft`.
+    pub fn ifft<D: Dim>(&self, dim: D) -> Result<Self> {
+        check_float(self, "ifft")?;
+        let dim = self.complex_dim(dim, "ifft")?;
+        let fft = Fft { dim, inverse: true };
+        self.contiguous()?.apply_op1(fft)
+    }
+
+    /// Computes the discrete Fourier transform of a real tensor along the given dimension. Only
+    /// the `n / 2 + 1` non-negative frequencies are returned as a complex tensor.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device, D};
+    /// let t = Tensor::new(&[1f32, 2., 3., 4.], &Device::Cpu)?;
+    /// let f = t.rfft(D::Minus1)?;
+    /// assert_eq!(f.to_vec2::<f32>()?, &[[10., 0.], [-2., 2.], [-2., 0.]]);
+    /// let t = f.irfft(D::Minus1, 4)?;
+    /// assert_eq!(t.to_vec1::<f32>()?, &[1., 2., 3., 4.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn rfft<D: Dim>(&self, dim: D) -> Result<Self> {
+        check_float(self, "rfft")?;
+        let dim = dim.to_index(self.shape(), "rfft")?;
+        self.contiguous()?.apply_op1(Rfft { dim })
+    }
+
+    /// Computes the inverse of `rfft` along the given dimension, `n` is the length of the real
+    /// output. The input is truncated or zero padded to `n / 2 + 1` frequencies.
+    pub fn irfft<D: Dim>(&self, dim: D, n: usize) -> Result<Self> {
+        check_float(self, "irfft")?;
+        let dim = self.complex_dim(dim, "irfft")?;
+        if n == 0 {
+            bail!("irfft: the output length has to be positive")
+        }
+        self.contiguous()?.apply_op1(Irfft { dim, n })
+    }
+
+    /// Computes the short-time Fourier transform of a real tensor of shape `(..., time)`.
+    ///
+    /// The frames of length `n_fft` are separated by `hop_length` samples and multiplied by
+    /// `window` which must have `n_fft` elements. When `center` is true, the input is padded
+    /// with `n_fft / 2` reflected samples on both sides so that frames are centered on their
+    /// timestamp. The result is a complex tensor of shape `(..., n_fft / 2 + 1, n_frames, 2)`.
+    pub fn stft(
+        &self,
+        n_fft: usize,
+        hop_length: usize,
+        window: &Tensor,
+        center: bool,
+    ) -> Result<Self> {
+        if hop_length == 0 {
+            bail!("stft: hop_length has to be positive")
+        }
+        if window.dims() != [n_fft] {
+            bail!(
+                "stft: expected a window of shape [{n_fft}], got {:?}",
+                window.dims()
+            )
+        }
+        let dims = self.dims();
+        let (t, batch) = match dims.split_last() {
+            Some((&t, batch)) => (t, batch),
+            None => bail!("stft: expected a tensor with at least one dimension"),
+        };
+        let xs = if center {
+            let pad = n_fft / 2;
+            if pad >= t {
+                bail!("stft: reflect padding of {pad} requires more than {t} samples")
+            }
+            let index: Vec<u32> = (0..t + 2 * pad)
+                .map(|i| {
+                    let i = i as isize - pad as isize;
+                    let i = if i < 0 { -i } else { i };
+                    let i = if i >= t as isize {
+                        2 * (t as isize - 1) - i
+                    } else {
+                        i
+                    };
+                    i as u32
+                })
+                .collect();
+            let index = Tensor::new(index, self.device())?;
+            self.index_select(&index, D::Minus1)?
+        } else {
+            self.clone()
+        };
+        let t = xs.dim(D::Minus1)?;
+        if t < n_fft {
+            bail!("stft: n_fft {n_fft} is larger than the number of samples {t}")
+        }
+        let n_frames = 1 + (t - n_fft) / hop_length;
+        let index: Vec<u32> = (0..n_frames)
+            .flat_map(|f| (0..n_fft).map(move |i| (f * hop_length + i) as u32))
+            .collect();
+        let index = Tensor::new(index, self.device())?;
+        let frames = xs
+            .index_select(&index, D::Minus1)?
+            .reshape([batch, &[n_frames, n_fft]].concat())?
+            .broadcast_mul(window)?;
+        let rank = batch.len() + 3;
+        frames.rfft(D::Minus1)?.transpose(rank - 3, rank - 2)
+    }
+
+    /// Computes the inverse of `stft`, the input is a complex tensor of shape
+    /// `(..., n_fft / 2 + 1, n_frames, 2)` and the output has shape `(..., length)`.
+    ///
+    /// The frames are overlap-added and normalized by the sum of the squared windows, this
+    /// normalization has to be non-zero on the whole output. When `length` is not specified the
+    /// output covers all the frames.
+    pub fn istft(
+        &self,
+        n_fft: usize,
+        hop_length: usize,
+        window: &Tensor,
+        center: bool,
+        length: Option<usize>,
+    ) -> Result<Self> {
+        if hop_length == 0 {
+            bail!("istft: hop_length has to be positive")
+        }
+        if window.dims() != [n_fft] {
+            bail!(
+                "istft: expected a window of shape [{n_fft}], got {:?}",
+                window.dims()
+            )
+        }
+        let dims = complex_dims(self.dims(), "istft")?;
+        if dims.len() < 2 || dims[dims.len() - 2] != n_fft / 2 + 1 {
+            bail!(
+                "istft: expected a shape (..., {}, n_frames, 2), got {:?}",
+                n_fft / 2 + 1,
+                self.dims()
+            )
+        }
+        let batch = &dims[..dims.len() - 2];
+        let n_frames = dims[dims.len() - 1];
+        let rank = self.rank();
+        let frames = self
+            .transpose(rank - 3, rank - 2)?
+            .irfft(D::Minus1, n_fft)?
+            .broadcast_mul(window)?
+            .reshape([batch, &[n_frames * n_fft]].concat())?;
+        let full_len = n_fft + hop_length * (n_frames.max(1) - 1);
+        let index: Vec<u32> = (0..n_frames)
+            .flat_map(|f| (0..n_fft).map(move |i| (f * hop_length + i) as u32))
+            .collect();
+        let index = Tensor::new(index, self.device())?;
+        let ys = Tensor::zeros([batch, &[full_len]].concat(), self.dtype(), self.device())?
+            .index_add(&index, &frames, D::Minus1)?;
+
+        let window_sq: Vec<f64> = window.to_dtype(DType::F64)?.sqr()?.to_vec1::<f64>()?;
+        let mut envelope = vec![0f64; full_len];
+        for f in 0..n_frames {
+            for (i, w) in window_sq.iter().enumerate() {
+                envelope[f * hop_length + i] += w
+            }
+        }
+        let start = if center { n_fft / 2 } else { 0 };
+        let end = match length {
+            Some(length) => usize::min(start + length, full_len),
+            None if center => full_len.saturating_sub(n_fft / 2),
+            None => full_len,
+        };
+        if start >= end {
+            bail!("istft: not enough frames to produce an output")
+        }
+        if envelope[start..end].iter().any(|&e| e < 1e-11) {
+            bail!("istft: the sum of the squared windows is zero for some output samples")
+        }
+        let envelope = Tensor::new(&envelope[start..end], self.device())?.to_dtype(self.dtype())?;
+        let ys = ys
+            .narrow(D::Minus1, start, end - start)?
+            .broadcast_div(&envelope)?;
+        match length {
+            Some(length) if length > end - start => {
+                ys.pad_with_zeros(D::Minus1, 0, length - (end - start))
+            }
+            _ => Ok(ys),
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Random number generators with an explicit state.
+//!
+//! The `Tensor::rand` and `Tensor::randn` functions use the random number generator of the
+//! device which is shared by all the callers. A [`Generator`] owns its state instead so that
+//! each thread or data stream can use its own generator, and the state can be saved and restored
+//! to reproduce a sequence of samples. The values are sampled on the host and then copied to the
+//! target device, so a given seed produces the same values on all devices.
+use crate::{bail, DType, Device, Result, Shape, Tensor};
+use rand::Rng;
+
+/// A xoshiro256++ random number generator, see <https://prng.di.unimi.it/>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generator {
+    state: [u64; 4],
+}
+
+fn splitmix64(x: &mut u64) -> u64 {
+    *x = x.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl Generator {
+    /// The size in bytes of the state returned by `get_state`.
+    pub const STATE_SIZE: usize = 32;
+
+    /// Creates a generator from a seed, the same seed always results in the same samples.
+    pub fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let state = [(); 4].map(|_| splitmix64(&mut x));
+        Self { state }
+    }
+
+    /// Creates a generator seeded from the thread random number generator.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// Returns the current state of the generator, restoring it with `set_state` results in the
+    /// same samples being generated again.
+    pub fn get_state(&self) -> Vec<u8> {
+        self.state.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    /// Sets the state of the generator, `state` should have been returned by `get_state`.
+    pub fn set_state(&mut self, state: &[u8]) -> Result<()> {
+        if state.len() != Self::STATE_SIZE {
+            bail!(
+                "generator state should have {} bytes, got {}",
+                Self::STATE_SIZE,
+                state.len()
+            )
+        }
+        let mut s = [0u64; 4];
+        for (s, bytes) in s.iter_mut().zip(state.chunks_exact(8)) {
+            *s = u64::from_le_bytes(bytes.try_into().expect("chunks have 8 bytes"))
+        }
+        if s == [0; 4] {
+            bail!("the all zeros generator state is invalid")
+        }
+        self.state = s;
+        Ok(())
+    }
+
+    fn next(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+impl rand::RngCore for Generator {
+    fn next_u32(&mut self) -> u32 {
+        (self.next() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()])
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Returns the rows of a 1d or 2d tensor of probabilities or weights as `f64` values.
+fn rows(t: &Tensor, op: &'static str) -> Result<Vec<Vec<f64>>> {
+    let t = t.to_dtype(DType::F64)?;
+    match t.rank() {
+        1 => Ok(vec![t.to_vec1::<f64>()?]),
+        2 => t.to_vec2::<f64>(),
+        _ => bail!("{op} expects a 1d or 2d tensor, got {:?}", t.shape()),
+    }
+}
+
+impl Tensor {
+    /// Creates a new tensor initialized with values sampled uniformly between `lo` and `up`
+    /// using the generator `gen`.
+    pub fn rand_with_generator<S: Into<Shape>, T: crate::FloatDType>(
+        lo: T,
+        up: T,
+        s: S,
+        device: &Device,
+        gen: &mut Generator,
+    ) -> Result<Self> {
+        let s = s.into();
+        let (lo, up) = (lo.to_f64(), up.to_f64());
+        let data: Vec<T> = (0..s.elem_count())
+            .map(|_| T::from_f64(lo + (up - lo) * gen.gen::<f64>()))
+            .collect();
+        Tensor::from_vec(data, s, device)
+    }
+
+    /// Creates a new tensor initialized with values sampled from a normal distribution with the
+    /// specified `mean` and standard deviation `std` using the generator `gen`.
+    pub fn randn_with_generator<S: Into<Shape>, T: crate::FloatDType>(
+        mean: T,
+        std: T,
+        s: S,
+        device: &Device,
+        gen: &mut Generator,
+    ) -> Result<Self> {
+        use rand_distr::Distribution;
+        let s = s.into();
+        let (mean, std) = (mean.to_f64(), std.to_f64());
+        let data: Vec<T> = (0..s.elem_count())
+            .map(|_| {
+                let v: f64 = rand_distr::StandardNormal.sample(gen);
+                T::from_f64(mean + std * v)
+            })
+            .collect();
+        Tensor::from_vec(data, s, device)
+    }
+
+    /// Creates a new `i64` tensor with integers sampled uniformly from `low` (inclusive) to
+    /// `high` (exclusive).
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device, Generator};
+    /// let mut gen = Generator::new(42);
+    /// let t = Tensor::randint(-2, 3, (2, 4), &Device::Cpu, &mut gen)?;
+    /// assert!(t.flatten_all()?.to_vec1::<i64>()?.iter().all(|&v| (-2..3).contains(&v)));
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn randint<S: Into<Shape>>(
+        low: i64,
+        high: i64,
+        s: S,
+        device: &Device,
+        gen: &mut Generator,
+    ) -> Result<Self> {
+        if low >= high {
+            bail!("randint: low {low} should be smaller than high {high}")
+        }
+        let s = s.into();
+        let data: Vec<i64> = (0..s.elem_count())
+            .map(|_| gen.gen_range(low..high))
+            .collect();
+        Tensor::from_vec(data, s, device)
+    }
+
+    /// Returns a random permutation of the integers from `0` to `n - 1` as a `u32` tensor.
+    pub fn randperm(n: usize, device: &Device, gen: &mut Generator) -> Result<Self> {
+        use rand::seq::SliceRandom;
+        let mut data: Vec<u32> = (0..n as u32).collect();
+        data.shuffle(gen);
+        Tensor::from_vec(data, n, device)
+    }
+
+    /// Draws binary values, each element is one with the probability given by the corresponding
+    /// element of `self` and zero otherwise. The result has the shape and dtype of `self`.
+    pub fn bernoulli(&self, gen: &mut Generator) -> Result<Self> {
+        let probs = self.to_dtype(DType::F64)?.flatten_all()?.to_vec1::<f64>()?;
+        let data = probs
+            .iter()
+            .map(|&p| {
+                if !(0. ..=1.).contains(&p) {
+                    bail!("bernoulli: probabilities should be between 0 and 1, got {p}")
+                }
+                Ok(if gen.gen::<f64>() < p { 1. } else { 0. })
+            })
+            .collect::<Result<Vec<f64>>>()?;
+        Tensor::from_vec(data, self.shape(), self.device())?.to_dtype(self.dtype())
+    }
+
+    /// Samples `num_samples` category indexes from the multinomial distributions with the
+    /// non-negative weights of `self`, the weights do not have to sum to one.
+    ///
+    /// `self` can be a 1d tensor or a 2d tensor with a distribution per row, the result is a
+    /// `u32` tensor of shape `(num_samples,)` or `(rows, num_samples)`. Without `replacement`, a
+    /// category cannot be drawn twice for the same row so there must be at least `num_samples`
+    /// categories with a positive weight.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device, Generator};
+    /// let mut gen = Generator::new(42);
+    /// let probs = Tensor::new(&[0.5f32, 0., 0.5], &Device::Cpu)?;
+    /// let t = probs.multinomial(2, false, &mut gen)?.to_vec1::<u32>()?;
+    /// assert!(t == [0, 2] || t == [2, 0]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn multinomial(
+        &self,
+        num_samples: usize,
+        replacement: bool,
+        gen: &mut Generator,
+    ) -> Result<Self> {
+        let rows = rows(self, "multinomial")?;
+        let mut data = Vec::with_capacity(rows.len() * num_samples);
+        for mut weights in rows.into_iter() {
+            if weights.iter().any(|w| !(w.is_finite() && *w >= 0.)) {
+                bail!("multinomial: the weights should be finite and non-negative")
+            }
+            let positive = weights.iter().filter(|&&w| w > 0.).count();
+            if positive == 0 {
+                bail!("multinomial: the weights should have a positive sum")
+            }
+            if !replacement && num_samples > positive {
+                bail!("multinomial: cannot draw {num_samples} samples without replacement from {positive} categories")
+            }
+            for _ in 0..num_samples {
+                let total: f64 = weights.iter().sum();
+                let mut v = gen.gen::<f64>() * total;
+                let mut index = weights.iter().rposition(|&w| w > 0.).unwrap_or(0);
+                for (i, &w) in weights.iter().enumerate() {
+                    if w > 0. && v < w {
+                        index = i;
+                        break;
+                    }
+                    v -= w
+                }
+                if !replacement {
+                    weights[index] = 0.
+                }
+                data.push(index as u32)
+            }
+        }
+        let shape = match self.rank() {
+            1 => Shape::from(num_samples),
+            _ => Shape::from((self.dim(0)?, num_samples)),
+        };
+        Tensor::from_vec(data, shape, self.device())
+    }
+}
@@ -0,0 +1,340 @@
+//! Sampling of 2D inputs at arbitrary locations, as used by spatial transformer networks.
+//!
+//! The `grid_sample` kernels are only available on the cpu for the `f32` and `f64` dtypes, the
+//! computations are carried out in `f64`. `affine_grid` is expressed with the existing ops so it
+//! works on all devices.
+use crate::backend::BackendStorage;
+use crate::linalg::{from_f64_vec, to_f64_vec};
+use crate::{bail, CpuStorage, CustomOp2, CustomOp3, Layout, Result, Shape, Tensor};
+
+/// How the input values are combined at each sampling location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridSampleMode {
+    #[default]
+    Bilinear,
+    Nearest,
+}
+
+/// The values used for the sampling locations outside of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridSamplePaddingMode {
+    /// Use zero for the out of bound positions.
+    #[default]
+    Zeros,
+    /// Use the values on the border of the input.
+    Border,
+    /// Reflect the positions by the border of the input.
+    Reflection,
+}
+
+/// Clips a position to `[0, size - 1]`, also returns the derivative of the clipped position.
+fn clip(x: f64, size: usize) -> (f64, f64) {
+    let max = size as f64 - 1.;
+    if x <= 0. {
+        (0., 0.)
+    } else if x >= max {
+        (max, 0.)
+    } else {
+        (x, 1.)
+    }
+}
+
+/// Reflects a position until it lies between `twice_low / 2` and `twice_high / 2`, also returns
+/// the derivative of the reflected position.
+fn reflect(x: f64, twice_low: f64, twice_high: f64) -> (f64, f64) {
+    if twice_low == twice_high {
+        return (0., 0.);
+    }
+    let min = twice_low / 2.;
+    let span = (twice_high - twice_low) / 2.;
+    let (x, sign) = if x < min {
+        (min - x, -1.)
+    } else {
+        (x - min, 1.)
+    };
+    let extra = x % span;
+    let flips = (x / span).floor() as i64;
+    if flips % 2 == 0 {
+        (extra + min, sign)
+    } else {
+        (span - extra + min, -sign)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GridSample {
+    mode: GridSampleMode,
+    padding_mode: GridSamplePaddingMode,
+    align_corners: bool,
+}
+
+impl GridSample {
+    /// Converts a normalized coordinate to a position in the input, also returns the derivative
+    /// of the position with respect to the coordinate.
+    fn source_index(&self, coord: f64, size: usize) -> (f64, f64) {
+        let size_f = size as f64;
+        let (x, d) = if self.align_corners {
+            ((coord + 1.) / 2. * (size_f - 1.), (size_f - 1.) / 2.)
+        } else {
+            (((coord + 1.) * size_f - 1.) / 2., size_f / 2.)
+        };
+        match self.padding_mode {
+            GridSamplePaddingMode::Zeros => (x, d),
+            GridSamplePaddingMode::Border => {
+                let (x, dx) = clip(x, size);
+                (x, d * dx)
+            }
+            GridSamplePaddingMode::Reflection => {
+                let (x, dr) = if self.align_corners {
+                    reflect(x, 0., 2. * (size_f - 1.))
+                } else {
+                    reflect(x, -1., 2. * size_f - 1.)
+                };
+                let (x, dx) = clip(x, size);
+                (x, d * dr * dx)
+            }
+        }
+    }
+
+    /// Calls `f` on the input elements used for the sampling location `(gx, gy)` with their
+    /// index in the `h * w` plane, their weight, and the derivatives of the weight with respect
+    /// to the normalized `gx` and `gy` coordinates. The elements outside of the input are skipped.
+    fn for_each_tap<F: FnMut(usize, f64, f64, f64)>(
+        &self,
+        gx: f64,
+        gy: f64,
+        h: usize,
+        w: usize,
+        mut f: F,
+    ) {
+        let (ix, dix) = self.source_index(gx, w);
+        let (iy, diy) = self.source_index(gy, h);
+        let mut tap = |x: f64, y: f64, weight: f64, dwx: f64, dwy: f64| {
+            if x >= 0. && y >= 0. && x < w as f64 && y < h as f64 {
+                f(y as usize * w + x as usize, weight, dwx * dix, dwy * diy)
+            }
+        };
+        match self.mode {
+            GridSampleMode::Nearest => tap(ix.round_ties_even(), iy.round_ties_even(), 1., 0., 0.),
+            GridSampleMode::Bilinear => {
+                let (x0, y0) = (ix.floor(), iy.floor());
+                let (tx, ty) = (ix - x0, iy - y0);
+                tap(x0, y0, (1. - tx) * (1. - ty), ty - 1., tx - 1.);
+                tap(x0 + 1., y0, tx * (1. - ty), 1. - ty, -tx);
+                tap(x0, y0 + 1., (1. - tx) * ty, -ty, 1. - tx);
+                tap(x0 + 1., y0 + 1., tx * ty, ty, tx);
+            }
+        }
+    }
+}
+
+/// Returns `(n, c, h, w, h_out, w_out)` after checking the input and grid layouts.
+fn grid_sample_dims(
+    input_l: &Layout,
+    grid_l: &Layout,
+) -> Result<(usize, usize, usize, usize, usize, usize)> {
+    let (n, c, h, w) = input_l.shape().dims4()?;
+    let (grid_n, h_out, w_out, two) = grid_l.shape().dims4()?;
+    if grid_n != n || two != 2 {
+        bail!(
+            "grid-sample: expected a grid of shape ({n}, h_out, w_out, 2), got {:?}",
+            grid_l.shape()
+        )
+    }
+    Ok((n, c, h, w, h_out, w_out))
+}
+
+impl CustomOp2 for GridSample {
+    fn name(&self) -> &'static str {
+        "grid-sample"
+    }
+
+    fn cpu_fwd(
+        &self,
+        s1: &CpuStorage,
+        l1: &Layout,
+        s2: &CpuStorage,
+        l2: &Layout,
+    ) -> Result<(CpuStorage, Shape)> {
+        let (n, c, h, w, h_out, w_out) = grid_sample_dims(l1, l2)?;
+        let input = to_f64_vec(s1, l1, self.name())?;
+        let grid = to_f64_vec(s2, l2, self.name())?;
+        let out_size = h_out * w_out;
+        let mut dst = vec![0f64; n * c * out_size];
+        for b in 0..n {
+            for p in 0..out_size {
+                let g = (b * out_size + p) * 2;
+                self.for_each_tap(grid[g], grid[g + 1], h, w, |index, weight, _, _| {
+                    for c_idx in 0..c {
+                        let plane = b * c + c_idx;
+                        dst[plane * out_size + p] += weight * input[plane * h * w + index]
+                    }
+                })
+            }
+        }
+        Ok((from_f64_vec(dst, s1.dtype()), (n, c, h_out, w_out).into()))
+    }
+
+    fn bwd(
+        &self,
+        input: &Tensor,
+        grid: &Tensor,
+        _res: &Tensor,
+        grad_res: &Tensor,
+    ) -> Result<(Option<Tensor>, Option<Tensor>)> {
+        let grad_res = grad_res.contiguous()?;
+        let grad = |wrt_input| GridSampleGrad {
+            op: *self,
+            wrt_input,
+        };
+        let input_grad = input.apply_op3_no_bwd(grid, &grad_res, &grad(true))?;
+        let grid_grad = input.apply_op3_no_bwd(grid, &grad_res, &grad(false))?;
+        Ok((Some(input_grad), Some(grid_grad)))
+    }
+}
+
+/// The gradient of `grid_sample` with respect to its input or to the grid.
+struct GridSampleGrad {
+    op: GridSample,
+    wrt_input: bool,
+}
+
+impl CustomOp3 for GridSampleGrad {
+    fn name(&self) -> &'static str {
+        "grid-sample-grad"
+    }
+
+    fn cpu_fwd(
+        &self,
+        s1: &CpuStorage,
+        l1: &Layout,
+        s2: &CpuStorage,
+        l2: &Layout,
+        s3: &CpuStorage,
+        l3: &Layout,
+    ) -> Result<(CpuStorage, Shape)> {
+        let (n, c, h, w, h_out, w_out) = grid_sample_dims(l1, l2)?;
+        let input = to_f64_vec(s1, l1, self.name())?;
+        let grid = to_f64_vec(s2, l2, self.name())?;
+        let grad = to_f64_vec(s3, l3, self.name())?;
+        let out_size = h_out * w_out;
+        let mut dst = if self.wrt_input {
+            vec![0f64; input.len()]
+        } else {
+            vec![0f64; grid.len()]
+        };
+        for b in 0..n {
+            for p in 0..out_size {
+                let g = (b * out_size + p) * 2;
+                self.op
+                    .for_each_tap(grid[g], grid[g + 1], h, w, |index, weight, dwx, dwy| {
+                        for c_idx in 0..c {
+                            let plane = b * c + c_idx;
+                            let grad = grad[plane * out_size + p];
+                            if self.wrt_input {
+                                dst[plane * h * w + index] += weight * grad
+                            } else {
+                                let v = input[plane * h * w + index] * grad;
+                                dst[g] += dwx * v;
+                                dst[g + 1] += dwy * v;
+                            }
+                        }
+                    })
+            }
+        }
+        let shape = if self.wrt_input {
+            l1.shape()
+        } else {
+            l2.shape()
+        };
+        Ok((from_f64_vec(dst, s1.dtype()), shape.clone()))
+    }
+}
+
+impl Tensor {
+    /// Samples the input at the locations given by `grid`.
+    ///
+    /// The input has shape `(n, c, h, w)` and the grid has shape `(n, h_out, w_out, 2)`, the
+    /// last dimension holds the `x` and `y` coordinates normalized to `[-1, 1]` where `-1` is the
+    /// left/top side of the input and `1` its right/bottom side. When `align_corners` is set,
+    /// these extremes refer to the centers of the corner elements rather than to their outer
+    /// edges. The result has shape `(n, c, h_out, w_out)`, gradients flow to both the input and
+    /// the grid.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// use candle_core::grid_sample::{GridSampleMode, GridSamplePaddingMode};
+    /// let xs = Tensor::new(&[[[[1f32, 2.], [3., 4.]]]], &Device::Cpu)?;
+    /// let grid = Tensor::new(&[[[[-1f32, -1.], [0., 0.], [1., -1.]]]], &Device::Cpu)?;
+    /// let ys = xs.grid_sample(&grid, GridSampleMode::Bilinear, GridSamplePaddingMode::Zeros, true)?;
+    /// assert_eq!(ys.flatten_all()?.to_vec1::<f32>()?, &[1., 2.5, 2.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn grid_sample(
+        &self,
+        grid: &Self,
+        mode: GridSampleMode,
+        padding_mode: GridSamplePaddingMode,
+        align_corners: bool,
+    ) -> Result<Self> {
+        if self.dtype() != grid.dtype() {
+            Err(crate::Error::DTypeMismatchBinaryOp {
+                lhs: self.dtype(),
+                rhs: grid.dtype(),
+                op: "grid-sample",
+            }
+            .bt())?
+        }
+        grid_sample_dims(self.layout(), grid.layout())?;
+        let op = GridSample {
+            mode,
+            padding_mode,
+            align_corners,
+        };
+        self.contiguous()?.apply_op2(&grid.contiguous()?, op)
+    }
+
+    /// Generates the sampling grid for the affine transforms `theta` of shape `(n, 2, 3)`, the
+    /// grid can be passed to `grid_sample` to get an output of size `(n, c, h, w)`.
+    ///
+    /// The transforms map the normalized output coordinates to the normalized input ones, the
+    /// returned grid has shape `(n, h, w, 2)`.
+    pub fn affine_grid(
+        theta: &Self,
+        (n, _c, h, w): (usize, usize, usize, usize),
+        align_corners: bool,
+    ) -> Result<Self> {
+        let (theta_n, two, three) = theta.dims3()?;
+        if theta_n != n || two != 2 || three != 3 {
+            bail!(
+                "affine-grid: expected theta of shape ({n}, 2, 3), got {:?}",
+                theta.shape()
+            )
+        }
+        let linspace = |size: usize| -> Vec<f64> {
+            (0..size)
+                .map(|i| {
+                    if align_corners {
+                        if size > 1 {
+                            -1. + 2. * i as f64 / (size - 1) as f64
+                        } else {
+                            0.
+                        }
+                    } else {
+                        (2 * i + 1) as f64 / size as f64 - 1.
+                    }
+                })
+                .collect()
+        };
+        let (xs, ys) = (linspace(w), linspace(h));
+        let mut base = Vec::with_capacity(h * w * 3);
+        for &y in ys.iter() {
+            for &x in xs.iter() {
+                base.extend_from_slice(&[x, y, 1.])
+            }
+        }
+        let base =
+            Tensor::from_vec(base, (1, h * w, 3), theta.device())?.to_dtype(theta.dtype())?;
+        base.broadcast_matmul(&theta.t()?)?.reshape((n, h, w, 2))
+    }
+}
@@ -0,0 +1,411 @@
+//! Counting and binning ops: `bincount`, `histc`, `unique` and `searchsorted`.
+//!
+//! The output sizes of these ops depend on the input values so they are implemented as custom
+//! ops, the kernels are only available on the cpu. Indexes and counts use the `u32` dtype.
+use crate::backend::BackendStorage;
+use crate::cpu_backend::Map1Any;
+use crate::linalg::{from_f64_vec, to_f64_vec};
+use crate::{
+    bail, CpuStorage, CustomOp1, CustomOp2, DType, Layout, NumDType, Result, Shape, Tensor,
+    WithDType,
+};
+
+fn contiguous<'a, T>(vs: &'a [T], layout: &Layout, op: &'static str) -> Result<&'a [T]> {
+    match layout.contiguous_offsets() {
+        Some((start, end)) => Ok(&vs[start..end]),
+        None => bail!("{op}: input has to be contiguous"),
+    }
+}
+
+struct Bincount {
+    rows: usize,
+    batched: bool,
+    minlength: usize,
+}
+
+impl Bincount {
+    /// Counts the `i64` values of each row, the weights default to one.
+    fn bincount(&self, vs: &[i64], weights: Option<&[f64]>) -> Result<(Vec<f64>, Shape)> {
+        let mut bins = self.minlength;
+        for &v in vs.iter() {
+            if v < 0 {
+                bail!("bincount: the input values should be non-negative, got {v}")
+            }
+            bins = bins.max(v as usize + 1)
+        }
+        let cols = vs.len() / self.rows.max(1);
+        let mut counts = vec![0f64; self.rows * bins];
+        for (i, &v) in vs.iter().enumerate() {
+            counts[i / cols * bins + v as usize] += weights.map_or(1., |w| w[i])
+        }
+        let shape = if self.batched {
+            Shape::from((self.rows, bins))
+        } else {
+            Shape::from(bins)
+        };
+        Ok((counts, shape))
+    }
+}
+
+impl CustomOp1 for Bincount {
+    fn name(&self) -> &'static str {
+        "bincount"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let vs = contiguous(i64::cpu_storage_as_slice(storage)?, layout, "bincount")?;
+        let (counts, shape) = self.bincount(vs, None)?;
+        let counts = counts.into_iter().map(|v| v as u32).collect();
+        Ok((CpuStorage::U32(counts), shape))
+    }
+}
+
+impl CustomOp2 for Bincount {
+    fn name(&self) -> &'static str {
+        "bincount"
+    }
+
+    fn cpu_fwd(
+        &self,
+        s1: &CpuStorage,
+        l1: &Layout,
+        s2: &CpuStorage,
+        l2: &Layout,
+    ) -> Result<(CpuStorage, Shape)> {
+        let vs = contiguous(i64::cpu_storage_as_slice(s1)?, l1, "bincount")?;
+        let weights = to_f64_vec(s2, l2, "bincount")?;
+        let (counts, shape) = self.bincount(vs, Some(&weights))?;
+        Ok((from_f64_vec(counts, s2.dtype()), shape))
+    }
+}
+
+struct Histc {
+    bins: usize,
+    min: f64,
+    max: f64,
+}
+
+impl CustomOp1 for Histc {
+    fn name(&self) -> &'static str {
+        "histc"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let vs = to_f64_vec(storage, layout, self.name())?;
+        let (mut min, mut max) = (self.min, self.max);
+        if min == max {
+            // Use the range of the data, as done by PyTorch.
+            let finite = || vs.iter().copied().filter(|v| v.is_finite());
+            min = finite().fold(f64::INFINITY, f64::min);
+            max = finite().fold(f64::NEG_INFINITY, f64::max);
+            if min > max {
+                (min, max) = (0., 0.)
+            }
+            if min == max {
+                (min, max) = (min - 1., max + 1.)
+            }
+        }
+        let mut hist = vec![0f64; self.bins];
+        for &v in vs.iter() {
+            if v < min || v > max || v.is_nan() {
+                continue;
+            }
+            let bin = ((v - min) / (max - min) * self.bins as f64) as usize;
+            hist[bin.min(self.bins - 1)] += 1.
+        }
+        Ok((from_f64_vec(hist, storage.dtype()), Shape::from(self.bins)))
+    }
+}
+
+struct UniqueMap {
+    sorted: bool,
+    inverse: bool,
+    len: std::cell::Cell<usize>,
+}
+
+impl Map1Any for UniqueMap {
+    const BOOL: bool = true;
+
+    fn f<T: NumDType, W: Fn(Vec<T>) -> CpuStorage>(
+        &self,
+        vs: &[T],
+        layout: &Layout,
+        wrap: W,
+    ) -> Result<CpuStorage> {
+        let vs = contiguous(vs, layout, "unique")?;
+        let mut order: Vec<usize> = (0..vs.len()).collect();
+        order.sort_by(|&i, &j| {
+            vs[i]
+                .partial_cmp(&vs[j])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        // Group the equal values, the sort is stable so the first index of each group is the
+        // first occurrence of the value.
+        let mut groups: Vec<&[usize]> = order
+            .chunk_by(|&i, &j| vs[i].partial_cmp(&vs[j]) == Some(std::cmp::Ordering::Equal))
+            .collect();
+        if !self.sorted {
+            groups.sort_by_key(|g| g[0])
+        }
+        self.len.set(groups.len());
+        if self.inverse {
+            let mut inverse = vec![0u32; vs.len()];
+            for (id, group) in groups.iter().enumerate() {
+                for &i in group.iter() {
+                    inverse[i] = id as u32
+                }
+            }
+            Ok(CpuStorage::U32(inverse))
+        } else {
+            Ok(wrap(groups.iter().map(|g| vs[g[0]]).collect()))
+        }
+    }
+}
+
+struct Unique {
+    sorted: bool,
+}
+
+impl CustomOp1 for Unique {
+    fn name(&self) -> &'static str {
+        "unique"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let map = UniqueMap {
+            sorted: self.sorted,
+            inverse: false,
+            len: Default::default(),
+        };
+        let storage = map.map(storage, layout)?;
+        Ok((storage, Shape::from(map.len.get())))
+    }
+}
+
+struct UniqueInverse {
+    sorted: bool,
+}
+
+impl CustomOp1 for UniqueInverse {
+    fn name(&self) -> &'static str {
+        "unique-inverse"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let map = UniqueMap {
+            sorted: self.sorted,
+            inverse: true,
+            len: Default::default(),
+        };
+        Ok((map.map(storage, layout)?, layout.shape().clone()))
+    }
+}
+
+struct SearchsortedMap<'a> {
+    values: &'a CpuStorage,
+    values_l: &'a Layout,
+    right: bool,
+}
+
+impl Map1Any for SearchsortedMap<'_> {
+    fn f<T: NumDType, W: Fn(Vec<T>) -> CpuStorage>(
+        &self,
+        seq: &[T],
+        layout: &Layout,
+        _wrap: W,
+    ) -> Result<CpuStorage> {
+        let seq = contiguous(seq, layout, "searchsorted")?;
+        let values = T::cpu_storage_as_slice(self.values)?;
+        let values = contiguous(values, self.values_l, "searchsorted")?;
+        let seq_len = layout.dims().last().copied().unwrap_or(0);
+        let n_values = match self.values_l.dims() {
+            // A 1d sequence is shared by all the values.
+            _ if layout.dims().len() == 1 => values.len(),
+            dims => dims.last().copied().unwrap_or(0),
+        };
+        let mut indexes = Vec::with_capacity(values.len());
+        for (i, &v) in values.iter().enumerate() {
+            let start = i.checked_div(n_values).unwrap_or(0) * seq_len;
+            let seq = &seq[start..start + seq_len];
+            let index = if self.right {
+                seq.partition_point(|&s| s <= v)
+            } else {
+                seq.partition_point(|&s| s < v)
+            };
+            indexes.push(index as u32)
+        }
+        Ok(CpuStorage::U32(indexes))
+    }
+}
+
+struct Searchsorted {
+    right: bool,
+}
+
+impl CustomOp2 for Searchsorted {
+    fn name(&self) -> &'static str {
+        "searchsorted"
+    }
+
+    fn cpu_fwd(
+        &self,
+        s1: &CpuStorage,
+        l1: &Layout,
+        s2: &CpuStorage,
+        l2: &Layout,
+    ) -> Result<(CpuStorage, Shape)> {
+        let map = SearchsortedMap {
+            values: s2,
+            values_l: l2,
+            right: self.right,
+        };
+        Ok((map.map(s1, l1)?, l2.shape().clone()))
+    }
+}
+
+impl Tensor {
+    /// Counts the number of occurrences of each value in a tensor of non-negative integers.
+    ///
+    /// For a 1d input the result has `max(self) + 1` elements, at least `minlength`. A 2d input is
+    /// processed as a batch of rows and the result has shape `(rows, bins)` with the same number
+    /// of bins for all the rows. When `weights` is given, it must have the same shape as the input
+    /// and the weight of each value is added rather than one, the result then uses the weights
+    /// dtype rather than `u32`.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[1u32, 3, 1, 0], &Device::Cpu)?;
+    /// assert_eq!(a.bincount(None, 0)?.to_vec1::<u32>()?, &[1, 2, 0, 1]);
+    /// let w = Tensor::new(&[0.5f32, 1., 2., 1.], &Device::Cpu)?;
+    /// assert_eq!(a.bincount(Some(&w), 5)?.to_vec1::<f32>()?, &[1., 2.5, 0., 1., 0.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn bincount(&self, weights: Option<&Tensor>, minlength: usize) -> Result<Self> {
+        if !self.dtype().is_int() {
+            bail!("bincount expects an integer tensor, got {:?}", self.dtype())
+        }
+        let (rows, batched) = match self.dims() {
+            [_] => (1, false),
+            [rows, _] => (*rows, true),
+            _ => bail!("bincount expects a 1d or 2d tensor, got {:?}", self.shape()),
+        };
+        let op = Bincount {
+            rows,
+            batched,
+            minlength,
+        };
+        let xs = self.to_dtype(DType::I64)?.contiguous()?;
+        match weights {
+            None => xs.apply_op1_no_bwd(&op),
+            Some(weights) => {
+                self.same_shape_binary_op(weights, "bincount")?;
+                let dtype = weights.dtype();
+                let weights = weights.to_dtype(DType::F64)?.contiguous()?;
+                xs.apply_op2_no_bwd(&weights, &op)?.to_dtype(dtype)
+            }
+        }
+    }
+
+    /// Computes the histogram of all the tensor values using `bins` bins of equal width between
+    /// `min` and `max`. The values outside of this range are ignored, when `min` and `max` are
+    /// equal the range of the values is used instead.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[1f32, 2., 1., 4.], &Device::Cpu)?;
+    /// assert_eq!(a.histc(4, 0., 3.)?.to_vec1::<f32>()?, &[0., 2., 1., 0.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn histc(&self, bins: usize, min: f64, max: f64) -> Result<Self> {
+        if !self.dtype().is_float() {
+            bail!("histc expects a float tensor, got {:?}", self.dtype())
+        }
+        if bins == 0 || min > max {
+            bail!("histc: invalid arguments bins {bins}, min {min} and max {max}")
+        }
+        let op = Histc { bins, min, max };
+        self.flatten_all()?
+            .to_dtype(DType::F64)?
+            .contiguous()?
+            .apply_op1_no_bwd(&op)?
+            .to_dtype(self.dtype())
+    }
+
+    /// Returns the unique values of the tensor as a 1d tensor. The values are sorted in ascending
+    /// order if `sorted` is true, otherwise they are returned in the order of their first
+    /// occurrence.
+    ///
+    /// If `return_inverse` is true, the index of each input value in the unique values is also
+    /// returned with the same shape as the input. If `return_counts` is true, the number of
+    /// occurrences of each unique value is also returned.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[3u32, 1, 3, 2], &Device::Cpu)?;
+    /// let (values, inverse, counts) = a.unique(true, true, true)?;
+    /// assert_eq!(values.to_vec1::<u32>()?, &[1, 2, 3]);
+    /// assert_eq!(inverse.unwrap().to_vec1::<u32>()?, &[2, 0, 2, 1]);
+    /// assert_eq!(counts.unwrap().to_vec1::<u32>()?, &[1, 1, 2]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn unique(
+        &self,
+        sorted: bool,
+        return_inverse: bool,
+        return_counts: bool,
+    ) -> Result<(Self, Option<Self>, Option<Self>)> {
+        let xs = self.contiguous()?;
+        let values = xs.flatten_all()?.apply_op1_no_bwd(&Unique { sorted })?;
+        if !return_inverse && !return_counts {
+            return Ok((values, None, None));
+        }
+        let inverse = xs.apply_op1_no_bwd(&UniqueInverse { sorted })?;
+        let counts = if return_counts {
+            let counts = inverse.flatten_all()?.bincount(None, values.elem_count())?;
+            Some(counts)
+        } else {
+            None
+        };
+        let inverse = if return_inverse { Some(inverse) } else { None };
+        Ok((values, inverse, counts))
+    }
+
+    /// Finds the indexes where the `values` should be inserted in the sorted sequence `self` to
+    /// keep it sorted. With `right` set to false, the index of the first element that is not
+    /// smaller than the value is returned, otherwise the index of the first element that is
+    /// larger than the value.
+    ///
+    /// If `self` is 1d, it is used for all the values. Otherwise the sorted sequences are along
+    /// the last dimension and `values` must have the same leading dimensions. The result has the
+    /// shape of `values` and uses the `u32` dtype.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let seq = Tensor::new(&[1f32, 3., 5., 7.], &Device::Cpu)?;
+    /// let values = Tensor::new(&[[3f32, 6.], [0., 9.]], &Device::Cpu)?;
+    /// let indexes = seq.searchsorted(&values, false)?;
+    /// assert_eq!(indexes.to_vec2::<u32>()?, &[[1, 3], [0, 4]]);
+    /// let indexes = seq.searchsorted(&values, true)?;
+    /// assert_eq!(indexes.to_vec2::<u32>()?, &[[2, 3], [0, 4]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn searchsorted(&self, values: &Self, right: bool) -> Result<Self> {
+        if self.rank() == 0 {
+            bail!("searchsorted expects a sorted sequence with at least one dimension")
+        }
+        if self.rank() > 1 {
+            let seq_dims = &self.dims()[..self.rank() - 1];
+            let values_dims = values.dims();
+            if values.rank() != self.rank() || &values_dims[..values_dims.len() - 1] != seq_dims {
+                bail!(
+                    "searchsorted: the leading dimensions of the sequence {:?} and values {:?} differ",
+                    self.shape(),
+                    values.shape()
+                )
+            }
+        }
+        let values = values.to_dtype(self.dtype())?.contiguous()?;
+        self.contiguous()?
+            .apply_op2_no_bwd(&values, &Searchsorted { right })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Error, Tensor};
+use crate::{DType, Error, Shape, Tensor};
 use std::ops::{
     Bound, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
 };
@@ -26,10 +26,22 @@ impl Tensor {
     /// ```
     fn index(&self, indexers: &[TensorIndexer]) -> Result<Self, Error> {
         let mut x = self.clone();
-        let dims = self.shape().dims();
         let mut current_dim = 0;
-        for (i, indexer) in indexers.iter().enumerate() {
+        // The tensor indexes are applied once all the other indexers have been processed, the
+        // dimensions here are the ones of the final `x`.
+        let mut tensor_indexes = vec![];
+        // As in NumPy, integer indexes count as index tensors when combined with index tensors or
+        // masks so that they take part in the placement of the indexed dimensions.
+        let has_tensor_indexes = indexers
+            .iter()
+            .any(|i| matches!(i, TensorIndexer::IndexSelect(_) | TensorIndexer::Mask(_)));
+        for indexer in indexers.iter() {
             x = match indexer {
+                TensorIndexer::Select(n) if has_tensor_indexes => {
+                    tensor_indexes.push((current_dim, Tensor::new(*n as u32, x.device())?));
+                    current_dim += 1;
+                    x
+                }
                 TensorIndexer::Select(n) => x.narrow(current_dim, *n, 1)?.squeeze(current_dim)?,
                 TensorIndexer::Narrow(left_bound, right_bound) => {
                     let start = match left_bound {
@@ -40,27 +52,182 @@ impl Tensor {
                     let stop = match right_bound {
                         Bound::Included(n) => *n + 1,
                         Bound::Excluded(n) => *n,
-                        Bound::Unbounded => dims[i],
+                        Bound::Unbounded => x.dim(current_dim)?,
                     };
                     let out = x.narrow(current_dim, start, stop.saturating_sub(start))?;
                     current_dim += 1;
                     out
                 }
                 TensorIndexer::IndexSelect(indexes) => {
-                    if indexes.rank() != 1 {
-                        crate::bail!("multi-dimensional tensor indexing is not supported")
-                    }
-                    let out = x.index_select(&indexes.to_device(x.device())?, current_dim)?;
+                    tensor_indexes.push((current_dim, indexes.to_device(x.device())?));
                     current_dim += 1;
-                    out
+                    x
+                }
+                TensorIndexer::Mask(mask) => {
+                    let rank = mask.rank();
+                    let dims = x.dims();
+                    if rank == 0 || dims.len() < current_dim + rank {
+                        crate::bail!(
+                            "cannot index {:?} with a mask of shape {:?}",
+                            x.shape(),
+                            mask.shape()
+                        )
+                    }
+                    if mask.dims() != &dims[current_dim..current_dim + rank] {
+                        crate::bail!(
+                            "mask of shape {:?} does not match the indexed dims {:?}",
+                            mask.shape(),
+                            &dims[current_dim..current_dim + rank]
+                        )
+                    }
+                    // A mask is equivalent to indexing its dims with its non-zero positions.
+                    let nonzero = mask.to_device(x.device())?.nonzero()?;
+                    for i in 0..rank {
+                        let indexes = nonzero.narrow(1, i, 1)?.squeeze(1)?;
+                        tensor_indexes.push((current_dim + i, indexes));
+                    }
+                    current_dim += rank;
+                    x
                 }
                 TensorIndexer::Err(e) => crate::bail!("indexing error {e:?}"),
             };
         }
-        Ok(x)
+        match tensor_indexes.as_slice() {
+            [] => Ok(x),
+            [(dim, indexes)]
+                if indexes.rank() == 1 && matches!(indexes.dtype(), DType::U8 | DType::U32) =>
+            {
+                x.index_select(indexes, *dim)
+            }
+            tensor_indexes => x.index_tensors(tensor_indexes),
+        }
+    }
+
+    /// Indexes multiple dimensions with tensors, following the PyTorch semantics.
+    ///
+    /// The index tensors are broadcasted together and the dimensions of the broadcasted shape
+    /// replace the indexed dimensions. If the indexed dimensions are not adjacent, the new
+    /// dimensions are moved to the front.
+    fn index_tensors(&self, tensor_indexes: &[(usize, Tensor)]) -> Result<Self, Error> {
+        let dims = self.dims();
+        let mut index_shape = Shape::from(());
+        for (_, indexes) in tensor_indexes.iter() {
+            index_shape = index_shape.broadcast_shape_binary_op(indexes.shape(), "index")?;
+        }
+        // Combine the indexes into a single index over the flattened indexed dimensions.
+        let mut flat_indexes: Option<Tensor> = None;
+        let mut stride = 1;
+        for (dim, indexes) in tensor_indexes.iter().rev() {
+            let indexes = check_indexes(indexes, dims[*dim])?
+                .broadcast_as(&index_shape)?
+                .affine(stride as f64, 0.)?;
+            flat_indexes = Some(match flat_indexes {
+                None => indexes,
+                Some(flat_indexes) => (flat_indexes + indexes)?,
+            });
+            stride *= dims[*dim];
+        }
+        let flat_indexes = match flat_indexes {
+            None => crate::bail!("no index tensors"),
+            Some(flat_indexes) => flat_indexes.flatten_all()?,
+        };
+        let indexed_dims = tensor_indexes.iter().map(|(d, _)| *d).collect::<Vec<_>>();
+        let other_dims = (0..dims.len())
+            .filter(|d| !indexed_dims.contains(d))
+            .collect::<Vec<_>>();
+        let mut out_dims = index_shape.dims().to_vec();
+        out_dims.extend(other_dims.iter().map(|&d| dims[d]));
+        let xs = self.permute([indexed_dims.as_slice(), other_dims.as_slice()].concat())?;
+        let mut xs_dims = vec![stride];
+        xs_dims.extend(other_dims.iter().map(|&d| dims[d]));
+        let xs = xs
+            .reshape(xs_dims)?
+            .index_select(&flat_indexes, 0)?
+            .reshape(out_dims)?;
+        let first_dim = indexed_dims[0];
+        let adjacent = indexed_dims.windows(2).all(|w| w[1] == w[0] + 1);
+        if adjacent && first_dim > 0 {
+            // Move the new dimensions back to where the indexed dimensions were.
+            let index_rank = index_shape.rank();
+            let mut perm = (index_rank..index_rank + first_dim).collect::<Vec<_>>();
+            perm.extend(0..index_rank);
+            perm.extend(index_rank + first_dim..xs.rank());
+            xs.permute(perm)
+        } else {
+            Ok(xs)
+        }
+    }
+
+    /// Returns a copy of `self` where the elements selected by `index`, with the same semantics
+    /// as `.i()`, are replaced with `values`. The `values` tensor is broadcasted to the shape of
+    /// the selection. When `accumulate` is true, the values are added to the selected elements
+    /// instead, elements that are selected multiple times get all the values added. When
+    /// `accumulate` is false and an element is selected multiple times, its value is unspecified.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[0f32, 1., 2.], [3., 4., 5.]], &Device::Cpu)?;
+    /// let rows = Tensor::new(&[0u32, 1], &Device::Cpu)?;
+    /// let cols = Tensor::new(&[2u32, 0], &Device::Cpu)?;
+    /// let values = Tensor::new(&[-1f32, -2.], &Device::Cpu)?;
+    /// let b = a.index_put((&rows, &cols), &values, false)?;
+    /// assert_eq!(b.to_vec2::<f32>()?, &[[0., 1., -1.], [-2., 4., 5.]]);
+    /// let b = a.index_put((.., 1), &values, true)?;
+    /// assert_eq!(b.to_vec2::<f32>()?, &[[0., 0., 2.], [3., 2., 5.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn index_put<T>(&self, index: T, values: &Tensor, accumulate: bool) -> Result<Self, Error>
+    where
+        Self: IndexOp<T>,
+    {
+        let el_count = self.elem_count();
+        // The positions use i64 values as tensors can have more than `u32::MAX` elements.
+        let positions = Tensor::arange(0i64, el_count as i64, self.device())?
+            .reshape(self.shape())?
+            .i(index)?;
+        let values = values
+            .broadcast_as(positions.shape())?
+            .flatten_all()?
+            .contiguous()?;
+        let positions = positions.flatten_all()?.contiguous()?;
+        let xs = self.flatten_all()?;
+        let xs = if accumulate {
+            xs.index_add(&positions, &values, 0)?
+        } else {
+            let zeros = xs.zeros_like()?;
+            let selected = Tensor::zeros(el_count, DType::I64, self.device())?
+                .index_add(&positions, &positions.ones_like()?, 0)?
+                .ne(0i64)?;
+            selected.where_cond(&zeros.index_add(&positions, &values, 0)?, &xs)?
+        };
+        xs.reshape(self.shape())
     }
 }
 
+/// Validates the indexes for a dimension of size `size` and converts them to `u32`. Negative
+/// indexes count from the end of the dimension.
+///
+/// The range check reads the indexes on the host, it is only done on cpu as on other devices it
+/// would wait for the device on each indexing op.
+fn check_indexes(indexes: &Tensor, size: usize) -> Result<Tensor, Error> {
+    let indexes = match indexes.dtype() {
+        DType::U8 | DType::U16 | DType::U32 => indexes.clone(),
+        DType::I8 | DType::I16 | DType::I32 | DType::I64 => indexes
+            .lt(0f64)?
+            .where_cond(&(indexes + size as f64)?, indexes)?,
+        dtype => crate::bail!("unsupported dtype {dtype:?} for index tensors"),
+    };
+    if indexes.device().is_cpu() && indexes.elem_count() > 0 {
+        let indexes = indexes.flatten_all()?.to_dtype(DType::I64)?;
+        let min = indexes.min(0)?.to_scalar::<i64>()?;
+        let max = indexes.max(0)?.to_scalar::<i64>()?;
+        if min < 0 || max >= size as i64 {
+            crate::bail!("index out of range [{min}, {max}] for a dimension of size {size}")
+        }
+    }
+    indexes.to_dtype(DType::U32)
+}
+
 #[derive(Debug)]
 /// Generic structure used to index a slice of the tensor
 pub enum TensorIndexer {
@@ -68,8 +235,11 @@ pub enum TensorIndexer {
     Select(usize),
     /// This is a regular slice, purely indexing a chunk of the tensor
     Narrow(Bound<usize>, Bound<usize>),
-    /// Indexing via a 1d tensor
+    /// Indexing via a tensor of indexes, multiple index tensors are broadcasted together.
     IndexSelect(Tensor),
+    /// Indexing via a mask, this selects the positions where the mask is non-zero in the
+    /// dimensions covered by the mask.
+    Mask(Tensor),
     Err(Error),
 }
 
@@ -100,7 +270,13 @@ impl From<Vec<u32>> for TensorIndexer {
 
 impl From<&Tensor> for TensorIndexer {
     fn from(tensor: &Tensor) -> Self {
-        TensorIndexer::IndexSelect(tensor.clone())
+        // Bool tensors, e.g. the results of comparisons, are used as masks. The comparisons
+        // return `u8` values on cuda and metal so masks there need an explicit `Mask` indexer.
+        if tensor.dtype() == DType::Bool {
+            TensorIndexer::Mask(tensor.clone())
+        } else {
+            TensorIndexer::IndexSelect(tensor.clone())
+        }
     }
 }
 
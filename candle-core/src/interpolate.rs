@@ -0,0 +1,262 @@
+//! Linear and cubic interpolation with `align_corners` and antialiasing.
+//!
+//! The interpolations are separable so they are applied one spatial dimension at a time with the
+//! `Resample` op: each output position is a weighted sum of a few input positions. The positions
+//! and weights follow the conventions of PyTorch `interpolate` when an output size is given.
+use crate::op::{BackpropOp, Op, Resample};
+use crate::tensor::from_storage;
+use crate::{bail, Result, Tensor};
+use std::sync::Arc;
+
+/// The interpolation algorithm, `Linear` and `Cubic` are applied on each spatial dimension so
+/// they correspond to the PyTorch `bilinear` and `bicubic` modes for 2d inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterpolationMode {
+    #[default]
+    Nearest,
+    Linear,
+    Cubic,
+}
+
+/// Interpolation options, the defaults match the PyTorch ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InterpolateConfig {
+    pub mode: InterpolationMode,
+    /// Align the centers of the corner elements of the input and output rather than their outer
+    /// edges.
+    pub align_corners: bool,
+    /// Widen the interpolation filter when downsampling so that all the input elements contribute
+    /// to the output, as done by PIL.
+    pub antialias: bool,
+}
+
+// The cubic convolution from https://en.wikipedia.org/wiki/Bicubic_interpolation
+fn cubic_filter(x: f64, a: f64) -> f64 {
+    let x = x.abs();
+    if x < 1. {
+        ((a + 2.) * x - (a + 3.)) * x * x + 1.
+    } else if x < 2. {
+        ((a * x - 5. * a) * x + 8. * a) * x - 4. * a
+    } else {
+        0.
+    }
+}
+
+fn linear_filter(x: f64) -> f64 {
+    (1. - x.abs()).max(0.)
+}
+
+/// Computes the resampling of `dim` from `src_size` to `dst_size` positions.
+fn resample(
+    dim: usize,
+    src_size: usize,
+    dst_size: usize,
+    config: &InterpolateConfig,
+) -> Result<Resample> {
+    if src_size == 0 || dst_size == 0 {
+        bail!("interpolate: input size {src_size} and output size {dst_size} should be positive")
+    }
+    let scale = if config.align_corners {
+        if dst_size > 1 {
+            (src_size - 1) as f64 / (dst_size - 1) as f64
+        } else {
+            0.
+        }
+    } else {
+        src_size as f64 / dst_size as f64
+    };
+    let mut taps = vec![];
+    match config.mode {
+        InterpolationMode::Nearest => bail!("interpolate: nearest mode has no resampling weights"),
+        _ if config.antialias => {
+            // The filter support is widened by the scale when downsampling, the cubic filter uses
+            // a = -0.5 in this case as in PIL.
+            let (support, filter): (f64, fn(f64) -> f64) = match config.mode {
+                InterpolationMode::Cubic => (2., |x| cubic_filter(x, -0.5)),
+                _ => (1., linear_filter),
+            };
+            let (support, inv_scale) = if scale >= 1. {
+                (support * scale, 1. / scale)
+            } else {
+                (support, 1.)
+            };
+            for o in 0..dst_size {
+                let center = scale * (o as f64 + 0.5);
+                let min = ((center - support + 0.5) as isize).max(0) as usize;
+                let max = ((center + support + 0.5) as isize).clamp(0, src_size as isize) as usize;
+                let mut weights: Vec<_> = (min..max)
+                    .map(|i| (i, filter((i as f64 - center + 0.5) * inv_scale)))
+                    .collect();
+                let total = weights.iter().map(|(_, w)| w).sum::<f64>();
+                if total != 0. {
+                    weights.iter_mut().for_each(|(_, w)| *w /= total)
+                }
+                taps.push(weights)
+            }
+        }
+        InterpolationMode::Linear => {
+            for o in 0..dst_size {
+                let src = if config.align_corners {
+                    o as f64 * scale
+                } else {
+                    ((o as f64 + 0.5) * scale - 0.5).max(0.)
+                };
+                let i0 = (src as usize).min(src_size - 1);
+                let i1 = (i0 + 1).min(src_size - 1);
+                let l = (src - i0 as f64).clamp(0., 1.);
+                taps.push(vec![(i0, 1. - l), (i1, l)])
+            }
+        }
+        InterpolationMode::Cubic => {
+            for o in 0..dst_size {
+                let src = if config.align_corners {
+                    o as f64 * scale
+                } else {
+                    (o as f64 + 0.5) * scale - 0.5
+                };
+                // The positions outside of the input use the value on the border.
+                let i = src.floor();
+                let t = src - i;
+                let weights = (-1..3).map(|k| {
+                    let index = (i as isize + k).clamp(0, src_size as isize - 1) as usize;
+                    (index, cubic_filter(t - k as f64, -0.75))
+                });
+                taps.push(weights.collect())
+            }
+        }
+    }
+    let n_taps = taps.iter().map(|t| t.len()).max().unwrap_or(0).max(1);
+    let mut indexes = Vec::with_capacity(dst_size * n_taps);
+    let mut weights = Vec::with_capacity(dst_size * n_taps);
+    for t in taps.iter() {
+        for k in 0..n_taps {
+            let (index, weight) = t
+                .get(k)
+                .copied()
+                .unwrap_or((t.first().map_or(0, |t| t.0), 0.));
+            indexes.push(index);
+            weights.push(weight)
+        }
+    }
+    Ok(Resample {
+        dim,
+        src_size,
+        dst_size,
+        taps: n_taps,
+        indexes,
+        weights,
+        transpose: false,
+    })
+}
+
+impl Tensor {
+    pub(crate) fn resample(&self, resample: &Resample) -> Result<Self> {
+        let (src_size, dst_size) = resample.in_out_sizes();
+        if self.dim(resample.dim)? != src_size {
+            bail!(
+                "resample: expected a size of {src_size} for dim {} got {:?}",
+                resample.dim,
+                self.shape()
+            )
+        }
+        let mut dims = self.dims().to_vec();
+        dims[resample.dim] = dst_size;
+        let storage = self.storage().resample(self.layout(), resample)?;
+        let op = BackpropOp::new1(self, |arg| Op::Resample(arg, Arc::new(resample.clone())));
+        Ok(from_storage(storage, dims, op, false))
+    }
+
+    fn interpolate_dim(&self, dim: usize, size: usize, config: &InterpolateConfig) -> Result<Self> {
+        let resample = resample(dim, self.dim(dim)?, size, config)?;
+        self.resample(&resample)
+    }
+
+    /// Same as `interpolate1d` using the interpolation mode and options from `config`.
+    ///
+    /// The input tensor should have three dimensions, `(batch, channels, l)`, the returned
+    /// tensor also has three dimensions, `(batch, channels, target_size)`.
+    pub fn interpolate1d_with_config(
+        &self,
+        target_size: usize,
+        config: &InterpolateConfig,
+    ) -> Result<Self> {
+        let (_n, _c, _l) = self.dims3()?;
+        if config.mode == InterpolationMode::Nearest {
+            if config.align_corners || config.antialias {
+                bail!("interpolate: align_corners and antialias are not supported in nearest mode")
+            }
+            return self.interpolate1d(target_size);
+        }
+        self.interpolate_dim(2, target_size, config)
+    }
+
+    /// Same as `interpolate2d` using the interpolation mode and options from `config`.
+    ///
+    /// The input tensor should have four dimensions, `(batch, channels, h, w)`, the returned
+    /// tensor also has four dimensions, `(batch, channels, target_h, target_w)`.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// use candle_core::interpolate::{InterpolateConfig, InterpolationMode};
+    /// let a = Tensor::new(&[[[[0f32, 2.], [4., 6.]]]], &Device::Cpu)?;
+    /// let config = InterpolateConfig {
+    ///     mode: InterpolationMode::Linear,
+    ///     align_corners: true,
+    ///     antialias: false,
+    /// };
+    /// let a = a.interpolate2d_with_config(3, 3, &config)?.squeeze(0)?.squeeze(0)?;
+    /// assert_eq!(a.to_vec2::<f32>()?, &[[0., 1., 2.], [2., 3., 4.], [4., 5., 6.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn interpolate2d_with_config(
+        &self,
+        target_h: usize,
+        target_w: usize,
+        config: &InterpolateConfig,
+    ) -> Result<Self> {
+        let (_n, _c, _h, _w) = self.dims4()?;
+        if config.mode == InterpolationMode::Nearest {
+            if config.align_corners || config.antialias {
+                bail!("interpolate: align_corners and antialias are not supported in nearest mode")
+            }
+            return self.interpolate2d(target_h, target_w);
+        }
+        self.interpolate_dim(3, target_w, config)?
+            .interpolate_dim(2, target_h, config)
+    }
+
+    /// Bilinear interpolation of the input tensor to the `(target_h, target_w)` size.
+    ///
+    /// The input tensor should have four dimensions, `(batch, channels, h, w)`.
+    pub fn upsample_bilinear2d(
+        &self,
+        target_h: usize,
+        target_w: usize,
+        align_corners: bool,
+    ) -> Result<Self> {
+        let config = InterpolateConfig {
+            mode: InterpolationMode::Linear,
+            align_corners,
+            antialias: false,
+        };
+        self.interpolate2d_with_config(target_h, target_w, &config)
+    }
+
+    /// Bicubic interpolation of the input tensor to the `(target_h, target_w)` size. The values
+    /// are not clamped so they can go slightly out of the input range.
+    ///
+    /// The input tensor should have four dimensions, `(batch, channels, h, w)`.
+    pub fn upsample_bicubic2d(
+        &self,
+        target_h: usize,
+        target_w: usize,
+        align_corners: bool,
+    ) -> Result<Self> {
+        let config = InterpolateConfig {
+            mode: InterpolationMode::Cubic,
+            align_corners,
+            antialias: false,
+        };
+        self.interpolate2d_with_config(target_h, target_w, &config)
+    }
+}
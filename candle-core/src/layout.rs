@@ -106,6 +106,37 @@ impl Layout {
         })
     }
 
+    /// Sliding windows of `size` elements taken every `step` elements along `dim`. The windows are
+    /// indexed by `dim` and the elements of each window by a new trailing dimension.
+    pub fn unfold(&self, dim: usize, size: usize, step: usize) -> Result<Self> {
+        let dims = self.shape().dims();
+        if dim >= dims.len() {
+            Err(Error::DimOutOfRange {
+                shape: self.shape().clone(),
+                dim: dim as i32,
+                op: "unfold",
+            }
+            .bt())?
+        }
+        if size == 0 || step == 0 || size > dims[dim] {
+            crate::bail!(
+                "unfold: size {size} and step {step} should be positive and size at most {}",
+                dims[dim]
+            )
+        }
+        let mut dims = dims.to_vec();
+        dims[dim] = (dims[dim] - size) / step + 1;
+        dims.push(size);
+        let mut stride = self.stride.clone();
+        stride.push(stride[dim]);
+        stride[dim] *= step;
+        Ok(Self {
+            shape: Shape::from(dims),
+            stride,
+            start_offset: self.start_offset,
+        })
+    }
+
     pub fn transpose(&self, dim1: usize, dim2: usize) -> Result<Self> {
         let rank = self.shape.rank();
         if rank <= dim1 || rank <= dim2 {
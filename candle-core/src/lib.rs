@@ -51,6 +51,7 @@
 mod accelerate;
 pub mod backend;
 pub mod backprop;
+mod complex;
 pub mod conv;
 mod convert;
 pub mod cpu;
@@ -59,21 +60,33 @@ pub mod cpu_backend;
 pub mod cuda_backend;
 mod custom_op;
 mod device;
+mod diagonal;
 pub mod display;
 mod dtype;
 pub mod dummy_cuda_backend;
 mod dummy_metal_backend;
+mod einsum;
 pub mod error;
+mod fft;
+mod generator;
+pub mod grid_sample;
+mod histogram;
 mod indexer;
+pub mod interpolate;
 pub mod layout;
+pub mod linalg;
+mod mask;
 #[cfg(feature = "metal")]
 pub mod metal_backend;
 #[cfg(feature = "mkl")]
 mod mkl;
 pub mod npy;
 pub mod op;
+pub mod pad;
 pub mod pickle;
+pub mod pool;
 pub mod quantized;
+pub mod reduce;
 pub mod safetensors;
 pub mod scalar;
 pub mod shape;
@@ -93,8 +106,9 @@ pub use cuda_backend::cudnn;
 pub use cpu_backend::{CpuStorage, CpuStorageRef};
 pub use custom_op::{CustomOp1, CustomOp2, CustomOp3, InplaceOp1, InplaceOp2, InplaceOp3, UgIOp1};
 pub use device::{Device, DeviceLocation, NdArray};
-pub use dtype::{DType, DTypeParseError, FloatDType, IntDType, WithDType};
+pub use dtype::{DType, DTypeParseError, FloatDType, IntDType, NumDType, WithDType};
 pub use error::{Context, Error, Result};
+pub use generator::Generator;
 pub use indexer::{IndexOp, TensorIndexer};
 pub use layout::Layout;
 pub use shape::{Shape, D};
@@ -0,0 +1,928 @@
+//! Linear algebra operations: inverse, linear solve, decompositions and determinants.
+//!
+//! All the functions in this module operate on the last two dimensions of their inputs, the
+//! leading dimensions being treated as batch dimensions. They are only implemented on the cpu
+//! for the `f32` and `f64` dtypes, the computations are always carried out in `f64`.
+//!
+//! ```rust
+//! use candle_core::{linalg, Device, Tensor};
+//! # fn main() -> candle_core::Result<()> {
+//! let a = Tensor::new(&[[2f64, 1.], [0., 4.]], &Device::Cpu)?;
+//! let a_inv = linalg::inv(&a)?;
+//! assert_eq!(a_inv.to_vec2::<f64>()?, &[[0.5, -0.125], [0., 0.25]]);
+//! let det = linalg::det(&a)?;
+//! assert_eq!(det.to_vec0::<f64>()?, 8.);
+//! # Ok(())
+//! # }
+//! ```
+use crate::backend::BackendStorage;
+use crate::{bail, CpuStorage, CustomOp1, CustomOp2, DType, Layout, Result, Shape, Tensor, D};
+
+/// The number of Jacobi sweeps after which `eigh` and `svd` give up on convergence.
+const MAX_SWEEPS: usize = 100;
+
+pub(crate) fn to_f64_vec(
+    storage: &CpuStorage,
+    layout: &Layout,
+    op: &'static str,
+) -> Result<Vec<f64>> {
+    let (start, end) = match layout.contiguous_offsets() {
+        Some(offsets) => offsets,
+        None => bail!("{op}: input has to be contiguous"),
+    };
+    let vs = match storage {
+        CpuStorage::F32(vs) => vs[start..end].iter().map(|&v| v as f64).collect(),
+        CpuStorage::F64(vs) => vs[start..end].to_vec(),
+        s => Err(crate::Error::UnsupportedDTypeForOp(s.dtype(), op).bt())?,
+    };
+    Ok(vs)
+}
+
+pub(crate) fn from_f64_vec(vs: Vec<f64>, dtype: DType) -> CpuStorage {
+    match dtype {
+        DType::F32 => CpuStorage::F32(vs.into_iter().map(|v| v as f32).collect()),
+        _ => CpuStorage::F64(vs),
+    }
+}
+
+/// Splits the dimensions of a layout into (batch dims, rows, cols).
+fn matrix_dims<'a>(layout: &'a Layout, op: &'static str) -> Result<(&'a [usize], usize, usize)> {
+    let dims = layout.dims();
+    let rank = dims.len();
+    if rank < 2 {
+        bail!(
+            "{op}: expected a tensor with at least two dimensions, got {:?}",
+            dims
+        )
+    }
+    Ok((&dims[..rank - 2], dims[rank - 2], dims[rank - 1]))
+}
+
+fn square_dims<'a>(layout: &'a Layout, op: &'static str) -> Result<(&'a [usize], usize)> {
+    let (batch, m, n) = matrix_dims(layout, op)?;
+    if m != n {
+        bail!("{op}: expected square matrices, got {:?}", layout.dims())
+    }
+    Ok((batch, n))
+}
+
+fn check_float(t: &Tensor, op: &'static str) -> Result<()> {
+    match t.dtype() {
+        DType::F32 | DType::F64 => Ok(()),
+        dtype => Err(crate::Error::UnsupportedDTypeForOp(dtype, op).bt()),
+    }
+}
+
+fn transpose(a: &[f64], m: usize, n: usize) -> Vec<f64> {
+    let mut t = vec![0f64; m * n];
+    for i in 0..m {
+        for j in 0..n {
+            t[j * m + i] = a[i * n + j]
+        }
+    }
+    t
+}
+
+/// An LU decomposition with partial pivoting of a square `n x n` matrix, `lu` holds both the unit
+/// lower triangular and the upper triangular factors.
+struct Lu {
+    lu: Vec<f64>,
+    perm: Vec<usize>,
+    n: usize,
+    sign: f64,
+    singular: bool,
+}
+
+impl Lu {
+    fn new(mut lu: Vec<f64>, n: usize) -> Self {
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut sign = 1.;
+        let mut singular = false;
+        for k in 0..n {
+            let mut p = k;
+            for i in k + 1..n {
+                if lu[i * n + k].abs() > lu[p * n + k].abs() {
+                    p = i
+                }
+            }
+            if lu[p * n + k] == 0. {
+                singular = true;
+                continue;
+            }
+            if p != k {
+                for j in 0..n {
+                    lu.swap(p * n + j, k * n + j)
+                }
+                perm.swap(p, k);
+                sign = -sign;
+            }
+            let pivot = lu[k * n + k];
+            for i in k + 1..n {
+                let f = lu[i * n + k] / pivot;
+                lu[i * n + k] = f;
+                for j in k + 1..n {
+                    lu[i * n + j] -= f * lu[k * n + j]
+                }
+            }
+        }
+        Self {
+            lu,
+            perm,
+            n,
+            sign,
+            singular,
+        }
+    }
+
+    /// Solves `a x = b` where `b` is a `n x k` matrix.
+    fn solve(&self, b: &[f64], k: usize) -> Vec<f64> {
+        let (n, lu) = (self.n, &self.lu);
+        let mut x = vec![0f64; n * k];
+        for (i, &p) in self.perm.iter().enumerate() {
+            x[i * k..(i + 1) * k].copy_from_slice(&b[p * k..(p + 1) * k])
+        }
+        for i in 0..n {
+            for j in 0..i {
+                let f = lu[i * n + j];
+                for c in 0..k {
+                    x[i * k + c] -= f * x[j * k + c]
+                }
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                let f = lu[i * n + j];
+                for c in 0..k {
+                    x[i * k + c] -= f * x[j * k + c]
+                }
+            }
+            let d = lu[i * n + i];
+            for c in 0..k {
+                x[i * k + c] /= d
+            }
+        }
+        x
+    }
+
+    fn inv(&self) -> Vec<f64> {
+        let n = self.n;
+        let mut eye = vec![0f64; n * n];
+        for i in 0..n {
+            eye[i * n + i] = 1.
+        }
+        self.solve(&eye, n)
+    }
+
+    /// Returns the sign and the log of the absolute value of the determinant.
+    fn slogdet(&self) -> (f64, f64) {
+        if self.singular {
+            return (0., f64::NEG_INFINITY);
+        }
+        let n = self.n;
+        let mut sign = self.sign;
+        let mut logabsdet = 0.;
+        for i in 0..n {
+            let d = self.lu[i * n + i];
+            if d < 0. {
+                sign = -sign
+            }
+            logabsdet += d.abs().ln()
+        }
+        (sign, logabsdet)
+    }
+
+    fn det(&self) -> f64 {
+        if self.singular {
+            return 0.;
+        }
+        let n = self.n;
+        (0..n).fold(self.sign, |acc, i| acc * self.lu[i * n + i])
+    }
+}
+
+fn cholesky_(a: &[f64], n: usize) -> Result<Vec<f64>> {
+    let mut l = vec![0f64; n * n];
+    for j in 0..n {
+        let mut d = a[j * n + j];
+        for k in 0..j {
+            d -= l[j * n + k] * l[j * n + k]
+        }
+        if d.is_nan() || d <= 0. {
+            bail!("cholesky: the input matrix is not positive-definite")
+        }
+        let d = d.sqrt();
+        l[j * n + j] = d;
+        for i in j + 1..n {
+            let mut v = a[i * n + j];
+            for k in 0..j {
+                v -= l[i * n + k] * l[j * n + k]
+            }
+            l[i * n + j] = v / d
+        }
+    }
+    Ok(l)
+}
+
+/// Reduced QR decomposition using Householder reflections, returns `q` with shape `m x k` and
+/// `r` with shape `k x n` where `k = min(m, n)`.
+fn qr_(a: &[f64], m: usize, n: usize) -> (Vec<f64>, Vec<f64>) {
+    let k = usize::min(m, n);
+    let mut r = a.to_vec();
+    let mut reflectors = Vec::with_capacity(k);
+    for j in 0..k {
+        let norm = (j..m).map(|i| r[i * n + j].powi(2)).sum::<f64>().sqrt();
+        let x0 = r[j * n + j];
+        let alpha = if x0 < 0. { norm } else { -norm };
+        let mut v: Vec<f64> = (j..m).map(|i| r[i * n + j]).collect();
+        v[0] -= alpha;
+        let vnorm = v.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if vnorm == 0. {
+            reflectors.push(None);
+            continue;
+        }
+        v.iter_mut().for_each(|v| *v /= vnorm);
+        for c in j..n {
+            let dot: f64 = (j..m).map(|i| v[i - j] * r[i * n + c]).sum();
+            for i in j..m {
+                r[i * n + c] -= 2. * v[i - j] * dot
+            }
+        }
+        reflectors.push(Some(v))
+    }
+    let mut q = vec![0f64; m * k];
+    for i in 0..k {
+        q[i * k + i] = 1.
+    }
+    for (j, v) in reflectors.iter().enumerate().rev() {
+        if let Some(v) = v {
+            for c in 0..k {
+                let dot: f64 = (j..m).map(|i| v[i - j] * q[i * k + c]).sum();
+                for i in j..m {
+                    q[i * k + c] -= 2. * v[i - j] * dot
+                }
+            }
+        }
+    }
+    // Flip the signs so that the diagonal of r is non-negative, this makes the decomposition
+    // unique for full rank inputs and continuous.
+    let mut r_ = vec![0f64; k * n];
+    for i in 0..k {
+        let sign = if r[i * n + i] < 0. { -1. } else { 1. };
+        for j in i..n {
+            r_[i * n + j] = sign * r[i * n + j]
+        }
+        for row in 0..m {
+            q[row * k + i] *= sign
+        }
+    }
+    (q, r_)
+}
+
+/// Eigen-decomposition of a symmetric `n x n` matrix using the cyclic Jacobi method. The
+/// eigenvalues are returned in ascending order and the eigenvectors are the columns of the
+/// returned matrix.
+fn eigh_(a: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut a = a.to_vec();
+    let mut v = vec![0f64; n * n];
+    for i in 0..n {
+        v[i * n + i] = 1.
+    }
+    let total: f64 = a.iter().map(|v| v * v).sum();
+    for _sweep in 0..MAX_SWEEPS {
+        let mut off = 0.;
+        for i in 0..n {
+            for j in 0..n {
+                if i != j {
+                    off += a[i * n + j] * a[i * n + j]
+                }
+            }
+        }
+        if off <= f64::EPSILON * f64::EPSILON * total {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq == 0. {
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2. * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+                let c = 1. / (t * t + 1.).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[k * n + p], a[k * n + q]);
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[p * n + k], a[q * n + k]);
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[k * n + p], v[k * n + q]);
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[i * n + i].total_cmp(&a[j * n + j]));
+    let w = order.iter().map(|&i| a[i * n + i]).collect();
+    let mut vs = vec![0f64; n * n];
+    for (dst, &src) in order.iter().enumerate() {
+        for k in 0..n {
+            vs[k * n + dst] = v[k * n + src]
+        }
+    }
+    (w, vs)
+}
+
+/// Reduced singular value decomposition of a `m x n` matrix with `m >= n` using the one-sided
+/// Jacobi method. Returns `u` (`m x n`), the singular values in descending order, and `v`
+/// (`n x n`).
+fn svd_tall(a: &[f64], m: usize, n: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let mut u = a.to_vec();
+    let mut v = vec![0f64; n * n];
+    for i in 0..n {
+        v[i * n + i] = 1.
+    }
+    for _sweep in 0..MAX_SWEEPS {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in p + 1..n {
+                let (mut alpha, mut beta, mut gamma) = (0., 0., 0.);
+                for k in 0..m {
+                    let (ukp, ukq) = (u[k * n + p], u[k * n + q]);
+                    alpha += ukp * ukp;
+                    beta += ukq * ukq;
+                    gamma += ukp * ukq;
+                }
+                if gamma == 0. || gamma.abs() <= f64::EPSILON * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+                let zeta = (beta - alpha) / (2. * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1. + zeta * zeta).sqrt());
+                let c = 1. / (1. + t * t).sqrt();
+                let s = c * t;
+                for k in 0..m {
+                    let (ukp, ukq) = (u[k * n + p], u[k * n + q]);
+                    u[k * n + p] = c * ukp - s * ukq;
+                    u[k * n + q] = s * ukp + c * ukq;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[k * n + p], v[k * n + q]);
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+    let norms: Vec<f64> = (0..n)
+        .map(|j| (0..m).map(|k| u[k * n + j].powi(2)).sum::<f64>().sqrt())
+        .collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| norms[j].total_cmp(&norms[i]));
+    let s: Vec<f64> = order.iter().map(|&i| norms[i]).collect();
+    let threshold = s.first().copied().unwrap_or(0.) * f64::EPSILON * m as f64;
+    let mut us = vec![0f64; m * n];
+    let mut vs = vec![0f64; n * n];
+    let mut valid = vec![false; n];
+    for (dst, &src) in order.iter().enumerate() {
+        for k in 0..n {
+            vs[k * n + dst] = v[k * n + src]
+        }
+        if s[dst] > threshold {
+            for k in 0..m {
+                us[k * n + dst] = u[k * n + src] / s[dst]
+            }
+            valid[dst] = true
+        }
+    }
+    // The left singular vectors associated with null singular values are not determined by the
+    // Jacobi iterations, complete them into an orthonormal basis.
+    let mut basis = 0;
+    for j in 0..n {
+        while !valid[j] && basis < m {
+            let mut cand = vec![0f64; m];
+            cand[basis] = 1.;
+            basis += 1;
+            for i in (0..n).filter(|&i| valid[i]) {
+                let dot: f64 = (0..m).map(|k| us[k * n + i] * cand[k]).sum();
+                for (k, c) in cand.iter_mut().enumerate() {
+                    *c -= dot * us[k * n + i]
+                }
+            }
+            let norm = cand.iter().map(|c| c * c).sum::<f64>().sqrt();
+            if norm > 0.5 {
+                for (k, c) in cand.iter().enumerate() {
+                    us[k * n + j] = c / norm
+                }
+                valid[j] = true
+            }
+        }
+    }
+    (us, s, vs)
+}
+
+/// Reduced singular value decomposition, returns `u` (`m x k`), `s` (`k`) and `vh` (`k x n`).
+fn svd_(a: &[f64], m: usize, n: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    if m >= n {
+        let (u, s, v) = svd_tall(a, m, n);
+        (u, s, transpose(&v, n, n))
+    } else {
+        // a^T = u' s v'^T so a = v' s u'^T
+        let (u, s, v) = svd_tall(&transpose(a, m, n), n, m);
+        (v, s, transpose(&u, n, m))
+    }
+}
+
+/// The adjugate of a square `n x n` matrix, i.e. the transpose of its cofactor matrix. This is
+/// `det(a) a^-1` for invertible matrices, singular ones go through the svd `a = u s vh` which
+/// gives `adj(a) = det(u) det(vh) vh^T adj(s) u^T` where `adj(s)` is diagonal with the products
+/// of all the other singular values.
+fn adjugate_(a: &[f64], n: usize) -> Vec<f64> {
+    let lu = Lu::new(a.to_vec(), n);
+    if !lu.singular {
+        let det = lu.det();
+        return lu.inv().into_iter().map(|v| v * det).collect();
+    }
+    let (u, s, vh) = svd_(a, n, n);
+    let sign = Lu::new(u.clone(), n).det() * Lu::new(vh.clone(), n).det();
+    let mut adj = vec![0f64; n * n];
+    for k in 0..n {
+        let cofactor: f64 = sign * (0..n).filter(|&j| j != k).map(|j| s[j]).product::<f64>();
+        if cofactor == 0. {
+            continue;
+        }
+        for i in 0..n {
+            for j in 0..n {
+                adj[i * n + j] += vh[k * n + i] * cofactor * u[j * n + k]
+            }
+        }
+    }
+    adj
+}
+
+/// Splits the last dimension of a packed tensor into multiple tensors with the given trailing
+/// shapes.
+fn unpack(packed: &Tensor, shapes: &[&[usize]]) -> Result<Vec<Tensor>> {
+    let dims = packed.dims();
+    let batch = &dims[..dims.len() - 1];
+    let mut offset = 0;
+    let mut res = Vec::with_capacity(shapes.len());
+    for shape in shapes.iter() {
+        let len = shape.iter().product::<usize>();
+        let dims = [batch, shape].concat();
+        res.push(packed.narrow(D::Minus1, offset, len)?.reshape(dims)?);
+        offset += len
+    }
+    Ok(res)
+}
+
+/// Turns a `(..., k)` tensor into a batch of `k x k` diagonal matrices.
+fn diag_embed(v: &Tensor) -> Result<Tensor> {
+    let k = v.dim(D::Minus1)?;
+    let eye = Tensor::eye(k, v.dtype(), v.device())?;
+    v.unsqueeze(D::Minus1)?.broadcast_mul(&eye)
+}
+
+/// Returns a `(..., k, k)` tensor where the `(i, j)` entry is `1 / (w_j - w_i)`, or zero when
+/// `i == j`.
+fn inv_diff(w: &Tensor) -> Result<Tensor> {
+    let diff = w
+        .unsqueeze(D::Minus2)?
+        .broadcast_sub(&w.unsqueeze(D::Minus1)?)?;
+    let k = w.dim(D::Minus1)?;
+    let eye = Tensor::eye(k, DType::U8, w.device())?.broadcast_as(diff.shape())?;
+    eye.where_cond(&diff.zeros_like()?, &diff.recip()?)
+}
+
+struct Inv;
+
+impl CustomOp1 for Inv {
+    fn name(&self) -> &'static str {
+        "inv"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (_, n) = square_dims(layout, "inv")?;
+        let vs = to_f64_vec(storage, layout, "inv")?;
+        let mut dst = Vec::with_capacity(vs.len());
+        for a in vs.chunks_exact((n * n).max(1)) {
+            let lu = Lu::new(a.to_vec(), n);
+            if lu.singular {
+                bail!("inv: the input matrix is singular")
+            }
+            dst.extend(lu.inv())
+        }
+        Ok((from_f64_vec(dst, storage.dtype()), layout.shape().clone()))
+    }
+
+    fn bwd(&self, _arg: &Tensor, res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // d(a^-1) = -a^-1 da a^-1
+        let res_t = res.t()?;
+        let grad = res_t.matmul(grad_res)?.matmul(&res_t)?.neg()?;
+        Ok(Some(grad))
+    }
+}
+
+struct Solve;
+
+impl CustomOp2 for Solve {
+    fn name(&self) -> &'static str {
+        "solve"
+    }
+
+    fn cpu_fwd(
+        &self,
+        s1: &CpuStorage,
+        l1: &Layout,
+        s2: &CpuStorage,
+        l2: &Layout,
+    ) -> Result<(CpuStorage, Shape)> {
+        let (_, n) = square_dims(l1, "solve")?;
+        let (_, _, k) = matrix_dims(l2, "solve")?;
+        let a = to_f64_vec(s1, l1, "solve")?;
+        let b = to_f64_vec(s2, l2, "solve")?;
+        let mut dst = Vec::with_capacity(b.len());
+        for (a, b) in a
+            .chunks_exact((n * n).max(1))
+            .zip(b.chunks_exact((n * k).max(1)))
+        {
+            let lu = Lu::new(a.to_vec(), n);
+            if lu.singular {
+                bail!("solve: the input matrix is singular")
+            }
+            dst.extend(lu.solve(b, k))
+        }
+        Ok((from_f64_vec(dst, s1.dtype()), l2.shape().clone()))
+    }
+
+    fn bwd(
+        &self,
+        arg1: &Tensor,
+        _arg2: &Tensor,
+        res: &Tensor,
+        grad_res: &Tensor,
+    ) -> Result<(Option<Tensor>, Option<Tensor>)> {
+        let grad_b = solve(&arg1.t()?, grad_res)?;
+        let grad_a = grad_b.matmul(&res.t()?)?.neg()?;
+        Ok((Some(grad_a), Some(grad_b)))
+    }
+}
+
+struct Adjugate;
+
+impl CustomOp1 for Adjugate {
+    fn name(&self) -> &'static str {
+        "adjugate"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (_, n) = square_dims(layout, "adjugate")?;
+        let vs = to_f64_vec(storage, layout, "adjugate")?;
+        let mut dst = Vec::with_capacity(vs.len());
+        for a in vs.chunks_exact((n * n).max(1)) {
+            dst.extend(adjugate_(a, n))
+        }
+        Ok((from_f64_vec(dst, storage.dtype()), layout.shape().clone()))
+    }
+}
+
+struct Det;
+
+impl CustomOp1 for Det {
+    fn name(&self) -> &'static str {
+        "det"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (batch, n) = square_dims(layout, "det")?;
+        let vs = to_f64_vec(storage, layout, "det")?;
+        let dst = if n == 0 {
+            vec![1.; batch.iter().product()]
+        } else {
+            vs.chunks_exact((n * n).max(1))
+                .map(|a| Lu::new(a.to_vec(), n).det())
+                .collect()
+        };
+        Ok((from_f64_vec(dst, storage.dtype()), Shape::from(batch)))
+    }
+
+    fn bwd(&self, arg: &Tensor, _res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // d(det a) = tr(adj(a) da), the adjugate is also defined for singular matrices.
+        let scale = grad_res.unsqueeze(D::Minus1)?.unsqueeze(D::Minus1)?;
+        let adj = arg.apply_op1_no_bwd(&Adjugate)?;
+        let grad = adj.t()?.broadcast_mul(&scale)?;
+        Ok(Some(grad))
+    }
+}
+
+struct SLogDet;
+
+impl CustomOp1 for SLogDet {
+    fn name(&self) -> &'static str {
+        "slogdet"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (batch, n) = square_dims(layout, "slogdet")?;
+        let vs = to_f64_vec(storage, layout, "slogdet")?;
+        let dst = if n == 0 {
+            [1., 0.].repeat(batch.iter().product())
+        } else {
+            let mut dst = Vec::with_capacity(2 * vs.len() / (n * n));
+            for a in vs.chunks_exact((n * n).max(1)) {
+                let (sign, logabsdet) = Lu::new(a.to_vec(), n).slogdet();
+                dst.push(sign);
+                dst.push(logabsdet);
+            }
+            dst
+        };
+        let shape = Shape::from([batch, &[2]].concat());
+        Ok((from_f64_vec(dst, storage.dtype()), shape))
+    }
+
+    fn bwd(&self, arg: &Tensor, _res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // Only the log of the absolute value has a gradient.
+        let scale = grad_res.narrow(D::Minus1, 1, 1)?.unsqueeze(D::Minus1)?;
+        let grad = inv(arg)?.t()?.broadcast_mul(&scale)?;
+        Ok(Some(grad))
+    }
+}
+
+struct Cholesky;
+
+impl CustomOp1 for Cholesky {
+    fn name(&self) -> &'static str {
+        "cholesky"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (_, n) = square_dims(layout, "cholesky")?;
+        let vs = to_f64_vec(storage, layout, "cholesky")?;
+        let mut dst = Vec::with_capacity(vs.len());
+        for a in vs.chunks_exact((n * n).max(1)) {
+            dst.extend(cholesky_(a, n)?)
+        }
+        Ok((from_f64_vec(dst, storage.dtype()), layout.shape().clone()))
+    }
+
+    fn bwd(&self, _arg: &Tensor, res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // "Differentiation of the Cholesky decomposition", Murray 2016.
+        // grad_a = 1/2 l^-T (phi(l^T grad_l) + phi(l^T grad_l)^T) l^-1 where phi takes the
+        // lower triangular part and halves the diagonal.
+        let n = res.dim(D::Minus1)?;
+        let (dtype, device) = (res.dtype(), res.device());
+        let phi_mask =
+            (Tensor::tril2(n, dtype, device)? - (Tensor::eye(n, dtype, device)? * 0.5)?)?;
+        let phi = res.t()?.matmul(grad_res)?.broadcast_mul(&phi_mask)?;
+        let l_inv = inv(res)?;
+        let s = l_inv.t()?.matmul(&phi)?.matmul(&l_inv)?;
+        let grad = ((&s + s.t()?)? * 0.5)?;
+        Ok(Some(grad))
+    }
+}
+
+struct Qr;
+
+impl CustomOp1 for Qr {
+    fn name(&self) -> &'static str {
+        "qr"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (batch, m, n) = matrix_dims(layout, "qr")?;
+        let k = usize::min(m, n);
+        let vs = to_f64_vec(storage, layout, "qr")?;
+        let b = batch.iter().product::<usize>();
+        let mut dst = Vec::with_capacity(b * (m * k + k * n));
+        for i in 0..b {
+            let (q, r) = qr_(&vs[i * m * n..(i + 1) * m * n], m, n);
+            dst.extend(q);
+            dst.extend(r);
+        }
+        let shape = Shape::from([batch, &[m * k + k * n]].concat());
+        Ok((from_f64_vec(dst, storage.dtype()), shape))
+    }
+
+    fn bwd(&self, arg: &Tensor, res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // "Auto-differentiating linear algebra", Seeger et al. 2017, only valid for m >= n.
+        let (m, n) = (arg.dim(D::Minus2)?, arg.dim(D::Minus1)?);
+        if m < n {
+            bail!("qr: backward is only supported when the number of rows is at least the number of columns")
+        }
+        let qr = unpack(res, &[&[m, n], &[n, n]])?;
+        let (q, r) = (&qr[0], &qr[1]);
+        let grad = unpack(grad_res, &[&[m, n], &[n, n]])?;
+        let (grad_q, grad_r) = (&grad[0], &grad[1]);
+        let mm = (r.matmul(&grad_r.t()?)? - grad_q.t()?.matmul(q)?)?;
+        let (dtype, device) = (arg.dtype(), arg.device());
+        let lower = mm.broadcast_mul(&Tensor::tril2(n, dtype, device)?)?;
+        let strict_lower = (Tensor::tril2(n, dtype, device)? - Tensor::eye(n, dtype, device)?)?;
+        let copyltu = (lower + mm.broadcast_mul(&strict_lower)?.t()?)?;
+        let b = (grad_q + q.matmul(&copyltu)?)?;
+        let grad = b.matmul(&inv(r)?.t()?)?;
+        Ok(Some(grad))
+    }
+}
+
+struct Eigh;
+
+impl CustomOp1 for Eigh {
+    fn name(&self) -> &'static str {
+        "eigh"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (batch, n) = square_dims(layout, "eigh")?;
+        let vs = to_f64_vec(storage, layout, "eigh")?;
+        let mut dst = Vec::with_capacity(vs.len() + vs.len() / n.max(1));
+        for a in vs.chunks_exact((n * n).max(1)) {
+            let mut sym = a.to_vec();
+            for i in 0..n {
+                for j in 0..n {
+                    sym[i * n + j] = 0.5 * (a[i * n + j] + a[j * n + i])
+                }
+            }
+            let (w, v) = eigh_(&sym, n);
+            dst.extend(w);
+            dst.extend(v);
+        }
+        let shape = Shape::from([batch, &[n + n * n]].concat());
+        Ok((from_f64_vec(dst, storage.dtype()), shape))
+    }
+
+    fn bwd(&self, arg: &Tensor, res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // grad_a = v (diag(grad_w) + f * (v^T grad_v)) v^T with f_ij = 1 / (w_j - w_i), the forward
+        // pass uses the symmetric part of the input so the gradient is symmetrized too.
+        let n = arg.dim(D::Minus1)?;
+        let wv = unpack(res, &[&[n], &[n, n]])?;
+        let (w, v) = (&wv[0], &wv[1]);
+        let grad = unpack(grad_res, &[&[n], &[n, n]])?;
+        let (grad_w, grad_v) = (&grad[0], &grad[1]);
+        let inner = (diag_embed(grad_w)? + (v.t()?.matmul(grad_v)? * inv_diff(w)?)?)?;
+        let grad = v.matmul(&inner)?.matmul(&v.t()?)?;
+        let grad = ((&grad + grad.t()?)? * 0.5)?;
+        Ok(Some(grad))
+    }
+}
+
+struct Svd;
+
+impl CustomOp1 for Svd {
+    fn name(&self) -> &'static str {
+        "svd"
+    }
+
+    fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
+        let (batch, m, n) = matrix_dims(layout, "svd")?;
+        let k = usize::min(m, n);
+        let vs = to_f64_vec(storage, layout, "svd")?;
+        let b = batch.iter().product::<usize>();
+        let mut dst = Vec::with_capacity(b * (m * k + k + k * n));
+        for i in 0..b {
+            let (u, s, vh) = svd_(&vs[i * m * n..(i + 1) * m * n], m, n);
+            dst.extend(u);
+            dst.extend(s);
+            dst.extend(vh);
+        }
+        let shape = Shape::from([batch, &[m * k + k + k * n]].concat());
+        Ok((from_f64_vec(dst, storage.dtype()), shape))
+    }
+
+    fn bwd(&self, arg: &Tensor, res: &Tensor, grad_res: &Tensor) -> Result<Option<Tensor>> {
+        // "Differentiating the Singular Value Decomposition", Townsend 2016.
+        let (m, n) = (arg.dim(D::Minus2)?, arg.dim(D::Minus1)?);
+        let k = usize::min(m, n);
+        let usv = unpack(res, &[&[m, k], &[k], &[k, n]])?;
+        let (u, s, v) = (&usv[0], &usv[1], &usv[2].t()?);
+        let grad = unpack(grad_res, &[&[m, k], &[k], &[k, n]])?;
+        let (grad_u, grad_s, grad_v) = (&grad[0], &grad[1], &grad[2].t()?);
+        let f = inv_diff(&s.sqr()?)?;
+        let ut_gu = u.t()?.matmul(grad_u)?;
+        let vt_gv = v.t()?.matmul(grad_v)?;
+        let j = ((&ut_gu - ut_gu.t()?)? * &f)?;
+        let kk = ((&vt_gv - vt_gv.t()?)? * &f)?;
+        let inner = (j.broadcast_mul(&s.unsqueeze(D::Minus2)?)?
+            + diag_embed(grad_s)?
+            + kk.broadcast_mul(&s.unsqueeze(D::Minus1)?)?)?;
+        let mut grad = u.matmul(&inner)?.matmul(&v.t()?)?;
+        if m > k {
+            // (I - u u^T) grad_u s^-1 v^T
+            let proj = (grad_u - u.matmul(&ut_gu)?)?;
+            let proj = proj.broadcast_div(&s.unsqueeze(D::Minus2)?)?;
+            grad = (grad + proj.matmul(&v.t()?)?)?;
+        }
+        if n > k {
+            // u s^-1 grad_v^T (I - v v^T)
+            let proj = (grad_v.t()? - vt_gv.t()?.matmul(&v.t()?)?)?;
+            let u_s = u.broadcast_div(&s.unsqueeze(D::Minus2)?)?;
+            grad = (grad + u_s.matmul(&proj)?)?;
+        }
+        Ok(Some(grad))
+    }
+}
+
+/// Computes the inverse of a batch of square matrices.
+pub fn inv(a: &Tensor) -> Result<Tensor> {
+    check_float(a, "inv")?;
+    a.contiguous()?.apply_op1(Inv)
+}
+
+/// Solves the linear system `a x = b` where `a` has shape `(..., n, n)` and `b` has shape
+/// `(..., n, k)` with the same batch dimensions.
+pub fn solve(a: &Tensor, b: &Tensor) -> Result<Tensor> {
+    check_float(a, "solve")?;
+    let (a_dims, b_dims) = (a.dims(), b.dims());
+    let rank = a_dims.len();
+    if rank < 2 || b_dims.len() != rank || b_dims[..rank - 1] != a_dims[..rank - 1] {
+        Err(crate::Error::ShapeMismatchBinaryOp {
+            lhs: a.shape().clone(),
+            rhs: b.shape().clone(),
+            op: "solve",
+        }
+        .bt())?
+    }
+    if a.dtype() != b.dtype() {
+        Err(crate::Error::DTypeMismatchBinaryOp {
+            lhs: a.dtype(),
+            rhs: b.dtype(),
+            op: "solve",
+        }
+        .bt())?
+    }
+    a.contiguous()?.apply_op2(&b.contiguous()?, Solve)
+}
+
+/// Computes the determinant of a batch of square matrices, the result has the batch dimensions
+/// of the input.
+pub fn det(a: &Tensor) -> Result<Tensor> {
+    check_float(a, "det")?;
+    a.contiguous()?.apply_op1(Det)
+}
+
+/// Computes the sign and the natural logarithm of the absolute value of the determinant of a
+/// batch of square matrices. For singular matrices the sign is zero and the log is `-inf`.
+pub fn slogdet(a: &Tensor) -> Result<(Tensor, Tensor)> {
+    check_float(a, "slogdet")?;
+    let packed = a.contiguous()?.apply_op1(SLogDet)?;
+    let sign = packed.narrow(D::Minus1, 0, 1)?.squeeze(D::Minus1)?.detach();
+    let logabsdet = packed.narrow(D::Minus1, 1, 1)?.squeeze(D::Minus1)?;
+    Ok((sign, logabsdet))
+}
+
+/// Computes the Cholesky decomposition `a = l l^T` of a batch of symmetric positive-definite
+/// matrices, returning the lower triangular factor `l`.
+pub fn cholesky(a: &Tensor) -> Result<Tensor> {
+    check_float(a, "cholesky")?;
+    a.contiguous()?.apply_op1(Cholesky)
+}
+
+/// Computes the reduced QR decomposition of a batch of `m x n` matrices. With `k = min(m, n)`,
+/// `q` has shape `(..., m, k)` with orthonormal columns and `r` has shape `(..., k, n)` and is
+/// upper triangular with a non-negative diagonal.
+///
+/// The backward pass is only supported when `m >= n`.
+pub fn qr(a: &Tensor) -> Result<(Tensor, Tensor)> {
+    check_float(a, "qr")?;
+    let (m, n) = (a.dim(D::Minus2)?, a.dim(D::Minus1)?);
+    let k = usize::min(m, n);
+    let packed = a.contiguous()?.apply_op1(Qr)?;
+    let qr = unpack(&packed, &[&[m, k], &[k, n]])?;
+    Ok((qr[0].clone(), qr[1].clone()))
+}
+
+/// Computes the eigenvalues and eigenvectors of a batch of symmetric matrices, only the
+/// symmetric part of the input is used. The eigenvalues are returned in ascending order with
+/// shape `(..., n)`, the associated eigenvectors are the columns of the second returned tensor.
+///
+/// The gradient is not well defined when some eigenvalues are repeated.
+pub fn eigh(a: &Tensor) -> Result<(Tensor, Tensor)> {
+    check_float(a, "eigh")?;
+    let n = a.dim(D::Minus1)?;
+    let packed = a.contiguous()?.apply_op1(Eigh)?;
+    let wv = unpack(&packed, &[&[n], &[n, n]])?;
+    Ok((wv[0].clone(), wv[1].clone()))
+}
+
+/// Computes the reduced singular value decomposition `a = u diag(s) vh` of a batch of `m x n`
+/// matrices. With `k = min(m, n)`, `u` has shape `(..., m, k)`, `s` has shape `(..., k)` and is
+/// sorted in descending order, and `vh` has shape `(..., k, n)`.
+///
+/// The gradient is not well defined when some singular values are repeated or null.
+pub fn svd(a: &Tensor) -> Result<(Tensor, Tensor, Tensor)> {
+    check_float(a, "svd")?;
+    let (m, n) = (a.dim(D::Minus2)?, a.dim(D::Minus1)?);
+    let k = usize::min(m, n);
+    let packed = a.contiguous()?.apply_op1(Svd)?;
+    let usv = unpack(&packed, &[&[m, k], &[k], &[k, n]])?;
+    Ok((usv[0].clone(), usv[1].clone(), usv[2].clone()))
+}
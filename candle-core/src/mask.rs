@@ -0,0 +1,129 @@
+//! Mask based ops: `nonzero`, `masked_select`, `masked_fill` and `masked_scatter`.
+//!
+//! The output shapes of `nonzero` and `masked_select` depend on the mask values, so on non-cpu
+//! devices they wait for the device and round-trip the mask through the host, as does
+//! `masked_scatter` to find the positions to fill. `masked_fill` stays on the device.
+use crate::{bail, DType, Device, Result, Tensor, WithDType};
+
+impl Tensor {
+    /// Returns the indexes of the non-zero elements as a `u32` tensor of shape `(n, rank)` where
+    /// `n` is the number of non-zero elements. The indexes are in row-major order.
+    ///
+    /// The output shape depends on the tensor values so on non-cpu devices this waits for the
+    /// device and copies a `u8` mask to the host.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[0f32, 1.], [2., 0.]], &Device::Cpu)?;
+    /// let a = a.nonzero()?;
+    /// assert_eq!(a.to_vec2::<u32>()?, &[[0, 1], [1, 0]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn nonzero(&self) -> Result<Self> {
+        let mask = self
+            .ne(0f64)?
+            .to_dtype(DType::U8)?
+            .flatten_all()?
+            .to_device(&Device::Cpu)?
+            .to_vec1::<u8>()?;
+        let dims = self.dims();
+        let mut indexes = vec![];
+        let mut n = 0;
+        for (flat_index, _) in mask.iter().enumerate().filter(|(_, &m)| m != 0) {
+            let start = indexes.len();
+            let mut flat_index = flat_index;
+            indexes.resize(start + dims.len(), 0u32);
+            for (index, &dim) in indexes[start..].iter_mut().zip(dims.iter()).rev() {
+                *index = (flat_index % dim) as u32;
+                flat_index /= dim;
+            }
+            n += 1;
+        }
+        Tensor::from_vec(indexes, (n, dims.len()), self.device())
+    }
+
+    /// Flat indexes of the non-zero elements of `mask` once broadcasted to the shape of `self`.
+    fn mask_indexes(&self, mask: &Self) -> Result<Self> {
+        let shape = self
+            .shape()
+            .broadcast_shape_binary_op(mask.shape(), "mask")?;
+        if &shape != self.shape() {
+            bail!(
+                "mask of shape {:?} cannot be broadcasted to {:?}",
+                mask.shape(),
+                self.shape()
+            )
+        }
+        mask.broadcast_as(shape)?
+            .flatten_all()?
+            .nonzero()?
+            .squeeze(1)
+    }
+
+    /// Returns a 1D tensor with the elements of `self` where `mask` is non-zero. The mask is
+    /// broadcasted to the shape of `self`.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[0f32, 1.], [2., 3.]], &Device::Cpu)?;
+    /// let mask = Tensor::new(&[true, false], &Device::Cpu)?;
+    /// let a = a.masked_select(&mask)?;
+    /// assert_eq!(a.to_vec1::<f32>()?, &[0., 2.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn masked_select(&self, mask: &Self) -> Result<Self> {
+        let indexes = self.mask_indexes(mask)?;
+        self.flatten_all()?.index_select(&indexes, 0)
+    }
+
+    /// Returns a tensor where the elements of `self` are replaced by `value` where `mask` is
+    /// non-zero. The mask is broadcasted to the shape of `self` and `value` is converted to the
+    /// dtype of `self`.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[0f32, 1.], [2., 3.]], &Device::Cpu)?;
+    /// let mask = Tensor::new(&[[true], [false]], &Device::Cpu)?;
+    /// let a = a.masked_fill(&mask, -1.)?;
+    /// assert_eq!(a.to_vec2::<f32>()?, &[[-1., -1.], [2., 3.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn masked_fill<T: WithDType>(&self, mask: &Self, value: T) -> Result<Self> {
+        let mask = mask.ne(0f64)?.broadcast_as(self.shape())?;
+        let value = Tensor::new(value, self.device())?
+            .to_dtype(self.dtype())?
+            .broadcast_as(self.shape())?;
+        mask.where_cond(&value, self)
+    }
+
+    /// Returns a tensor where the elements of `self` where `mask` is non-zero are replaced by
+    /// consecutive elements of `source`, in row-major order. The mask is broadcasted to the shape
+    /// of `self` and `source` must have at least as many elements as there are non-zero values in
+    /// the mask.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[0f32, 1.], [2., 3.]], &Device::Cpu)?;
+    /// let mask = Tensor::new(&[[false, true], [true, false]], &Device::Cpu)?;
+    /// let source = Tensor::new(&[5f32, 6., 7.], &Device::Cpu)?;
+    /// let a = a.masked_scatter(&mask, &source)?;
+    /// assert_eq!(a.to_vec2::<f32>()?, &[[0., 5.], [6., 3.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn masked_scatter(&self, mask: &Self, source: &Self) -> Result<Self> {
+        let indexes = self.mask_indexes(mask)?;
+        let n = indexes.elem_count();
+        if source.elem_count() < n {
+            bail!(
+                "masked_scatter: source has {} elements but the mask selects {n}",
+                source.elem_count()
+            )
+        }
+        let source = source.flatten_all()?.narrow(0, 0, n)?;
+        let scattered = Tensor::zeros(self.elem_count(), self.dtype(), self.device())?
+            .index_add(&indexes, &source, 0)?
+            .reshape(self.shape())?;
+        let mask = mask.ne(0f64)?.broadcast_as(self.shape())?;
+        mask.where_cond(&scattered, self)
+    }
+}
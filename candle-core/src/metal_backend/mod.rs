@@ -1,8 +1,11 @@
 //! Implementation of Backend traits for Metal
 //!
 use crate::backend::{BackendDevice, BackendStorage};
-use crate::conv::{ParamsConv1D, ParamsConv2D, ParamsConvTranspose1D, ParamsConvTranspose2D};
-use crate::op::{BinaryOpT, CmpOp, ReduceOp, UnaryOpT};
+use crate::conv::{
+    ParamsConv1D, ParamsConv2D, ParamsConv3D, ParamsConvTranspose1D, ParamsConvTranspose2D,
+    ParamsConvTranspose3D,
+};
+use crate::op::{BinaryOpT, BitwiseOp, CmpOp, ReduceOp, Resample, ScanOp, UnaryOpT};
 use crate::{CpuStorage, CpuStorageRef, DType, Layout, Result, Shape};
 use candle_metal_kernels::{BufferOffset, CallConvTranspose2dCfg, Kernels};
 use metal::{Buffer, MTLResourceOptions, NSUInteger};
@@ -99,6 +102,17 @@ impl BackendStorage for MetalStorage {
             DType::U8 => Ok(CpuStorage::U8(self.to_cpu()?)),
             DType::U32 => Ok(CpuStorage::U32(self.to_cpu()?)),
             DType::I64 => Ok(CpuStorage::I64(self.to_cpu()?)),
+            DType::U16
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => {
+                crate::bail!("Metal {dtype:?} dtype not supported", dtype = self.dtype)
+            }
             DType::F16 => Ok(CpuStorage::F16(self.to_cpu()?)),
             DType::BF16 => Ok(CpuStorage::BF16(self.to_cpu()?)),
             DType::F32 => Ok(CpuStorage::F32(self.to_cpu()?)),
@@ -401,6 +415,18 @@ impl BackendStorage for MetalStorage {
         Ok(Self::new(buffer, device, dst_el, dtype))
     }
 
+    fn bitwise(&self, op: BitwiseOp, _: &Self, _: &Layout, _: &Layout) -> Result<Self> {
+        crate::bail!("Metal {} not implemented", op.name())
+    }
+
+    fn scan_op(&self, op: ScanOp, _: &Layout, _: usize, _: bool) -> Result<Self> {
+        crate::bail!("Metal {} not implemented", op.name())
+    }
+
+    fn resample(&self, _: &Layout, _: &Resample) -> Result<Self> {
+        crate::bail!("Metal resample not implemented")
+    }
+
     fn cmp(&self, op: CmpOp, rhs: &Self, lhs_l: &Layout, rhs_l: &Layout) -> Result<Self> {
         let name = match op {
             CmpOp::Eq => "eq",
@@ -1164,6 +1190,20 @@ impl BackendStorage for MetalStorage {
         Ok(Self::new(buffer, self.device.clone(), dst_el, self.dtype))
     }
 
+    fn conv3d(&self, _: &Layout, _: &Self, _: &Layout, _: &ParamsConv3D) -> Result<Self> {
+        crate::bail!("Metal conv3d not implemented")
+    }
+
+    fn conv_transpose3d(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &ParamsConvTranspose3D,
+    ) -> Result<Self> {
+        crate::bail!("Metal conv_transpose3d not implemented")
+    }
+
     fn avg_pool2d(
         &self,
         inp_l: &Layout,
@@ -1441,6 +1481,32 @@ impl BackendStorage for MetalStorage {
         Ok(Self::new(buffer, device.clone(), dst_el, dtype))
     }
 
+    fn scatter(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: usize,
+    ) -> Result<Self> {
+        crate::bail!("Metal scatter not implemented")
+    }
+
+    fn scatter_reduce(
+        &self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: &Self,
+        _: &Layout,
+        _: usize,
+        _: crate::op::ScatterReduceOp,
+        _: bool,
+    ) -> Result<Self> {
+        crate::bail!("Metal scatter-reduce not implemented")
+    }
+
     fn index_add(
         &self,
         l: &Layout,
@@ -1970,6 +2036,17 @@ impl BackendDevice for MetalDevice {
             DType::U8 => "fill_u8",
             DType::U32 => "fill_u32",
             DType::I64 => "fill_i64",
+            DType::U16
+            | DType::I8
+            | DType::I16
+            | DType::I32
+            | DType::Bool
+            | DType::F8E4M3
+            | DType::F8E5M2
+            | DType::C64
+            | DType::C128 => {
+                crate::bail!("Metal {dtype:?} dtype not supported")
+            }
             DType::F16 => "fill_f16",
             DType::BF16 => "fill_bf16",
             DType::F32 => "fill_f32",
@@ -2004,6 +2081,20 @@ impl BackendDevice for MetalDevice {
             CpuStorageRef::U8(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorageRef::U32(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorageRef::I64(storage) => (storage.len(), self.new_buffer_with_data(storage)),
+            // There is no boolean storage on this backend, the values are uploaded as `u8`.
+            CpuStorageRef::Bool(storage) => {
+                return self.storage_from_slice(crate::cpu_backend::bool_as_u8(storage))
+            }
+            CpuStorageRef::U16(_)
+            | CpuStorageRef::I8(_)
+            | CpuStorageRef::I16(_)
+            | CpuStorageRef::I32(_)
+            | CpuStorageRef::F8E4M3(_)
+            | CpuStorageRef::F8E5M2(_)
+            | CpuStorageRef::C64(_)
+            | CpuStorageRef::C128(_) => {
+                crate::bail!("Metal {:?} dtype not supported", T::DTYPE)
+            }
             CpuStorageRef::BF16(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorageRef::F16(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorageRef::F32(storage) => (storage.len(), self.new_buffer_with_data(storage)),
@@ -2017,6 +2108,20 @@ impl BackendDevice for MetalDevice {
             CpuStorage::U8(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorage::U32(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorage::I64(storage) => (storage.len(), self.new_buffer_with_data(storage)),
+            // There is no boolean storage on this backend, the values are uploaded as `u8`.
+            CpuStorage::Bool(storage) => {
+                return self.storage_from_slice(crate::cpu_backend::bool_as_u8(storage))
+            }
+            CpuStorage::U16(_)
+            | CpuStorage::I8(_)
+            | CpuStorage::I16(_)
+            | CpuStorage::I32(_)
+            | CpuStorage::F8E4M3(_)
+            | CpuStorage::F8E5M2(_)
+            | CpuStorage::C64(_)
+            | CpuStorage::C128(_) => {
+                crate::bail!("Metal {:?} dtype not supported", storage.dtype())
+            }
             CpuStorage::BF16(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorage::F16(storage) => (storage.len(), self.new_buffer_with_data(storage)),
             CpuStorage::F32(storage) => (storage.len(), self.new_buffer_with_data(storage)),
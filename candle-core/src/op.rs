@@ -3,6 +3,7 @@
 #![allow(clippy::redundant_closure_call)]
 use crate::Tensor;
 use half::{bf16, f16};
+use num_complex::{Complex32, Complex64};
 use num_traits::float::Float;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -15,6 +16,28 @@ pub enum CmpOp {
     Gt,
 }
 
+/// Bitwise operations, these are only supported on integer dtypes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitwiseOp {
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+impl BitwiseOp {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::And => "bitwise-and",
+            Self::Or => "bitwise-or",
+            Self::Xor => "bitwise-xor",
+            Self::Shl => "shl",
+            Self::Shr => "shr",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReduceOp {
     Sum,
@@ -36,6 +59,75 @@ impl ReduceOp {
     }
 }
 
+/// The reduction used to combine the values that are scattered to the same position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScatterReduceOp {
+    Sum,
+    Prod,
+    Mean,
+    Amax,
+    Amin,
+}
+
+/// The operation used to combine the values of a cumulative scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanOp {
+    Sum,
+    Prod,
+    Min,
+    Max,
+    // The index of the running minimum/maximum, the last one is used on ties.
+    ArgMin,
+    ArgMax,
+    LogSumExp,
+}
+
+impl ScanOp {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Sum => "cumsum",
+            Self::Prod => "cumprod",
+            Self::Min | Self::ArgMin => "cummin",
+            Self::Max | Self::ArgMax => "cummax",
+            Self::LogSumExp => "logcumsumexp",
+        }
+    }
+}
+
+/// A linear map along a single dimension where each output position is a weighted sum of `taps`
+/// input positions, this is used for the separable interpolations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resample {
+    pub dim: usize,
+    pub src_size: usize,
+    pub dst_size: usize,
+    pub taps: usize,
+    /// The input positions for each output position, `dst_size * taps` values.
+    pub indexes: Vec<usize>,
+    pub weights: Vec<f64>,
+    /// Apply the transposed map, going from `dst_size` positions back to `src_size` ones. This is
+    /// the gradient of the non-transposed map.
+    pub transpose: bool,
+}
+
+impl Resample {
+    pub(crate) fn transposed(&self) -> Self {
+        Self {
+            transpose: !self.transpose,
+            ..self.clone()
+        }
+    }
+
+    /// The size of `dim` for the input and output of the map.
+    pub(crate) fn in_out_sizes(&self) -> (usize, usize) {
+        if self.transpose {
+            (self.dst_size, self.src_size)
+        } else {
+            (self.src_size, self.dst_size)
+        }
+    }
+}
+
 // These ops return the same type as their input type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
@@ -45,6 +137,9 @@ pub enum BinaryOp {
     Div,
     Maximum,
     Minimum,
+    FloorDiv,
+    Remainder,
+    Fmod,
 }
 
 // Unary ops with no argument
@@ -78,9 +173,15 @@ pub enum Op {
     Cmp(Tensor, CmpOp),
     // The third argument is the reduced shape with `keepdim=true`.
     Reduce(Tensor, ReduceOp, Vec<usize>),
+    // The last argument is set for scans going from the last element to the first one.
+    Scan(Tensor, ScanOp, usize, bool),
+    Resample(Tensor, std::sync::Arc<Resample>),
     Matmul(Tensor, Tensor),
     Gather(Tensor, Tensor, usize),
     ScatterAdd(Tensor, Tensor, Tensor, usize),
+    Scatter(Tensor, Tensor, Tensor, usize),
+    // The last argument is `include_self`.
+    ScatterReduce(Tensor, Tensor, Tensor, usize, ScatterReduceOp, bool),
     IndexSelect(Tensor, Tensor, usize),
     IndexAdd(Tensor, Tensor, Tensor, usize),
     WhereCond(Tensor, Tensor, Tensor),
@@ -123,6 +224,25 @@ pub enum Op {
         dilation: usize,
     },
 
+    #[allow(dead_code)]
+    Conv3D {
+        arg: Tensor,
+        kernel: Tensor,
+        padding: usize,
+        stride: usize,
+        dilation: usize,
+    },
+
+    #[allow(dead_code)]
+    ConvTranspose3D {
+        arg: Tensor,
+        kernel: Tensor,
+        padding: usize,
+        output_padding: usize,
+        stride: usize,
+        dilation: usize,
+    },
+
     AvgPool2D {
         arg: Tensor,
         kernel_size: (usize, usize),
@@ -157,6 +277,8 @@ pub enum Op {
     Copy(Tensor),
     Broadcast(Tensor),
     Narrow(Tensor, usize, usize, usize),
+    // The arguments are the dimension, the window size and the step.
+    Unfold(Tensor, usize, usize, usize),
     SliceScatter0(Tensor, Tensor, usize),
     Reshape(Tensor),
     ToDevice(Tensor),
@@ -193,6 +315,29 @@ pub trait UnaryOpT {
     fn u32(v1: u32) -> u32;
     fn i64(v1: i64) -> i64;
 
+    // The smaller integer types go through the wider ones by default.
+    fn u16(v1: u16) -> u16 {
+        Self::u32(v1 as u32) as u16
+    }
+    fn i8(v1: i8) -> i8 {
+        Self::i64(v1 as i64) as i8
+    }
+    fn i16(v1: i16) -> i16 {
+        Self::i64(v1 as i64) as i16
+    }
+    fn i32(v1: i32) -> i32 {
+        Self::i64(v1 as i64) as i32
+    }
+
+    // Only a few ops support complex values, the flag marks the functions as existing.
+    const COMPLEX: bool = false;
+    fn c64(v1: Complex32) -> Complex32 {
+        v1
+    }
+    fn c128(v1: Complex64) -> Complex64 {
+        v1
+    }
+
     // There is no very good way to represent optional function in traits so we go for an explicit
     // boolean flag to mark the function as existing.
     const BF16_VEC: bool = false;
@@ -217,6 +362,37 @@ pub trait BinaryOpT {
     fn u32(v1: u32, v2: u32) -> u32;
     fn i64(v1: i64, v2: i64) -> i64;
 
+    // The smaller integer types go through the wider ones by default, the results are truncated
+    // so overflows wrap around.
+    fn u16(v1: u16, v2: u16) -> u16 {
+        Self::u32(v1 as u32, v2 as u32) as u16
+    }
+    fn i8(v1: i8, v2: i8) -> i8 {
+        Self::i64(v1 as i64, v2 as i64) as i8
+    }
+    fn i16(v1: i16, v2: i16) -> i16 {
+        Self::i64(v1 as i64, v2 as i64) as i16
+    }
+    fn i32(v1: i32, v2: i32) -> i32 {
+        Self::i64(v1 as i64, v2 as i64) as i32
+    }
+
+    // The ops that have a logical counterpart on bool values, e.g. `or` for `add`, the flag marks
+    // the function as existing.
+    const BOOL: bool = false;
+    fn bool(v1: bool, _v2: bool) -> bool {
+        v1
+    }
+
+    // Only the arithmetic ops support complex values, the flag marks the functions as existing.
+    const COMPLEX: bool = false;
+    fn c64(v1: Complex32, _v2: Complex32) -> Complex32 {
+        v1
+    }
+    fn c128(v1: Complex64, _v2: Complex64) -> Complex64 {
+        v1
+    }
+
     const BF16_VEC: bool = false;
     fn bf16_vec(_xs1: &[bf16], _xs2: &[bf16], _ys: &mut [bf16]) {}
     const F16_VEC: bool = false;
@@ -239,6 +415,9 @@ pub(crate) struct Mul;
 pub(crate) struct Sub;
 pub(crate) struct Maximum;
 pub(crate) struct Minimum;
+pub(crate) struct FloorDiv;
+pub(crate) struct Remainder;
+pub(crate) struct Fmod;
 pub(crate) struct Exp;
 pub(crate) struct Log;
 pub(crate) struct Sin;
@@ -261,6 +440,38 @@ pub(crate) struct Sign;
 
 macro_rules! bin_op {
     ($op:ident, $name: literal, $e: expr, $f32_vec: ident, $f64_vec: ident) => {
+        bin_op!($op, $name, $e, $f32_vec, $f64_vec, {});
+    };
+    ($op:ident, $name: literal, $e: expr, $f32_vec: ident, $f64_vec: ident, complex $(, bool: $b: expr)?) => {
+        bin_op!($op, $name, $e, $f32_vec, $f64_vec, {
+            const COMPLEX: bool = true;
+            #[inline(always)]
+            fn c64(v1: Complex32, v2: Complex32) -> Complex32 {
+                $e(v1, v2)
+            }
+            #[inline(always)]
+            fn c128(v1: Complex64, v2: Complex64) -> Complex64 {
+                $e(v1, v2)
+            }
+            $(
+                const BOOL: bool = true;
+                #[inline(always)]
+                fn bool(v1: bool, v2: bool) -> bool {
+                    $b(v1, v2)
+                }
+            )?
+        });
+    };
+    ($op:ident, $name: literal, $e: expr, $f32_vec: ident, $f64_vec: ident, bool: $b: expr) => {
+        bin_op!($op, $name, $e, $f32_vec, $f64_vec, {
+            const BOOL: bool = true;
+            #[inline(always)]
+            fn bool(v1: bool, v2: bool) -> bool {
+                $b(v1, v2)
+            }
+        });
+    };
+    ($op:ident, $name: literal, $e: expr, $f32_vec: ident, $f64_vec: ident, { $($complex:tt)* }) => {
         impl BinaryOpT for $op {
             const NAME: &'static str = $name;
             const KERNEL: &'static str = concat!("b", $name);
@@ -323,32 +534,144 @@ macro_rules! bin_op {
             fn f64_vec(xs1: &[f64], xs2: &[f64], ys: &mut [f64]) {
                 crate::accelerate::$f64_vec(xs1, xs2, ys)
             }
+
+            $($complex)*
         }
     };
 }
 
-bin_op!(Add, "add", |v1, v2| v1 + v2, vs_add, vd_add);
-bin_op!(Sub, "sub", |v1, v2| v1 - v2, vs_sub, vd_sub);
-bin_op!(Mul, "mul", |v1, v2| v1 * v2, vs_mul, vd_mul);
-bin_op!(Div, "div", |v1, v2| v1 / v2, vs_div, vd_div);
+// On bool values add and maximum act as a logical or, mul and minimum as a logical and.
+bin_op!(Add, "add", |v1, v2| v1 + v2, vs_add, vd_add, complex, bool: |v1, v2| v1 | v2);
+bin_op!(Sub, "sub", |v1, v2| v1 - v2, vs_sub, vd_sub, complex);
+bin_op!(Mul, "mul", |v1, v2| v1 * v2, vs_mul, vd_mul, complex, bool: |v1, v2| v1 & v2);
+bin_op!(Div, "div", |v1, v2| v1 / v2, vs_div, vd_div, complex);
 bin_op!(
     Minimum,
     "minimum",
     |v1, v2| if v1 > v2 { v2 } else { v1 },
     vs_min,
-    vd_min
+    vd_min,
+    bool: |v1, v2| v1 & v2
 );
 bin_op!(
     Maximum,
     "maximum",
     |v1, v2| if v1 < v2 { v2 } else { v1 },
     vs_max,
-    vd_max
+    vd_max,
+    bool: |v1, v2| v1 | v2
+);
+
+// Division like ops, these use a different expression for floats, unsigned and signed integers.
+// Integer divisions by zero return 0 rather than panicking.
+macro_rules! div_op {
+    ($op:ident, $name: literal, $float: expr, $unsigned: expr, $signed: expr) => {
+        impl BinaryOpT for $op {
+            const NAME: &'static str = $name;
+            const KERNEL: &'static str = concat!("b", $name);
+            const V: Self = $op;
+            #[inline(always)]
+            fn bf16(v1: bf16, v2: bf16) -> bf16 {
+                let f: fn(bf16, bf16) -> bf16 = $float;
+                f(v1, v2)
+            }
+            #[inline(always)]
+            fn f16(v1: f16, v2: f16) -> f16 {
+                let f: fn(f16, f16) -> f16 = $float;
+                f(v1, v2)
+            }
+            #[inline(always)]
+            fn f32(v1: f32, v2: f32) -> f32 {
+                let f: fn(f32, f32) -> f32 = $float;
+                f(v1, v2)
+            }
+            #[inline(always)]
+            fn f64(v1: f64, v2: f64) -> f64 {
+                let f: fn(f64, f64) -> f64 = $float;
+                f(v1, v2)
+            }
+            #[inline(always)]
+            fn u8(v1: u8, v2: u8) -> u8 {
+                let f: fn(u8, u8) -> u8 = $unsigned;
+                f(v1, v2)
+            }
+            #[inline(always)]
+            fn u32(v1: u32, v2: u32) -> u32 {
+                let f: fn(u32, u32) -> u32 = $unsigned;
+                f(v1, v2)
+            }
+            #[inline(always)]
+            fn i64(v1: i64, v2: i64) -> i64 {
+                let f: fn(i64, i64) -> i64 = $signed;
+                f(v1, v2)
+            }
+        }
+    };
+}
+
+div_op!(
+    FloorDiv,
+    "floor_div",
+    |v1, v2| (v1 / v2).floor(),
+    |v1, v2| v1.checked_div(v2).unwrap_or(0),
+    |v1: i64, v2: i64| {
+        if v2 == 0 {
+            return 0;
+        }
+        let q = v1.wrapping_div(v2);
+        if v1.wrapping_rem(v2) != 0 && (v1 < 0) != (v2 < 0) {
+            q - 1
+        } else {
+            q
+        }
+    }
+);
+div_op!(
+    Remainder,
+    "remainder",
+    |v1, v2| v1 - v2 * (v1 / v2).floor(),
+    |v1, v2| v1.checked_rem(v2).unwrap_or(0),
+    |v1: i64, v2: i64| {
+        if v2 == 0 {
+            return 0;
+        }
+        let r = v1.wrapping_rem(v2);
+        if r != 0 && (r < 0) != (v2 < 0) {
+            r + v2
+        } else {
+            r
+        }
+    }
+);
+div_op!(
+    Fmod,
+    "fmod",
+    |v1, v2| v1 % v2,
+    |v1, v2| v1.checked_rem(v2).unwrap_or(0),
+    |v1: i64, v2: i64| if v2 == 0 { 0 } else { v1.wrapping_rem(v2) }
 );
 
 #[allow(clippy::redundant_closure_call)]
 macro_rules! unary_op {
     ($op: ident, $name: literal, $a: ident, $e: expr) => {
+        unary_op!($op, $name, $a, $e, {});
+    };
+
+    ($op: ident, $name: literal, $a: ident, $e: expr, complex) => {
+        unary_op!($op, $name, $a, $e, {
+            const COMPLEX: bool = true;
+            #[inline(always)]
+            fn c64($a: Complex32) -> Complex32 {
+                $e
+            }
+            #[inline(always)]
+            fn c128($a: Complex64) -> Complex64 {
+                $e
+            }
+        });
+    };
+
+    ($op: ident, $name: literal, $a: ident, $e: expr, { $($complex:tt)* }) => {
         impl UnaryOpT for $op {
             const NAME: &'static str = $name;
             const KERNEL: &'static str = concat!("u", $name);
@@ -381,6 +704,8 @@ macro_rules! unary_op {
             fn i64(_: i64) -> i64 {
                 todo!("no unary function for i64")
             }
+
+            $($complex)*
         }
     };
 
@@ -456,7 +781,7 @@ unary_op!(Log, "log", v, v.ln(), vs_ln, vd_ln);
 unary_op!(Sin, "sin", v, v.sin(), vs_sin, vd_sin);
 unary_op!(Cos, "cos", v, v.cos(), vs_cos, vd_cos);
 unary_op!(Tanh, "tanh", v, v.tanh(), vs_tanh, vd_tanh);
-unary_op!(Neg, "neg", v, -v);
+unary_op!(Neg, "neg", v, -v, complex);
 unary_op!(Recip, "recip", v, v.recip());
 unary_op!(Sqr, "sqr", v, v * v, vs_sqr, vd_sqr);
 unary_op!(Sqrt, "sqrt", v, v.sqrt(), vs_sqrt, vd_sqrt);
@@ -0,0 +1,115 @@
+//! Padding with constant, reflected, replicated or circular values.
+//!
+//! The non-constant modes gather the padded values with `index_select` so that they work on all
+//! devices and get their gradients from the existing ops.
+use crate::{bail, Result, Tensor};
+
+/// How the values added around a tensor by [`Tensor::pad`] are computed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadMode {
+    /// All the padding values are set to the given value.
+    Constant(f64),
+    /// The values are mirrored around the first and last elements, these elements are not
+    /// repeated, e.g. `[1, 2, 3]` is padded as `[.., 3, 2, 1, 2, 3, 2, 1, ..]`.
+    Reflect,
+    /// The first and last elements are repeated.
+    Replicate,
+    /// The values wrap around, e.g. `[1, 2, 3]` is padded as `[.., 2, 3, 1, 2, 3, 1, 2, ..]`.
+    Circular,
+}
+
+impl PadMode {
+    /// The index of the input element used at position `i` of the padded dimension.
+    fn src_index(&self, i: usize, left: usize, size: usize) -> usize {
+        let i = i as i64 - left as i64;
+        let size = size as i64;
+        let index = match self {
+            Self::Constant(_) => i,
+            Self::Replicate => i.clamp(0, size - 1),
+            Self::Circular => i.rem_euclid(size),
+            Self::Reflect => {
+                if size == 1 {
+                    0
+                } else {
+                    let period = 2 * size - 2;
+                    let i = i.rem_euclid(period);
+                    if i < size {
+                        i
+                    } else {
+                        period - i
+                    }
+                }
+            }
+        };
+        index as usize
+    }
+}
+
+impl Tensor {
+    fn pad_dim(&self, dim: usize, left: usize, right: usize, mode: PadMode) -> Result<Self> {
+        if left == 0 && right == 0 {
+            return Ok(self.clone());
+        }
+        let size = self.dim(dim)?;
+        match mode {
+            PadMode::Constant(value) => {
+                let value = Tensor::new(value, self.device())?.to_dtype(self.dtype())?;
+                let fill = |len: usize| {
+                    let mut dims = self.dims().to_vec();
+                    dims[dim] = len;
+                    value.broadcast_as(dims)
+                };
+                let mut xs = vec![];
+                if left > 0 {
+                    xs.push(fill(left)?)
+                }
+                xs.push(self.clone());
+                if right > 0 {
+                    xs.push(fill(right)?)
+                }
+                Tensor::cat(&xs, dim)
+            }
+            _ => {
+                if size == 0 {
+                    bail!("pad: cannot use {mode:?} padding on an empty dimension {dim}")
+                }
+                let indexes: Vec<u32> = (0..left + size + right)
+                    .map(|i| mode.src_index(i, left, size) as u32)
+                    .collect();
+                let indexes = Tensor::new(indexes, self.device())?;
+                self.index_select(&indexes, dim)
+            }
+        }
+    }
+
+    /// Pads the last `pad.len()` dimensions of the tensor, `pad` contains the number of elements
+    /// to add before and after each of these dimensions, starting with the outermost one. Contrary
+    /// to PyTorch, the padding can be larger than the dimension size for the reflect and circular
+    /// modes in which case the values are reflected or wrapped multiple times.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// use candle_core::pad::PadMode;
+    /// let a = Tensor::new(&[[1f32, 2., 3.], [4., 5., 6.]], &Device::Cpu)?;
+    /// let b = a.pad(&[(2, 1)], PadMode::Reflect)?;
+    /// assert_eq!(b.to_vec2::<f32>()?, &[[3., 2., 1., 2., 3., 2.], [6., 5., 4., 5., 6., 5.]]);
+    /// let b = a.pad(&[(1, 0), (0, 1)], PadMode::Constant(-1.))?;
+    /// assert_eq!(b.to_vec2::<f32>()?, &[[-1., -1., -1., -1.], [1., 2., 3., -1.], [4., 5., 6., -1.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn pad(&self, pad: &[(usize, usize)], mode: PadMode) -> Result<Self> {
+        let rank = self.rank();
+        if pad.len() > rank {
+            bail!(
+                "pad: {} padded dimensions for a tensor of shape {:?}",
+                pad.len(),
+                self.shape()
+            )
+        }
+        let mut xs = self.clone();
+        for (dim, &(left, right)) in (rank - pad.len()..rank).zip(pad.iter()).rev() {
+            xs = xs.pad_dim(dim, left, right, mode)?
+        }
+        Ok(xs)
+    }
+}
@@ -0,0 +1,304 @@
+//! 2D pooling with padding, dilation and ceil mode.
+//!
+//! The plain pooling ops are provided by the backends, see `Tensor::max_pool2d_with_stride` and
+//! `Tensor::avg_pool2d_with_stride`. The more general variants here are expressed by gathering
+//! each kernel offset with `index_select` so that they work on all devices and get their
+//! gradients from the existing ops.
+use crate::{bail, DType, Device, Result, Tensor};
+
+/// Pooling options, the defaults match the PyTorch ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pool2dConfig {
+    /// Implicit padding added on both sides of the two spatial dimensions. The padding never
+    /// wins a max pooling and counts as zero for average pooling.
+    pub padding: (usize, usize),
+    /// Spacing between the kernel elements.
+    pub dilation: (usize, usize),
+    /// Use ceil rather than floor when computing the output size.
+    pub ceil_mode: bool,
+    /// Include the padding elements in the divisor of average pooling.
+    pub count_include_pad: bool,
+}
+
+impl Default for Pool2dConfig {
+    fn default() -> Self {
+        Self {
+            padding: (0, 0),
+            dilation: (1, 1),
+            ceil_mode: false,
+            count_include_pad: true,
+        }
+    }
+}
+
+impl Pool2dConfig {
+    /// Whether all the windows are fully within the input so the backend kernels can be used.
+    fn is_plain(&self) -> bool {
+        self.padding == (0, 0) && self.dilation == (1, 1) && !self.ceil_mode
+    }
+}
+
+/// The pooling windows along a single spatial dimension.
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    in_size: usize,
+    kernel_size: usize,
+    stride: usize,
+    padding: usize,
+    dilation: usize,
+    out_size: usize,
+}
+
+impl Axis {
+    fn new(
+        in_size: usize,
+        kernel_size: usize,
+        stride: usize,
+        padding: usize,
+        dilation: usize,
+        ceil_mode: bool,
+    ) -> Result<Self> {
+        if kernel_size == 0 || stride == 0 || dilation == 0 {
+            bail!("pooling kernel-size {kernel_size}, stride {stride} and dilation {dilation} should be positive")
+        }
+        if padding > kernel_size / 2 {
+            bail!("pooling padding {padding} should be at most half the kernel-size {kernel_size}")
+        }
+        let kernel_extent = dilation * (kernel_size - 1) + 1;
+        if in_size + 2 * padding < kernel_extent {
+            bail!("kernel-size {kernel_extent} is larger than the padded input size {in_size} + 2 * {padding}")
+        }
+        // https://pytorch.org/docs/stable/generated/torch.nn.MaxPool2d.html#torch.nn.MaxPool2d
+        let span = in_size + 2 * padding - kernel_extent;
+        let mut out_size = if ceil_mode {
+            span.div_ceil(stride)
+        } else {
+            span / stride
+        } + 1;
+        // With ceil_mode, the last window has to start in the input or in the left padding.
+        if ceil_mode && (out_size - 1) * stride >= in_size + padding {
+            out_size -= 1
+        }
+        Ok(Self {
+            in_size,
+            kernel_size,
+            stride,
+            padding,
+            dilation,
+            out_size,
+        })
+    }
+
+    /// The position in the padded input of kernel element `k` for output `o`.
+    fn padded_pos(&self, o: usize, k: usize) -> usize {
+        o * self.stride + k * self.dilation
+    }
+
+    /// The input position of kernel element `k` for output `o`, `None` if it is in the padding.
+    fn pos(&self, o: usize, k: usize) -> Option<usize> {
+        self.padded_pos(o, k)
+            .checked_sub(self.padding)
+            .filter(|&p| p < self.in_size)
+    }
+
+    /// The input positions for kernel element `k`, padding elements are mapped to 0 and have to
+    /// be masked out by the caller.
+    fn indexes(&self, k: usize, device: &Device) -> Result<Tensor> {
+        let indexes = (0..self.out_size)
+            .map(|o| self.pos(o, k).unwrap_or(0) as u32)
+            .collect::<Vec<_>>();
+        Tensor::from_vec(indexes, self.out_size, device)
+    }
+
+    /// The number of elements of window `o` that count for average pooling.
+    fn count(&self, o: usize, count_include_pad: bool) -> usize {
+        (0..self.kernel_size)
+            .filter(|&k| {
+                if count_include_pad {
+                    self.padded_pos(o, k) < self.in_size + 2 * self.padding
+                } else {
+                    self.pos(o, k).is_some()
+                }
+            })
+            .count()
+    }
+}
+
+fn axes<T: crate::ToUsize2>(
+    xs: &Tensor,
+    kernel_size: T,
+    stride: T,
+    config: &Pool2dConfig,
+) -> Result<(Axis, Axis)> {
+    let (k_h, k_w) = kernel_size.to_usize2();
+    let (s_h, s_w) = stride.to_usize2();
+    let (_n, _c, h, w) = xs.dims4()?;
+    let axis_h = Axis::new(
+        h,
+        k_h,
+        s_h,
+        config.padding.0,
+        config.dilation.0,
+        config.ceil_mode,
+    )?;
+    let axis_w = Axis::new(
+        w,
+        k_w,
+        s_w,
+        config.padding.1,
+        config.dilation.1,
+        config.ceil_mode,
+    )?;
+    Ok((axis_h, axis_w))
+}
+
+/// Calls `f` on each kernel element with the `(batch, channels, h_out, w_out)` input values, the
+/// `(h_out, w_out)` u8 mask of the values that are not padding and the `(h_out, w_out)` u32 flat
+/// input indexes of these values.
+fn for_each_kernel_element<F>(xs: &Tensor, axis_h: Axis, axis_w: Axis, mut f: F) -> Result<()>
+where
+    F: FnMut(Tensor, Tensor, Tensor) -> Result<()>,
+{
+    let device = xs.device();
+    let (h_out, w_out) = (axis_h.out_size, axis_w.out_size);
+    for k_h in 0..axis_h.kernel_size {
+        let rows = xs.index_select(&axis_h.indexes(k_h, device)?, 2)?;
+        for k_w in 0..axis_w.kernel_size {
+            let values = rows.index_select(&axis_w.indexes(k_w, device)?, 3)?;
+            let mut mask = vec![0u8; h_out * w_out];
+            let mut indexes = vec![0u32; h_out * w_out];
+            for o_h in 0..h_out {
+                for o_w in 0..w_out {
+                    if let (Some(p_h), Some(p_w)) = (axis_h.pos(o_h, k_h), axis_w.pos(o_w, k_w)) {
+                        mask[o_h * w_out + o_w] = 1;
+                        indexes[o_h * w_out + o_w] = (p_h * axis_w.in_size + p_w) as u32;
+                    }
+                }
+            }
+            let mask = Tensor::from_vec(mask, (h_out, w_out), device)?;
+            let indexes = Tensor::from_vec(indexes, (h_out, w_out), device)?;
+            f(values, mask, indexes)?
+        }
+    }
+    Ok(())
+}
+
+impl Tensor {
+    /// Same as `avg_pool2d_with_stride` with the additional options from `config`.
+    pub fn avg_pool2d_with_config<T: crate::ToUsize2>(
+        &self,
+        kernel_size: T,
+        stride: T,
+        config: &Pool2dConfig,
+    ) -> Result<Self> {
+        if config.is_plain() {
+            return self.avg_pool2d_with_stride(kernel_size, stride);
+        }
+        let (axis_h, axis_w) = axes(self, kernel_size, stride, config)?;
+        let (n, c, _h, _w) = self.dims4()?;
+        let shape = (n, c, axis_h.out_size, axis_w.out_size);
+        let mut sum = Tensor::zeros(shape, self.dtype(), self.device())?;
+        for_each_kernel_element(self, axis_h, axis_w, |values, mask, _indexes| {
+            let mask = mask.broadcast_as(shape)?;
+            sum = (&sum + mask.where_cond(&values, &values.zeros_like()?)?)?;
+            Ok(())
+        })?;
+        let mut scale = Vec::with_capacity(axis_h.out_size * axis_w.out_size);
+        for o_h in 0..axis_h.out_size {
+            let count_h = axis_h.count(o_h, config.count_include_pad);
+            for o_w in 0..axis_w.out_size {
+                let count_w = axis_w.count(o_w, config.count_include_pad);
+                scale.push(1. / (count_h * count_w) as f32)
+            }
+        }
+        let scale = Tensor::from_vec(scale, (axis_h.out_size, axis_w.out_size), self.device())?
+            .to_dtype(self.dtype())?;
+        sum.broadcast_mul(&scale)
+    }
+
+    /// Same as `max_pool2d_with_stride` with the additional options from `config`.
+    pub fn max_pool2d_with_config<T: crate::ToUsize2>(
+        &self,
+        kernel_size: T,
+        stride: T,
+        config: &Pool2dConfig,
+    ) -> Result<Self> {
+        if config.is_plain() {
+            return self.max_pool2d_with_stride(kernel_size, stride);
+        }
+        let (values, _indexes) = self.max_pool2d_with_indices(kernel_size, stride, config)?;
+        Ok(values)
+    }
+
+    /// 2D max pooling that also returns the position of the maximum of each window.
+    ///
+    /// The returned indexes are `u32` values with the same shape as the pooled values, each index
+    /// points into the flattened `h * w` spatial dimensions of the input. When a window contains
+    /// multiple maximums, the first one is used. The indexes can be passed to `max_unpool2d`.
+    pub fn max_pool2d_with_indices<T: crate::ToUsize2>(
+        &self,
+        kernel_size: T,
+        stride: T,
+        config: &Pool2dConfig,
+    ) -> Result<(Self, Self)> {
+        let (axis_h, axis_w) = axes(self, kernel_size, stride, config)?;
+        let (n, c, _h, _w) = self.dims4()?;
+        let shape = (n, c, axis_h.out_size, axis_w.out_size);
+        let mut max = Tensor::full(f64::NEG_INFINITY, shape, self.device())?
+            .to_dtype(self.dtype())?
+            .contiguous()?;
+        let mut argmax = Tensor::zeros(shape, DType::U32, self.device())?;
+        // The first non-padding element of each window is always taken so that the indexes point
+        // into the window even when no value is greater than the initial one, e.g. for u8 zeros.
+        let mut seen = Tensor::zeros(shape, DType::U8, self.device())?;
+        for_each_kernel_element(self, axis_h, axis_w, |values, mask, indexes| {
+            let mask = mask.broadcast_as(shape)?;
+            let update = seen
+                .logical_not()?
+                .logical_or(&values.gt(&max)?)?
+                .logical_and(&mask)?;
+            max = update.where_cond(&values, &max)?;
+            argmax = update.where_cond(&indexes.broadcast_as(shape)?, &argmax)?;
+            seen = seen.logical_or(&mask)?;
+            Ok(())
+        })?;
+        Ok((max, argmax))
+    }
+
+    /// Partial inverse of `max_pool2d_with_indices`, the values are written at the position
+    /// given by `indexes` in a zero tensor of shape `(batch, channels, h, w)`.
+    pub fn max_unpool2d<T: crate::ToUsize2>(&self, indexes: &Self, output_size: T) -> Result<Self> {
+        let (h, w) = output_size.to_usize2();
+        let (n, c, _h_in, _w_in) = self.dims4()?;
+        let (indexes, values) = (indexes.flatten_from(2)?, self.flatten_from(2)?);
+        let zeros = Tensor::zeros((n, c, h * w), self.dtype(), self.device())?;
+        let sum = zeros.scatter_add(&indexes, &values, 2)?;
+        // With overlapping windows, the same input element can be the maximum of multiple
+        // windows so it gets accumulated multiple times.
+        let count = zeros.scatter_add(&indexes, &values.ones_like()?, 2)?;
+        let count = count.maximum(1f64)?;
+        (sum / count)?.reshape((n, c, h, w))
+    }
+}
+
+/// The gradient of `avg_pool2d_with_stride`, `grad` has the shape of the pooled values.
+pub(crate) fn avg_pool2d_backward(
+    arg: &Tensor,
+    grad: &Tensor,
+    kernel_size: (usize, usize),
+    stride: (usize, usize),
+) -> Result<Tensor> {
+    let config = Pool2dConfig::default();
+    let (axis_h, axis_w) = axes(arg, kernel_size, stride, &config)?;
+    let (n, c, h, w) = arg.dims4()?;
+    let device = arg.device();
+    let mut grad_rows = Tensor::zeros((n, c, axis_h.out_size, w), grad.dtype(), device)?;
+    for k_w in 0..axis_w.kernel_size {
+        grad_rows = grad_rows.index_add(&axis_w.indexes(k_w, device)?, grad, 3)?;
+    }
+    let mut grad_arg = Tensor::zeros((n, c, h, w), grad.dtype(), device)?;
+    for k_h in 0..axis_h.kernel_size {
+        grad_arg = grad_arg.index_add(&axis_h.indexes(k_h, device)?, &grad_rows, 2)?;
+    }
+    grad_arg * (1f64 / (kernel_size.0 * kernel_size.1) as f64)
+}
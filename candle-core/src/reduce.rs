@@ -0,0 +1,226 @@
+//! Reductions expressed with the existing ops: products, standard deviations, norms, boolean
+//! reductions and maxima over multiple dimensions. They get their gradients from the ops they
+//! are built on.
+use crate::shape::Dims;
+use crate::{DType, Result, Tensor};
+
+/// The vector norms supported by [`Tensor::norm`], the norm is computed over all the values of
+/// the reduced dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormOrd {
+    /// The sum of the absolute values.
+    L1,
+    /// The square root of the sum of the squares.
+    L2,
+    /// The maximum absolute value.
+    Inf,
+    /// The Frobenius norm, this is the same as `L2` applied to the flattened dimensions.
+    Fro,
+}
+
+impl Tensor {
+    fn prod_impl<D: Dims>(&self, prod_dims: D, keepdim: bool) -> Result<Self> {
+        let prod_dims = prod_dims.to_indexes(self.shape(), "prod")?;
+        if prod_dims.is_empty() {
+            return Ok(self.clone());
+        }
+        // Move the reduced dimensions to the end and merge them so that the product is the last
+        // element of a cumulative product.
+        let dims = self.dims();
+        let kept: Vec<usize> = (0..dims.len()).filter(|d| !prod_dims.contains(d)).collect();
+        let mut out_dims: Vec<usize> = kept.iter().map(|&d| dims[d]).collect();
+        let len: usize = prod_dims.iter().map(|&d| dims[d]).product();
+        let prod = if len == 0 {
+            Tensor::ones(out_dims.as_slice(), self.dtype(), self.device())?
+        } else {
+            let perm = [kept.as_slice(), prod_dims.as_slice()].concat();
+            out_dims.push(len);
+            let last = out_dims.len() - 1;
+            self.permute(perm)?
+                .reshape(out_dims)?
+                .cumprod(last)?
+                .narrow(last, len - 1, 1)?
+                .squeeze(last)?
+        };
+        if keepdim {
+            let mut dims = dims.to_vec();
+            prod_dims.iter().for_each(|&d| dims[d] = 1);
+            prod.reshape(dims)
+        } else {
+            Ok(prod)
+        }
+    }
+
+    /// Returns the product of the elements over the selected dimensions, these dimensions are
+    /// kept with a single element. This relies on `cumprod` so it is only available on the cpu.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1f32, 2.], [3., 4.]], &Device::Cpu)?;
+    /// assert_eq!(a.prod_keepdim(0)?.to_vec2::<f32>()?, &[[3., 8.]]);
+    /// assert_eq!(a.prod_keepdim((0, 1))?.to_vec2::<f32>()?, &[[24.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn prod_keepdim<D: Dims>(&self, prod_dims: D) -> Result<Self> {
+        self.prod_impl(prod_dims, true)
+    }
+
+    /// Similar to `prod_keepdim` but the reduced dimensions are squeezed.
+    pub fn prod<D: Dims>(&self, prod_dims: D) -> Result<Self> {
+        self.prod_impl(prod_dims, false)
+    }
+
+    /// Returns the unbiased standard deviation over the selected dimensions.
+    pub fn std_keepdim<D: Dims>(&self, std_dims: D) -> Result<Self> {
+        self.var_keepdim(std_dims)?.sqrt()
+    }
+
+    /// Similar to `std_keepdim` but the reduced dimensions are squeezed.
+    pub fn std<D: Dims>(&self, std_dims: D) -> Result<Self> {
+        self.var(std_dims)?.sqrt()
+    }
+
+    /// Returns the standard deviation over the selected dimensions, see
+    /// `var_with_correction_keepdim` for the meaning of `correction`.
+    pub fn std_with_correction_keepdim<D: Dims>(
+        &self,
+        std_dims: D,
+        correction: usize,
+    ) -> Result<Self> {
+        self.var_with_correction_keepdim(std_dims, correction)?
+            .sqrt()
+    }
+
+    /// Similar to `std_with_correction_keepdim` but the reduced dimensions are squeezed.
+    pub fn std_with_correction<D: Dims>(&self, std_dims: D, correction: usize) -> Result<Self> {
+        self.var_with_correction(std_dims, correction)?.sqrt()
+    }
+
+    /// Returns the norm of the values over the selected dimensions, these dimensions are kept
+    /// with a single element.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// use candle_core::reduce::NormOrd;
+    /// let a = Tensor::new(&[[3f32, -4.], [0., 1.]], &Device::Cpu)?;
+    /// assert_eq!(a.norm_keepdim(NormOrd::L2, 1)?.to_vec2::<f32>()?, &[[5.], [1.]]);
+    /// assert_eq!(a.norm_keepdim(NormOrd::L1, 0)?.to_vec2::<f32>()?, &[[3., 5.]]);
+    /// assert_eq!(a.norm_keepdim(NormOrd::Inf, (0, 1))?.to_vec2::<f32>()?, &[[4.]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn norm_keepdim<D: Dims>(&self, ord: NormOrd, norm_dims: D) -> Result<Self> {
+        match ord {
+            NormOrd::L1 => self.abs()?.sum_keepdim(norm_dims),
+            NormOrd::L2 | NormOrd::Fro => self.sqr()?.sum_keepdim(norm_dims)?.sqrt(),
+            NormOrd::Inf => self.abs()?.amax_keepdim(norm_dims),
+        }
+    }
+
+    /// Similar to `norm_keepdim` but the reduced dimensions are squeezed.
+    pub fn norm<D: Dims>(&self, ord: NormOrd, norm_dims: D) -> Result<Self> {
+        match ord {
+            NormOrd::L1 => self.abs()?.sum(norm_dims),
+            NormOrd::L2 | NormOrd::Fro => self.sqr()?.sum(norm_dims)?.sqrt(),
+            NormOrd::Inf => self.abs()?.amax(norm_dims),
+        }
+    }
+
+    /// Returns the number of non-zero elements over the selected dimensions as a `u32` tensor.
+    fn count_nonzero_keepdim(&self, dims: &[usize]) -> Result<Self> {
+        self.ne(0f64)?.to_dtype(DType::U32)?.sum_keepdim(dims)
+    }
+
+    fn any_impl<D: Dims>(&self, any_dims: D, keepdim: bool, all: bool) -> Result<Self> {
+        let op = if all { "all" } else { "any" };
+        let dims = any_dims.to_indexes(self.shape(), op)?;
+        let res = if all {
+            let len: usize = dims.iter().map(|&d| self.dims()[d]).product();
+            self.count_nonzero_keepdim(&dims)?.eq(len as f64)?
+        } else {
+            self.count_nonzero_keepdim(&dims)?.gt(0f64)?
+        };
+        if keepdim {
+            Ok(res)
+        } else {
+            res.squeeze_dims(&dims)
+        }
+    }
+
+    /// Checks whether any element is non-zero over the selected dimensions, these dimensions are
+    /// kept with a single element. The result uses the same dtype as the comparison ops.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[0u8, 1, 0], [0, 0, 0]], &Device::Cpu)?;
+    /// assert_eq!(a.any_keepdim(1)?.to_vec2::<bool>()?, &[[true], [false]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn any_keepdim<D: Dims>(&self, any_dims: D) -> Result<Self> {
+        self.any_impl(any_dims, true, false)
+    }
+
+    /// Similar to `any_keepdim` but the reduced dimensions are squeezed.
+    pub fn any<D: Dims>(&self, any_dims: D) -> Result<Self> {
+        self.any_impl(any_dims, false, false)
+    }
+
+    /// Checks whether all the elements are non-zero over the selected dimensions, these
+    /// dimensions are kept with a single element. The result uses the same dtype as the
+    /// comparison ops.
+    pub fn all_keepdim<D: Dims>(&self, all_dims: D) -> Result<Self> {
+        self.any_impl(all_dims, true, true)
+    }
+
+    /// Similar to `all_keepdim` but the reduced dimensions are squeezed.
+    pub fn all<D: Dims>(&self, all_dims: D) -> Result<Self> {
+        self.any_impl(all_dims, false, true)
+    }
+
+    fn amax_impl<D: Dims>(&self, dims: D, keepdim: bool, max: bool) -> Result<Self> {
+        let op = if max { "amax" } else { "amin" };
+        let dims = dims.to_indexes(self.shape(), op)?;
+        let mut res = self.clone();
+        for &dim in dims.iter() {
+            res = if max {
+                res.max_keepdim(dim)?
+            } else {
+                res.min_keepdim(dim)?
+            }
+        }
+        if keepdim {
+            Ok(res)
+        } else {
+            res.squeeze_dims(&dims)
+        }
+    }
+
+    /// Returns the maximum value over the selected dimensions, these dimensions are kept with a
+    /// single element. As for `max`, the gradient flows to all the elements equal to the maximum.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[[1f32, 7.], [3., 2.]], [[0., 4.], [8., 1.]]], &Device::Cpu)?;
+    /// assert_eq!(a.amax((0, 2))?.to_vec1::<f32>()?, &[7., 8.]);
+    /// assert_eq!(a.amin((1, 2))?.to_vec1::<f32>()?, &[1., 0.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn amax_keepdim<D: Dims>(&self, dims: D) -> Result<Self> {
+        self.amax_impl(dims, true, true)
+    }
+
+    /// Similar to `amax_keepdim` but the reduced dimensions are squeezed.
+    pub fn amax<D: Dims>(&self, dims: D) -> Result<Self> {
+        self.amax_impl(dims, false, true)
+    }
+
+    /// Returns the minimum value over the selected dimensions, these dimensions are kept with a
+    /// single element.
+    pub fn amin_keepdim<D: Dims>(&self, dims: D) -> Result<Self> {
+        self.amax_impl(dims, true, false)
+    }
+
+    /// Similar to `amin_keepdim` but the reduced dimensions are squeezed.
+    pub fn amin<D: Dims>(&self, dims: D) -> Result<Self> {
+        self.amax_impl(dims, false, false)
+    }
+}
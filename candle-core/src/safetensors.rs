@@ -9,7 +9,9 @@
 //! Tensors can also be serialized to safetensor format using the `save` function or
 //! `Tensor::save_safetensors` method.
 //!
-use crate::{DType, Device, Error, Result, Tensor, WithDType};
+use crate::op::BackpropOp;
+use crate::tensor::from_storage;
+use crate::{CpuStorage, DType, Device, Error, Result, Shape, Storage, Tensor, WithDType};
 use safetensors::tensor as st;
 use safetensors::tensor::SafeTensors;
 use std::borrow::Cow;
@@ -20,8 +22,18 @@ impl From<DType> for st::Dtype {
     fn from(value: DType) -> Self {
         match value {
             DType::U8 => st::Dtype::U8,
+            DType::U16 => st::Dtype::U16,
             DType::U32 => st::Dtype::U32,
+            DType::I8 => st::Dtype::I8,
+            DType::I16 => st::Dtype::I16,
+            DType::I32 => st::Dtype::I32,
             DType::I64 => st::Dtype::I64,
+            DType::Bool => st::Dtype::BOOL,
+            DType::F8E4M3 => st::Dtype::F8_E4M3,
+            DType::F8E5M2 => st::Dtype::F8_E5M2,
+            DType::C64 | DType::C128 => {
+                panic!("complex tensors cannot be serialized, use view_as_real first")
+            }
             DType::BF16 => st::Dtype::BF16,
             DType::F16 => st::Dtype::F16,
             DType::F32 => st::Dtype::F32,
@@ -35,8 +47,15 @@ impl TryFrom<st::Dtype> for DType {
     fn try_from(value: st::Dtype) -> Result<Self> {
         match value {
             st::Dtype::U8 => Ok(DType::U8),
+            st::Dtype::U16 => Ok(DType::U16),
             st::Dtype::U32 => Ok(DType::U32),
+            st::Dtype::I8 => Ok(DType::I8),
+            st::Dtype::I16 => Ok(DType::I16),
+            st::Dtype::I32 => Ok(DType::I32),
             st::Dtype::I64 => Ok(DType::I64),
+            st::Dtype::BOOL => Ok(DType::Bool),
+            st::Dtype::F8_E4M3 => Ok(DType::F8E4M3),
+            st::Dtype::F8_E5M2 => Ok(DType::F8E5M2),
             st::Dtype::BF16 => Ok(DType::BF16),
             st::Dtype::F16 => Ok(DType::F16),
             st::Dtype::F32 => Ok(DType::F32),
@@ -90,11 +109,20 @@ impl st::View for &Tensor {
 
 impl Tensor {
     pub fn save_safetensors<P: AsRef<Path>>(&self, name: &str, filename: P) -> Result<()> {
+        check_not_complex(self)?;
         let data = [(name, self.clone())];
         Ok(st::serialize_to_file(data, &None, filename.as_ref())?)
     }
 }
 
+/// Safetensors has no complex dtypes, this avoids panicking when converting the dtype.
+fn check_not_complex(tensor: &Tensor) -> Result<()> {
+    if tensor.dtype().is_complex() {
+        crate::bail!("complex tensors cannot be serialized, use view_as_real first")
+    }
+    Ok(())
+}
+
 fn convert_slice<T: WithDType>(data: &[u8], shape: &[usize], device: &Device) -> Result<Tensor> {
     let size_in_bytes = T::DTYPE.size_in_bytes();
     let elem_count = data.len() / size_in_bytes;
@@ -120,48 +148,47 @@ fn convert_slice<T: WithDType>(data: &[u8], shape: &[usize], device: &Device) ->
     }
 }
 
-fn convert_slice_with_cast<T: Sized + Copy, U: WithDType, F: Fn(T) -> Result<U>>(
+fn convert_<T: WithDType>(view: &st::TensorView<'_>, device: &Device) -> Result<Tensor> {
+    convert_slice::<T>(view.data(), view.shape(), device)
+}
+
+/// The fp8 values have no rust type, the tensors are built directly from the raw bytes.
+fn convert_fp8_slice(
     data: &[u8],
+    dtype: DType,
     shape: &[usize],
     device: &Device,
-    conv: F,
 ) -> Result<Tensor> {
-    let size_in_bytes = std::mem::size_of::<T>();
-    let elem_count = data.len() / size_in_bytes;
-    if (data.as_ptr() as usize) % size_in_bytes == 0 {
-        // SAFETY This is safe because we just checked that this
-        // was correctly aligned.
-        let data: &[T] =
-            unsafe { std::slice::from_raw_parts(data.as_ptr() as *const T, elem_count) };
-        let data = data.iter().map(|t| conv(*t)).collect::<Result<Vec<_>>>()?;
-        Tensor::from_vec(data, shape, device)
-    } else {
-        // XXX: We need to specify `T` here, otherwise the compiler will infer u8 because of the following cast
-        // Making this vector too small to fit a full f16/f32/f64 weights, resulting in out-of-bounds access
-        let mut c: Vec<T> = Vec::with_capacity(elem_count);
-        // SAFETY: We just created c, so the allocated memory is necessarily
-        // contiguous and non overlapping with the view's data.
-        // We're downgrading the `c` pointer from T to u8, which removes alignment
-        // constraints.
-        unsafe {
-            std::ptr::copy_nonoverlapping(data.as_ptr(), c.as_mut_ptr() as *mut u8, data.len());
-            c.set_len(elem_count)
+    let shape = Shape::from_dims(shape);
+    if shape.elem_count() != data.len() {
+        return Err(Error::ShapeMismatch {
+            buffer_size: data.len(),
+            shape,
         }
-        let c = c.into_iter().map(conv).collect::<Result<Vec<_>>>()?;
-        Tensor::from_vec(c, shape, device)
-    }
-}
-
-fn convert_with_cast_<T: Sized + Copy, U: WithDType, F: Fn(T) -> Result<U>>(
-    view: &st::TensorView<'_>,
-    device: &Device,
-    conv: F,
-) -> Result<Tensor> {
-    convert_slice_with_cast::<T, U, F>(view.data(), view.shape(), device, conv)
+        .bt());
+    }
+    let storage = match dtype {
+        DType::F8E4M3 => CpuStorage::F8E4M3(data.to_vec()),
+        DType::F8E5M2 => CpuStorage::F8E5M2(data.to_vec()),
+        dtype => crate::bail!("unexpected fp8 dtype {dtype:?}"),
+    };
+    let tensor = from_storage(Storage::Cpu(storage), shape, BackpropOp::none(), false);
+    tensor.to_device(device)
 }
 
-fn convert_<T: WithDType>(view: &st::TensorView<'_>, device: &Device) -> Result<Tensor> {
-    convert_slice::<T>(view.data(), view.shape(), device)
+pub(crate) fn convert_back_fp8(tensor: &Tensor) -> Result<Vec<u8>> {
+    let tensor = tensor.to_device(&Device::Cpu)?.contiguous()?;
+    let (storage, layout) = tensor.storage_and_layout();
+    let (start, end) = match layout.contiguous_offsets() {
+        Some(offsets) => offsets,
+        None => crate::bail!("fp8 tensor is not contiguous"),
+    };
+    match &*storage {
+        Storage::Cpu(CpuStorage::F8E4M3(data) | CpuStorage::F8E5M2(data)) => {
+            Ok(data[start..end].to_vec())
+        }
+        _ => crate::bail!("unexpected storage for fp8 tensor {:?}", tensor.dtype()),
+    }
 }
 
 fn convert_back_<T: WithDType>(mut vs: Vec<T>) -> Vec<u8> {
@@ -197,8 +224,25 @@ impl Tensor {
     ) -> Result<Self> {
         match dtype {
             DType::U8 => convert_slice::<u8>(data, shape, device),
+            DType::U16 => convert_slice::<u16>(data, shape, device),
             DType::U32 => convert_slice::<u32>(data, shape, device),
+            DType::I8 => convert_slice::<i8>(data, shape, device),
+            DType::I16 => convert_slice::<i16>(data, shape, device),
+            DType::I32 => convert_slice::<i32>(data, shape, device),
             DType::I64 => convert_slice::<i64>(data, shape, device),
+            DType::Bool => convert_slice::<u8>(data, shape, device)?.to_dtype(DType::Bool),
+            DType::F8E4M3 | DType::F8E5M2 => convert_fp8_slice(data, dtype, shape, device),
+            // The real and imaginary parts are interleaved.
+            DType::C64 | DType::C128 => {
+                let mut real_shape = shape.to_vec();
+                real_shape.push(2);
+                let t = if dtype == DType::C64 {
+                    convert_slice::<f32>(data, &real_shape, device)?
+                } else {
+                    convert_slice::<f64>(data, &real_shape, device)?
+                };
+                t.view_as_complex()
+            }
             DType::BF16 => convert_slice::<half::bf16>(data, shape, device),
             DType::F16 => convert_slice::<half::f16>(data, shape, device),
             DType::F32 => convert_slice::<f32>(data, shape, device),
@@ -207,19 +251,39 @@ impl Tensor {
     }
 }
 
+/// Loads a tensor with a dtype that the cuda and metal backends do not support. The tensor is
+/// created on the cpu and, for the other devices, widened to `widened` before being moved.
+fn convert_widened(
+    view: &st::TensorView<'_>,
+    device: &Device,
+    convert: fn(&st::TensorView<'_>, &Device) -> Result<Tensor>,
+    widened: DType,
+) -> Result<Tensor> {
+    let tensor = convert(view, &Device::Cpu)?;
+    if device.is_cpu() {
+        Ok(tensor)
+    } else {
+        tensor.to_dtype(widened)?.to_device(device)
+    }
+}
+
 fn convert(view: &st::TensorView<'_>, device: &Device) -> Result<Tensor> {
     match view.dtype() {
         st::Dtype::U8 => convert_::<u8>(view, device),
-        st::Dtype::U16 => {
-            let conv = |x| Ok(u32::from(x));
-            convert_with_cast_::<u16, u32, _>(view, device, conv)
-        }
+        st::Dtype::U16 => convert_widened(view, device, convert_::<u16>, DType::U32),
         st::Dtype::U32 => convert_::<u32>(view, device),
-        st::Dtype::I32 => {
-            let conv = |x| Ok(i64::from(x));
-            convert_with_cast_::<i32, i64, _>(view, device, conv)
-        }
+        st::Dtype::I8 => convert_widened(view, device, convert_::<i8>, DType::I64),
+        st::Dtype::I16 => convert_widened(view, device, convert_::<i16>, DType::I64),
+        st::Dtype::I32 => convert_widened(view, device, convert_::<i32>, DType::I64),
         st::Dtype::I64 => convert_::<i64>(view, device),
+        st::Dtype::BOOL => {
+            let convert = |view: &st::TensorView<'_>, device: &Device| {
+                convert_::<u8>(view, device)?.to_dtype(DType::Bool)
+            };
+            convert_widened(view, device, convert, DType::U8)
+        }
+        st::Dtype::F8_E4M3 => convert_fp8_slice(view.data(), DType::F8E4M3, view.shape(), device),
+        st::Dtype::F8_E5M2 => convert_fp8_slice(view.data(), DType::F8E5M2, view.shape(), device),
         st::Dtype::BF16 => convert_::<half::bf16>(view, device),
         st::Dtype::F16 => convert_::<half::f16>(view, device),
         st::Dtype::F32 => convert_::<f32>(view, device),
@@ -233,12 +297,25 @@ fn convert_back(tensor: &Tensor) -> Result<Vec<u8>> {
     let tensor = tensor.flatten_all()?;
     match tensor.dtype() {
         DType::U8 => Ok(convert_back_::<u8>(tensor.to_vec1()?)),
+        DType::Bool => Ok(tensor
+            .to_vec1::<bool>()?
+            .into_iter()
+            .map(u8::from)
+            .collect()),
+        DType::U16 => Ok(convert_back_::<u16>(tensor.to_vec1()?)),
         DType::U32 => Ok(convert_back_::<u32>(tensor.to_vec1()?)),
+        DType::I8 => Ok(convert_back_::<i8>(tensor.to_vec1()?)),
+        DType::I16 => Ok(convert_back_::<i16>(tensor.to_vec1()?)),
+        DType::I32 => Ok(convert_back_::<i32>(tensor.to_vec1()?)),
         DType::I64 => Ok(convert_back_::<i64>(tensor.to_vec1()?)),
         DType::F16 => Ok(convert_back_::<half::f16>(tensor.to_vec1()?)),
         DType::BF16 => Ok(convert_back_::<half::bf16>(tensor.to_vec1()?)),
         DType::F32 => Ok(convert_back_::<f32>(tensor.to_vec1()?)),
         DType::F64 => Ok(convert_back_::<f64>(tensor.to_vec1()?)),
+        DType::F8E4M3 | DType::F8E5M2 => convert_back_fp8(&tensor),
+        DType::C64 | DType::C128 => {
+            crate::bail!("complex tensors cannot be serialized, use view_as_real first")
+        }
     }
 }
 
@@ -259,6 +336,9 @@ pub fn save<K: AsRef<str> + Ord + std::fmt::Display, P: AsRef<Path>>(
     tensors: &HashMap<K, Tensor>,
     filename: P,
 ) -> Result<()> {
+    for tensor in tensors.values() {
+        check_not_complex(tensor)?;
+    }
     Ok(st::serialize_to_file(tensors, &None, filename.as_ref())?)
 }
 
@@ -8,7 +8,7 @@ struct ArgSort {
 }
 
 impl ArgSort {
-    fn asort<T: crate::WithDType>(&self, vs: &[T], layout: &crate::Layout) -> Vec<u32> {
+    fn asort<T: crate::NumDType>(&self, vs: &[T], layout: &crate::Layout) -> Vec<u32> {
         #[allow(clippy::uninit_vec)]
         // Safety: indexes are set later in the parallelized section.
         let mut sort_indexes = unsafe {
@@ -52,6 +52,99 @@ impl ArgSort {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct TopK {
+    k: usize,
+    dim: usize,
+    largest: bool,
+}
+
+impl TopK {
+    fn topk<T: crate::NumDType>(&self, vs: &[T], layout: &crate::Layout) -> Result<Vec<u32>> {
+        let vs = match layout.contiguous_offsets() {
+            None => crate::bail!("input has to be contiguous"),
+            Some((o1, o2)) => &vs[o1..o2],
+        };
+        let dims = layout.dims();
+        let dim_size = dims[self.dim];
+        let inner: usize = dims[self.dim + 1..].iter().product();
+        let outer: usize = dims[..self.dim].iter().product();
+        let k = self.k;
+        let mut dst = vec![0u32; outer * k * inner];
+        if dst.is_empty() {
+            return Ok(dst);
+        }
+        let largest = self.largest;
+        dst.par_chunks_exact_mut(k * inner)
+            .zip(vs.par_chunks_exact(dim_size * inner))
+            .for_each(|(dst, vs)| {
+                let mut indexes: Vec<u32> = Vec::with_capacity(dim_size);
+                for i_inner in 0..inner {
+                    indexes.clear();
+                    indexes.extend(0..dim_size as u32);
+                    let v = |i: u32| vs[i as usize * inner + i_inner];
+                    // Values that cannot be compared, e.g. NaN, are put at the end.
+                    #[allow(clippy::eq_op)]
+                    let cmp = |&i: &u32, &j: &u32| {
+                        let (vi, vj) = (v(i), v(j));
+                        let ord = if largest {
+                            vj.partial_cmp(&vi)
+                        } else {
+                            vi.partial_cmp(&vj)
+                        };
+                        let ord = ord.unwrap_or_else(|| (vi != vi).cmp(&(vj != vj)));
+                        ord.then_with(|| i.cmp(&j))
+                    };
+                    if k < dim_size {
+                        indexes.select_nth_unstable_by(k, cmp);
+                        indexes.truncate(k);
+                    }
+                    indexes.sort_unstable_by(cmp);
+                    for (i_k, &index) in indexes.iter().enumerate() {
+                        dst[i_k * inner + i_inner] = index
+                    }
+                }
+            });
+        Ok(dst)
+    }
+}
+
+impl crate::CustomOp1 for TopK {
+    fn name(&self) -> &'static str {
+        "topk"
+    }
+
+    fn cpu_fwd(
+        &self,
+        storage: &crate::CpuStorage,
+        layout: &crate::Layout,
+    ) -> Result<(crate::CpuStorage, crate::Shape)> {
+        let indexes = match storage {
+            crate::CpuStorage::U8(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::U16(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::U32(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::I8(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::I16(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::I32(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::I64(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::Bool(vs) => self.topk(crate::cpu_backend::bool_as_u8(vs), layout)?,
+            crate::CpuStorage::F8E4M3(_) | crate::CpuStorage::F8E5M2(_) => {
+                crate::bail!("topk is not supported for fp8 tensors")
+            }
+            crate::CpuStorage::C64(_) | crate::CpuStorage::C128(_) => {
+                crate::bail!("topk is not supported for complex tensors")
+            }
+            crate::CpuStorage::BF16(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::F16(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::F32(vs) => self.topk(vs, layout)?,
+            crate::CpuStorage::F64(vs) => self.topk(vs, layout)?,
+        };
+        let mut dims = layout.dims().to_vec();
+        dims[self.dim] = self.k;
+        Ok((crate::CpuStorage::U32(indexes), dims.into()))
+    }
+}
+
 #[cfg(feature = "cuda")]
 mod cuda {
     use super::*;
@@ -107,8 +200,19 @@ impl crate::CustomOp1 for ArgSort {
     ) -> Result<(crate::CpuStorage, crate::Shape)> {
         let sort_indexes = match storage {
             crate::CpuStorage::U8(vs) => self.asort(vs, layout),
+            crate::CpuStorage::U16(vs) => self.asort(vs, layout),
             crate::CpuStorage::U32(vs) => self.asort(vs, layout),
+            crate::CpuStorage::I8(vs) => self.asort(vs, layout),
+            crate::CpuStorage::I16(vs) => self.asort(vs, layout),
+            crate::CpuStorage::I32(vs) => self.asort(vs, layout),
             crate::CpuStorage::I64(vs) => self.asort(vs, layout),
+            crate::CpuStorage::Bool(vs) => self.asort(crate::cpu_backend::bool_as_u8(vs), layout),
+            crate::CpuStorage::F8E4M3(_) | crate::CpuStorage::F8E5M2(_) => {
+                crate::bail!("argsort is not supported for fp8 tensors")
+            }
+            crate::CpuStorage::C64(_) | crate::CpuStorage::C128(_) => {
+                crate::bail!("argsort is not supported for complex tensors")
+            }
             crate::CpuStorage::BF16(vs) => self.asort(vs, layout),
             crate::CpuStorage::F16(vs) => self.asort(vs, layout),
             crate::CpuStorage::F32(vs) => self.asort(vs, layout),
@@ -154,6 +258,7 @@ impl crate::CustomOp1 for ArgSort {
                     DType::U8 => "asort_asc_u8",
                     DType::U32 => "asort_asc_u32",
                     DType::I64 => "asort_asc_i64",
+                    dtype => crate::bail!("Metal argsort {dtype:?} not implemented"),
                 }
             } else {
                 match storage.dtype() {
@@ -164,6 +269,7 @@ impl crate::CustomOp1 for ArgSort {
                     DType::U8 => "asort_desc_u8",
                     DType::U32 => "asort_desc_u32",
                     DType::I64 => "asort_desc_i64",
+                    dtype => crate::bail!("Metal argsort {dtype:?} not implemented"),
                 }
             }
         };
@@ -241,4 +347,117 @@ impl Tensor {
         let sorted = self.gather(&asort, crate::D::Minus1)?;
         Ok((sorted, asort))
     }
+
+    /// Returns the indices that sort the tensor along dimension `dim`, as a `u32` tensor.
+    ///
+    /// If `descending` is `true`, the largest elements come first. Values that cannot be compared
+    /// such as NaN are put at the end. With `stable` set, equal elements keep their relative order.
+    /// The cpu sort is always stable, stable sorts are not supported on the other devices.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let t = Tensor::new(&[[3f32, 1.], [1., 5.], [2., 0.]], &Device::Cpu)?;
+    /// let indexes = t.argsort(0, false, true)?;
+    /// assert_eq!(indexes.to_vec2::<u32>()?, &[[1, 2], [2, 0], [0, 1]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn argsort<D: crate::shape::Dim>(
+        &self,
+        dim: D,
+        descending: bool,
+        stable: bool,
+    ) -> Result<Tensor> {
+        let dim = dim.to_index(self.shape(), "argsort")?;
+        let xs = self.contiguous()?;
+        if xs.device().is_cpu() {
+            // Sorting all the elements with topk breaks ties with the lowest index first so the
+            // sort is stable.
+            let k = xs.dim(dim)?;
+            let largest = descending;
+            xs.apply_op1_no_bwd(&TopK { k, dim, largest })
+        } else if stable {
+            crate::bail!("argsort: stable sorts are only supported on the cpu")
+        } else {
+            let last = xs.rank() - 1;
+            xs.transpose(dim, last)?
+                .contiguous()?
+                .arg_sort_last_dim(!descending)?
+                .transpose(dim, last)?
+                .contiguous()
+        }
+    }
+
+    /// Sorts the tensor along dimension `dim`, returns the sorted tensor together with the
+    /// sorting indexes, see [`Tensor::argsort`] for the meaning of `descending` and `stable`.
+    ///
+    /// The gradient of the sorted values is scattered back to the positions of the input
+    /// elements.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let t = Tensor::new(&[[3f32, 1., 2.], [1., 5., 1.]], &Device::Cpu)?;
+    /// let (sorted, indexes) = t.sort(1, true, true)?;
+    /// assert_eq!(sorted.to_vec2::<f32>()?, &[[3., 2., 1.], [5., 1., 1.]]);
+    /// assert_eq!(indexes.to_vec2::<u32>()?, &[[0, 2, 1], [1, 0, 2]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn sort<D: crate::shape::Dim>(
+        &self,
+        dim: D,
+        descending: bool,
+        stable: bool,
+    ) -> Result<(Tensor, Tensor)> {
+        let dim = dim.to_index(self.shape(), "sort")?;
+        let xs = self.contiguous()?;
+        let indexes = xs.argsort(dim, descending, stable)?;
+        let sorted = xs.gather(&indexes, dim)?;
+        Ok((sorted, indexes))
+    }
+
+    /// Returns the `k` largest elements of the tensor along dimension `dim`, together with their
+    /// indexes as a `u32` tensor. If `largest` is `false`, the `k` smallest elements are returned
+    /// instead.
+    ///
+    /// The returned elements are sorted, in descending order when `largest` is `true` and in
+    /// ascending order otherwise. Ties are broken by returning the lowest index first. The
+    /// gradient flows back through the returned values.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let t = Tensor::new(&[[3f32, 1., 4., 1., 5.], [9., 2., 6., 5., 3.]], &Device::Cpu)?;
+    /// let (values, indexes) = t.topk(2, 1, true)?;
+    /// assert_eq!(values.to_vec2::<f32>()?, &[[5., 4.], [9., 6.]]);
+    /// assert_eq!(indexes.to_vec2::<u32>()?, &[[4, 2], [0, 2]]);
+    /// let (values, indexes) = t.topk(1, 0, false)?;
+    /// assert_eq!(values.to_vec2::<f32>()?, &[[3., 1., 4., 1., 3.]]);
+    /// assert_eq!(indexes.to_vec2::<u32>()?, &[[0, 0, 0, 0, 1]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn topk<D: crate::shape::Dim>(
+        &self,
+        k: usize,
+        dim: D,
+        largest: bool,
+    ) -> Result<(Tensor, Tensor)> {
+        let dim = dim.to_index(self.shape(), "topk")?;
+        let dim_size = self.dim(dim)?;
+        if k > dim_size {
+            crate::bail!("topk: k ({k}) is larger than the size of dim {dim} ({dim_size})")
+        }
+        let xs = self.contiguous()?;
+        let indexes = if xs.device().is_cpu() {
+            // No need for a backward pass here, the gradient goes through the gather below.
+            xs.apply_op1_no_bwd(&TopK { k, dim, largest })?
+        } else {
+            let last = xs.rank() - 1;
+            xs.transpose(dim, last)?
+                .contiguous()?
+                .arg_sort_last_dim(!largest)?
+                .narrow(last, 0, k)?
+                .transpose(dim, last)?
+                .contiguous()?
+        };
+        let values = xs.gather(&indexes, dim)?;
+        Ok((values, indexes))
+    }
 }
@@ -1,7 +1,7 @@
 //! Tensors are N-dimensional matrixes of elements using a single data type.
 #![allow(clippy::redundant_closure_call)]
 use crate::backend::{BackendDevice, BackendStorage};
-use crate::op::{BackpropOp, BinaryOp, CmpOp, Op, ReduceOp, UnaryOp};
+use crate::op::{BackpropOp, BinaryOp, BitwiseOp, CmpOp, Op, ReduceOp, ScanOp, UnaryOp};
 use crate::scalar::TensorOrScalar;
 use crate::shape::{Dim, Dims};
 use crate::{bail, storage::Storage, DType, Device, Error, Layout, Result, Shape};
@@ -92,7 +92,8 @@ macro_rules! unary_op {
 }
 
 macro_rules! binary_op {
-    ($fn_name:ident, $op_name:ident) => {
+    ($(#[$attr:meta])* $fn_name:ident, $op_name:ident) => {
+        $(#[$attr])*
         pub fn $fn_name(&self, rhs: &Self) -> Result<Self> {
             let shape = self.same_shape_binary_op(rhs, stringify!($fn_name))?;
             if shape.elem_count() == 0 {
@@ -110,7 +111,8 @@ macro_rules! binary_op {
 }
 
 macro_rules! binary_op_scalar {
-    ($fn_name:ident, $op_name:ident) => {
+    ($(#[$attr:meta])* $fn_name:ident, $op_name:ident) => {
+        $(#[$attr])*
         pub fn $fn_name<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
             let rhs = match rhs.to_tensor_scalar()? {
                 crate::scalar::TensorScalar::Tensor(rhs) => rhs,
@@ -176,6 +178,32 @@ pub(crate) fn from_storage<S: Into<Shape>>(
     Tensor(Arc::new(tensor_))
 }
 
+/// The `(start, len)` input windows used by adaptive pooling, output `i` covers
+/// `[floor(i * in_size / out_size), ceil((i + 1) * in_size / out_size))`.
+fn adaptive_windows(in_size: usize, out_size: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..out_size).map(move |i| {
+        let start = i * in_size / out_size;
+        let end = ((i + 1) * in_size).div_ceil(out_size);
+        (start, end - start)
+    })
+}
+
+/// A `(in_size, out_size)` matrix averaging the adaptive pooling windows.
+fn adaptive_avg_weights(
+    in_size: usize,
+    out_size: usize,
+    dtype: DType,
+    device: &Device,
+) -> Result<Tensor> {
+    let mut weights = vec![0f32; in_size * out_size];
+    for (i, (start, len)) in adaptive_windows(in_size, out_size).enumerate() {
+        for j in start..start + len {
+            weights[j * out_size + i] = 1. / len as f32;
+        }
+    }
+    Tensor::from_vec(weights, (in_size, out_size), device)?.to_dtype(dtype)
+}
+
 impl Tensor {
     pub(crate) fn ones_impl<S: Into<Shape>>(
         shape: S,
@@ -413,7 +441,7 @@ impl Tensor {
     /// assert_eq!(a.to_vec1::<f64>()?, &[2., 3., 4.]);
     /// # Ok::<(), candle_core::Error>(())
     /// ```
-    pub fn arange<D: crate::WithDType>(start: D, end: D, device: &Device) -> Result<Self> {
+    pub fn arange<D: crate::NumDType>(start: D, end: D, device: &Device) -> Result<Self> {
         Self::arange_step(start, end, D::one(), device)
     }
 
@@ -426,7 +454,7 @@ impl Tensor {
     /// assert_eq!(a.to_vec1::<f64>()?, &[2.0, 2.5, 3.0, 3.5]);
     /// # Ok::<(), candle_core::Error>(())
     /// ```
-    pub fn arange_step<D: crate::WithDType>(
+    pub fn arange_step<D: crate::NumDType>(
         start: D,
         end: D,
         step: D,
@@ -544,21 +572,65 @@ impl Tensor {
     binary_op!(add, Add);
     binary_op!(mul, Mul);
     binary_op!(sub, Sub);
-    binary_op!(div, Div);
+    binary_op!(
+        /// Element-wise division, for integer dtypes the result is truncated toward zero so
+        /// `-7 / 2 = -3`, see `floor_div` for rounding toward negative infinity.
+        div,
+        Div
+    );
     binary_op_scalar!(maximum, Maximum);
     binary_op_scalar!(minimum, Minimum);
+    binary_op_scalar!(
+        /// Element-wise division rounded toward negative infinity, `-7 // 2 = -4`, this matches
+        /// the Python `//` operator. Integer divisions by zero return 0.
+        ///
+        /// ```rust
+        /// use candle_core::{Tensor, Device};
+        /// let a = Tensor::new(&[7i64, -7, 7, -7], &Device::Cpu)?;
+        /// let b = Tensor::new(&[2i64, 2, -2, -2], &Device::Cpu)?;
+        /// assert_eq!(a.floor_div(&b)?.to_vec1::<i64>()?, &[3, -4, -4, 3]);
+        /// assert_eq!(a.remainder(&b)?.to_vec1::<i64>()?, &[1, 1, -1, -1]);
+        /// assert_eq!(a.fmod(&b)?.to_vec1::<i64>()?, &[1, -1, 1, -1]);
+        /// # Ok::<(), candle_core::Error>(())
+        /// ```
+        floor_div,
+        FloorDiv
+    );
+    binary_op_scalar!(
+        /// Element-wise remainder of `floor_div`, the result has the sign of the divisor. This
+        /// matches the Python `%` operator. Integer divisions by zero return 0.
+        remainder,
+        Remainder
+    );
+    binary_op_scalar!(
+        /// Element-wise remainder of the division truncated toward zero, the result has the sign
+        /// of the dividend. This matches the C `fmod` function and the Rust `%` operator. Integer
+        /// divisions by zero return 0.
+        fmod,
+        Fmod
+    );
     broadcast_binary_op!(broadcast_add, add);
     broadcast_binary_op!(broadcast_mul, mul);
     broadcast_binary_op!(broadcast_sub, sub);
     broadcast_binary_op!(broadcast_div, div);
     broadcast_binary_op!(broadcast_maximum, maximum);
     broadcast_binary_op!(broadcast_minimum, minimum);
+    broadcast_binary_op!(broadcast_floor_div, floor_div);
+    broadcast_binary_op!(broadcast_remainder, remainder);
+    broadcast_binary_op!(broadcast_fmod, fmod);
     broadcast_binary_op!(broadcast_eq, eq);
     broadcast_binary_op!(broadcast_ne, ne);
     broadcast_binary_op!(broadcast_lt, lt);
     broadcast_binary_op!(broadcast_le, le);
     broadcast_binary_op!(broadcast_gt, gt);
     broadcast_binary_op!(broadcast_ge, ge);
+    broadcast_binary_op!(broadcast_bitwise_and, bitwise_and);
+    broadcast_binary_op!(broadcast_bitwise_or, bitwise_or);
+    broadcast_binary_op!(broadcast_bitwise_xor, bitwise_xor);
+    broadcast_binary_op!(broadcast_shl, shl);
+    broadcast_binary_op!(broadcast_shr, shr);
+    broadcast_binary_op!(broadcast_logical_and, logical_and);
+    broadcast_binary_op!(broadcast_logical_or, logical_or);
 
     unary_op!(recip, Recip);
     unary_op!(neg, Neg);
@@ -567,7 +639,6 @@ impl Tensor {
     unary_op!(sin, Sin);
     unary_op!(cos, Cos);
     unary_op!(tanh, Tanh);
-    unary_op!(abs, Abs);
     unary_op!(sqr, Sqr);
     unary_op!(sqrt, Sqrt);
     unary_op!(gelu, Gelu);
@@ -580,6 +651,20 @@ impl Tensor {
     unary_op!(round, Round);
     unary_op!(sign, Sign);
 
+    /// Element-wise absolute value, the modulus is returned as a real tensor for complex inputs.
+    pub fn abs(&self) -> Result<Self> {
+        if self.dtype().is_complex() {
+            return self.complex_abs();
+        }
+        let shape = self.shape();
+        if shape.elem_count() == 0 {
+            return Ok(self.clone());
+        }
+        let storage = self.storage().unary_impl::<crate::op::Abs>(self.layout())?;
+        let op = BackpropOp::new1(self, |s| Op::Unary(s, UnaryOp::Abs));
+        Ok(from_storage(storage, shape.clone(), op, false))
+    }
+
     /// Round element of the input tensor to the nearest integer.
     ///
     /// If the number of decimals is negative, it specifies the number of positions to the left of
@@ -846,7 +931,34 @@ impl Tensor {
         }
     }
 
-    fn squeeze_dims(self, dims: &[usize]) -> Result<Self> {
+    /// Returns a view with all the slices of `size` elements along `dim`, starting every `step`
+    /// elements. The slices are indexed by `dim` and their elements by an additional trailing
+    /// dimension, the values are not copied.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::arange(0u32, 7, &Device::Cpu)?;
+    /// let b = a.unfold(0, 3, 2)?;
+    /// assert_eq!(b.to_vec2::<u32>()?, &[[0, 1, 2], [2, 3, 4], [4, 5, 6]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn unfold<D: Dim>(&self, dim: D, size: usize, step: usize) -> Result<Self> {
+        let dim = dim.to_index(self.shape(), "unfold")?;
+        let layout = self.layout().unfold(dim, size, step)?;
+        let op = BackpropOp::new1(self, |t| Op::Unfold(t, dim, size, step));
+        let tensor_ = Tensor_ {
+            id: TensorId::new(),
+            storage: self.storage.clone(),
+            layout,
+            op,
+            is_variable: false,
+            dtype: self.dtype,
+            device: self.device.clone(),
+        };
+        Ok(Tensor(Arc::new(tensor_)))
+    }
+
+    pub(crate) fn squeeze_dims(self, dims: &[usize]) -> Result<Self> {
         match dims {
             [] => Ok(self),
             [i] => self.squeeze(*i),
@@ -996,18 +1108,44 @@ impl Tensor {
         self.sum_impl(mean_dims, false)? * scale
     }
 
-    /// Returns the unbiased variance over the selected dimension.
-    pub fn var_keepdim<D: Dim>(&self, dim: D) -> Result<Self> {
-        let dim = dim.to_index(self.shape(), "var")?;
-        let mean = self.mean_keepdim(dim)?;
+    /// Returns the unbiased variance over the selected dimensions.
+    pub fn var_keepdim<D: Dims>(&self, var_dims: D) -> Result<Self> {
+        self.var_with_correction_keepdim(var_dims, 1)
+    }
+
+    /// Returns the unbiased variance over the selected dimensions.
+    pub fn var<D: Dims>(&self, var_dims: D) -> Result<Self> {
+        self.var_with_correction(var_dims, 1)
+    }
+
+    /// Returns the variance over the selected dimensions, the sum of the squared deviations is
+    /// divided by the number of elements minus `correction`. A `correction` of 1 gives the
+    /// unbiased variance and a `correction` of 0 the population variance.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1f32, 2.], [3., 6.]], &Device::Cpu)?;
+    /// let v = a.var_with_correction_keepdim((0, 1), 0)?;
+    /// assert_eq!(v.to_vec2::<f32>()?, &[[3.5]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn var_with_correction_keepdim<D: Dims>(
+        &self,
+        var_dims: D,
+        correction: usize,
+    ) -> Result<Self> {
+        let var_dims = var_dims.to_indexes(self.shape(), "var")?;
+        let reduced_dim: usize = var_dims.iter().map(|i| self.dims()[*i]).product();
+        let mean = self.mean_keepdim(var_dims.as_slice())?;
         let squares = self.broadcast_sub(&mean)?.sqr()?;
-        squares.sum_impl(dim, true)? / (self.dim(dim)? - 1) as f64
+        squares.sum_impl(var_dims, true)? / (reduced_dim as f64 - correction as f64).max(0.)
     }
 
-    /// Returns the unbiased variance over the selected dimension.
-    pub fn var<D: Dim>(&self, dim: D) -> Result<Self> {
-        let dim = dim.to_index(self.shape(), "var")?;
-        self.var_keepdim(dim)?.squeeze(dim)
+    /// Similar to `var_with_correction_keepdim` but the reduced dimensions are squeezed.
+    pub fn var_with_correction<D: Dims>(&self, var_dims: D, correction: usize) -> Result<Self> {
+        let var_dims = var_dims.to_indexes(self.shape(), "var")?;
+        self.var_with_correction_keepdim(var_dims.as_slice(), correction)?
+            .squeeze_dims(&var_dims)
     }
 
     /// Gathers the maximum value across the selected dimension. The resulting shape has the same
@@ -1053,7 +1191,8 @@ impl Tensor {
     /// Element-wise comparison between two tensors, e.g. equality, greater than, ... The actual
     /// comparison operation is specified by the `op` argument.
     ///
-    /// The returned tensor has the same shape as the original tensors and uses `u8` elements.
+    /// The returned tensor has the same shape as the original tensors and uses `bool` elements,
+    /// the cuda and metal backends have no boolean storage and return `u8` elements instead.
     pub fn cmp<T: TensorOrScalar>(&self, rhs: T, op: CmpOp) -> Result<Self> {
         let rhs = match rhs.to_tensor_scalar()? {
             crate::scalar::TensorScalar::Tensor(rhs) => rhs,
@@ -1080,30 +1219,130 @@ impl Tensor {
         self.cmp(rhs, CmpOp::Ne)
     }
 
-    /// Element-wise comparison with lower-than, the returned tensor uses value 1 where `self <
-    /// rhs` and 0 otherwise.
+    /// Element-wise comparison with lower-than, the returned tensor is true where `self < rhs`
+    /// and false otherwise.
     pub fn lt<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
         self.cmp(rhs, CmpOp::Lt)
     }
 
-    /// Element-wise comparison with greater-than, the returned tensor uses value 1 where `self >
-    /// rhs` and 0 otherwise.
+    /// Element-wise comparison with greater-than, the returned tensor is true where `self > rhs`
+    /// and false otherwise.
     pub fn gt<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
         self.cmp(rhs, CmpOp::Gt)
     }
 
-    /// Element-wise comparison with greater-equal, the returned tensor uses value 1 where `self >=
-    /// rhs` and 0 otherwise.
+    /// Element-wise comparison with greater-equal, the returned tensor is true where `self >=
+    /// rhs` and false otherwise.
     pub fn ge<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
         self.cmp(rhs, CmpOp::Ge)
     }
 
-    /// Element-wise comparison with lower-equal, the returned tensor uses value 1 where `self <=
-    /// rhs` and 0 otherwise.
+    /// Element-wise comparison with lower-equal, the returned tensor is true where `self <=
+    /// rhs` and false otherwise.
     pub fn le<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
         self.cmp(rhs, CmpOp::Le)
     }
 
+    /// Element-wise bitwise operation between two integer tensors. The actual operation is
+    /// specified by the `op` argument.
+    pub fn bitwise<T: TensorOrScalar>(&self, rhs: T, op: BitwiseOp) -> Result<Self> {
+        let rhs = match rhs.to_tensor_scalar()? {
+            crate::scalar::TensorScalar::Tensor(rhs) => rhs,
+            crate::scalar::TensorScalar::Scalar(rhs) => rhs
+                .to_dtype(self.dtype())?
+                .to_device(self.device())?
+                .broadcast_as(self.shape())?,
+        };
+        let shape = self.same_shape_binary_op(&rhs, op.name())?;
+        let storage = self
+            .storage()
+            .bitwise(op, &rhs.storage(), self.layout(), rhs.layout())?;
+        Ok(from_storage(
+            storage,
+            shape.dims(),
+            BackpropOp::none(),
+            false,
+        ))
+    }
+
+    /// Element-wise bitwise and.
+    pub fn bitwise_and<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
+        self.bitwise(rhs, BitwiseOp::And)
+    }
+
+    /// Element-wise bitwise or.
+    pub fn bitwise_or<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
+        self.bitwise(rhs, BitwiseOp::Or)
+    }
+
+    /// Element-wise bitwise xor.
+    pub fn bitwise_xor<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
+        self.bitwise(rhs, BitwiseOp::Xor)
+    }
+
+    /// Element-wise bitwise not, for `u8` masks `logical_not` should be used instead.
+    pub fn bitwise_not(&self) -> Result<Self> {
+        let all_ones = match self.dtype() {
+            DType::U8 => Tensor::new(u8::MAX, self.device())?,
+            DType::U16 => Tensor::new(u16::MAX, self.device())?,
+            DType::U32 => Tensor::new(u32::MAX, self.device())?,
+            DType::I8 => Tensor::new(-1i8, self.device())?,
+            DType::I16 => Tensor::new(-1i16, self.device())?,
+            DType::I32 => Tensor::new(-1i32, self.device())?,
+            DType::I64 => Tensor::new(-1i64, self.device())?,
+            DType::Bool => Tensor::new(true, self.device())?,
+            dtype => Err(Error::UnsupportedDTypeForOp(dtype, "bitwise-not").bt())?,
+        };
+        self.bitwise_xor(&all_ones.broadcast_as(self.shape())?)
+    }
+
+    /// Element-wise left shift of the bits of `self` by `rhs`, shifting by the bit width or more
+    /// returns 0.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[1u32, 3, 5], &Device::Cpu)?;
+    /// let a = a.shl(2u32)?;
+    /// assert_eq!(a.to_vec1::<u32>()?, &[4, 12, 20]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn shl<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
+        self.bitwise(rhs, BitwiseOp::Shl)
+    }
+
+    /// Element-wise right shift of the bits of `self` by `rhs`, this is an arithmetic shift for
+    /// `i64` values.
+    pub fn shr<T: TensorOrScalar>(&self, rhs: T) -> Result<Self> {
+        self.bitwise(rhs, BitwiseOp::Shr)
+    }
+
+    /// Element-wise logical and, the values are considered as true when they are non-zero. The
+    /// returned tensor uses `bool` elements.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[0u8, 1, 2, 0], &Device::Cpu)?;
+    /// let b = Tensor::new(&[0u8, 0, 3, 4], &Device::Cpu)?;
+    /// assert_eq!(a.logical_and(&b)?.to_vec1::<bool>()?, &[false, false, true, false]);
+    /// assert_eq!(a.logical_or(&b)?.to_vec1::<bool>()?, &[false, true, true, true]);
+    /// assert_eq!(a.logical_not()?.to_vec1::<bool>()?, &[true, false, false, true]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn logical_and(&self, rhs: &Self) -> Result<Self> {
+        self.ne(0f64)?.bitwise_and(&rhs.ne(0f64)?)
+    }
+
+    /// Element-wise logical or, see `logical_and`.
+    pub fn logical_or(&self, rhs: &Self) -> Result<Self> {
+        self.ne(0f64)?.bitwise_or(&rhs.ne(0f64)?)
+    }
+
+    /// Element-wise logical not, the returned tensor is true where `self` is zero and false
+    /// otherwise.
+    pub fn logical_not(&self) -> Result<Self> {
+        self.eq(0f64)
+    }
+
     /// Clamp the tensor values to be between `min` and `max`.
     pub fn clamp<T1: TensorOrScalar, T2: TensorOrScalar>(&self, min: T1, max: T2) -> Result<Self> {
         self.maximum(min)?.minimum(max)
@@ -1226,6 +1465,110 @@ impl Tensor {
         Ok(from_storage(storage, (n, c, h_out, w_out), op, false))
     }
 
+    /// 1D average pooling over an input tensor with multiple channels.
+    ///
+    /// The input tensor should have three dimensions, `(batch, channels, l)`, the returned
+    /// tensor also has three dimensions, `(batch, channels, l')`. The pooling is performed on
+    /// the last dimension using a kernel of size `sz`. The returned element is the average value
+    /// over the kernel window.
+    pub fn avg_pool1d(&self, sz: usize) -> Result<Self> {
+        self.avg_pool1d_with_stride(sz, sz)
+    }
+
+    /// Same as `avg_pool1d` but with a `stride` that can be set to a value different from the
+    /// kernel size.
+    pub fn avg_pool1d_with_stride(&self, kernel_size: usize, stride: usize) -> Result<Self> {
+        let (_n, _c, _l) = self.dims3()?;
+        self.unsqueeze(2)?
+            .avg_pool2d_with_stride((1, kernel_size), (1, stride))?
+            .squeeze(2)
+    }
+
+    /// 1D max pooling over an input tensor with multiple channels.
+    ///
+    /// The input tensor should have three dimensions, `(batch, channels, l)`, the returned
+    /// tensor also has three dimensions, `(batch, channels, l')`. The pooling is performed on
+    /// the last dimension using a kernel of size `sz`, the returned element is the maximum value
+    /// over the kernel window.
+    pub fn max_pool1d(&self, sz: usize) -> Result<Self> {
+        self.max_pool1d_with_stride(sz, sz)
+    }
+
+    /// Same as `max_pool1d` but with a `stride` that can be set to a value different from the
+    /// kernel size.
+    pub fn max_pool1d_with_stride(&self, kernel_size: usize, stride: usize) -> Result<Self> {
+        let (_n, _c, _l) = self.dims3()?;
+        self.unsqueeze(2)?
+            .max_pool2d_with_stride((1, kernel_size), (1, stride))?
+            .squeeze(2)
+    }
+
+    /// 1D adaptive average pooling over an input tensor with multiple channels.
+    ///
+    /// The input tensor should have three dimensions, `(batch, channels, l)`, the returned tensor
+    /// has dimensions `(batch, channels, out_size)`. Output element `i` is the average over the
+    /// input window `[floor(i * l / out_size), ceil((i + 1) * l / out_size))`, the same windows
+    /// as PyTorch's `AdaptiveAvgPool1d`.
+    pub fn adaptive_avg_pool1d(&self, out_size: usize) -> Result<Self> {
+        let (_n, _c, l) = self.dims3()?;
+        if out_size == 0 {
+            bail!("adaptive_avg_pool1d: output size should be positive")
+        }
+        if l % out_size == 0 {
+            return self.avg_pool1d(l / out_size);
+        }
+        let weights = adaptive_avg_weights(l, out_size, self.dtype(), self.device())?;
+        self.broadcast_matmul(&weights)
+    }
+
+    /// 2D adaptive average pooling over an input tensor with multiple channels.
+    ///
+    /// The input tensor should have four dimensions, `(batch, channels, h, w)`, the returned
+    /// tensor has dimensions `(batch, channels, out_h, out_w)`. The pooling windows are computed
+    /// independently on each spatial dimension as in `adaptive_avg_pool1d`.
+    pub fn adaptive_avg_pool2d<T: crate::ToUsize2>(&self, out_size: T) -> Result<Self> {
+        let (out_h, out_w) = out_size.to_usize2();
+        let (_n, _c, h, w) = self.dims4()?;
+        if out_h == 0 || out_w == 0 {
+            bail!("adaptive_avg_pool2d: output size should be positive")
+        }
+        if h % out_h == 0 && w % out_w == 0 {
+            return self.avg_pool2d((h / out_h, w / out_w));
+        }
+        let weights_w = adaptive_avg_weights(w, out_w, self.dtype(), self.device())?;
+        let weights_h = adaptive_avg_weights(h, out_h, self.dtype(), self.device())?;
+        self.broadcast_matmul(&weights_w)?
+            .transpose(2, 3)?
+            .broadcast_matmul(&weights_h)?
+            .transpose(2, 3)
+    }
+
+    /// 2D adaptive max pooling over an input tensor with multiple channels.
+    ///
+    /// The input tensor should have four dimensions, `(batch, channels, h, w)`, the returned
+    /// tensor has dimensions `(batch, channels, out_h, out_w)`. The returned element is the
+    /// maximum value over the same windows as the ones used by `adaptive_avg_pool2d`.
+    pub fn adaptive_max_pool2d<T: crate::ToUsize2>(&self, out_size: T) -> Result<Self> {
+        let (out_h, out_w) = out_size.to_usize2();
+        let (_n, _c, h, w) = self.dims4()?;
+        if out_h == 0 || out_w == 0 {
+            bail!("adaptive_max_pool2d: output size should be positive")
+        }
+        if h % out_h == 0 && w % out_w == 0 {
+            return self.max_pool2d((h / out_h, w / out_w));
+        }
+        // The max over a rectangular window is the max over its rows of the max over its
+        // columns so the two spatial dimensions can be reduced one after the other.
+        let adaptive_max = |xs: &Self, dim: usize, in_size: usize, out_size: usize| {
+            let xs = adaptive_windows(in_size, out_size)
+                .map(|(start, len)| xs.narrow(dim, start, len)?.max_keepdim(dim))
+                .collect::<Result<Vec<_>>>()?;
+            Self::cat(&xs, dim)
+        };
+        let xs = adaptive_max(self, 3, w, out_w)?;
+        adaptive_max(&xs, 2, h, out_h)
+    }
+
     /// Returns the matrix-multiplication of the input tensor with the other provided tensor.
     ///
     /// # Arguments
@@ -1349,8 +1692,13 @@ impl Tensor {
         self.index_select(ids, 0)
     }
 
-    pub fn scatter_add<D: Dim>(&self, indexes: &Self, source: &Self, dim: D) -> Result<Self> {
-        let dim = dim.to_index(self.shape(), "scatter-add")?;
+    fn scatter_checks(
+        &self,
+        indexes: &Self,
+        source: &Self,
+        dim: usize,
+        op: &'static str,
+    ) -> Result<()> {
         let source_dims = source.dims();
         let self_dims = self.dims();
         let mismatch = if source_dims.len() != self_dims.len() {
@@ -1367,7 +1715,7 @@ impl Tensor {
         };
         if mismatch {
             Err(Error::ShapeMismatchBinaryOp {
-                op: "scatter-add (self, src)",
+                op,
                 lhs: self.shape().clone(),
                 rhs: source.shape().clone(),
             }
@@ -1375,12 +1723,18 @@ impl Tensor {
         }
         if indexes.dims() != source.dims() {
             Err(Error::ShapeMismatchBinaryOp {
-                op: "scatter-add (indexes, src)",
+                op,
                 lhs: indexes.shape().clone(),
                 rhs: source.shape().clone(),
             }
             .bt())?
         }
+        Ok(())
+    }
+
+    pub fn scatter_add<D: Dim>(&self, indexes: &Self, source: &Self, dim: D) -> Result<Self> {
+        let dim = dim.to_index(self.shape(), "scatter-add")?;
+        self.scatter_checks(indexes, source, dim, "scatter-add")?;
         let storage = self.storage().scatter_add(
             self.layout(),
             &indexes.storage(),
@@ -1395,6 +1749,73 @@ impl Tensor {
         Ok(from_storage(storage, self.shape(), op, false))
     }
 
+    /// Writes the values from `source` at the positions given by `indexes` along dimension `dim`,
+    /// the other elements are copied from `self`.
+    ///
+    /// `indexes` and `source` should have the same shape, this shape should also match the shape
+    /// of `self` except on dimension `dim`. When multiple values are written to the same
+    /// position, the result is unspecified.
+    pub fn scatter<D: Dim>(&self, indexes: &Self, source: &Self, dim: D) -> Result<Self> {
+        let dim = dim.to_index(self.shape(), "scatter")?;
+        self.scatter_checks(indexes, source, dim, "scatter")?;
+        let storage = self.storage().scatter(
+            self.layout(),
+            &indexes.storage(),
+            indexes.layout(),
+            &source.storage(),
+            source.layout(),
+            dim,
+        )?;
+        let op = BackpropOp::new3(self, indexes, source, |t1, t2, t3| {
+            Op::Scatter(t1, t2, t3, dim)
+        });
+        Ok(from_storage(storage, self.shape(), op, false))
+    }
+
+    /// Combines the values from `source` at the positions given by `indexes` along dimension
+    /// `dim` using the `reduce` operation, the shape constraints are the same as for `scatter`.
+    ///
+    /// When `include_self` is true, the values from `self` are part of the reduction, otherwise
+    /// the positions that receive at least one value only use the values from `source`. The
+    /// positions that receive no value are copied from `self` in both cases.
+    ///
+    /// ```rust
+    /// use candle_core::{op::ScatterReduceOp, Tensor, Device};
+    /// let t = Tensor::new(&[1f32, 2., 3.], &Device::Cpu)?;
+    /// let indexes = Tensor::new(&[0u32, 0, 2, 0], &Device::Cpu)?;
+    /// let source = Tensor::new(&[4f32, 5., 6., 7.], &Device::Cpu)?;
+    /// let r = t.scatter_reduce(&indexes, &source, 0, ScatterReduceOp::Amax, true)?;
+    /// assert_eq!(r.to_vec1::<f32>()?, &[7., 2., 6.]);
+    /// let r = t.scatter_reduce(&indexes, &source, 0, ScatterReduceOp::Mean, false)?;
+    /// assert_eq!(r.to_vec1::<f32>()?, &[16. / 3., 2., 6.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn scatter_reduce<D: Dim>(
+        &self,
+        indexes: &Self,
+        source: &Self,
+        dim: D,
+        reduce: crate::op::ScatterReduceOp,
+        include_self: bool,
+    ) -> Result<Self> {
+        let dim = dim.to_index(self.shape(), "scatter-reduce")?;
+        self.scatter_checks(indexes, source, dim, "scatter-reduce")?;
+        let storage = self.storage().scatter_reduce(
+            self.layout(),
+            &indexes.storage(),
+            indexes.layout(),
+            &source.storage(),
+            source.layout(),
+            dim,
+            reduce,
+            include_self,
+        )?;
+        let op = BackpropOp::new3(self, indexes, source, |t1, t2, t3| {
+            Op::ScatterReduce(t1, t2, t3, dim, reduce, include_self)
+        });
+        Ok(from_storage(storage, self.shape(), op, false))
+    }
+
     /// Embeds the values of the `src` tensor into the `self` tensor on the specified dimension.
     pub fn slice_scatter<D: Dim>(&self, src: &Self, dim: D, start: usize) -> Result<Self> {
         let dim = dim.to_index(self.shape(), "slice-scatter")?;
@@ -1515,6 +1936,36 @@ impl Tensor {
         Ok(from_storage(storage, self.shape(), op, false))
     }
 
+    /// Combines the slices of `source` along dimension `dim` into the slices of `self` given by
+    /// the 1D tensor `indexes`, using the `reduce` operation. This is the `index_add` counterpart
+    /// of `scatter_reduce`, see there for the meaning of `include_self`.
+    pub fn index_reduce<D: Dim>(
+        &self,
+        indexes: &Self,
+        source: &Self,
+        dim: D,
+        reduce: crate::op::ScatterReduceOp,
+        include_self: bool,
+    ) -> Result<Self> {
+        let dim = dim.to_index(self.shape(), "index-reduce")?;
+        let indexes_len = indexes.dims1()?;
+        if source.rank() != self.rank() || source.dims()[dim] != indexes_len {
+            Err(Error::ShapeMismatchBinaryOp {
+                op: "index-reduce (ids, source))",
+                lhs: indexes.shape().clone(),
+                rhs: source.shape().clone(),
+            }
+            .bt())?
+        }
+        let mut dims = vec![1; source.rank()];
+        dims[dim] = indexes_len;
+        let indexes = indexes
+            .reshape(dims)?
+            .broadcast_as(source.shape())?
+            .contiguous()?;
+        self.scatter_reduce(&indexes, &source.contiguous()?, dim, reduce, include_self)
+    }
+
     /// Gather values across the target dimension.
     ///
     /// # Arguments
@@ -2476,16 +2927,40 @@ impl Tensor {
         t1.eq(&t2)?.to_dtype(dtype)
     }
 
+    pub(crate) fn scan<D: Dim>(&self, dim: D, op: ScanOp, reverse: bool) -> Result<Self> {
+        let dim = dim.to_index(self.shape(), op.name())?;
+        if self.rank() == 0 {
+            return match op {
+                ScanOp::ArgMin | ScanOp::ArgMax => self.zeros_like()?.to_dtype(DType::U32),
+                _ => Ok(self.clone()),
+            };
+        }
+        let storage = self.storage().scan_op(op, self.layout(), dim, reverse)?;
+        let op = match op {
+            ScanOp::ArgMin | ScanOp::ArgMax => BackpropOp::none(),
+            _ => BackpropOp::new1(self, |arg| Op::Scan(arg, op, dim, reverse)),
+        };
+        Ok(from_storage(storage, self.shape(), op, false))
+    }
+
     /// Returns the cumulative sum of elements of the input tensor summed over the specified
     /// dimension.
     ///
-    /// This operation is most efficient when dim is the last dimension of the tensor.
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[[1u32, 2, 3], [4, 5, 6]], &Device::Cpu)?;
+    /// let a = a.cumsum(1)?;
+    /// assert_eq!(a.to_vec2::<u32>()?, &[[1, 3, 6], [4, 9, 15]]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
     pub fn cumsum<D: Dim>(&self, dim: D) -> Result<Self> {
         let dim = dim.to_index(self.shape(), "cumsum")?;
         let rank = self.rank();
-        if rank == 0 {
-            return Ok(self.clone());
+        if rank == 0 || self.device().is_cpu() {
+            return self.scan(dim, ScanOp::Sum, false);
         }
+        // The scan kernels are only available on cpu, use a matmul with a triangular matrix on
+        // the other devices.
         let n_axis = self.dim(dim)?;
         let triu = Tensor::triu2(n_axis, self.dtype(), self.device())?;
         if rank == 1 {
@@ -2498,6 +2973,67 @@ impl Tensor {
         }
     }
 
+    /// Returns the cumulative product of elements of the input tensor over the specified
+    /// dimension.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[1f32, 2., 3., 4.], &Device::Cpu)?;
+    /// let a = a.cumprod(0)?;
+    /// assert_eq!(a.to_vec1::<f32>()?, &[1., 2., 6., 24.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn cumprod<D: Dim>(&self, dim: D) -> Result<Self> {
+        self.scan(dim, ScanOp::Prod, false)
+    }
+
+    /// Returns the cumulative maximum of the elements of the input tensor over the specified
+    /// dimension, together with the `u32` index of each maximum. On ties, the last index is used.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[1f32, 3., 2., 3., 5.], &Device::Cpu)?;
+    /// let (values, indexes) = a.cummax(0)?;
+    /// assert_eq!(values.to_vec1::<f32>()?, &[1., 3., 3., 3., 5.]);
+    /// assert_eq!(indexes.to_vec1::<u32>()?, &[0, 1, 1, 3, 4]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn cummax<D: Dim>(&self, dim: D) -> Result<(Self, Self)> {
+        let dim = dim.to_index(self.shape(), "cummax")?;
+        let values = self.scan(dim, ScanOp::Max, false)?;
+        let indexes = self.scan(dim, ScanOp::ArgMax, false)?;
+        Ok((values, indexes))
+    }
+
+    /// Returns the cumulative minimum of the elements of the input tensor over the specified
+    /// dimension, together with the `u32` index of each minimum. On ties, the last index is used.
+    pub fn cummin<D: Dim>(&self, dim: D) -> Result<(Self, Self)> {
+        let dim = dim.to_index(self.shape(), "cummin")?;
+        let values = self.scan(dim, ScanOp::Min, false)?;
+        let indexes = self.scan(dim, ScanOp::ArgMin, false)?;
+        Ok((values, indexes))
+    }
+
+    /// Returns the logarithm of the cumulative sum of the exponentials of the elements of the
+    /// input tensor over the specified dimension, this is computed in a numerically stable way.
+    ///
+    /// ```rust
+    /// use candle_core::{Tensor, Device};
+    /// let a = Tensor::new(&[0f64, 0., 0., 0.], &Device::Cpu)?;
+    /// let a = a.logcumsumexp(0)?.exp()?.round_to(4)?;
+    /// assert_eq!(a.to_vec1::<f64>()?, &[1., 2., 3., 4.]);
+    /// # Ok::<(), candle_core::Error>(())
+    /// ```
+    pub fn logcumsumexp<D: Dim>(&self, dim: D) -> Result<Self> {
+        if !self.dtype().is_float() {
+            bail!(
+                "logcumsumexp is only supported for float dtypes, got {:?}",
+                self.dtype()
+            )
+        }
+        self.scan(dim, ScanOp::LogSumExp, false)
+    }
+
     /// Returns a copy of `self` where the values within `ranges` have been replaced with the
     /// content of `src`.
     pub fn slice_assign<D: std::ops::RangeBounds<usize>>(
@@ -2650,6 +3186,80 @@ macro_rules! bin_trait {
     };
 }
 
+macro_rules! bitwise_trait {
+    ($trait:ident, $fn1:ident, $fn2:ident) => {
+        impl<B: std::borrow::Borrow<Tensor>> std::ops::$trait<B> for Tensor {
+            type Output = Result<Tensor>;
+
+            fn $fn1(self, rhs: B) -> Self::Output {
+                Tensor::$fn2(&self, rhs.borrow())
+            }
+        }
+
+        impl<B: std::borrow::Borrow<Tensor>> std::ops::$trait<B> for &Tensor {
+            type Output = Result<Tensor>;
+
+            fn $fn1(self, rhs: B) -> Self::Output {
+                Tensor::$fn2(self, rhs.borrow())
+            }
+        }
+
+        impl<B: std::borrow::Borrow<Tensor>> std::ops::$trait<Tensor> for Result<B> {
+            type Output = Result<Tensor>;
+
+            fn $fn1(self, rhs: Tensor) -> Self::Output {
+                Tensor::$fn2(self?.borrow(), &rhs)
+            }
+        }
+
+        impl<B: std::borrow::Borrow<Tensor>> std::ops::$trait<&Tensor> for Result<B> {
+            type Output = Result<Tensor>;
+
+            fn $fn1(self, rhs: &Tensor) -> Self::Output {
+                Tensor::$fn2(self?.borrow(), rhs)
+            }
+        }
+
+        impl<B: std::borrow::Borrow<Tensor>> std::ops::$trait<Result<B>> for Tensor {
+            type Output = Result<Tensor>;
+
+            fn $fn1(self, rhs: Result<B>) -> Self::Output {
+                Tensor::$fn2(&self, rhs?.borrow())
+            }
+        }
+
+        impl<B: std::borrow::Borrow<Tensor>> std::ops::$trait<Result<B>> for &Tensor {
+            type Output = Result<Tensor>;
+
+            fn $fn1(self, rhs: Result<B>) -> Self::Output {
+                Tensor::$fn2(self, rhs?.borrow())
+            }
+        }
+    };
+}
+
+bitwise_trait!(BitAnd, bitand, bitwise_and);
+bitwise_trait!(BitOr, bitor, bitwise_or);
+bitwise_trait!(BitXor, bitxor, bitwise_xor);
+bitwise_trait!(Shl, shl, shl);
+bitwise_trait!(Shr, shr, shr);
+
+impl std::ops::Not for Tensor {
+    type Output = Result<Tensor>;
+
+    fn not(self) -> Self::Output {
+        self.bitwise_not()
+    }
+}
+
+impl std::ops::Not for &Tensor {
+    type Output = Result<Tensor>;
+
+    fn not(self) -> Self::Output {
+        self.bitwise_not()
+    }
+}
+
 bin_trait!(Add, add, |_| 1., |v| v);
 bin_trait!(Sub, sub, |_| 1., |v: f64| -v);
 bin_trait!(Mul, mul, |v| v, |_| 0.);
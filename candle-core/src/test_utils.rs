@@ -1,4 +1,4 @@
-use crate::{Result, Tensor};
+use crate::{DType, Result, Tensor, Var};
 
 #[macro_export]
 macro_rules! test_device {
@@ -24,6 +24,42 @@ macro_rules! test_device {
     };
 }
 
+/// The largest absolute difference between the elements of `a` and `b`.
+pub fn max_diff(a: &Tensor, b: &Tensor) -> Result<f64> {
+    let diff = (a - b)?.abs()?.flatten_all()?.max(0)?;
+    diff.to_dtype(DType::F64)?.to_vec0::<f64>()
+}
+
+/// Compares the gradient computed via backprop for the scalar function `f` with a finite
+/// differences approximation, `x` has to be a `f64` tensor.
+pub fn check_grad<F: Fn(&Tensor) -> Result<Tensor>>(
+    x: &Tensor,
+    f: F,
+    tolerance: f64,
+) -> Result<()> {
+    let var = Var::from_tensor(x)?;
+    let grads = f(var.as_tensor())?.backward()?;
+    let grad = grads.get(&var).expect("no grad for x");
+    let xs = x.flatten_all()?.to_vec1::<f64>()?;
+    let eps = 1e-6;
+    let mut numerical = Vec::with_capacity(xs.len());
+    for i in 0..xs.len() {
+        let mut plus = xs.clone();
+        plus[i] += eps;
+        let mut minus = xs.clone();
+        minus[i] -= eps;
+        let plus = f(&Tensor::from_vec(plus, x.shape(), x.device())?)?.to_vec0::<f64>()?;
+        let minus = f(&Tensor::from_vec(minus, x.shape(), x.device())?)?.to_vec0::<f64>()?;
+        numerical.push((plus - minus) / (2. * eps))
+    }
+    let numerical = Tensor::from_vec(numerical, x.shape(), x.device())?;
+    assert!(
+        max_diff(grad, &numerical)? < tolerance,
+        "grad {grad}\nnumerical {numerical}"
+    );
+    Ok(())
+}
+
 pub fn to_vec0_round(t: &Tensor, digits: i32) -> Result<f32> {
     let b = 10f32.powi(digits);
     let t = t.to_vec0::<f32>()?;
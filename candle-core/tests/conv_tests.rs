@@ -1,4 +1,5 @@
 use anyhow::Result;
+use candle_core::test_utils::max_diff;
 use candle_core::{test_device, test_utils, Device, IndexOp, Tensor};
 
 /* This test is based on the following script.
@@ -830,6 +831,200 @@ fn conv2d_grad(dev: &Device) -> Result<()> {
     Ok(())
 }
 
+fn arange_sin(dims: &[usize]) -> Result<Tensor> {
+    let n = dims.iter().product::<usize>();
+    let t = Tensor::arange(0f32, n as f32, &Device::Cpu)?;
+    Ok((t.sqr()? * 0.37)?.sin()?.reshape(dims)?)
+}
+
+// Reference implementation of conv3d as a sum of 2d convolutions over the depth offsets.
+fn conv3d_ref(
+    t: &Tensor,
+    w: &Tensor,
+    padding: usize,
+    stride: usize,
+    dilation: usize,
+) -> Result<Tensor> {
+    let (_, _, i_d, _, _) = t.dims5()?;
+    let (_, _, k_d, _, _) = w.dims5()?;
+    let t = t.pad_with_zeros(2, padding, padding)?;
+    let out_d = (i_d + 2 * padding - dilation * (k_d - 1) - 1) / stride + 1;
+    let mut out = vec![];
+    for d in 0..out_d {
+        let mut acc: Option<Tensor> = None;
+        for kd in 0..k_d {
+            let slice = t.i((.., .., d * stride + kd * dilation))?;
+            let w = w.i((.., .., kd))?.contiguous()?;
+            let c = slice.conv2d(&w, padding, stride, dilation, 1)?;
+            acc = Some(match acc {
+                None => c,
+                Some(acc) => (acc + c)?,
+            })
+        }
+        out.push(acc.unwrap())
+    }
+    Ok(Tensor::stack(&out, 2)?)
+}
+
+#[test]
+fn conv3d() -> Result<()> {
+    let t = arange_sin(&[2, 4, 5, 6, 7])?;
+    let w = arange_sin(&[3, 4, 2, 3, 3])?.cos()?;
+    let res = t.conv3d(&w, 0, 1, 1, 1)?;
+    assert_eq!(res.dims(), [2, 3, 4, 4, 5]);
+    assert!(max_diff(&res, &conv3d_ref(&t, &w, 0, 1, 1)?)? < 1e-5);
+
+    for (padding, stride, dilation) in [(1, 2, 1), (2, 1, 2), (1, 2, 2)] {
+        let res = t.conv3d(&w, padding, stride, dilation, 1)?;
+        let expected = conv3d_ref(&t, &w, padding, stride, dilation)?;
+        assert_eq!(res.dims(), expected.dims());
+        assert!(max_diff(&res, &expected)? < 1e-5);
+    }
+
+    // Grouped convolution.
+    let w = arange_sin(&[4, 2, 1, 2, 2])?;
+    let res = t.conv3d(&w, 0, 1, 1, 2)?;
+    let expected = Tensor::cat(
+        &[
+            t.narrow(1, 0, 2)?.conv3d(&w.narrow(0, 0, 2)?, 0, 1, 1, 1)?,
+            t.narrow(1, 2, 2)?.conv3d(&w.narrow(0, 2, 2)?, 0, 1, 1, 1)?,
+        ],
+        1,
+    )?;
+    assert!(max_diff(&res, &expected)? < 1e-5);
+    assert!(t.conv3d(&w, 0, 1, 1, 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn conv_transpose3d() -> Result<()> {
+    // conv_transpose3d is the adjoint of conv3d: <conv3d(x, w), y> = <x, conv_transpose3d(y, w)>
+    let x = arange_sin(&[2, 4, 6, 6, 6])?;
+    let w = arange_sin(&[3, 4, 3, 3, 3])?.cos()?;
+    for (padding, stride, dilation) in [(0, 1, 1), (1, 2, 1), (1, 1, 2)] {
+        let res = x.conv3d(&w, padding, stride, dilation, 1)?;
+        let y = arange_sin(res.dims())?.exp()?;
+        let out_d = (res.dim(2)? - 1) * stride + 2 * dilation + 1 - 2 * padding;
+        let output_padding = 6 - out_d;
+        let res_t = y.conv_transpose3d(&w, padding, output_padding, stride, dilation)?;
+        assert_eq!(res_t.dims(), x.dims());
+        let lhs = (res * &y)?.sum_all()?.to_vec0::<f32>()?;
+        let rhs = (res_t * &x)?.sum_all()?.to_vec0::<f32>()?;
+        assert!((lhs - rhs).abs() < 1e-3, "{lhs} {rhs}");
+    }
+
+    // With a single depth plane, this matches conv_transpose2d.
+    let x = arange_sin(&[1, 4, 1, 5, 5])?;
+    let w = arange_sin(&[4, 2, 1, 3, 3])?;
+    let res = x.conv_transpose3d(&w, 0, 1, 2, 1)?;
+    let expected = x.squeeze(2)?.conv_transpose2d(&w.squeeze(2)?, 0, 1, 2, 1)?;
+    assert_eq!(res.dims(), [1, 2, 2, 12, 12]);
+    assert!(max_diff(&res.i((.., .., 0))?, &expected)? < 1e-5);
+    // The second plane only comes from the output padding.
+    assert_eq!(res.i((.., .., 1))?.abs()?.sum_all()?.to_vec0::<f32>()?, 0.);
+    Ok(())
+}
+
+#[test]
+fn conv3d_grad() -> Result<()> {
+    use candle_core::Var;
+    // The reference gradients use a batch size of one so that the transposed kernels used in
+    // the conv3d backward pass are contiguous. The last cases have a depth that is not covered by
+    // the same number of trailing elements as the height and width.
+    let t1 = Var::from_tensor(&arange_sin(&[1, 2, 5, 4, 4])?)?;
+    let t2 = Var::from_tensor(&arange_sin(&[1, 2, 6, 5, 5])?)?;
+    let w = Var::from_tensor(&arange_sin(&[3, 2, 2, 3, 3])?.cos()?)?;
+    for (t, padding, stride, dilation) in [
+        (&t1, 0, 1, 1),
+        (&t1, 1, 2, 1),
+        (&t1, 1, 1, 2),
+        (&t2, 0, 2, 1),
+        (&t2, 0, 3, 1),
+        (&t2, 1, 3, 1),
+    ] {
+        let loss = t
+            .conv3d(&w, padding, stride, dilation, 1)?
+            .sqr()?
+            .sum_all()?;
+        let grads = loss.backward()?;
+        let loss_ref = conv3d_ref(t, &w, padding, stride, dilation)?
+            .sqr()?
+            .sum_all()?;
+        let grads_ref = loss_ref.backward()?;
+        let grad_t = grads.get(t).unwrap();
+        let grad_w = grads.get(&w).unwrap();
+        assert!(max_diff(grad_t, grads_ref.get(t).unwrap())? < 1e-3);
+        assert!(max_diff(grad_w, grads_ref.get(&w).unwrap())? < 1e-3);
+    }
+
+    // The gradients of conv_transpose3d with a single depth plane match the 2d version.
+    let t = Var::from_tensor(&arange_sin(&[1, 4, 1, 5, 5])?)?;
+    let w = Var::from_tensor(&arange_sin(&[4, 2, 1, 3, 3])?)?;
+    let loss = t.conv_transpose3d(&w, 0, 0, 1, 1)?.sqr()?.sum_all()?;
+    let grads = loss.backward()?;
+    let t2 = t.squeeze(2)?;
+    let w2 = w.squeeze(2)?;
+    let loss2 = t2.conv_transpose2d(&w2, 0, 0, 1, 1)?.sqr()?.sum_all()?;
+    let grads2 = loss2.backward()?;
+    let grad_t = grads.get(&t).unwrap().squeeze(2)?;
+    let grad_w = grads.get(&w).unwrap().squeeze(2)?;
+    assert!(max_diff(&grad_t, &grads2.get(&t).unwrap().squeeze(2)?)? < 1e-3);
+    assert!(max_diff(&grad_w, &grads2.get(&w).unwrap().squeeze(2)?)? < 1e-3);
+    Ok(())
+}
+
+fn unfold_fold2d(dev: &Device) -> Result<()> {
+    let t = Tensor::arange(0f32, 16., dev)?.reshape((1, 1, 4, 4))?;
+    let cols = t.unfold2d(2, 1, 0, 2)?;
+    assert_eq!(
+        cols.i(0)?.to_vec2::<f32>()?,
+        [
+            [0., 2., 8., 10.],
+            [1., 3., 9., 11.],
+            [4., 6., 12., 14.],
+            [5., 7., 13., 15.]
+        ]
+    );
+    // Non-overlapping blocks are put back in place.
+    assert_eq!(
+        cols.fold2d(4, 2, 1, 0, 2)?
+            .flatten_all()?
+            .to_vec1::<f32>()?,
+        t.flatten_all()?.to_vec1::<f32>()?
+    );
+    let t = Tensor::arange(0f32, 9., dev)?.reshape((1, 1, 3, 3))?;
+    let cols = t.unfold2d(2, 2, 0, 1)?;
+    assert_eq!(cols.i(0)?.to_vec2::<f32>()?, [[0.], [2.], [6.], [8.]]);
+
+    // Overlapping blocks are summed up.
+    let ones = Tensor::ones((1, 4, 4), candle_core::DType::F32, dev)?;
+    let folded = ones.fold2d(3, 2, 1, 0, 1)?;
+    assert_eq!(
+        folded.i((0, 0))?.to_vec2::<f32>()?,
+        [[1., 2., 1.], [2., 4., 2.], [1., 2., 1.]]
+    );
+    let ones = Tensor::ones((1, 4, 9), candle_core::DType::F32, dev)?;
+    let folded = ones.fold2d(2, 2, 1, 1, 1)?;
+    assert_eq!(folded.i((0, 0))?.to_vec2::<f32>()?, [[4., 4.], [4., 4.]]);
+    assert!(ones.fold2d(3, 2, 1, 1, 1).is_err());
+
+    // A convolution is a matmul on the unfolded input.
+    let t = Tensor::arange(0f32, 2. * 5. * 6., dev)?.reshape((1, 2, 5, 6))?;
+    let w = Tensor::arange(0f32, 3. * 2. * 9., dev)?.reshape((3, 2, 3, 3))?;
+    let res = t.conv2d(&w, 1, 2, 1, 1)?;
+    let cols = t.unfold2d(3, 1, 1, 2)?;
+    let (_, _, h, w_) = res.dims4()?;
+    let res2 = w
+        .reshape((3, 18))?
+        .matmul(&cols.i(0)?)?
+        .reshape((1, 3, h, w_))?;
+    assert_eq!(
+        res.flatten_all()?.to_vec1::<f32>()?,
+        res2.flatten_all()?.to_vec1::<f32>()?
+    );
+    Ok(())
+}
+
 test_device!(conv1d, conv1d_cpu, conv1d_gpu, conv1d_metal);
 test_device!(
     conv1d_small,
@@ -856,6 +1051,12 @@ test_device!(
     conv2d_smaller_gpu,
     conv2d_smaller_metal
 );
+test_device!(
+    unfold_fold2d,
+    unfold_fold2d_cpu,
+    unfold_fold2d_gpu,
+    unfold_fold2d_metal
+);
 test_device!(
     conv2d_grad,
     conv2d_grad_cpu,
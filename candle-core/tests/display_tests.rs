@@ -82,3 +82,12 @@ Tensor[[2, 1, 1, 100, 100], f32]"#;
     assert_eq!(&t, expected);
     Ok(())
 }
+
+#[test]
+fn display_int_and_bool() -> Result<()> {
+    let t = Tensor::new(&[-3i8, 0, 12], &Cpu)?;
+    assert_eq!(format!("{t}"), "[-3,  0, 12]\nTensor[[3], i8]");
+    let t = t.to_dtype(DType::Bool)?;
+    assert_eq!(format!("{t}"), "[ true, false,  true]\nTensor[[3], bool]");
+    Ok(())
+}
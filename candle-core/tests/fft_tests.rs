@@ -0,0 +1,153 @@
+use anyhow::Result;
+use candle_core::test_utils::{check_grad, max_diff};
+use candle_core::{DType, Device, Tensor, D};
+
+/// A direct O(n^2) DFT of a complex tensor of shape (n, 2).
+fn naive_dft(xs: &Tensor) -> Result<Tensor> {
+    let xs = xs.to_vec2::<f64>()?;
+    let n = xs.len();
+    let mut ys = Vec::with_capacity(n);
+    for k in 0..n {
+        let (mut re, mut im) = (0., 0.);
+        for (j, x) in xs.iter().enumerate() {
+            let angle = -2. * std::f64::consts::PI * (j * k) as f64 / n as f64;
+            re += x[0] * angle.cos() - x[1] * angle.sin();
+            im += x[0] * angle.sin() + x[1] * angle.cos();
+        }
+        ys.push(re);
+        ys.push(im);
+    }
+    Ok(Tensor::from_vec(ys, (n, 2), &Device::Cpu)?)
+}
+
+fn signal(dims: &[usize]) -> Result<Tensor> {
+    let n = dims.iter().product::<usize>();
+    let ws = Tensor::arange(0., n as f64, &Device::Cpu)?;
+    Ok((ws.sqr()? * 0.37)?.sin()?.reshape(dims)?)
+}
+
+#[test]
+fn fft() -> Result<()> {
+    for n in [1, 2, 5, 8, 12, 17, 30] {
+        let xs = signal(&[n, 2])?;
+        let ys = xs.fft(0)?;
+        assert!(max_diff(&ys, &naive_dft(&xs)?)? < 1e-10, "{n}");
+        assert!(max_diff(&ys.ifft(0)?, &xs)? < 1e-12, "{n}");
+    }
+
+    // Transforms along the non-last dimension of a batch.
+    let xs = signal(&[3, 6, 4, 2])?;
+    let ys = xs.fft(1)?;
+    for (i, j) in [(0, 0), (2, 3)] {
+        let line = xs
+            .narrow(0, i, 1)?
+            .narrow(2, j, 1)?
+            .squeeze(2)?
+            .squeeze(0)?;
+        let expected = naive_dft(&line)?;
+        let line = ys
+            .narrow(0, i, 1)?
+            .narrow(2, j, 1)?
+            .squeeze(2)?
+            .squeeze(0)?;
+        assert!(max_diff(&line, &expected)? < 1e-10);
+    }
+    let ys = xs.to_dtype(DType::F32)?.fft(D::Minus1)?;
+    assert_eq!(ys.dims(), &[3, 6, 4, 2]);
+    assert!(max_diff(&ys.to_dtype(DType::F64)?, &xs.fft(2)?)? < 1e-5);
+
+    assert!(xs.fft(3).is_err());
+    assert!(signal(&[4, 3])?.fft(0).is_err());
+    Ok(())
+}
+
+#[test]
+fn rfft() -> Result<()> {
+    let xs = Tensor::new(&[1f32, 2., 3., 4.], &Device::Cpu)?;
+    let ys = xs.rfft(0)?;
+    assert_eq!(ys.to_vec2::<f32>()?, &[[10., 0.], [-2., 2.], [-2., 0.]]);
+
+    for n in [1, 6, 7, 16] {
+        let xs = signal(&[2, n])?;
+        let ys = xs.rfft(D::Minus1)?;
+        assert_eq!(ys.dims(), &[2, n / 2 + 1, 2]);
+        let complex = Tensor::stack(&[&xs, &xs.zeros_like()?], D::Minus1)?;
+        let full = complex.fft(1)?.narrow(1, 0, n / 2 + 1)?;
+        assert!(max_diff(&ys, &full)? < 1e-10);
+        assert!(max_diff(&ys.irfft(1, n)?, &xs)? < 1e-12);
+    }
+
+    // irfft truncates or zero pads the input spectrum.
+    let ys = Tensor::new(&[[4f64, 0.], [1., 0.], [7., 7.]], &Device::Cpu)?;
+    let xs = ys.irfft(0, 2)?;
+    assert_eq!(xs.to_vec1::<f64>()?, &[2.5, 1.5]);
+    let xs = ys.narrow(0, 0, 1)?.irfft(0, 3)?;
+    assert!(max_diff(&xs, &Tensor::new(&[4f64 / 3.; 3], &Device::Cpu)?)? < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn stft() -> Result<()> {
+    let n_fft = 8;
+    let window = Tensor::arange(0., n_fft as f64, &Device::Cpu)?;
+    let window = ((window * (2. * std::f64::consts::PI / n_fft as f64))?.cos()? * -0.5)?;
+    let window = (window + 0.5)?;
+    let xs = signal(&[2, 40])?;
+
+    let spec = xs.stft(n_fft, 2, &window, true)?;
+    assert_eq!(spec.dims(), &[2, 5, 21, 2]);
+    let ys = spec.istft(n_fft, 2, &window, true, Some(40))?;
+    assert!(max_diff(&ys, &xs)? < 1e-12);
+
+    // Without centering, the 10th frame starts at sample 9 * hop_length. The window is shifted
+    // so that the first sample can be recovered.
+    let window = (window + 0.1)?;
+    let spec = xs.stft(n_fft, 3, &window, false)?;
+    assert_eq!(spec.dims(), &[2, 5, 11, 2]);
+    let frame = xs.narrow(1, 27, n_fft)?.broadcast_mul(&window)?.rfft(1)?;
+    assert!(max_diff(&spec.narrow(2, 9, 1)?.squeeze(2)?, &frame)? < 1e-12);
+    let ys = spec.istft(n_fft, 3, &window, false, None)?;
+    assert_eq!(ys.dims(), &[2, 38]);
+    assert!(max_diff(&ys, &xs.narrow(1, 0, 38)?)? < 1e-12);
+
+    assert!(xs.stft(n_fft, 2, &window.narrow(0, 0, 4)?, true).is_err());
+    assert!(xs.narrow(1, 0, 4)?.stft(n_fft, 2, &window, true).is_err());
+    Ok(())
+}
+
+#[test]
+fn fft_grad() -> Result<()> {
+    let xs = signal(&[6, 2])?;
+    let ws = signal(&[6, 2])?.cos()?;
+    check_grad(&xs, |xs| (xs.fft(0)? * &ws)?.sum_all(), 1e-6)?;
+    check_grad(&xs, |xs| (xs.ifft(0)? * &ws)?.sum_all(), 1e-6)?;
+
+    for n in [6, 7] {
+        let xs = signal(&[2, n])?;
+        let ws = signal(&[2, n / 2 + 1, 2])?.cos()?;
+        check_grad(&xs, |xs| (xs.rfft(1)? * &ws)?.sum_all(), 1e-6)?;
+        // The spectrum gets truncated or zero padded to the output length.
+        let ws = signal(&[2, 10])?.cos()?;
+        let spec = xs.rfft(1)?;
+        check_grad(&spec, |s| (s.irfft(1, 10)? * &ws)?.sum_all(), 1e-6)?;
+        let ws = signal(&[2, 3])?.cos()?;
+        check_grad(&spec, |s| (s.irfft(1, 3)? * &ws)?.sum_all(), 1e-6)?;
+    }
+
+    let window = (signal(&[8])? + 2.)?;
+    let xs = signal(&[1, 20])?;
+    let ws = signal(&[1, 5, 11, 2])?.cos()?;
+    check_grad(
+        &xs,
+        |xs| (xs.stft(8, 2, &window, true)? * &ws)?.sum_all(),
+        1e-6,
+    )?;
+    let spec = xs.stft(8, 2, &window, true)?;
+    let ws = signal(&[1, 20])?.cos()?;
+    check_grad(
+        &spec,
+        |s| (s.istft(8, 2, &window, true, Some(20))? * &ws)?.sum_all(),
+        1e-6,
+    )?;
+    Ok(())
+}
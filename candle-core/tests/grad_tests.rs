@@ -505,6 +505,468 @@ fn binary_grad(device: &Device) -> Result<()> {
     Ok(())
 }
 
+fn topk_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[[3f32, 1., 4., 1., 5.], [2., 7., 1., 8., 2.]], device)?;
+    let x = x.as_tensor();
+    let (values, _indexes) = x.topk(2, 1, true)?;
+    let y = values.sqr()?.sum_all()?;
+    let grads = y.backward()?;
+    let grad_x = grads.get(x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.to_vec2::<f32>()?,
+        [[0., 0., 8., 0., 10.], [0., 14., 0., 16., 0.]]
+    );
+    Ok(())
+}
+
+fn sort_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[[3f32, 1., 4.], [1., 5., 9.]], device)?;
+    let (sorted, _indexes) = x.sort(0, true, false)?;
+    let w = Tensor::new(&[[1f32, 2., 3.], [4., 5., 6.]], device)?;
+    let grads = sorted.mul(&w)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[1., 5., 6.], [4., 2., 3.]]);
+    Ok(())
+}
+
+fn masking_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[[1f32, -2., 3.], [-4., 5., -6.]], device)?;
+    let mask = x.ge(0f64)?;
+    let y = x.masked_select(&mask)?.sqr()?.sum_all()?;
+    let grads = y.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[2., 0., 6.], [0., 10., 0.]]);
+
+    let y = x.masked_fill(&mask, 0.)?.sum_all()?;
+    let grads = y.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[0., 1., 0.], [1., 0., 1.]]);
+
+    let source = Var::new(&[1f32, 2., 3., 4.], device)?;
+    let y = x.masked_scatter(&mask, &source)?;
+    let y = y
+        .broadcast_mul(&Tensor::new(&[1f32, 2., 3.], device)?)?
+        .sum_all()?;
+    let grads = y.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    let grad_source = grads.get(&source).context("no grad for source")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[0., 2., 0.], [1., 0., 3.]]);
+    assert_eq!(grad_source.to_vec1::<f32>()?, [1., 3., 2., 0.]);
+    Ok(())
+}
+
+#[test]
+fn div_grad() -> Result<()> {
+    let device = &Device::Cpu;
+    let x = Var::new(&[7f32, -7., 5.5], device)?;
+    let y = Var::new(&[2f32, 2., -2.], device)?;
+    let z = x.remainder(y.as_tensor())?;
+    let grads = z.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    let grad_y = grads.get(&y).context("no grad for y")?;
+    assert_eq!(z.to_vec1::<f32>()?, [1., 1., -0.5]);
+    assert_eq!(grad_x.to_vec1::<f32>()?, [1., 1., 1.]);
+    assert_eq!(grad_y.to_vec1::<f32>()?, [-3., 4., 3.]);
+
+    let z = x.fmod(y.as_tensor())?;
+    let grads = z.backward()?;
+    let grad_y = grads.get(&y).context("no grad for y")?;
+    assert_eq!(z.to_vec1::<f32>()?, [1., -1., 1.5]);
+    assert_eq!(grad_y.to_vec1::<f32>()?, [-3., 3., 2.]);
+
+    let z = x.floor_div(y.as_tensor())?;
+    let grads = z.backward()?;
+    assert!(grads.get(&x).is_none());
+    Ok(())
+}
+
+#[test]
+fn scan_grad() -> Result<()> {
+    let device = &Device::Cpu;
+    let grad = |xs: &[f32], f: &dyn Fn(&Tensor) -> candle_core::Result<Tensor>| {
+        let xs = Var::new(xs, device)?;
+        let grads = f(&xs)?.sum_all()?.backward()?;
+        let grad = grads.get(&xs).context("no grad for xs")?;
+        Ok::<_, anyhow::Error>(test_utils::to_vec1_round(grad, 4)?)
+    };
+    assert_eq!(grad(&[1., 2., 3.], &|xs| xs.cumsum(0))?, [3., 2., 1.]);
+    assert_eq!(grad(&[1., 2., 3.], &|xs| xs.cumprod(0))?, [9., 4., 2.]);
+    assert_eq!(
+        grad(&[2., 0., 3., 0., 5.], &|xs| xs.cumprod(0))?,
+        [1., 8., 0., 0., 0.]
+    );
+    assert_eq!(
+        grad(&[1., 3., 2., 3., 5.], &|xs| Ok(xs.cummax(0)?.0))?,
+        [1., 2., 0., 1., 1.]
+    );
+    assert_eq!(
+        grad(&[3., 1., 2., 1., 5.], &|xs| Ok(xs.cummin(0)?.0))?,
+        [1., 2., 0., 2., 0.]
+    );
+    assert_eq!(
+        grad(&[0., 0., 0.], &|xs| xs.logcumsumexp(0))?,
+        [1.8333, 0.8333, 0.3333]
+    );
+    // Negative gradients and large values.
+    assert_eq!(
+        grad(&[100., 100., 0.], &|xs| xs
+            .logcumsumexp(0)?
+            .mul(&Tensor::new(&[1f32, -1., 2.], device)?))?,
+        [1.5, 0.5, 0.]
+    );
+    // Non-contiguous input.
+    let xs = Var::new(&[[1f32, 2.], [3., 4.]], device)?;
+    let ys = xs.t()?.cumsum(1)?.sqr()?.sum_all()?;
+    let grads = ys.backward()?;
+    let dx = grads.get(&xs).context("no grad for xs")?;
+    assert_eq!(dx.to_vec2::<f32>()?, [[10., 16.], [8., 12.]]);
+    Ok(())
+}
+
+#[test]
+fn reduce_grad() -> Result<()> {
+    use candle_core::reduce::NormOrd;
+    let device = &Device::Cpu;
+    let grad = |xs: &[f32], f: &dyn Fn(&Tensor) -> candle_core::Result<Tensor>| {
+        let xs = Var::new(xs, device)?;
+        let grads = f(&xs)?.sum_all()?.backward()?;
+        let grad = grads.get(&xs).context("no grad for xs")?;
+        Ok::<_, anyhow::Error>(test_utils::to_vec1_round(grad, 4)?)
+    };
+    assert_eq!(grad(&[2., 3., 4.], &|xs| xs.prod(0))?, [12., 8., 6.]);
+    assert_eq!(grad(&[2., 0., 3.], &|xs| xs.prod(0))?, [0., 6., 0.]);
+    assert_eq!(grad(&[0., 0., 3.], &|xs| xs.prod(0))?, [0., 0., 0.]);
+    assert_eq!(
+        grad(&[1., 3.], &|xs| xs.std_with_correction(0, 0))?,
+        [-0.5, 0.5]
+    );
+    assert_eq!(grad(&[3., 4.], &|xs| xs.norm(NormOrd::L2, 0))?, [0.6, 0.8]);
+    assert_eq!(grad(&[-1., 2.], &|xs| xs.norm(NormOrd::L1, 0))?, [-1., 1.]);
+    assert_eq!(
+        grad(&[1., -3., 2.], &|xs| xs.norm(NormOrd::Inf, 0))?,
+        [0., -1., 0.]
+    );
+
+    let xs = Var::new(&[[1f32, 2.], [3., 4.]], device)?;
+    let grads = xs.prod(0)?.sum_all()?.backward()?;
+    let dx = grads.get(&xs).context("no grad for xs")?;
+    assert_eq!(dx.to_vec2::<f32>()?, [[3., 4.], [1., 2.]]);
+    let xs = Var::new(&[[1f32, 5.], [5., 2.]], device)?;
+    let grads = xs.amax((0, 1))?.backward()?;
+    let dx = grads.get(&xs).context("no grad for xs")?;
+    assert_eq!(dx.to_vec2::<f32>()?, [[0., 1.], [1., 0.]]);
+    Ok(())
+}
+
+#[test]
+fn interpolate_grad() -> Result<()> {
+    use candle_core::interpolate::{InterpolateConfig, InterpolationMode};
+    let device = &Device::Cpu;
+    let xs = Var::new(&[[[1f32, 2., 3.]]], device)?;
+    let config = InterpolateConfig {
+        mode: InterpolationMode::Linear,
+        ..Default::default()
+    };
+    let ys = xs.interpolate1d_with_config(5, &config)?;
+    let ys = ys.broadcast_mul(&Tensor::new(&[1f32, 2., 3., 4., 5.], device)?)?;
+    let grads = ys.sum_all()?.backward()?;
+    let dx = grads.get(&xs).context("no grad for xs")?;
+    assert_eq!(test_utils::to_vec3_round(dx, 4)?, [[[2.2, 5.4, 7.4]]]);
+
+    // The weights for each output position sum to one.
+    let xs = Var::new(&[[[[1f32, 2.], [3., 4.]]]], device)?;
+    let ys = xs.upsample_bicubic2d(4, 5, false)?;
+    let grads = ys.sum_all()?.backward()?;
+    let dx = grads.get(&xs).context("no grad for xs")?;
+    assert_eq!(test_utils::to_vec0_round(&dx.sum_all()?, 4)?, 20.);
+    Ok(())
+}
+
+#[test]
+fn grid_sample_grad() -> Result<()> {
+    use candle_core::grid_sample::{GridSampleMode, GridSamplePaddingMode};
+    let device = &Device::Cpu;
+    let xs = Var::new(&[[[[1f32, 2., 3.]]]], device)?;
+    let grid = Var::new(&[[[[0f32, 0.], [-1. / 3., 0.]]]], device)?;
+    let ys = xs.grid_sample(
+        &grid,
+        GridSampleMode::Bilinear,
+        GridSamplePaddingMode::Zeros,
+        false,
+    )?;
+    assert_eq!(
+        test_utils::to_vec3_round(&ys.flatten_to(1)?, 4)?,
+        [[[2., 1.5]]]
+    );
+    let grads = ys.sum_all()?.backward()?;
+    let dx = grads.get(&xs).context("no grad for xs")?;
+    assert_eq!(
+        test_utils::to_vec1_round(&dx.flatten_all()?, 4)?,
+        [0.5, 1.5, 0.]
+    );
+    let dgrid = grads.get(&grid).context("no grad for grid")?;
+    assert_eq!(
+        test_utils::to_vec2_round(&dgrid.flatten_to(2)?, 4)?,
+        [[1.5, -1.], [1.5, -0.75]]
+    );
+
+    // The gradient flows back to the affine transform through the grid.
+    let theta = Var::new(&[[[1f32, 0., 0.], [0., 1., 0.]]], device)?;
+    let grid = Tensor::affine_grid(&theta, (1, 1, 1, 3), false)?;
+    let ys = xs.grid_sample(
+        &grid,
+        GridSampleMode::Bilinear,
+        GridSamplePaddingMode::Border,
+        false,
+    )?;
+    let grads = ys.sum_all()?.backward()?;
+    let dtheta = grads.get(&theta).context("no grad for theta")?;
+    assert_eq!(dtheta.dims(), [1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn scatter_grad() -> Result<()> {
+    use candle_core::op::ScatterReduceOp::{self, Amax, Amin, Mean, Prod, Sum};
+    let device = &Device::Cpu;
+    let weights = Tensor::new(&[1f32, 2., 3.], device)?;
+    let ids = Tensor::new(&[0u32, 0, 2, 0], device)?;
+    let grads = |src: &[f32], op: Option<ScatterReduceOp>, include_self: bool| {
+        let init = Var::new(&[1f32, 2., 3.], device)?;
+        let src = Var::new(src, device)?;
+        let ys = match op {
+            None => init.scatter(&ids.narrow(0, 1, 2)?, &src, 0)?,
+            Some(op) => init.scatter_reduce(&ids, &src, 0, op, include_self)?,
+        };
+        let grads = (ys * &weights)?.sum_all()?.backward()?;
+        let grad_init = grads.get(&init).context("no grad for init")?;
+        let grad_src = grads.get(&src).context("no grad for src")?;
+        Ok::<_, anyhow::Error>((
+            test_utils::to_vec1_round(grad_init, 4)?,
+            test_utils::to_vec1_round(grad_src, 4)?,
+        ))
+    };
+    let src = [4f32, 5., 6., 7.];
+    assert_eq!(
+        grads(&[8., 9.], None, false)?,
+        (vec![0., 2., 0.], vec![1., 3.])
+    );
+    assert_eq!(
+        grads(&src, Some(Sum), true)?,
+        (vec![1., 2., 3.], vec![1., 1., 3., 1.])
+    );
+    assert_eq!(
+        grads(&src, Some(Sum), false)?,
+        (vec![0., 2., 0.], vec![1., 1., 3., 1.])
+    );
+    assert_eq!(
+        grads(&src, Some(Mean), true)?,
+        (vec![0.25, 2., 1.5], vec![0.25, 0.25, 1.5, 0.25])
+    );
+    assert_eq!(
+        grads(&src, Some(Mean), false)?,
+        (vec![0., 2., 0.], vec![0.3333, 0.3333, 3., 0.3333])
+    );
+    assert_eq!(
+        grads(&src, Some(Amax), true)?,
+        (vec![0., 2., 0.], vec![0., 0., 3., 1.])
+    );
+    assert_eq!(
+        grads(&src, Some(Amin), true)?,
+        (vec![1., 2., 3.], vec![0., 0., 0., 0.])
+    );
+    assert_eq!(
+        grads(&src, Some(Amin), false)?,
+        (vec![0., 2., 0.], vec![1., 0., 3., 0.])
+    );
+    // Ties share the gradient.
+    assert_eq!(
+        grads(&[7., 5., 6., 7.], Some(Amax), true)?,
+        (vec![0., 2., 0.], vec![0.5, 0., 3., 0.5])
+    );
+    assert_eq!(
+        grads(&src, Some(Prod), true)?,
+        (vec![140., 2., 18.], vec![35., 28., 9., 20.])
+    );
+    assert_eq!(
+        grads(&src, Some(Prod), false)?,
+        (vec![0., 2., 0.], vec![35., 28., 3., 20.])
+    );
+    assert_eq!(
+        grads(&[0., 5., 6., 7.], Some(Prod), true)?,
+        (vec![0., 2., 18.], vec![35., 0., 9., 0.])
+    );
+    Ok(())
+}
+
+fn einsum_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[[1f32, 2., 3.], [4., 5., 6.]], device)?;
+    let y = Var::new(&[[1f32, -1.], [0., 2.], [3., 1.]], device)?;
+    let z = Tensor::einsum("ij,jk->ik", &[x.as_tensor(), y.as_tensor()])?;
+    let grads = z.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    let grad_y = grads.get(&y).context("no grad for y")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[0., 2., 4.], [0., 2., 4.]]);
+    assert_eq!(grad_y.to_vec2::<f32>()?, [[5., 5.], [7., 7.], [9., 9.]]);
+
+    // The diagonal of a matrix.
+    let m = Var::new(&[[1f32, 2.], [3., 4.]], device)?;
+    let d = Tensor::einsum("ii->i", &[m.as_tensor()])?;
+    let grads = d.sqr()?.sum_all()?.backward()?;
+    let grad_m = grads.get(&m).context("no grad for m")?;
+    assert_eq!(grad_m.to_vec2::<f32>()?, [[2., 0.], [0., 8.]]);
+    Ok(())
+}
+
+fn unfold_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[1f32, 2., 3., 4., 5., 6., 7.], device)?;
+    let grads = x.unfold(0, 3, 2)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec1::<f32>()?, [1., 1., 2., 1., 2., 1., 1.]);
+
+    let x = Var::new(&[[1f32, 2., 3.], [4., 5., 6.]], device)?;
+    let y = x.unfold(0, 2, 1)?.unfold(1, 2, 1)?;
+    let grads = y.sqr()?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[2., 8., 6.], [8., 20., 12.]]);
+
+    let x = Var::from_tensor(&Tensor::arange(0f32, 9., device)?.reshape((1, 1, 3, 3))?)?;
+    let grads = x.unfold2d(2, 1, 0, 1)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.squeeze(0)?.squeeze(0)?.to_vec2::<f32>()?,
+        [[1., 2., 1.], [2., 4., 2.], [1., 2., 1.]]
+    );
+    let cols = Var::ones((1, 4, 4), candle_core::DType::F32, device)?;
+    let w = Tensor::arange(0f32, 9., device)?.reshape((1, 1, 3, 3))?;
+    let grads = cols.fold2d(3, 2, 1, 0, 1)?.mul(&w)?.sum_all()?.backward()?;
+    let grad_cols = grads.get(&cols).context("no grad for cols")?;
+    assert_eq!(
+        grad_cols.squeeze(0)?.to_vec2::<f32>()?,
+        w.unfold2d(2, 1, 0, 1)?.squeeze(0)?.to_vec2::<f32>()?
+    );
+    Ok(())
+}
+
+fn pad_grad(device: &Device) -> Result<()> {
+    use candle_core::pad::PadMode;
+    let x = Var::new(&[1f32, 2., 3.], device)?;
+    let grads = x.pad(&[(2, 1)], PadMode::Reflect)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec1::<f32>()?, [1., 3., 2.]);
+    let grads = x.pad(&[(2, 1)], PadMode::Circular)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec1::<f32>()?, [2., 2., 2.]);
+    let y = x.pad(&[(2, 1)], PadMode::Constant(5.))?;
+    let grads = y.sqr()?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec1::<f32>()?, [2., 4., 6.]);
+    Ok(())
+}
+
+fn diagonal_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[[1f32, 2.], [3., 4.]], device)?;
+    let grads = x.tril(0)?.sqr()?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[2., 0.], [6., 8.]]);
+    let grads = x.triu(1)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[0., 1.], [0., 0.]]);
+    let grads = x.trace()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[1., 0.], [0., 1.]]);
+    let grads = x.diagonal(-1, 0, 1)?.sqr()?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec2::<f32>()?, [[0., 0.], [6., 0.]]);
+    let x = Var::new(&[1f32, 2.], device)?;
+    let grads = x.diag_embed(1)?.sqr()?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(grad_x.to_vec1::<f32>()?, [2., 4.]);
+    Ok(())
+}
+
+fn pool_grad(device: &Device) -> Result<()> {
+    let x = Var::new(&[[[1f32, 2., 3., 4., 5., 6., 7.]]], device)?;
+    let grads = x.avg_pool1d(2)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.flatten_all()?.to_vec1::<f32>()?,
+        [0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.]
+    );
+    let grads = x.max_pool1d(3)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.flatten_all()?.to_vec1::<f32>()?,
+        [0., 0., 1., 0., 0., 1., 0.]
+    );
+
+    let x = Var::new(&[[[1f32, 2., 3., 4., 5.]]], device)?;
+    let grads = x.adaptive_avg_pool1d(3)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        test_utils::to_vec1_round(&grad_x.flatten_all()?, 4)?,
+        [0.5, 0.8333, 0.3333, 0.8333, 0.5]
+    );
+
+    let x = Var::from_tensor(&Tensor::arange(0f32, 20f32, device)?.reshape((1, 1, 4, 5))?)?;
+    let grads = x.adaptive_max_pool2d(3)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.squeeze(0)?.squeeze(0)?.to_vec2::<f32>()?,
+        [
+            [0., 0., 0., 0., 0.],
+            [0., 1., 0., 1., 1.],
+            [0., 1., 0., 1., 1.],
+            [0., 1., 0., 1., 1.]
+        ]
+    );
+    let grads = x.adaptive_avg_pool2d((1, 2))?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        test_utils::to_vec2_round(&grad_x.squeeze(0)?.squeeze(0)?, 4)?,
+        [[0.0833, 0.0833, 0.1667, 0.0833, 0.0833]; 4]
+    );
+
+    // Overlapping windows.
+    let x = Var::new(&[[[[1f32, 5., 2.], [3., 0., 4.], [2., 6., 1.]]]], device)?;
+    let grads = x.max_pool2d_with_stride(2, 1)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.squeeze(0)?.squeeze(0)?.to_vec2::<f32>()?,
+        [[0., 2., 0.], [0., 0., 0.], [0., 2., 0.]]
+    );
+    let grads = x.avg_pool2d_with_stride(2, 1)?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.squeeze(0)?.squeeze(0)?.to_vec2::<f32>()?,
+        [[0.25, 0.5, 0.25], [0.5, 1., 0.5], [0.25, 0.5, 0.25]]
+    );
+    let config = candle_core::pool::Pool2dConfig {
+        padding: (1, 1),
+        count_include_pad: false,
+        ..Default::default()
+    };
+    let grads = x
+        .max_pool2d_with_config(3, 2, &config)?
+        .sum_all()?
+        .backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.squeeze(0)?.squeeze(0)?.to_vec2::<f32>()?,
+        [[0., 2., 0.], [0., 0., 0.], [0., 2., 0.]]
+    );
+    let grads = x
+        .avg_pool2d_with_config(3, 2, &config)?
+        .sum_all()?
+        .backward()?;
+    let grad_x = grads.get(&x).context("no grad for x")?;
+    assert_eq!(
+        grad_x.squeeze(0)?.squeeze(0)?.to_vec2::<f32>()?,
+        [[0.25, 0.5, 0.25], [0.5, 1., 0.5], [0.25, 0.5, 0.25]]
+    );
+    Ok(())
+}
+
 test_device!(
     simple_grad,
     simple_grad_cpu,
@@ -531,3 +993,31 @@ test_device!(
     binary_grad_gpu,
     binary_grad_metal
 );
+test_device!(topk_grad, topk_grad_cpu, topk_grad_gpu, topk_grad_metal);
+test_device!(sort_grad, sort_grad_cpu, sort_grad_gpu, sort_grad_metal);
+test_device!(
+    masking_grad,
+    masking_grad_cpu,
+    masking_grad_gpu,
+    masking_grad_metal
+);
+test_device!(
+    einsum_grad,
+    einsum_grad_cpu,
+    einsum_grad_gpu,
+    einsum_grad_metal
+);
+test_device!(pool_grad, pool_grad_cpu, pool_grad_gpu, pool_grad_metal);
+test_device!(pad_grad, pad_grad_cpu, pad_grad_gpu, pad_grad_metal);
+test_device!(
+    diagonal_grad,
+    diagonal_grad_cpu,
+    diagonal_grad_gpu,
+    diagonal_grad_metal
+);
+test_device!(
+    unfold_grad,
+    unfold_grad_cpu,
+    unfold_grad_gpu,
+    unfold_grad_metal
+);
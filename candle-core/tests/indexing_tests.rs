@@ -1,5 +1,5 @@
 use anyhow::Result;
-use candle_core::{Device, IndexOp, Tensor};
+use candle_core::{Device, IndexOp, Tensor, TensorIndexer, Var};
 
 #[test]
 fn integer_index() -> Result<()> {
@@ -92,6 +92,143 @@ fn index_3d() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn tensor_index() -> Result<()> {
+    let dev = Device::Cpu;
+    let tensor = Tensor::arange(0u32, 24, &dev)?.reshape((2, 3, 4))?;
+
+    let i0 = Tensor::new(&[1u32, 0], &dev)?;
+    let i1 = Tensor::new(&[2u32, 1], &dev)?;
+    let result = tensor.i((&i0, &i1))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[20, 21, 22, 23], [4, 5, 6, 7]]);
+
+    // The index tensors are broadcasted together.
+    let i0 = Tensor::new(&[[0u32], [1]], &dev)?;
+    let i1 = Tensor::new(&[0u32, 2], &dev)?;
+    let result = tensor.i((&i0, &i1, 1))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[1, 9], [13, 21]]);
+
+    // Non-adjacent index tensors put the indexed dims first.
+    let i0 = Tensor::new(&[0u32, 1], &dev)?;
+    let i2 = Tensor::new(&[3u32, 0], &dev)?;
+    let result = tensor.i((&i0, .., &i2))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[3, 7, 11], [12, 16, 20]]);
+    let result = tensor.i((&i0, 1.., &i2))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[7, 11], [16, 20]]);
+
+    // Adjacent index tensors stay in place.
+    let i1 = Tensor::new(&[0u32, 2], &dev)?;
+    let i2 = Tensor::new(&[1u32, 3], &dev)?;
+    let result = tensor.i((.., &i1, &i2))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[1, 11], [13, 23]]);
+
+    // Multi-dimensional index tensors.
+    let i1 = Tensor::new(&[[0u32, 1], [2, 2]], &dev)?;
+    let result = tensor.i((.., &i1))?;
+    assert_eq!(result.dims(), &[2, 2, 2, 4]);
+    assert_eq!(result.i((1, 1, .., 0))?.to_vec1::<u32>()?, &[20, 20]);
+
+    // Negative indexes count from the end.
+    let i2 = Tensor::new(&[-1i64, 0], &dev)?;
+    let result = tensor.i((.., 1, &i2))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[7, 4], [19, 16]]);
+
+    // Integers count as index tensors when combined with them, as in NumPy the indexed dims are
+    // moved first when they are not adjacent.
+    let result = tensor.i((1, .., &i2))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[15, 19, 23], [12, 16, 20]]);
+    let tensor4 = Tensor::arange(0u32, 120, &dev)?.reshape((2, 3, 4, 5))?;
+    let idx = Tensor::new(&[3u32, 1], &dev)?;
+    let result = tensor4.i((0, .., &idx))?;
+    assert_eq!(result.dims(), &[2, 3, 5]);
+    assert_eq!(result.i((.., 2, 0))?.to_vec1::<u32>()?, &[55, 45]);
+    let result = tensor4.i((.., 0, &idx))?;
+    assert_eq!(result.dims(), &[2, 2, 5]);
+    assert_eq!(result.i((1, .., 0))?.to_vec1::<u32>()?, &[75, 65]);
+    assert!(tensor4.i((2, .., &idx)).is_err());
+
+    let i2 = Tensor::new(&[4u32], &dev)?;
+    assert!(tensor.i((.., &i0, &i2)).is_err());
+    Ok(())
+}
+
+#[test]
+fn mask_index() -> Result<()> {
+    let dev = Device::Cpu;
+    let tensor = Tensor::arange(0u32, 24, &dev)?.reshape((2, 3, 4))?;
+
+    let mask = Tensor::new(&[[1u8, 0, 1], [0, 1, 0]], &dev)?;
+    let result = tensor.i(TensorIndexer::Mask(mask.clone()))?;
+    assert_eq!(
+        result.to_vec2::<u32>()?,
+        &[[0, 1, 2, 3], [8, 9, 10, 11], [16, 17, 18, 19]]
+    );
+    let mask = Tensor::new(&[0u8, 1, 0, 1], &dev)?;
+    let result = tensor.i((1, .., TensorIndexer::Mask(mask)))?;
+    assert_eq!(result.to_vec2::<u32>()?, &[[13, 17, 21], [15, 19, 23]]);
+    let mask = tensor.ge(20u32)?;
+    let result = tensor.i(TensorIndexer::Mask(mask))?;
+    assert_eq!(result.to_vec1::<u32>()?, &[20, 21, 22, 23]);
+    let mask = Tensor::new(&[1u8, 0], &dev)?;
+    assert!(tensor.i((.., TensorIndexer::Mask(mask))).is_err());
+
+    // The result of a comparison is used as a mask without an explicit indexer.
+    let x = Tensor::new(&[-1f32, 2., -3., 4.], &dev)?;
+    assert_eq!(x.i(&x.gt(0f64)?)?.to_vec1::<f32>()?, &[2., 4.]);
+    Ok(())
+}
+
+#[test]
+fn index_put() -> Result<()> {
+    let dev = Device::Cpu;
+    let tensor = Tensor::arange(0f32, 6., &dev)?.reshape((2, 3))?;
+
+    let rows = Tensor::new(&[1u32, 0], &dev)?;
+    let cols = Tensor::new(&[1u32, 2], &dev)?;
+    let values = Tensor::new(&[-1f32, -2.], &dev)?;
+    let result = tensor.index_put((&rows, &cols), &values, false)?;
+    assert_eq!(result.to_vec2::<f32>()?, &[[0., 1., -2.], [3., -1., 5.]]);
+    let result = tensor.index_put((&rows, &cols), &values, true)?;
+    assert_eq!(result.to_vec2::<f32>()?, &[[0., 1., 0.], [3., 3., 5.]]);
+
+    // The values are broadcasted to the selection.
+    let result = tensor.index_put((.., &cols), &Tensor::new(7f32, &dev)?, false)?;
+    assert_eq!(result.to_vec2::<f32>()?, &[[0., 7., 7.], [3., 7., 7.]]);
+    let mask = tensor.gt(2.5)?;
+    let result = tensor.index_put(TensorIndexer::Mask(mask), &Tensor::new(0f32, &dev)?, false)?;
+    assert_eq!(result.to_vec2::<f32>()?, &[[0., 1., 2.], [0., 0., 0.]]);
+
+    // With accumulate, repeated indexes get all their values.
+    let tensor = Tensor::zeros(5, candle_core::DType::F32, &dev)?;
+    let indexes = Tensor::new(&[0u32, 0, 2, 0], &dev)?;
+    let result = tensor.index_put(&indexes, &tensor.ones_like()?.narrow(0, 0, 4)?, true)?;
+    assert_eq!(result.to_vec1::<f32>()?, &[3., 0., 1., 0., 0.]);
+    Ok(())
+}
+
+#[test]
+fn tensor_index_grad() -> Result<()> {
+    let dev = Device::Cpu;
+    let x = Var::new(&[[1f32, 2., 3.], [4., 5., 6.]], &dev)?;
+    let rows = Tensor::new(&[1u32, 0, 1], &dev)?;
+    let cols = Tensor::new(&[0u32, 2, 0], &dev)?;
+    let grads = x.i((&rows, &cols))?.sqr()?.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).unwrap();
+    assert_eq!(grad_x.to_vec2::<f32>()?, &[[0., 0., 6.], [16., 0., 0.]]);
+
+    let values = Var::new(&[10f32, 20.], &dev)?;
+    let rows = Tensor::new(&[0u32, 1], &dev)?;
+    let cols = Tensor::new(&[1u32, 2], &dev)?;
+    let ys = x.index_put((&rows, &cols), &values, false)?;
+    let ys = ys.broadcast_mul(&Tensor::new(&[1f32, 2., 3.], &dev)?)?;
+    let grads = ys.sum_all()?.backward()?;
+    let grad_x = grads.get(&x).unwrap();
+    let grad_values = grads.get(&values).unwrap();
+    assert_eq!(grad_x.to_vec2::<f32>()?, &[[1., 0., 3.], [1., 2., 0.]]);
+    assert_eq!(grad_values.to_vec1::<f32>()?, &[2., 3.]);
+    Ok(())
+}
+
 #[test]
 fn slice_assign() -> Result<()> {
     let dev = Device::Cpu;
@@ -0,0 +1,261 @@
+use anyhow::Result;
+use candle_core::test_utils::{check_grad, max_diff};
+use candle_core::{linalg, DType, Device, Tensor, Var, D};
+
+fn weights(dims: &[usize]) -> Result<Tensor> {
+    let n = dims.iter().product::<usize>();
+    let ws = Tensor::arange(0., n as f64, &Device::Cpu)?;
+    Ok((ws.sqr()? * 0.37)?.sin()?.reshape(dims)?)
+}
+
+#[test]
+fn inv() -> Result<()> {
+    let a = Tensor::new(&[[4f32, 7.], [2., 6.]], &Device::Cpu)?;
+    let a_inv = linalg::inv(&a)?;
+    let expected = Tensor::new(&[[0.6f32, -0.7], [-0.2, 0.4]], &Device::Cpu)?;
+    assert!(max_diff(&a_inv, &expected)? < 1e-6);
+
+    let eye = Tensor::eye(4, DType::F64, &Device::Cpu)?;
+    let a = weights(&[3, 4, 4])?.broadcast_add(&(eye * 3.)?)?;
+    let a_inv = linalg::inv(&a)?;
+    let eye = Tensor::eye(4, DType::F64, &Device::Cpu)?.broadcast_as((3, 4, 4))?;
+    assert!(max_diff(&a.matmul(&a_inv)?, &eye)? < 1e-12);
+
+    let singular = Tensor::new(&[[1f64, 2.], [2., 4.]], &Device::Cpu)?;
+    assert!(linalg::inv(&singular).is_err());
+    let ints = Tensor::new(&[[1u32, 2], [3, 4]], &Device::Cpu)?;
+    assert!(linalg::inv(&ints).is_err());
+    assert!(linalg::inv(&Tensor::zeros((2, 3), DType::F32, &Device::Cpu)?).is_err());
+    Ok(())
+}
+
+#[test]
+fn solve() -> Result<()> {
+    let a = Tensor::new(&[[3f64, 1.], [1., 2.]], &Device::Cpu)?;
+    let b = Tensor::new(&[[9f64], [8.]], &Device::Cpu)?;
+    let x = linalg::solve(&a, &b)?;
+    assert!(max_diff(&x, &Tensor::new(&[[2f64], [3.]], &Device::Cpu)?)? < 1e-12);
+
+    let a = (weights(&[2, 3, 3])?
+        + (Tensor::eye(3, DType::F64, &Device::Cpu)? * 2.)?
+            .unsqueeze(0)?
+            .broadcast_as((2, 3, 3))?)?;
+    let b = weights(&[2, 3, 4])?.cos()?;
+    let x = linalg::solve(&a, &b)?;
+    assert!(max_diff(&a.matmul(&x)?, &b)? < 1e-12);
+    assert!(linalg::solve(&a, &b.narrow(1, 0, 2)?).is_err());
+    Ok(())
+}
+
+#[test]
+fn det() -> Result<()> {
+    let a = Tensor::new(
+        &[
+            [[4f64, 7.], [2., 6.]],
+            [[1., 2.], [2., 4.]],
+            [[0., 1.], [1., 0.]],
+        ],
+        &Device::Cpu,
+    )?;
+    let det = linalg::det(&a)?;
+    assert_eq!(det.to_vec1::<f64>()?, &[10., 0., -1.]);
+    let (sign, logabsdet) = linalg::slogdet(&a)?;
+    assert_eq!(sign.to_vec1::<f64>()?, &[1., 0., -1.]);
+    let logabsdet = logabsdet.to_vec1::<f64>()?;
+    assert!((logabsdet[0] - 10f64.ln()).abs() < 1e-12);
+    assert_eq!(logabsdet[1], f64::NEG_INFINITY);
+    assert_eq!(logabsdet[2], 0.);
+    Ok(())
+}
+
+#[test]
+fn cholesky() -> Result<()> {
+    let a = Tensor::new(
+        &[[4f64, 12., -16.], [12., 37., -43.], [-16., -43., 98.]],
+        &Device::Cpu,
+    )?;
+    let l = linalg::cholesky(&a)?;
+    assert_eq!(
+        l.to_vec2::<f64>()?,
+        &[[2., 0., 0.], [6., 1., 0.], [-8., 5., 3.]]
+    );
+    let not_pd = Tensor::new(&[[1f64, 2.], [2., 1.]], &Device::Cpu)?;
+    assert!(linalg::cholesky(&not_pd).is_err());
+    Ok(())
+}
+
+#[test]
+fn qr() -> Result<()> {
+    for dims in [[2, 5, 3], [2, 3, 3], [2, 3, 5]] {
+        let a = weights(&dims)?;
+        let (q, r) = linalg::qr(&a)?;
+        let k = usize::min(dims[1], dims[2]);
+        assert_eq!(q.dims(), &[2, dims[1], k]);
+        assert_eq!(r.dims(), &[2, k, dims[2]]);
+        assert!(max_diff(&q.matmul(&r)?, &a)? < 1e-12);
+        let eye = Tensor::eye(k, DType::F64, &Device::Cpu)?.broadcast_as((2, k, k))?;
+        assert!(max_diff(&q.t()?.matmul(&q)?, &eye)? < 1e-12);
+        let tril = (Tensor::tril2(k, DType::F64, &Device::Cpu)? - &eye.get(0)?)?;
+        let below = r.narrow(2, 0, k)?.broadcast_mul(&tril)?;
+        assert_eq!(below.abs()?.sum_all()?.to_vec0::<f64>()?, 0.);
+    }
+    Ok(())
+}
+
+#[test]
+fn eigh() -> Result<()> {
+    let a = Tensor::new(&[[2f64, 1.], [1., 2.]], &Device::Cpu)?;
+    let (w, v) = linalg::eigh(&a)?;
+    assert!(max_diff(&w, &Tensor::new(&[1f64, 3.], &Device::Cpu)?)? < 1e-12);
+    let av = a.matmul(&v)?;
+    assert!(max_diff(&av, &v.broadcast_mul(&w.unsqueeze(0)?)?)? < 1e-12);
+
+    let b = weights(&[3, 5, 5])?;
+    let a = (&b + b.t()?)?;
+    let (w, v) = linalg::eigh(&a)?;
+    let w = w.to_vec2::<f64>()?;
+    assert!(w.iter().all(|w| w.windows(2).all(|w| w[0] <= w[1])));
+    let w = Tensor::new(w, &Device::Cpu)?;
+    let recons = v.broadcast_mul(&w.unsqueeze(1)?)?.matmul(&v.t()?)?;
+    assert!(max_diff(&recons, &a)? < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn svd() -> Result<()> {
+    for dims in [[2, 5, 3], [2, 4, 4], [2, 3, 5]] {
+        let a = weights(&dims)?;
+        let (u, s, vh) = linalg::svd(&a)?;
+        let k = usize::min(dims[1], dims[2]);
+        assert_eq!(u.dims(), &[2, dims[1], k]);
+        assert_eq!(s.dims(), &[2, k]);
+        assert_eq!(vh.dims(), &[2, k, dims[2]]);
+        let recons = u.broadcast_mul(&s.unsqueeze(1)?)?.matmul(&vh)?;
+        assert!(max_diff(&recons, &a)? < 1e-12);
+        let eye = Tensor::eye(k, DType::F64, &Device::Cpu)?.broadcast_as((2, k, k))?;
+        assert!(max_diff(&u.t()?.matmul(&u)?, &eye)? < 1e-12);
+        assert!(max_diff(&vh.matmul(&vh.t()?)?, &eye)? < 1e-12);
+        let s = s.to_vec2::<f64>()?;
+        assert!(s.iter().all(|s| s.windows(2).all(|s| s[0] >= s[1])));
+    }
+
+    // Rank deficient matrix, the left singular vectors should still be orthonormal.
+    let a = Tensor::new(&[[1f32, 2.], [2., 4.], [3., 6.]], &Device::Cpu)?;
+    let (u, s, _vh) = linalg::svd(&a)?;
+    let s = s.to_vec1::<f32>()?;
+    assert!((s[0] - 70f32.sqrt()).abs() < 1e-5);
+    assert!(s[1].abs() < 1e-5);
+    let eye = Tensor::eye(2, DType::F32, &Device::Cpu)?;
+    assert!(max_diff(&u.t()?.matmul(&u)?, &eye)? < 1e-5);
+    Ok(())
+}
+
+#[test]
+fn linalg_grad() -> Result<()> {
+    let dev = &Device::Cpu;
+    let eye3 = Tensor::eye(3, DType::F64, dev)?;
+    let a = (weights(&[2, 3, 3])? + (&eye3 * 2.)?.unsqueeze(0)?.broadcast_as((2, 3, 3))?)?;
+    let w33 = weights(&[2, 3, 3])?.cos()?;
+    check_grad(&a, |a| (linalg::inv(a)? * &w33)?.sum_all(), 1e-5)?;
+    check_grad(
+        &a,
+        |a| (linalg::det(a)? * Tensor::new(&[0.5f64, -1.5], dev)?)?.sum_all(),
+        1e-5,
+    )?;
+    check_grad(&a, |a| linalg::slogdet(a)?.1.sum_all(), 1e-5)?;
+
+    // The gradient of the determinant is the transposed adjugate, also for singular matrices.
+    let singular = Var::new(&[[1f64, 2.], [2., 4.]], dev)?;
+    let grads = linalg::det(singular.as_tensor())?.backward()?;
+    let grad = grads.get(&singular).expect("no grad for singular");
+    let expected = Tensor::new(&[[4f64, -2.], [-2., 1.]], dev)?;
+    assert!(max_diff(grad, &expected)? < 1e-12);
+    let singular = Tensor::new(
+        &[
+            [[1f64, 2., 3.], [4., 5., 6.], [7., 8., 9.]],
+            [[1., 2., 3.], [2., 4., 6.], [3., 6., 9.]],
+        ],
+        dev,
+    )?;
+    check_grad(
+        &singular,
+        |a| (linalg::det(a)? * Tensor::new(&[0.5f64, -1.5], dev)?)?.sum_all(),
+        1e-5,
+    )?;
+
+    let b = weights(&[2, 3, 2])?.cos()?;
+    let w32 = weights(&[2, 3, 2])?;
+    check_grad(&a, |a| (linalg::solve(a, &b)? * &w32)?.sum_all(), 1e-5)?;
+    check_grad(&b, |b| (linalg::solve(&a, b)? * &w32)?.sum_all(), 1e-5)?;
+
+    let spd = (a.matmul(&a.t()?)? + eye3.unsqueeze(0)?.broadcast_as((2, 3, 3))?)?;
+    // The gradient of the cholesky decomposition is symmetric, so compare with the finite
+    // differences of a function of the symmetric part of the input.
+    check_grad(
+        &spd,
+        |a| {
+            let a = ((a + a.t()?)? * 0.5)?;
+            (linalg::cholesky(&a)? * &w33)?.sum_all()
+        },
+        1e-5,
+    )?;
+    // Eigh only uses the symmetric part of its input, including for non-symmetric matrices.
+    let non_sym = (&spd + (&a * 0.1)?)?;
+    check_grad(
+        &non_sym,
+        |a| {
+            let (w, v) = linalg::eigh(a)?;
+            let lw = w.broadcast_mul(&Tensor::new(&[1f64, -2., 0.5], dev)?)?;
+            let lw = lw.sum_all()?;
+            lw + (v.sqr()? * &w33)?.sum_all()?
+        },
+        1e-5,
+    )?;
+    let non_sym = Tensor::new(&[[2f64, 1.], [0.5, 3.]], dev)?;
+    check_grad(&non_sym, |a| linalg::eigh(a)?.1.get(0)?.get(1), 1e-5)?;
+
+    let tall = weights(&[2, 4, 3])?;
+    let w43 = weights(&[2, 4, 3])?.cos()?;
+    check_grad(
+        &tall,
+        |a| {
+            let (q, r) = linalg::qr(a)?;
+            (q * &w43)?.sum_all()? + (r * &w33)?.sum_all()?
+        },
+        1e-5,
+    )?;
+    assert!(Var::from_tensor(&tall.t()?.contiguous()?)
+        .and_then(|a| linalg::qr(a.as_tensor())?.1.sum_all()?.backward())
+        .is_err());
+
+    for dims in [[2, 4, 3], [2, 3, 3], [2, 3, 4]] {
+        let a = weights(&dims)?;
+        let k = usize::min(dims[1], dims[2]);
+        let wu = weights(&[2, dims[1], k])?.cos()?;
+        let wv = weights(&[2, k, dims[2]])?.cos()?;
+        let ws = Tensor::arange(1f64, k as f64 + 1., dev)?;
+        check_grad(
+            &a,
+            |a| {
+                let (u, s, vh) = linalg::svd(a)?;
+                let l = (s.broadcast_mul(&ws))?.sum_all()?;
+                let l = (l + (u.sqr()? * &wu)?.sum_all()?)?;
+                l + (vh.sqr()? * &wv)?.sum_all()?
+            },
+            1e-5,
+        )?;
+    }
+    Ok(())
+}
+
+#[test]
+fn linalg_last_dims() -> Result<()> {
+    // Non-contiguous inputs are supported and the batch dimensions are preserved.
+    let a = weights(&[3, 3, 2])?.transpose(D::Minus1, 0)?;
+    let a = a.broadcast_add(&Tensor::eye(3, DType::F64, &Device::Cpu)?)?;
+    let a_inv = linalg::inv(&a)?;
+    assert_eq!(a_inv.dims(), &[2, 3, 3]);
+    let eye = Tensor::eye(3, DType::F64, &Device::Cpu)?.broadcast_as((2, 3, 3))?;
+    assert!(max_diff(&a.matmul(&a_inv)?, &eye)? < 1e-12);
+    Ok(())
+}
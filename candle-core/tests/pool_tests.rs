@@ -1,4 +1,4 @@
-use candle_core::{test_device, test_utils, Device, IndexOp, Result, Tensor};
+use candle_core::{pool::Pool2dConfig, test_device, test_utils, Device, IndexOp, Result, Tensor};
 
 // https://github.com/huggingface/candle/issues/364
 fn avg_pool2d(dev: &Device) -> Result<()> {
@@ -101,6 +101,187 @@ fn upsample_nearest2d(dev: &Device) -> Result<()> {
     Ok(())
 }
 
+fn pool1d(dev: &Device) -> Result<()> {
+    let t = Tensor::arange(0f32, 7f32, dev)?.reshape((1, 1, 7))?;
+    let pool = t.avg_pool1d(2)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec1::<f32>()?, [0.5, 2.5, 4.5]);
+    let pool = t.avg_pool1d_with_stride(3, 2)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec1::<f32>()?, [1., 3., 5.]);
+    let pool = t.max_pool1d(2)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec1::<f32>()?, [1., 3., 5.]);
+    let pool = t.max_pool1d_with_stride(3, 2)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec1::<f32>()?, [2., 4., 6.]);
+    Ok(())
+}
+
+fn adaptive_pool(dev: &Device) -> Result<()> {
+    let t = Tensor::arange(0f32, 5f32, dev)?.reshape((1, 1, 5))?;
+    let pool = t.adaptive_avg_pool1d(3)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(test_utils::to_vec1_round(&pool, 4)?, [0.5, 2., 3.5]);
+    let pool = t.adaptive_avg_pool1d(5)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec1::<f32>()?, [0., 1., 2., 3., 4.]);
+    let pool = t.adaptive_avg_pool1d(1)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec1::<f32>()?, [2.]);
+
+    let t = Tensor::arange(0f32, 20f32, dev)?.reshape((1, 1, 4, 5))?;
+    let pool = t.adaptive_avg_pool2d(3)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(
+        test_utils::to_vec2_round(&pool, 4)?,
+        [[3., 4.5, 6.], [8., 9.5, 11.], [13., 14.5, 16.]]
+    );
+    let pool = t.adaptive_avg_pool2d((2, 1))?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec2::<f32>()?, [[4.5], [14.5]]);
+    let pool = t.adaptive_max_pool2d(3)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(
+        pool.to_vec2::<f32>()?,
+        [[6., 8., 9.], [11., 13., 14.], [16., 18., 19.]]
+    );
+    let pool = t.adaptive_max_pool2d(1)?.squeeze(0)?.squeeze(0)?;
+    assert_eq!(pool.to_vec2::<f32>()?, [[19.]]);
+    Ok(())
+}
+
+fn pool2d_config(dev: &Device) -> Result<()> {
+    let t = Tensor::arange(0f32, 16f32, dev)?.reshape((1, 1, 4, 4))?;
+    let config = Pool2dConfig {
+        padding: (1, 1),
+        ..Default::default()
+    };
+    let (pool, indexes) = t.max_pool2d_with_indices(3, 2, &config)?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[5., 7.], [13., 15.]]);
+    assert_eq!(indexes.i((0, 0))?.to_vec2::<u32>()?, [[5, 7], [13, 15]]);
+    let pool = t.avg_pool2d_with_config(3, 2, &config)?;
+    assert_eq!(
+        test_utils::to_vec2_round(&pool.i((0, 0))?, 4)?,
+        [[1.1111, 2.6667], [5.6667, 10.]]
+    );
+    let config = Pool2dConfig {
+        count_include_pad: false,
+        ..config
+    };
+    let pool = t.avg_pool2d_with_config(3, 2, &config)?;
+    assert_eq!(
+        test_utils::to_vec2_round(&pool.i((0, 0))?, 4)?,
+        [[2.5, 4.], [8.5, 10.]]
+    );
+
+    // With ceil_mode, the last window only covers the last element.
+    let t = Tensor::new(&[[[[0f32, 3., 1., 4., 2.]]]], dev)?;
+    let config = Pool2dConfig {
+        ceil_mode: true,
+        ..Default::default()
+    };
+    let pool = t.max_pool2d_with_config((1, 2), (1, 2), &config)?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[3., 4., 2.]]);
+    let pool = t.avg_pool2d_with_config((1, 2), (1, 2), &config)?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[1.5, 2.5, 2.]]);
+
+    let config = Pool2dConfig {
+        dilation: (1, 2),
+        ..Default::default()
+    };
+    let (pool, indexes) = t.max_pool2d_with_indices((1, 2), (1, 1), &config)?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[1., 4., 2.]]);
+    assert_eq!(indexes.i((0, 0))?.to_vec2::<u32>()?, [[2, 3, 4]]);
+
+    // The default config uses the same windows as max_pool2d.
+    let (pool, indexes) = t.max_pool2d_with_indices((1, 2), (1, 2), &Default::default())?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[3., 4.]]);
+    assert_eq!(indexes.i((0, 0))?.to_vec2::<u32>()?, [[1, 3]]);
+
+    // Windows where no value beats the initial maximum still return one of their elements.
+    let t = Tensor::new(&[[[[1u8, 2, 0, 0], [3, 4, 0, 0]]]], dev)?;
+    let (pool, indexes) = t.max_pool2d_with_indices(2, 2, &Default::default())?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<u8>()?, [[4, 0]]);
+    assert_eq!(indexes.i((0, 0))?.to_vec2::<u32>()?, [[5, 2]]);
+    let t = Tensor::new(
+        &[[[[1f32, f32::NEG_INFINITY], [0., f32::NEG_INFINITY]]]],
+        dev,
+    )?;
+    let (pool, indexes) = t.max_pool2d_with_indices((2, 1), (2, 1), &Default::default())?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[1., f32::NEG_INFINITY]]);
+    assert_eq!(indexes.i((0, 0))?.to_vec2::<u32>()?, [[0, 1]]);
+    Ok(())
+}
+
+fn max_unpool2d(dev: &Device) -> Result<()> {
+    let t = Tensor::new(&[[[[1f32, 5., 2., 0.], [3., 0., 4., 7.]]]], dev)?;
+    let (pool, indexes) = t.max_pool2d_with_indices(2, 2, &Default::default())?;
+    let unpool = pool.max_unpool2d(&indexes, (2, 4))?;
+    assert_eq!(
+        unpool.i((0, 0))?.to_vec2::<f32>()?,
+        [[0., 5., 0., 0.], [0., 0., 0., 7.]]
+    );
+    // Overlapping windows can share their maximum.
+    let (pool, indexes) = t.max_pool2d_with_indices((1, 3), (1, 1), &Default::default())?;
+    assert_eq!(pool.i((0, 0))?.to_vec2::<f32>()?, [[5., 5.], [4., 7.]]);
+    let unpool = pool.max_unpool2d(&indexes, (2, 4))?;
+    assert_eq!(
+        unpool.i((0, 0))?.to_vec2::<f32>()?,
+        [[0., 5., 0., 0.], [0., 0., 4., 7.]]
+    );
+    Ok(())
+}
+
+// The interpolation kernels are only available on the cpu.
+#[test]
+fn interpolate() -> Result<()> {
+    use candle_core::interpolate::{InterpolateConfig, InterpolationMode};
+    let dev = &Device::Cpu;
+    let t = Tensor::new(&[[[[1f32, 2.], [3., 4.]]]], dev)?;
+    let ys = t.upsample_bilinear2d(4, 4, false)?.i((0, 0))?;
+    assert_eq!(
+        ys.to_vec2::<f32>()?,
+        [
+            [1.0, 1.25, 1.75, 2.0],
+            [1.5, 1.75, 2.25, 2.5],
+            [2.5, 2.75, 3.25, 3.5],
+            [3.0, 3.25, 3.75, 4.0]
+        ]
+    );
+    let ys = t.upsample_bilinear2d(3, 3, true)?.i((0, 0))?;
+    assert_eq!(
+        ys.to_vec2::<f32>()?,
+        [[1.0, 1.5, 2.0], [2.0, 2.5, 3.0], [3.0, 3.5, 4.0]]
+    );
+    let ys = t.upsample_bicubic2d(4, 4, false)?.i((0, 0))?;
+    assert_eq!(
+        test_utils::to_vec2_round(&ys, 4)?,
+        [
+            [0.6836, 1.0156, 1.5625, 1.8945],
+            [1.3477, 1.6797, 2.2266, 2.5586],
+            [2.4414, 2.7734, 3.3203, 3.6523],
+            [3.1055, 3.4375, 3.9844, 4.3164]
+        ]
+    );
+    // Interpolating to the same size is the identity, also on non-contiguous inputs.
+    let t = Tensor::arange(0f32, 12., dev)?.reshape((1, 1, 4, 3))?;
+    let ys = t.t()?.upsample_bicubic2d(3, 4, false)?;
+    assert_eq!(
+        ys.i((0, 0))?.to_vec2::<f32>()?,
+        t.t()?.i((0, 0))?.to_vec2::<f32>()?
+    );
+
+    let t = Tensor::new(&[[[1f32, 2., 3., 4.]]], dev)?;
+    let mut config = InterpolateConfig {
+        mode: InterpolationMode::Linear,
+        ..Default::default()
+    };
+    let ys = t.interpolate1d_with_config(2, &config)?;
+    assert_eq!(ys.to_vec3::<f32>()?, [[[1.5, 3.5]]]);
+    config.antialias = true;
+    let ys = t.interpolate1d_with_config(2, &config)?;
+    assert_eq!(test_utils::to_vec3_round(&ys, 4)?, [[[1.7143, 3.2857]]]);
+    let ys = t.narrow(2, 0, 3)?.interpolate1d_with_config(5, &config)?;
+    assert_eq!(
+        test_utils::to_vec3_round(&ys, 4)?,
+        [[[1., 1.4, 2., 2.6, 3.]]]
+    );
+    config.mode = InterpolationMode::Nearest;
+    assert!(t.interpolate1d_with_config(2, &config).is_err());
+    Ok(())
+}
+
 test_device!(avg_pool2d, avg_pool2d_cpu, avg_pool2d_gpu, avg_pool2d_metal);
 test_device!(
     avg_pool2d_pytorch,
@@ -115,3 +296,22 @@ test_device!(
     upsample_nearest2d_gpu,
     upsample_nearest2d_metal
 );
+test_device!(pool1d, pool1d_cpu, pool1d_gpu, pool1d_metal);
+test_device!(
+    adaptive_pool,
+    adaptive_pool_cpu,
+    adaptive_pool_gpu,
+    adaptive_pool_metal
+);
+test_device!(
+    pool2d_config,
+    pool2d_config_cpu,
+    pool2d_config_gpu,
+    pool2d_config_metal
+);
+test_device!(
+    max_unpool2d,
+    max_unpool2d_cpu,
+    max_unpool2d_gpu,
+    max_unpool2d_metal
+);
@@ -69,3 +69,69 @@ fn safetensors() -> Result<()> {
     assert_eq!(diff, 0f32);
     Ok(())
 }
+
+#[test]
+fn int_and_bool_dtypes() -> Result<()> {
+    let device = &candle_core::Device::Cpu;
+    let xs = Tensor::new(&[-3i64, 0, 1, 127], device)?;
+    let tensors = [
+        DType::U8,
+        DType::U16,
+        DType::I8,
+        DType::I16,
+        DType::I32,
+        DType::Bool,
+    ]
+    .iter()
+    .map(|&dtype| Ok((dtype.as_str().to_string(), xs.to_dtype(dtype)?)))
+    .collect::<Result<std::collections::HashMap<_, _>>>()?;
+
+    let tmp_file = TmpFile::create("st-int");
+    candle_core::safetensors::save(&tensors, &tmp_file)?;
+    let st = candle_core::safetensors::load(&tmp_file, device)?;
+    for (name, t) in tensors.iter() {
+        let t2 = st.get(name).unwrap();
+        assert_eq!(t2.dtype(), t.dtype());
+        assert_eq!(
+            t2.to_dtype(DType::I64)?.to_vec1::<i64>()?,
+            t.to_dtype(DType::I64)?.to_vec1::<i64>()?
+        );
+    }
+
+    for (name, t) in tensors.iter() {
+        let tmp_file = TmpFile::create(&format!("npy-{name}"));
+        t.write_npy(&tmp_file)?;
+        let t2 = Tensor::read_npy(&tmp_file)?;
+        assert_eq!(t2.dtype(), t.dtype());
+        assert_eq!(
+            t2.to_dtype(DType::I64)?.to_vec1::<i64>()?,
+            t.to_dtype(DType::I64)?.to_vec1::<i64>()?
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn fp8_dtypes() -> Result<()> {
+    let device = &candle_core::Device::Cpu;
+    let xs = Tensor::new(&[[-3f32, 0.], [0.375, 448.]], device)?;
+    let tensors = [DType::F8E4M3, DType::F8E5M2]
+        .iter()
+        .map(|&dtype| Ok((dtype.as_str().to_string(), xs.to_dtype(dtype)?)))
+        .collect::<Result<std::collections::HashMap<_, _>>>()?;
+
+    let tmp_file = TmpFile::create("st-fp8");
+    candle_core::safetensors::save(&tensors, &tmp_file)?;
+    let st = candle_core::safetensors::load(&tmp_file, device)?;
+    for (name, t) in tensors.iter() {
+        let t2 = st.get(name).unwrap();
+        assert_eq!(t2.dtype(), t.dtype());
+        assert_eq!(t2.dims(), t.dims());
+        assert_eq!(
+            t2.to_dtype(DType::F32)?.to_vec2::<f32>()?,
+            [[-3., 0.], [0.375, 448.]]
+        );
+    }
+    assert!(tensors["f8e4m3"].write_npy(&tmp_file).is_err());
+    Ok(())
+}
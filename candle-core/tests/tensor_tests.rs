@@ -160,6 +160,123 @@ fn asort(device: &Device) -> Result<()> {
     Ok(())
 }
 
+fn sort(device: &Device) -> Result<()> {
+    let data = &[[3f32, 1., 4., 1.1, 5.], [2.1, 1.5, 7., 8., 2.]];
+    let tensor = Tensor::new(data, device)?;
+    let (sorted, indexes) = tensor.sort(0, false, false)?;
+    assert_eq!(
+        indexes.to_vec2::<u32>()?,
+        [[1, 0, 0, 0, 1], [0, 1, 1, 1, 0]]
+    );
+    assert_eq!(
+        sorted.to_vec2::<f32>()?,
+        [[2.1, 1.0, 4.0, 1.1, 2.0], [3.0, 1.5, 7.0, 8.0, 5.0]]
+    );
+    let indexes = tensor.argsort(D::Minus1, true, false)?;
+    assert_eq!(
+        indexes.to_vec2::<u32>()?,
+        [[4, 2, 0, 3, 1], [3, 2, 0, 4, 1]],
+    );
+    // Non-contiguous inputs.
+    let (sorted, indexes) = tensor.t()?.sort(0, true, false)?;
+    assert_eq!(
+        sorted.t()?.to_vec2::<f32>()?,
+        [[5.0, 4.0, 3.0, 1.1, 1.0], [8.0, 7.0, 2.1, 2.0, 1.5]]
+    );
+    assert_eq!(
+        indexes.t()?.to_vec2::<u32>()?,
+        [[4, 2, 0, 3, 1], [3, 2, 0, 4, 1]],
+    );
+    let tensor = Tensor::arange(0u32, 24, device)?.reshape((2, 3, 4))?;
+    let indexes = tensor.argsort(1, true, false)?;
+    assert_eq!(indexes.i((1, .., 2))?.to_vec1::<u32>()?, [2, 1, 0]);
+
+    if device.is_cpu() {
+        // Ties keep their original order with a stable sort.
+        let tensor = Tensor::new(&[[2i64, 1, 2, 1, 0]], device)?;
+        let indexes = tensor.argsort(1, false, true)?;
+        assert_eq!(indexes.to_vec2::<u32>()?, [[4, 1, 3, 0, 2]]);
+        let indexes = tensor.argsort(1, true, true)?;
+        assert_eq!(indexes.to_vec2::<u32>()?, [[0, 2, 1, 3, 4]]);
+        let (sorted, _) = tensor.t()?.sort(0, true, true)?;
+        assert_eq!(sorted.t()?.to_vec2::<i64>()?, [[2, 2, 1, 1, 0]]);
+    }
+    Ok(())
+}
+
+fn topk(device: &Device) -> Result<()> {
+    let data = &[[3f32, 1., 4., 1.1, 5.], [2.1, 1., 7., 8., 2.]];
+    let tensor = Tensor::new(data, device)?;
+    let (values, indexes) = tensor.topk(3, 1, true)?;
+    assert_eq!(values.to_vec2::<f32>()?, [[5.0, 4.0, 3.0], [8.0, 7.0, 2.1]]);
+    assert_eq!(indexes.to_vec2::<u32>()?, [[4, 2, 0], [3, 2, 0]]);
+    let (values, indexes) = tensor.topk(2, D::Minus1, false)?;
+    assert_eq!(values.to_vec2::<f32>()?, [[1.0, 1.1], [1.0, 2.0]]);
+    assert_eq!(indexes.to_vec2::<u32>()?, [[1, 3], [1, 4]]);
+    let (values, indexes) = tensor.topk(1, 0, true)?;
+    assert_eq!(values.to_vec2::<f32>()?, [[3.0, 1.0, 7.0, 8.0, 5.0]]);
+    assert_eq!(indexes.to_vec2::<u32>()?, [[0, 0, 1, 1, 0]]);
+    let (values, indexes) = tensor.t()?.topk(2, 0, true)?;
+    assert_eq!(values.to_vec2::<f32>()?, [[5.0, 8.0], [4.0, 7.0]]);
+    assert_eq!(indexes.to_vec2::<u32>()?, [[4, 3], [2, 2]]);
+
+    let tensor = Tensor::new(&[[4i64, 2, 4, 1], [0, 0, 3, 3]], device)?;
+    let (values, indexes) = tensor.topk(2, 1, true)?;
+    assert_eq!(values.to_vec2::<i64>()?, [[4, 4], [3, 3]]);
+    assert_eq!(indexes.to_vec2::<u32>()?, [[0, 2], [2, 3]]);
+    let (values, indexes) = tensor.topk(4, 1, false)?;
+    assert_eq!(values.to_vec2::<i64>()?, [[1, 2, 4, 4], [0, 0, 3, 3]]);
+    assert_eq!(indexes.to_vec2::<u32>()?, [[3, 1, 0, 2], [0, 1, 2, 3]]);
+    assert!(tensor.topk(5, 1, true).is_err());
+    Ok(())
+}
+
+fn masking(device: &Device) -> Result<()> {
+    let tensor = Tensor::new(&[[3f32, 0., 4.], [0., 0., 5.]], device)?;
+    let nonzero = tensor.nonzero()?;
+    assert_eq!(nonzero.to_vec2::<u32>()?, [[0, 0], [0, 2], [1, 2]]);
+    let nonzero = tensor.t()?.nonzero()?;
+    assert_eq!(nonzero.to_vec2::<u32>()?, [[0, 0], [2, 0], [2, 1]]);
+    let nonzero = tensor.zeros_like()?.nonzero()?;
+    assert_eq!(nonzero.dims2()?, (0, 2));
+    let nonzero = Tensor::new(&[0i64, 7, 0, -1], device)?.nonzero()?;
+    assert_eq!(nonzero.to_vec2::<u32>()?, [[1], [3]]);
+
+    let mask = tensor.gt(1f64)?;
+    let selected = tensor.masked_select(&mask)?;
+    assert_eq!(selected.to_vec1::<f32>()?, [3., 4., 5.]);
+    let mask = Tensor::new(&[1u8, 0, 1], device)?;
+    let selected = tensor.masked_select(&mask)?;
+    assert_eq!(selected.to_vec1::<f32>()?, [3., 4., 0., 5.]);
+    assert!(tensor
+        .masked_select(&mask.unsqueeze(0)?.repeat((3, 1))?)
+        .is_err());
+
+    let filled = tensor.masked_fill(&mask, -1.)?;
+    assert_eq!(filled.to_vec2::<f32>()?, [[-1., 0., -1.], [-1., 0., -1.]]);
+    let filled = tensor.masked_fill(&tensor.eq(0f64)?, 9.)?;
+    assert_eq!(filled.to_vec2::<f32>()?, [[3., 9., 4.], [9., 9., 5.]]);
+    // Integer values are not rounded through f64.
+    let big = (1i64 << 53) + 1;
+    let xs = Tensor::new(&[1i64, 2, 3], device)?;
+    let filled = xs.masked_fill(&mask, big)?;
+    assert_eq!(filled.to_vec1::<i64>()?, [big, 2, big]);
+
+    let source = Tensor::new(&[[10f32, 11.], [12., 13.]], device)?;
+    let scattered = tensor.masked_scatter(&mask, &source)?;
+    assert_eq!(
+        scattered.to_vec2::<f32>()?,
+        [[10., 0., 11.], [12., 0., 13.]]
+    );
+    let mask = Tensor::new(&[[0u8, 1, 0], [1, 0, 0]], device)?;
+    let scattered = tensor.masked_scatter(&mask, &source)?;
+    assert_eq!(scattered.to_vec2::<f32>()?, [[3., 10., 4.], [11., 0., 5.]]);
+    assert!(tensor
+        .masked_scatter(&tensor.ones_like()?, &source)
+        .is_err());
+    Ok(())
+}
+
 fn unary_op(device: &Device) -> Result<()> {
     let data = &[[-3f32, 1., 4., -0.1, 0.5], [2.7, -1.8, -0.28, 1.8, 2.8]];
     let tensor = Tensor::new(data, device)?;
@@ -296,6 +413,101 @@ fn var(device: &Device) -> Result<()> {
         test_utils::to_vec2_round(&tensor.var_keepdim(1)?, 4)?,
         &[[1.0631], [0.559], [1.4893], [0.8258]]
     );
+
+    let tensor = Tensor::new(&[[1f32, 2.], [3., 6.]], device)?;
+    assert_eq!(
+        tensor.var_with_correction(0, 0)?.to_vec1::<f32>()?,
+        &[1., 4.]
+    );
+    assert_eq!(tensor.var_keepdim((0, 1))?.dims(), &[1, 1]);
+    assert_eq!(test_utils::to_vec0_round(&tensor.var((0, 1))?, 4)?, 4.6667);
+    assert_eq!(
+        tensor.var_with_correction((0, 1), 0)?.to_scalar::<f32>()?,
+        3.5
+    );
+    assert_eq!(
+        tensor.std_with_correction(0, 0)?.to_vec1::<f32>()?,
+        &[1., 2.]
+    );
+    let tensor = Tensor::new(&[[1f32, 3., 5.], [2., 2., 2.]], device)?;
+    assert_eq!(tensor.std(1)?.to_vec1::<f32>()?, &[2., 0.]);
+    assert_eq!(tensor.std_keepdim(1)?.dims(), &[2, 1]);
+    Ok(())
+}
+
+fn triangular_diagonal(device: &Device) -> Result<()> {
+    let t = Tensor::arange(0u32, 12, device)?.reshape((2, 2, 3))?;
+    assert_eq!(
+        t.tril(0)?.to_vec3::<u32>()?,
+        [[[0, 0, 0], [3, 4, 0]], [[6, 0, 0], [9, 10, 0]]]
+    );
+    assert_eq!(t.triu(-1)?.to_vec3::<u32>()?, t.to_vec3::<u32>()?);
+    assert_eq!(
+        t.triu(2)?.to_vec3::<u32>()?,
+        [[[0, 0, 2], [0, 0, 0]], [[0, 0, 8], [0, 0, 0]]]
+    );
+    assert_eq!(
+        t.t()?.tril(-1)?.to_vec3::<u32>()?,
+        [[[0, 0], [1, 0], [2, 5]], [[0, 0], [7, 0], [8, 11]]]
+    );
+    assert_eq!(t.diagonal(0, 1, 2)?.to_vec2::<u32>()?, [[0, 4], [6, 10]]);
+    assert_eq!(
+        t.diagonal(1, D::Minus2, D::Minus1)?.to_vec2::<u32>()?,
+        [[1, 5], [7, 11]]
+    );
+    assert_eq!(t.diagonal(0, 0, 2)?.to_vec2::<u32>()?, [[0, 7], [3, 10]]);
+    assert_eq!(t.diagonal(3, 1, 2)?.dims(), [2, 0]);
+    assert!(t.diagonal(0, 1, 1).is_err());
+    assert_eq!(t.trace()?.to_vec1::<u32>()?, [4, 16]);
+
+    let t = Tensor::new(&[[1f32, 2.], [3., 4.]], device)?;
+    assert_eq!(
+        t.diag_embed(1)?.to_vec3::<f32>()?,
+        [
+            [[0., 1., 0.], [0., 0., 2.], [0., 0., 0.]],
+            [[0., 3., 0.], [0., 0., 4.], [0., 0., 0.]]
+        ]
+    );
+    let d = t.diag_embed(-2)?;
+    assert_eq!(d.dims(), [2, 4, 4]);
+    assert_eq!(d.diagonal(-2, 1, 2)?.to_vec2::<f32>()?, t.to_vec2::<f32>()?);
+    Ok(())
+}
+
+fn reductions(device: &Device) -> Result<()> {
+    use candle_core::reduce::NormOrd;
+    let t = Tensor::new(&[[3f32, -4.], [0., 1.]], device)?;
+    assert_eq!(t.norm(NormOrd::L2, 1)?.to_vec1::<f32>()?, &[5., 1.]);
+    assert_eq!(t.norm(NormOrd::L1, (0, 1))?.to_scalar::<f32>()?, 8.);
+    assert_eq!(t.norm(NormOrd::Inf, 0)?.to_vec1::<f32>()?, &[3., 4.]);
+    assert_eq!(
+        test_utils::to_vec2_round(&t.norm_keepdim(NormOrd::Fro, (0, 1))?, 4)?,
+        &[[5.099]]
+    );
+
+    let t = Tensor::new(&[[0u32, 1, 0], [2, 3, 4]], device)?;
+    let to_u8 = |t: Tensor| t.to_dtype(DType::U8);
+    assert_eq!(to_u8(t.any(1)?)?.to_vec1::<u8>()?, &[1, 1]);
+    assert_eq!(to_u8(t.all(1)?)?.to_vec1::<u8>()?, &[0, 1]);
+    assert_eq!(to_u8(t.all(0)?)?.to_vec1::<u8>()?, &[0, 1, 0]);
+    assert_eq!(to_u8(t.any_keepdim((0, 1))?)?.to_vec2::<u8>()?, &[[1]]);
+    assert_eq!(to_u8(t.all_keepdim((0, 1))?)?.to_vec2::<u8>()?, &[[0]]);
+
+    let t = Tensor::new(&[[[1f32, 7.], [3., 2.]], [[0., 4.], [8., 1.]]], device)?;
+    assert_eq!(t.amax((0, 2))?.to_vec1::<f32>()?, &[7., 8.]);
+    assert_eq!(t.amin((2, 1))?.to_vec1::<f32>()?, &[1., 0.]);
+    assert_eq!(t.amax_keepdim((0, 1))?.to_vec3::<f32>()?, &[[[8., 7.]]]);
+    assert_eq!(t.amin(0)?.to_vec2::<f32>()?, &[[0., 4.], [3., 1.]]);
+
+    if device.is_cpu() {
+        let t = Tensor::new(&[[1f32, 2., 3.], [4., 5., 6.]], device)?;
+        assert_eq!(t.prod(1)?.to_vec1::<f32>()?, &[6., 120.]);
+        assert_eq!(t.prod_keepdim(0)?.to_vec2::<f32>()?, &[[4., 10., 18.]]);
+        assert_eq!(t.prod((1, 0))?.to_scalar::<f32>()?, 720.);
+        assert_eq!(t.t()?.prod(0)?.to_vec1::<f32>()?, &[6., 120.]);
+        let t = Tensor::zeros((2, 0), DType::F32, device)?;
+        assert_eq!(t.prod(1)?.to_vec1::<f32>()?, &[1., 1.]);
+    }
     Ok(())
 }
 
@@ -668,6 +880,34 @@ fn argmax(device: &Device) -> Result<()> {
     Ok(())
 }
 
+fn unfold(device: &Device) -> Result<()> {
+    let t = Tensor::arange(0u32, 7, device)?;
+    assert_eq!(
+        t.unfold(0, 3, 2)?.to_vec2::<u32>()?,
+        [[0, 1, 2], [2, 3, 4], [4, 5, 6]]
+    );
+    assert_eq!(t.unfold(0, 3, 3)?.to_vec2::<u32>()?, [[0, 1, 2], [3, 4, 5]]);
+    let t = Tensor::arange(0u32, 12, device)?.reshape((3, 4))?;
+    let u = t.unfold(1, 2, 2)?;
+    assert_eq!(u.dims(), [3, 2, 2]);
+    assert_eq!(
+        u.to_vec3::<u32>()?,
+        [[[0, 1], [2, 3]], [[4, 5], [6, 7]], [[8, 9], [10, 11]]]
+    );
+    let u = t.unfold(0, 2, 1)?;
+    assert_eq!(
+        u.to_vec3::<u32>()?,
+        [
+            [[0, 4], [1, 5], [2, 6], [3, 7]],
+            [[4, 8], [5, 9], [6, 10], [7, 11]]
+        ]
+    );
+    assert!(t.unfold(1, 5, 1).is_err());
+    assert!(t.unfold(1, 2, 0).is_err());
+    assert!(t.unfold(2, 2, 1).is_err());
+    Ok(())
+}
+
 fn narrow(device: &Device) -> Result<()> {
     let data = &[[[3f32, 1., 4.], [1., 5., 9.]], [[2., 1., 7.], [8., 2., 8.]]];
     let tensor = Tensor::new(data, device)?;
@@ -829,12 +1069,347 @@ fn embeddings(device: &Device) -> Result<()> {
 fn cmp(device: &Device) -> Result<()> {
     let t1 = Tensor::new(&[[0f32, 1f32], [2f32, 3f32], [4f32, 5f32]], device)?;
     let t2 = Tensor::new(&[[1f32, 0f32], [3f32, 3f32], [4f32, 7f32]], device)?;
-    assert_eq!(t1.eq(&t2)?.to_vec2::<u8>()?, &[[0, 0], [0, 1], [1, 0]]);
-    assert_eq!(t1.ne(&t2)?.to_vec2::<u8>()?, &[[1, 1], [1, 0], [0, 1]]);
-    assert_eq!(t1.le(&t2)?.to_vec2::<u8>()?, &[[1, 0], [1, 1], [1, 1]]);
-    assert_eq!(t1.lt(&t2)?.to_vec2::<u8>()?, &[[1, 0], [1, 0], [0, 1]]);
-    assert_eq!(t1.gt(&t2)?.to_vec2::<u8>()?, &[[0, 1], [0, 0], [0, 0]]);
-    assert_eq!(t1.ge(&t2)?.to_vec2::<u8>()?, &[[0, 1], [0, 1], [1, 0]]);
+    // The comparisons return bool values on cpu and u8 values on cuda and metal.
+    assert_eq!(
+        t1.eq(&t2)?.to_dtype(DType::U8)?.to_vec2::<u8>()?,
+        &[[0, 0], [0, 1], [1, 0]]
+    );
+    assert_eq!(
+        t1.ne(&t2)?.to_dtype(DType::U8)?.to_vec2::<u8>()?,
+        &[[1, 1], [1, 0], [0, 1]]
+    );
+    assert_eq!(
+        t1.le(&t2)?.to_dtype(DType::U8)?.to_vec2::<u8>()?,
+        &[[1, 0], [1, 1], [1, 1]]
+    );
+    assert_eq!(
+        t1.lt(&t2)?.to_dtype(DType::U8)?.to_vec2::<u8>()?,
+        &[[1, 0], [1, 0], [0, 1]]
+    );
+    assert_eq!(
+        t1.gt(&t2)?.to_dtype(DType::U8)?.to_vec2::<u8>()?,
+        &[[0, 1], [0, 0], [0, 0]]
+    );
+    assert_eq!(
+        t1.ge(&t2)?.to_dtype(DType::U8)?.to_vec2::<u8>()?,
+        &[[0, 1], [0, 1], [1, 0]]
+    );
+    Ok(())
+}
+
+#[test]
+fn bitwise() -> Result<()> {
+    let device = &Device::Cpu;
+    let a = Tensor::new(&[0b1100u8, 0b1010, 0xff], device)?;
+    let b = Tensor::new(&[0b1010u8, 0b0110, 0x0f], device)?;
+    assert_eq!((&a & &b)?.to_vec1::<u8>()?, [0b1000, 0b0010, 0x0f]);
+    assert_eq!((&a | &b)?.to_vec1::<u8>()?, [0b1110, 0b1110, 0xff]);
+    assert_eq!((&a ^ &b)?.to_vec1::<u8>()?, [0b0110, 0b1100, 0xf0]);
+    assert_eq!((!&a)?.to_vec1::<u8>()?, [0xf3, 0xf5, 0x00]);
+    assert_eq!(
+        a.shl(4u8)?.to_vec1::<u8>()?,
+        [0b1100_0000, 0b1010_0000, 0xf0]
+    );
+    assert_eq!(
+        a.shr(&b.ones_like()?)?.to_vec1::<u8>()?,
+        [0b110, 0b101, 0x7f]
+    );
+    assert_eq!(a.shl(8u8)?.to_vec1::<u8>()?, [0, 0, 0]);
+
+    let a = Tensor::new(&[[-8i64, 5], [3, -1]], device)?;
+    let shifts = Tensor::new(&[1i64, 70], device)?;
+    assert_eq!(
+        a.broadcast_shr(&shifts)?.to_vec2::<i64>()?,
+        [[-4, 0], [1, -1]]
+    );
+    assert_eq!(
+        a.broadcast_shl(&shifts)?.to_vec2::<i64>()?,
+        [[-16, 0], [6, 0]]
+    );
+    assert_eq!((!&a)?.to_vec2::<i64>()?, [[7, -6], [-4, 0]]);
+    let a = Tensor::new(&[1u32, 2, 3], device)?;
+    assert_eq!(
+        a.bitwise_xor(u32::MAX)?.to_vec1::<u32>()?,
+        [u32::MAX - 1, u32::MAX - 2, u32::MAX - 3]
+    );
+
+    let pad = Tensor::new(&[[1u8, 1, 0]], device)?;
+    let causal = Tensor::tril2(3, DType::U8, device)?;
+    assert_eq!(
+        pad.broadcast_logical_and(&causal)?.to_vec2::<bool>()?,
+        [
+            [true, false, false],
+            [true, true, false],
+            [true, true, false]
+        ]
+    );
+    assert_eq!(
+        pad.broadcast_logical_or(&causal)?.to_vec2::<bool>()?,
+        [[true, true, false], [true, true, false], [true, true, true]]
+    );
+    let xs = Tensor::new(&[0f32, -2., 0.5], device)?;
+    assert_eq!(xs.logical_not()?.to_vec1::<bool>()?, [true, false, false]);
+    assert!(xs.bitwise_and(&xs).is_err());
+    assert!(xs.bitwise_not().is_err());
+    Ok(())
+}
+
+#[test]
+fn int_dtypes() -> Result<()> {
+    let device = &Device::Cpu;
+    let xs = Tensor::new(&[-3i8, 0, 5, 60], device)?;
+    assert_eq!(xs.dtype(), DType::I8);
+    assert_eq!((&xs + &xs)?.to_vec1::<i8>()?, [-6, 0, 10, 120]);
+    assert_eq!(xs.abs()?.to_vec1::<i8>()?, [3, 0, 5, 60]);
+    assert_eq!(xs.to_dtype(DType::I16)?.to_vec1::<i16>()?, [-3, 0, 5, 60]);
+    assert_eq!(
+        xs.to_dtype(DType::F32)?.to_vec1::<f32>()?,
+        [-3., 0., 5., 60.]
+    );
+    assert_eq!(xs.max(0)?.to_scalar::<i8>()?, 60);
+    assert_eq!(xs.sum_all()?.to_scalar::<i8>()?, 62);
+
+    let xs = Tensor::new(&[[1i32, -2], [3, -4]], device)?;
+    assert_eq!(xs.sum(1)?.to_vec1::<i32>()?, [-1, -1]);
+    assert_eq!(xs.to_dtype(DType::U16)?.to_vec2::<u16>()?, [[1, 0], [3, 0]]);
+    let ids = Tensor::new(&[1i32, 0, 1], device)?;
+    assert_eq!(
+        xs.index_select(&ids, 0)?.to_vec2::<i32>()?,
+        [[3, -4], [1, -2], [3, -4]]
+    );
+
+    let xs = Tensor::new(&[1u16, 300, 65535], device)?;
+    assert_eq!(xs.to_dtype(DType::I64)?.to_vec1::<i64>()?, [1, 300, 65535]);
+    assert_eq!(xs.to_dtype(DType::U8)?.to_vec1::<u8>()?, [1, 255, 255]);
+    assert_eq!(
+        Tensor::cat(&[&xs, &xs], 0)?.to_vec1::<u16>()?,
+        [1, 300, 65535, 1, 300, 65535]
+    );
+    let xs = Tensor::new(&[-1.7f32, 2.5, 40000.], device)?;
+    assert_eq!(
+        xs.to_dtype(DType::I16)?.to_vec1::<i16>()?,
+        [-1, 2, i16::MAX]
+    );
+    Ok(())
+}
+
+#[test]
+fn bool_dtype() -> Result<()> {
+    let device = &Device::Cpu;
+    let t1 = Tensor::new(&[0f32, 1., 2., 3.], device)?;
+    let t2 = Tensor::new(&[1f32, 1., 0., 4.], device)?;
+    let lt = t1.lt(&t2)?;
+    assert_eq!(lt.dtype(), DType::Bool);
+    assert_eq!(lt.to_vec1::<bool>()?, [true, false, false, true]);
+    let ge = t1.ge(1f64)?;
+    assert_eq!((&lt & &ge)?.to_vec1::<bool>()?, [false, false, false, true]);
+    assert_eq!((&lt | &ge)?.to_vec1::<bool>()?, [true, true, true, true]);
+    assert_eq!((&lt ^ &ge)?.to_vec1::<bool>()?, [true, true, true, false]);
+    assert_eq!(
+        lt.logical_not()?.to_vec1::<bool>()?,
+        [false, true, true, false]
+    );
+    assert_eq!(
+        lt.bitwise_not()?.to_vec1::<bool>()?,
+        [false, true, true, false]
+    );
+    // Add and maximum act as a logical or, mul and minimum as a logical and.
+    assert_eq!((&lt + &ge)?.to_vec1::<bool>()?, [true, true, true, true]);
+    assert_eq!((&lt * &ge)?.to_vec1::<bool>()?, [false, false, false, true]);
+    assert_eq!(
+        lt.maximum(&ge)?.to_vec1::<bool>()?,
+        [true, true, true, true]
+    );
+    assert_eq!(
+        lt.minimum(&ge)?.to_vec1::<bool>()?,
+        [false, false, false, true]
+    );
+    assert!((&lt / &lt).is_err());
+    assert!((&lt - &ge).is_err());
+    assert_eq!(lt.eq(&ge)?.to_vec1::<bool>()?, [false, false, false, true]);
+    assert_eq!(lt.where_cond(&t1, &t2)?.to_vec1::<f32>()?, [0., 1., 0., 3.]);
+    assert!(lt.max(0)?.to_vec0::<bool>()?);
+    assert_eq!(t1.i(&lt)?.to_vec1::<f32>()?, [0., 3.]);
+
+    let xs = Tensor::new(&[true, false, true], device)?;
+    assert_eq!(xs.dtype(), DType::Bool);
+    assert_eq!(xs.to_dtype(DType::U8)?.to_vec1::<u8>()?, [1, 0, 1]);
+    let xs = Tensor::new(&[0i64, 3, -2], device)?.to_dtype(DType::Bool)?;
+    assert_eq!(xs.to_vec1::<bool>()?, [false, true, true]);
+    assert_eq!(xs.to_dtype(DType::F32)?.to_vec1::<f32>()?, [0., 1., 1.]);
+    assert_eq!(
+        Tensor::ones(2, DType::Bool, device)?.to_vec1::<bool>()?,
+        [true, true]
+    );
+    // The arithmetic ops have no meaning on bools, the values have to be cast first.
+    assert!(xs.neg().is_err());
+    assert!(xs.abs().is_err());
+    assert!(xs.affine(2., 1.).is_err());
+    assert!(xs.sum_all().is_err());
+    assert!((&xs + &t1).is_err());
+    assert_eq!(xs.to_dtype(DType::U32)?.sum_all()?.to_vec0::<u32>()?, 2);
+    Ok(())
+}
+
+#[test]
+fn fp8_dtypes() -> Result<()> {
+    use candle_core::cpu::fp8;
+    let device = &Device::Cpu;
+    // Every finite value is converted back to the same bits.
+    for v in 0..=255u8 {
+        let f = fp8::f8e4m3_to_f32(v);
+        if !f.is_nan() {
+            assert_eq!(fp8::f32_to_f8e4m3(f), v, "{v:#x} {f}");
+        }
+        let f = fp8::f8e5m2_to_f32(v);
+        if !f.is_nan() {
+            assert_eq!(fp8::f32_to_f8e5m2(f), v, "{v:#x} {f}");
+        }
+    }
+
+    let xs = Tensor::new(&[0.5f32, -1.75, 1.0625, 1.1875, 460., 0.003, 500.], device)?;
+    let e4m3 = xs.to_dtype(DType::F8E4M3)?;
+    assert_eq!(e4m3.dtype(), DType::F8E4M3);
+    let ys = e4m3.to_dtype(DType::F32)?.to_vec1::<f32>()?;
+    assert_eq!(ys[..6], [0.5, -1.75, 1., 1.25, 448., 0.00390625]);
+    assert!(ys[6].is_nan());
+
+    let xs = Tensor::new(&[0.5f32, -1.75, 1.125, 60000., 70000.], device)?;
+    let e5m2 = xs.to_dtype(DType::BF16)?.to_dtype(DType::F8E5M2)?;
+    assert_eq!(
+        e5m2.to_dtype(DType::F32)?.to_vec1::<f32>()?,
+        [0.5, -1.75, 1., 57344., f32::INFINITY]
+    );
+    assert_eq!(
+        e5m2.to_dtype(DType::F8E4M3)?
+            .to_dtype(DType::BF16)?
+            .to_dtype(DType::F32)?
+            .to_vec1::<f32>()?[..3],
+        [0.5, -1.75, 1.]
+    );
+
+    let bits = [0x38u8, 0xb0, 0x01, 0x7e];
+    let xs = Tensor::from_raw_buffer(&bits, DType::F8E4M3, &[2, 2], device)?;
+    assert_eq!(
+        xs.to_dtype(DType::F32)?.to_vec2::<f32>()?,
+        [[1., -0.5], [0.001953125, 448.]]
+    );
+    // Moving values around works on the raw bits.
+    let xs = Tensor::cat(&[&xs.t()?, &xs.narrow(0, 1, 1)?], 0)?;
+    let mut buf = vec![];
+    xs.write_bytes(&mut buf)?;
+    assert_eq!(buf, [0x38, 0x01, 0xb0, 0x7e, 0x01, 0x7e]);
+    assert_eq!(
+        Tensor::ones(2, DType::F8E4M3, device)?
+            .to_dtype(DType::F32)?
+            .to_vec1::<f32>()?,
+        [1., 1.]
+    );
+    assert!(xs.to_vec2::<u8>().is_err());
+    assert!(xs.neg().is_err());
+    assert!(xs.sum_all().is_err());
+    Ok(())
+}
+
+#[test]
+fn grid_sample() -> Result<()> {
+    use candle_core::grid_sample::{GridSampleMode as M, GridSamplePaddingMode as P};
+    let device = &Device::Cpu;
+    // The identity transform samples the input at the center of each element.
+    let xs = Tensor::arange(0f32, 24., device)?.reshape((2, 3, 1, 4))?;
+    let theta = Tensor::new(&[[[1f32, 0., 0.], [0., 1., 0.]]], device)?.repeat((2, 1, 1))?;
+    let grid = Tensor::affine_grid(&theta, (2, 3, 1, 4), false)?;
+    assert_eq!(grid.dims(), [2, 1, 4, 2]);
+    let ys = xs.grid_sample(&grid, M::Bilinear, P::Zeros, false)?;
+    assert_eq!(
+        ys.flatten_all()?.to_vec1::<f32>()?,
+        xs.flatten_all()?.to_vec1::<f32>()?
+    );
+    // A horizontal flip.
+    let theta = Tensor::new(&[[[-1f32, 0., 0.], [0., 1., 0.]]], device)?;
+    let grid = Tensor::affine_grid(&theta, (1, 1, 1, 4), true)?;
+    let ys = xs.i(0)?.narrow(0, 0, 1)?.unsqueeze(0)?;
+    let ys = ys.grid_sample(&grid, M::Nearest, P::Zeros, true)?;
+    assert_eq!(ys.flatten_all()?.to_vec1::<f32>()?, [3., 2., 1., 0.]);
+
+    let xs = Tensor::new(&[[[[1f32, 2., 3.]]]], device)?;
+    let grid = Tensor::new(&[[[[-1f32, 0.], [1., 0.], [1.5, 0.], [0.2, 0.]]]], device)?;
+    let sample = |mode, padding_mode| -> Result<Vec<f32>> {
+        let ys = xs.grid_sample(&grid, mode, padding_mode, false)?;
+        test_utils::to_vec1_round(&ys.flatten_all()?, 4)
+    };
+    assert_eq!(sample(M::Bilinear, P::Zeros)?, [0.5, 1.5, 0., 2.3]);
+    assert_eq!(sample(M::Bilinear, P::Border)?, [1., 3., 3., 2.3]);
+    assert_eq!(sample(M::Bilinear, P::Reflection)?, [1., 3., 2.75, 2.3]);
+    assert_eq!(sample(M::Nearest, P::Zeros)?, [1., 3., 0., 2.]);
+    assert!(xs
+        .grid_sample(&grid.squeeze(0)?, M::Bilinear, P::Zeros, false)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn complex_dtypes() -> Result<()> {
+    let device = &Device::Cpu;
+    let c = |re: &[f32], im: &[f32]| {
+        Tensor::complex(&Tensor::new(re, device)?, &Tensor::new(im, device)?)
+    };
+    let parts = |t: Tensor| -> Result<Vec<Vec<f32>>> { t.view_as_real()?.to_vec2::<f32>() };
+    let a = c(&[1., 3.], &[2., -1.])?;
+    let b = c(&[2., 1.], &[0., 1.])?;
+    assert_eq!(a.dtype(), DType::C64);
+    assert_eq!(format!("{a:?}"), "Tensor[1+2i, 3-1i; c64]");
+    assert_eq!(parts((&a + &b)?)?, [[3., 2.], [4., 0.]]);
+    assert_eq!(parts((&a - &b)?)?, [[-1., 2.], [2., -2.]]);
+    assert_eq!(parts((&a * &b)?)?, [[2., 4.], [4., 2.]]);
+    assert_eq!(parts((&a / &b)?)?, [[0.5, 1.], [1., -2.]]);
+    let i = c(&[0.], &[1.])?;
+    assert_eq!(parts(a.broadcast_mul(&i)?)?, [[-2., 1.], [1., 3.]]);
+
+    assert_eq!(a.real()?.to_vec1::<f32>()?, [1., 3.]);
+    assert_eq!(a.imag()?.to_vec1::<f32>()?, [2., -1.]);
+    assert_eq!(a.abs()?.to_vec1::<f32>()?, [5f32.sqrt(), 10f32.sqrt()]);
+    assert_eq!(
+        b.angle()?.to_vec1::<f32>()?,
+        [0., std::f32::consts::FRAC_PI_4]
+    );
+    assert_eq!(parts(a.conj()?)?, [[1., -2.], [3., 1.]]);
+
+    // Complex tensors can be built from and read as complex values, the scalar ops use real values.
+    use num_complex::Complex32;
+    let z = Tensor::new(&[Complex32::new(1., 2.), Complex32::new(3., -1.)], device)?;
+    assert_eq!(z.to_vec1::<Complex32>()?, a.to_vec1::<Complex32>()?);
+    assert_eq!(z.get(1)?.to_scalar::<Complex32>()?, Complex32::new(3., -1.));
+    assert_eq!(parts(a.neg()?)?, [[-1., -2.], [-3., 1.]]);
+    assert_eq!(parts((&a * 2.)?)?, [[2., 4.], [6., -2.]]);
+    assert_eq!(parts((&a - 1.)?)?, [[0., 2.], [2., -1.]]);
+    assert_eq!(parts(a.affine(2., 1.)?)?, [[3., 4.], [7., -2.]]);
+
+    let m = c(&[1., 3., 0., 2.], &[2., -1., 1., 0.])?.reshape((2, 2))?;
+    let v = b.reshape((2, 1))?;
+    let mv = m.matmul(&v)?.squeeze(1)?;
+    assert_eq!(parts(mv)?, [[6., 6.], [2., 4.]]);
+    let mv = m
+        .to_dtype(DType::C128)?
+        .t()?
+        .matmul(&v.to_dtype(DType::C128)?)?;
+    assert_eq!(
+        mv.view_as_real()?.flatten_all()?.to_vec1::<f64>()?,
+        [1., 5., 8., 0.]
+    );
+
+    // Real values are cast with a zero imaginary part, the reverse conversion is an error.
+    let r = Tensor::new(&[1f64, -2.], device)?.to_dtype(DType::C64)?;
+    assert_eq!(parts(r.clone())?, [[1., 0.], [-2., 0.]]);
+    assert!(r.to_dtype(DType::F32).is_err());
+    assert!(r.exp().is_err());
+    let r = r.view_as_real()?.view_as_complex()?;
+    assert_eq!(r.dtype(), DType::C64);
+
+    let mut buf = vec![];
+    a.write_bytes(&mut buf)?;
+    let a2 = Tensor::from_raw_buffer(&buf, DType::C64, &[2], device)?;
+    assert_eq!(parts(a2)?, [[1., 2.], [3., -1.]]);
     Ok(())
 }
 
@@ -1020,6 +1595,61 @@ fn scatter_add(device: &Device) -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn scatter() -> Result<()> {
+    use candle_core::op::ScatterReduceOp::{Amax, Amin, Mean, Prod, Sum};
+    let device = &Device::Cpu;
+    let init = Tensor::ones((2, 4), DType::F32, device)?;
+    let ids = Tensor::new(&[[3u32, 0], [1, 2]], device)?;
+    let src = Tensor::new(&[[5f32, 6.], [7., 8.]], device)?;
+    let hs = init.scatter(&ids, &src, 1)?;
+    assert_eq!(hs.to_vec2::<f32>()?, &[[6., 1., 1., 5.], [1., 7., 8., 1.]]);
+    let ids = Tensor::new(&[[1u32, 0], [0, 1]], device)?;
+    let hs = init.narrow(1, 0, 2)?.scatter(&ids, &src, 0)?;
+    assert_eq!(hs.to_vec2::<f32>()?, &[[7., 6.], [5., 8.]]);
+    assert!(init.scatter(&ids, &src.t()?.narrow(0, 0, 1)?, 1).is_err());
+
+    let init = Tensor::new(&[1f32, 2., 3.], device)?;
+    let ids = Tensor::new(&[0u32, 0, 2, 0], device)?;
+    let src = Tensor::new(&[4f32, 5., 6., 7.], device)?;
+    let reduce = |op, include_self| -> Result<Vec<f32>> {
+        init.scatter_reduce(&ids, &src, 0, op, include_self)?
+            .to_vec1::<f32>()
+    };
+    assert_eq!(reduce(Sum, true)?, [17., 2., 9.]);
+    assert_eq!(reduce(Sum, false)?, [16., 2., 6.]);
+    assert_eq!(reduce(Prod, true)?, [140., 2., 18.]);
+    assert_eq!(reduce(Prod, false)?, [140., 2., 6.]);
+    assert_eq!(reduce(Mean, true)?, [4.25, 2., 4.5]);
+    assert_eq!(reduce(Mean, false)?, [16. / 3., 2., 6.]);
+    assert_eq!(reduce(Amax, true)?, [7., 2., 6.]);
+    assert_eq!(reduce(Amax, false)?, [7., 2., 6.]);
+    assert_eq!(reduce(Amin, true)?, [1., 2., 3.]);
+    assert_eq!(reduce(Amin, false)?, [4., 2., 6.]);
+
+    // Integer means are rounded down.
+    let hs = init.to_dtype(DType::I64)?.scatter_reduce(
+        &ids,
+        &src.to_dtype(DType::I64)?,
+        0,
+        Mean,
+        false,
+    )?;
+    assert_eq!(hs.to_vec1::<i64>()?, [5, 2, 6]);
+
+    let init = Tensor::ones((3, 2), DType::F32, device)?;
+    let ids = Tensor::new(&[0u32, 2, 0], device)?;
+    let src = Tensor::new(&[[1f32, 2.], [3., 4.], [5., 6.]], device)?;
+    let hs = init.index_reduce(&ids, &src, 0, Amax, false)?;
+    assert_eq!(hs.to_vec2::<f32>()?, &[[5., 6.], [1., 1.], [3., 4.]]);
+    let hs = init.index_reduce(&ids, &src, 0, Prod, true)?;
+    assert_eq!(hs.to_vec2::<f32>()?, &[[5., 12.], [1., 1.], [3., 4.]]);
+    let hs = init.t()?.index_reduce(&ids, &src.t()?, 1, Sum, true)?;
+    assert_eq!(hs.to_vec2::<f32>()?, &[[7., 1., 4.], [9., 1., 5.]]);
+    assert!(init.index_reduce(&ids, &src, 1, Sum, true).is_err());
+    Ok(())
+}
+
 fn gather(device: &Device) -> Result<()> {
     let ids = Tensor::new(&[[0u32], [2u32], [1u32], [0u32]], device)?;
     let t = Tensor::arange(0f32, 12f32, device)?.reshape((4, 3))?;
@@ -1460,6 +2090,71 @@ fn randn(device: &Device) -> Result<()> {
     Ok(())
 }
 
+fn generator(device: &Device) -> Result<()> {
+    use candle_core::Generator;
+    let mut gen = Generator::new(299792458);
+    let state = gen.get_state();
+    let t1 = Tensor::randn_with_generator(0f32, 1f32, (3, 4), device, &mut gen)?;
+    let t2 = Tensor::randn_with_generator(0f32, 1f32, (3, 4), device, &mut gen)?;
+    assert_ne!(t1.to_vec2::<f32>()?, t2.to_vec2::<f32>()?);
+    // Restoring the state or using the same seed results in the same values.
+    gen.set_state(&state)?;
+    let t3 = Tensor::randn_with_generator(0f32, 1f32, (3, 4), device, &mut gen)?;
+    assert_eq!(t1.to_vec2::<f32>()?, t3.to_vec2::<f32>()?);
+    let mut gen2 = Generator::new(299792458);
+    let t4 = Tensor::randn_with_generator(0f32, 1f32, (3, 4), device, &mut gen2)?;
+    assert_eq!(t1.to_vec2::<f32>()?, t4.to_vec2::<f32>()?);
+    assert!(gen.set_state(&state[1..]).is_err());
+    assert!(gen.set_state(&[0u8; 32]).is_err());
+
+    let t = Tensor::rand_with_generator(1f64, 3f64, 10000, device, &mut gen)?;
+    let v = t.to_vec1::<f64>()?;
+    assert!(v.iter().all(|v| (1. ..3.).contains(v)));
+    let mean = t.mean_all()?.to_scalar::<f64>()?;
+    assert!((mean - 2.).abs() < 0.05, "{mean}");
+    let t = Tensor::randn_with_generator(1f64, 2f64, 10000, device, &mut gen)?;
+    let mean = t.mean_all()?.to_scalar::<f64>()?;
+    let var = t.broadcast_sub(&t.mean_all()?)?.sqr()?.mean_all()?;
+    assert!((mean - 1.).abs() < 0.1, "{mean}");
+    assert!((var.to_scalar::<f64>()? - 4.).abs() < 0.3, "{var}");
+
+    let t = Tensor::randint(-3, 2, (4, 50), device, &mut gen)?;
+    assert_eq!(t.dtype(), DType::I64);
+    let v = t.flatten_all()?.to_vec1::<i64>()?;
+    assert!(v.iter().all(|v| (-3..2).contains(v)));
+    assert!((-3..2).all(|i| v.contains(&i)));
+    assert!(Tensor::randint(2, 2, 1, device, &mut gen).is_err());
+
+    let mut perm = Tensor::randperm(20, device, &mut gen)?.to_vec1::<u32>()?;
+    assert_ne!(perm, (0..20).collect::<Vec<_>>());
+    perm.sort();
+    assert_eq!(perm, (0..20).collect::<Vec<_>>());
+
+    let probs = Tensor::new(&[[0f32, 1.], [1., 0.]], device)?;
+    let t = probs.bernoulli(&mut gen)?;
+    assert_eq!(t.to_vec2::<f32>()?, [[0., 1.], [1., 0.]]);
+    let t = Tensor::full(0.25f32, 10000, device)?.bernoulli(&mut gen)?;
+    let mean = t.mean_all()?.to_scalar::<f32>()?;
+    assert!((mean - 0.25).abs() < 0.05, "{mean}");
+    assert!(Tensor::new(&[1.5f32], device)?.bernoulli(&mut gen).is_err());
+
+    let weights = Tensor::new(&[[0f32, 3., 0., 1.], [1., 1., 1., 1.]], device)?;
+    let t = weights.multinomial(2, false, &mut gen)?.to_vec2::<u32>()?;
+    let mut first = t[0].clone();
+    first.sort();
+    assert_eq!(first, [1, 3]);
+    assert_ne!(t[1][0], t[1][1]);
+    assert!(weights.multinomial(3, false, &mut gen).is_err());
+    let t = weights.i(0)?.multinomial(10000, true, &mut gen)?;
+    let counts = t.bincount(None, 4)?.to_vec1::<u32>()?;
+    assert_eq!((counts[0], counts[2]), (0, 0));
+    assert!(
+        (counts[1] as f32 / 10000. - 0.75).abs() < 0.05,
+        "{counts:?}"
+    );
+    Ok(())
+}
+
 fn zero_dim(device: &Device) -> Result<()> {
     let t = Tensor::zeros((4, 0, 1), DType::F32, device)?;
     assert_eq!(t.dims3()?, (4, 0, 1));
@@ -1488,6 +2183,8 @@ test_device!(arange, arange_cpu, arange_gpu, arange_metal);
 test_device!(add_mul, add_mul_cpu, add_mul_gpu, add_mul_metal);
 test_device!(tensor_2d, tensor_2d_cpu, tensor_2d_gpu, tensor_2d_metal);
 test_device!(narrow, narrow_cpu, narrow_gpu, narrow_metal);
+test_device!(unfold, unfold_cpu, unfold_gpu, unfold_metal);
+test_device!(pad, pad_cpu, pad_gpu, pad_metal);
 test_device!(broadcast, broadcast_cpu, broadcast_gpu, broadcast_metal);
 test_device!(slice_set, ss_cpu, ss_gpu, ss_metal);
 test_device!(cat, cat_cpu, cat_gpu, cat_metal);
@@ -1530,7 +2227,18 @@ test_device!(
 test_device!(randn, randn_cpu, randn_gpu, randn_metal);
 test_device!(clamp, clamp_cpu, clamp_gpu, clamp_metal);
 test_device!(asort, asort_cpu, asort_gpu, asort_metal);
+test_device!(sort, sort_cpu, sort_gpu, sort_metal);
+test_device!(topk, topk_cpu, topk_gpu, topk_metal);
+test_device!(generator, generator_cpu, generator_gpu, generator_metal);
+test_device!(masking, masking_cpu, masking_gpu, masking_metal);
 test_device!(var, var_cpu, var_gpu, var_metal);
+test_device!(
+    triangular_diagonal,
+    triangular_diagonal_cpu,
+    triangular_diagonal_gpu,
+    triangular_diagonal_metal
+);
+test_device!(reductions, reductions_cpu, reductions_gpu, reductions_metal);
 test_device!(zero_dim, zero_dim_cpu, zero_dim_gpu, zero_dim_metal);
 
 // There was originally a bug on the CPU implementation for randn
@@ -1560,6 +2268,117 @@ fn pad_with_same() -> Result<()> {
     Ok(())
 }
 
+fn pad(device: &Device) -> Result<()> {
+    use candle_core::pad::PadMode;
+    let t = Tensor::new(&[1f32, 2., 3.], device)?;
+    let p = |pad, mode| t.pad(&[pad], mode)?.to_vec1::<f32>();
+    assert_eq!(p((2, 1), PadMode::Reflect)?, [3., 2., 1., 2., 3., 2.]);
+    assert_eq!(
+        p((4, 4), PadMode::Reflect)?,
+        [1., 2., 3., 2., 1., 2., 3., 2., 1., 2., 3.]
+    );
+    assert_eq!(p((2, 1), PadMode::Replicate)?, [1., 1., 1., 2., 3., 3.]);
+    assert_eq!(p((2, 1), PadMode::Circular)?, [2., 3., 1., 2., 3., 1.]);
+    assert_eq!(p((0, 0), PadMode::Circular)?, [1., 2., 3.]);
+    let t = Tensor::new(&[1u32, 2, 3], device)?;
+    let t = t.pad(&[(1, 2)], PadMode::Constant(7.))?;
+    assert_eq!(t.to_vec1::<u32>()?, [7, 1, 2, 3, 7, 7]);
+
+    // The padding applies to the trailing dimensions.
+    let t = Tensor::arange(0u32, 6, device)?.reshape((2, 3))?;
+    let p = t.pad(&[(1, 1), (1, 0)], PadMode::Circular)?;
+    assert_eq!(
+        p.to_vec2::<u32>()?,
+        [[5, 3, 4, 5], [2, 0, 1, 2], [5, 3, 4, 5], [2, 0, 1, 2]]
+    );
+    let p = t.pad(&[(0, 1)], PadMode::Constant(0.))?;
+    assert_eq!(p.to_vec2::<u32>()?, [[0, 1, 2, 0], [3, 4, 5, 0]]);
+    assert!(t.pad(&[(1, 1); 3], PadMode::Reflect).is_err());
+    let t = Tensor::zeros((2, 0), DType::F32, device)?;
+    assert!(t.pad(&[(1, 1)], PadMode::Reflect).is_err());
+    assert_eq!(t.pad(&[(1, 1)], PadMode::Constant(1.))?.dims(), [2, 2]);
+    Ok(())
+}
+
+#[test]
+fn bincount_histc() -> Result<()> {
+    let dev = &Device::Cpu;
+    let t = Tensor::new(&[1i64, 3, 1, 0], dev)?;
+    assert_eq!(t.bincount(None, 0)?.to_vec1::<u32>()?, [1, 2, 0, 1]);
+    assert_eq!(t.bincount(None, 6)?.to_vec1::<u32>()?, [1, 2, 0, 1, 0, 0]);
+    let w = Tensor::new(&[0.5f64, 1., 2., 1.], dev)?;
+    let c = t.bincount(Some(&w), 0)?;
+    assert_eq!(c.dtype(), DType::F64);
+    assert_eq!(c.to_vec1::<f64>()?, [1., 2.5, 0., 1.]);
+    // The rows of a 2d input are counted separately.
+    let t = Tensor::new(&[[0u8, 0, 2], [1, 1, 1]], dev)?;
+    assert_eq!(
+        t.bincount(None, 0)?.to_vec2::<u32>()?,
+        [[2, 0, 1], [0, 3, 0]]
+    );
+    assert_eq!(
+        t.t()?.bincount(None, 0)?.to_vec2::<u32>()?,
+        [[1, 1, 0], [1, 1, 0], [0, 1, 1]]
+    );
+    assert!(Tensor::new(&[1i64, -1], dev)?.bincount(None, 0).is_err());
+    assert!(Tensor::new(&[1f32], dev)?.bincount(None, 0).is_err());
+    let t = Tensor::zeros(0, DType::U32, dev)?;
+    assert_eq!(t.bincount(None, 2)?.to_vec1::<u32>()?, [0, 0]);
+
+    let t = Tensor::new(&[1f32, 2., 1., 4., f32::NAN], dev)?;
+    assert_eq!(t.histc(4, 0., 3.)?.to_vec1::<f32>()?, [0., 2., 1., 0.]);
+    assert_eq!(t.histc(3, 1., 4.)?.to_vec1::<f32>()?, [2., 1., 1.]);
+    // Use the range of the values.
+    let t = Tensor::new(&[[1f64, 2.], [3., 3.]], dev)?;
+    assert_eq!(t.histc(2, 0., 0.)?.to_vec1::<f64>()?, [1., 3.]);
+    assert!(t.histc(0, 0., 1.).is_err());
+    Ok(())
+}
+
+#[test]
+fn unique_searchsorted() -> Result<()> {
+    let dev = &Device::Cpu;
+    let t = Tensor::new(&[[3u32, 1], [3, 2]], dev)?;
+    let (values, inverse, counts) = t.unique(true, true, true)?;
+    assert_eq!(values.to_vec1::<u32>()?, [1, 2, 3]);
+    assert_eq!(inverse.unwrap().to_vec2::<u32>()?, [[2, 0], [2, 1]]);
+    assert_eq!(counts.unwrap().to_vec1::<u32>()?, [1, 1, 2]);
+    let (values, inverse, counts) = t.unique(false, false, true)?;
+    assert_eq!(values.to_vec1::<u32>()?, [3, 1, 2]);
+    assert!(inverse.is_none());
+    assert_eq!(counts.unwrap().to_vec1::<u32>()?, [2, 1, 1]);
+    let t = Tensor::new(&[0.5f32, -1., 0.5, 0.5], dev)?;
+    let (values, inverse, counts) = t.unique(false, true, false)?;
+    assert_eq!(values.to_vec1::<f32>()?, [0.5, -1.]);
+    assert_eq!(inverse.unwrap().to_vec1::<u32>()?, [0, 1, 0, 0]);
+    assert!(counts.is_none());
+
+    let seq = Tensor::new(&[1f32, 3., 3., 5.], dev)?;
+    let values = Tensor::new(&[3f32, 0., 6., 4.], dev)?;
+    assert_eq!(
+        seq.searchsorted(&values, false)?.to_vec1::<u32>()?,
+        [1, 0, 4, 3]
+    );
+    assert_eq!(
+        seq.searchsorted(&values, true)?.to_vec1::<u32>()?,
+        [3, 0, 4, 3]
+    );
+    // Batched sequences, the values are converted to the sequence dtype.
+    let seq = Tensor::new(&[[1i64, 3, 5], [2, 4, 6]], dev)?;
+    let values = Tensor::new(&[[3u32, 5], [7, 2]], dev)?;
+    assert_eq!(
+        seq.searchsorted(&values, false)?.to_vec2::<u32>()?,
+        [[1, 2], [3, 0]]
+    );
+    assert_eq!(
+        seq.searchsorted(&values, true)?.to_vec2::<u32>()?,
+        [[2, 3], [3, 1]]
+    );
+    let values = Tensor::new(&[3i64, 5], dev)?;
+    assert!(seq.searchsorted(&values, false).is_err());
+    Ok(())
+}
+
 #[test]
 fn i64_abs() -> Result<()> {
     let t = Tensor::new(&[-42i64, 1337], &Device::Cpu)?;
@@ -1630,6 +2449,54 @@ fn cumsum() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn scan() -> Result<()> {
+    let device = &Device::Cpu;
+    let t = Tensor::new(&[[3u32, 1, 4], [1, 5, 9]], device)?;
+    assert_eq!(t.cumsum(0)?.to_vec2::<u32>()?, [[3, 1, 4], [4, 6, 13]]);
+    assert_eq!(
+        t.t()?.cumsum(1)?.to_vec2::<u32>()?,
+        [[3, 4], [1, 6], [4, 13]]
+    );
+    assert_eq!(t.cumprod(1)?.to_vec2::<u32>()?, [[3, 3, 12], [1, 5, 45]]);
+    let t = Tensor::ones(100_000, DType::I64, device)?.cumsum(0)?;
+    assert_eq!(t.i(99_999)?.to_scalar::<i64>()?, 100_000);
+
+    let t = Tensor::new(&[[2f32, 1., 2., 3.], [0., 4., -1., 4.]], device)?;
+    let (values, indexes) = t.cummax(1)?;
+    assert_eq!(
+        values.to_vec2::<f32>()?,
+        [[2., 2., 2., 3.], [0., 4., 4., 4.]]
+    );
+    assert_eq!(indexes.to_vec2::<u32>()?, [[0, 0, 2, 3], [0, 1, 1, 3]]);
+    let (values, indexes) = t.cummin(D::Minus1)?;
+    assert_eq!(
+        values.to_vec2::<f32>()?,
+        [[2., 1., 1., 1.], [0., 0., -1., -1.]]
+    );
+    assert_eq!(indexes.to_vec2::<u32>()?, [[0, 1, 1, 1], [0, 0, 2, 2]]);
+    let (values, indexes) = t.cummax(0)?;
+    assert_eq!(
+        values.to_vec2::<f32>()?,
+        [[2., 1., 2., 3.], [2., 4., 2., 4.]]
+    );
+    assert_eq!(indexes.to_vec2::<u32>()?, [[0, 0, 0, 0], [0, 1, 0, 1]]);
+
+    let t = Tensor::new(&[[-1000f32, 1000., 1000.], [1., 2., 3.]], device)?;
+    let t = t.logcumsumexp(1)?;
+    assert_eq!(
+        test_utils::to_vec2_round(&t, 4)?,
+        [[-1000., 1000., 1000.6932], [1., 2.3133, 3.4076]]
+    );
+    let t = Tensor::new(&[f32::NEG_INFINITY, 0.], device)?;
+    assert_eq!(
+        t.logcumsumexp(0)?.to_vec1::<f32>()?,
+        [f32::NEG_INFINITY, 0.]
+    );
+    assert!(Tensor::new(&[1u32, 2], device)?.logcumsumexp(0).is_err());
+    Ok(())
+}
+
 /// A helper function for floating point comparison. Both a and b must be 1D Tensor and contains the same amount of data.
 /// Assertion passes if the difference of all pairs of a and b is smaller than epsilon.
 fn assert_close(a: &Tensor, b: &Tensor, epsilon: f64) -> Result<()> {
@@ -1671,6 +2538,57 @@ fn log_sum_exp() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn floor_div_remainder() -> Result<()> {
+    let device = &Device::Cpu;
+    let a = Tensor::new(&[7i64, -7, 7, -7, 0, i64::MIN, 5], device)?;
+    let b = Tensor::new(&[2i64, 2, -2, -2, 3, -1, 0], device)?;
+    assert_eq!(
+        a.div(&b.maximum(1i64)?)?.to_vec1::<i64>()?,
+        [3, -3, 7, -7, 0, i64::MIN, 5]
+    );
+    assert_eq!(
+        a.floor_div(&b)?.to_vec1::<i64>()?,
+        [3, -4, -4, 3, 0, i64::MIN, 0]
+    );
+    assert_eq!(a.remainder(&b)?.to_vec1::<i64>()?, [1, 1, -1, -1, 0, 0, 0]);
+    assert_eq!(a.fmod(&b)?.to_vec1::<i64>()?, [1, -1, 1, -1, 0, 0, 0]);
+    // Large values that are not representable exactly as f32.
+    let a = Tensor::new(&[(1i64 << 40) + 3, -(1i64 << 40) - 3], device)?;
+    assert_eq!(a.remainder(4i64)?.to_vec1::<i64>()?, [3, 1]);
+    assert_eq!(
+        a.floor_div(4i64)?.to_vec1::<i64>()?,
+        [1 << 38, -(1 << 38) - 1]
+    );
+
+    let a = Tensor::new(&[7u32, 9, 3], device)?;
+    let b = Tensor::new(&[2u32, 3, 0], device)?;
+    assert_eq!(a.floor_div(&b)?.to_vec1::<u32>()?, [3, 3, 0]);
+    assert_eq!(a.remainder(&b)?.to_vec1::<u32>()?, [1, 0, 0]);
+    assert_eq!(a.fmod(&b)?.to_vec1::<u32>()?, [1, 0, 0]);
+
+    let a = Tensor::new(&[[5.5f32, -5.5], [3., -3.]], device)?;
+    let b = Tensor::new(&[2f32, -2.], device)?;
+    assert_eq!(
+        a.broadcast_floor_div(&b)?.to_vec2::<f32>()?,
+        [[2., 2.], [1., 1.]]
+    );
+    assert_eq!(
+        a.broadcast_remainder(&b)?.to_vec2::<f32>()?,
+        [[1.5, -1.5], [1., -1.]]
+    );
+    assert_eq!(
+        a.broadcast_fmod(&b)?.to_vec2::<f32>()?,
+        [[1.5, -1.5], [1., -1.]]
+    );
+    assert_eq!(
+        a.remainder(-2f32)?.to_vec2::<f32>()?,
+        [[-0.5, -1.5], [-1., -1.]]
+    );
+    assert_eq!(a.fmod(-2f32)?.to_vec2::<f32>()?, [[1.5, -1.5], [1., -1.]]);
+    Ok(())
+}
+
 #[test]
 fn pow() -> Result<()> {
     let lhs = Tensor::new(&[[1f32, 2., 3.], [4., 5., 6.]], &Device::Cpu)?;
@@ -1682,3 +2600,65 @@ fn pow() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn einsum() -> Result<()> {
+    let dev = &Device::Cpu;
+    let a = Tensor::arange(0f32, 24., dev)?.reshape((2, 3, 4))?;
+    let b = Tensor::arange(0f32, 40., dev)?.reshape((2, 5, 4))?;
+    let c = Tensor::einsum("bqd,bkd->bqk", &[&a, &b])?;
+    assert_eq!(c.dims(), [2, 3, 5]);
+    assert_eq!(
+        c.to_vec3::<f32>()?,
+        a.matmul(&b.transpose(1, 2)?)?.to_vec3::<f32>()?
+    );
+    // Implicit output, the labels that appear once are sorted alphabetically.
+    let c = Tensor::einsum("bkd,bqd", &[&b, &a])?;
+    assert_eq!(c.dims(), [5, 3]);
+    assert_eq!(
+        c.to_vec2::<f32>()?,
+        Tensor::einsum("bkd,bqd->kq", &[&b, &a])?.to_vec2::<f32>()?
+    );
+    let c = Tensor::einsum("bqd->db", &[&a])?;
+    assert_eq!(c.to_vec2::<f32>()?, a.sum(1)?.t()?.to_vec2::<f32>()?);
+    let c = Tensor::einsum("ijk->", &[&a])?;
+    assert_eq!(c.to_scalar::<f32>()?, 276.);
+
+    // Outer product and dot product.
+    let x = Tensor::new(&[1f32, 2., 3.], dev)?;
+    let y = Tensor::new(&[4f32, 5.], dev)?;
+    let c = Tensor::einsum("i,j->ij", &[&x, &y])?;
+    assert_eq!(c.to_vec2::<f32>()?, [[4., 5.], [8., 10.], [12., 15.]]);
+    let c = Tensor::einsum("i,i", &[&x, &x])?;
+    assert_eq!(c.to_scalar::<f32>()?, 14.);
+
+    // Diagonal and trace.
+    let m = Tensor::arange(0f32, 9., dev)?.reshape((3, 3))?;
+    let c = Tensor::einsum("ii->i", &[&m])?;
+    assert_eq!(c.to_vec1::<f32>()?, [0., 4., 8.]);
+    let c = Tensor::einsum("ii", &[&m])?;
+    assert_eq!(c.to_scalar::<f32>()?, 12.);
+    let c = Tensor::einsum("bii->b", &[&m.unsqueeze(0)?.repeat((2, 1, 1))?])?;
+    assert_eq!(c.to_vec1::<f32>()?, [12., 12.]);
+
+    // Ellipsis with broadcasting.
+    let m = Tensor::arange(0f32, 6., dev)?.reshape((1, 2, 3))?;
+    let c = Tensor::einsum("...ij,jk->...ik", &[&m, &m.squeeze(0)?.t()?])?;
+    assert_eq!(c.to_vec3::<f32>()?, [[[5., 14.], [14., 50.]]]);
+    let w = Tensor::ones((4, 1, 3, 2), DType::F32, dev)?;
+    let c = Tensor::einsum("...ij,...jk->...ik", &[&m, &w])?;
+    assert_eq!(c.dims(), [4, 1, 2, 2]);
+    assert_eq!(c.i((3, 0))?.to_vec2::<f32>()?, [[3., 3.], [12., 12.]]);
+    let c = Tensor::einsum("...j->j", &[&w])?;
+    assert_eq!(c.to_vec1::<f32>()?, [12., 12.]);
+
+    // Three operands.
+    let c = Tensor::einsum("i,ij,j->", &[&x, &m.squeeze(0)?.t()?, &y])?;
+    assert_eq!(c.to_scalar::<f32>()?, 162.);
+
+    assert!(Tensor::einsum("ij,jk->ik", &[&x, &y]).is_err());
+    assert!(Tensor::einsum("i,i->i", &[&x, &y]).is_err());
+    assert!(Tensor::einsum("i->j", &[&x]).is_err());
+    assert!(Tensor::einsum("i,j", &[&x]).is_err());
+    Ok(())
+}
@@ -255,6 +255,24 @@ pub fn dropout(xs: &Tensor, drop_p: f32) -> Result<Tensor> {
         candle::bail!("dropout probability has to be in [0, 1), got {drop_p}")
     }
     let rand = Tensor::rand(0f32, 1f32, xs.shape(), xs.device())?;
+    apply_dropout(xs, drop_p, &rand)
+}
+
+/// Same as `dropout` but the mask is sampled with the generator `gen` so that it can be
+/// reproduced.
+pub fn dropout_with_generator(
+    xs: &Tensor,
+    drop_p: f32,
+    gen: &mut candle::Generator,
+) -> Result<Tensor> {
+    if !(0. ..1.).contains(&drop_p) {
+        candle::bail!("dropout probability has to be in [0, 1), got {drop_p}")
+    }
+    let rand = Tensor::rand_with_generator(0f32, 1f32, xs.shape(), xs.device(), gen)?;
+    apply_dropout(xs, drop_p, &rand)
+}
+
+fn apply_dropout(xs: &Tensor, drop_p: f32, rand: &Tensor) -> Result<Tensor> {
     let scale = 1.0 / (1.0 - drop_p as f64);
     let drop_p = Tensor::new(drop_p, xs.device())?.broadcast_as(xs.shape())?;
     let mask = (rand.ge(&drop_p)?.to_dtype(xs.dtype())? * scale)?;
@@ -294,7 +312,7 @@ impl candle::CustomOp1 for SoftmaxLastDim {
     }
 
     fn cpu_fwd(&self, storage: &CpuStorage, layout: &Layout) -> Result<(CpuStorage, Shape)> {
-        fn softmax<T: candle::WithDType + num_traits::Float>(
+        fn softmax<T: candle::NumDType + num_traits::Float>(
             src: &[T],
             layout: &Layout,
         ) -> Result<(CpuStorage, Shape)> {
@@ -934,17 +952,17 @@ pub fn pixel_unshuffle(xs: &Tensor, downscale_factor: usize) -> Result<Tensor> {
 
 // https://pytorch.org/docs/stable/generated/torch.nn.ReplicationPad2d.html
 pub fn replication_pad2d(xs: &Tensor, pad: usize) -> Result<Tensor> {
-    match pad {
-        0 => Ok(xs.clone()),
-        1 => {
-            let (_b_size, _c, h, w) = xs.dims4()?;
-            let (first, last) = (xs.narrow(3, 0, 1)?, xs.narrow(3, w - 1, 1)?);
-            let xs = Tensor::cat(&[&first, xs, &last], 3)?;
-            let (first, last) = (xs.narrow(2, 0, 1)?, xs.narrow(2, h - 1, 1)?);
-            Tensor::cat(&[&first, &xs, &last], 2)
-        }
-        n => candle::bail!("replication-pad with a size of {n} is not supported"),
+    let (_b_size, _c, _h, _w) = xs.dims4()?;
+    xs.pad(&[(pad, pad), (pad, pad)], candle::pad::PadMode::Replicate)
+}
+
+// https://pytorch.org/docs/stable/generated/torch.nn.ReflectionPad2d.html
+pub fn reflection_pad2d(xs: &Tensor, pad: usize) -> Result<Tensor> {
+    let (_b_size, _c, h, w) = xs.dims4()?;
+    if pad >= h || pad >= w {
+        candle::bail!("reflection-pad of {pad} should be smaller than the input size ({h}, {w})")
     }
+    xs.pad(&[(pad, pad), (pad, pad)], candle::pad::PadMode::Reflect)
 }
 
 #[derive(Clone, Debug)]
@@ -719,6 +719,148 @@ impl Backend for ShardedSafeTensors {
     }
 }
 
+/// A backend for safetensors checkpoints that store fp8 weights together with their scales.
+///
+/// When a tensor `name` is stored with a fp8 dtype, it is dequantized on the cpu by multiplying it
+/// with the companion `{name}_scale` tensor, or `{name}_scale_inv` as used by DeepSeek-V3, before
+/// being converted to the requested dtype and moved to the target device. The scale can be a
+/// single value, have the same rank as the weight with some dimensions of size 1, or hold a value
+/// per block of the weight. Fp8 tensors without a scale are only converted.
+pub struct Fp8SafeTensors {
+    tensors: candle::safetensors::MmapedSafetensors,
+    block_size: Option<(usize, usize)>,
+}
+
+impl Fp8SafeTensors {
+    /// Initializes a `VarBuilder` that retrieves and dequantizes tensors stored in a collection of
+    /// safetensors files.
+    ///
+    /// `block_size` is the size of the blocks over the last two dimensions used by block-wise
+    /// scales, i.e. `weight_block_size` in the quantization config of the checkpoint. When not
+    /// specified, the blocks have to split the weight dimensions evenly.
+    ///
+    /// # Safety
+    ///
+    /// The unsafe is inherited from [`memmap2::MmapOptions`].
+    pub unsafe fn var_builder<'a, P: AsRef<std::path::Path>>(
+        paths: &[P],
+        block_size: Option<(usize, usize)>,
+        dtype: DType,
+        dev: &Device,
+    ) -> Result<VarBuilder<'a>> {
+        let tensors = candle::safetensors::MmapedSafetensors::multi(paths)?;
+        let backend = Fp8SafeTensors {
+            tensors,
+            block_size,
+        };
+        Ok(VarBuilder::from_backend(
+            Box::new(backend),
+            dtype,
+            dev.clone(),
+        ))
+    }
+
+    fn scale(&self, name: &str) -> Result<Option<Tensor>> {
+        for suffix in ["_scale", "_scale_inv"] {
+            let scale_name = format!("{name}{suffix}");
+            if self.tensors.get(&scale_name).is_ok() {
+                let scale = self.tensors.load(&scale_name, &Device::Cpu)?;
+                return Ok(Some(scale));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Multiplies the fp8 values `ws` by `scale` and returns the result as f32 values.
+    fn dequantize(&self, ws: &Tensor, scale: &Tensor) -> Result<Tensor> {
+        let ws = ws.to_dtype(DType::F32)?;
+        let scale = scale.to_dtype(DType::F32)?;
+        if scale.elem_count() == 1 {
+            return ws.broadcast_mul(&scale.flatten_all()?.squeeze(0)?);
+        }
+        let rank = ws.rank();
+        if scale.rank() != rank {
+            candle::bail!(
+                "fp8 scale of shape {:?} is incompatible with weight shape {:?}",
+                scale.shape(),
+                ws.shape()
+            )
+        }
+        let mut scale = scale;
+        for dim in 0..rank {
+            let (size, n_blocks) = (ws.dim(dim)?, scale.dim(dim)?);
+            if n_blocks == size || n_blocks == 1 {
+                continue;
+            }
+            let block_size = match self.block_size {
+                Some((b, _)) if dim + 2 == rank => b,
+                Some((_, b)) if dim + 1 == rank => b,
+                _ if size % n_blocks == 0 => size / n_blocks,
+                _ => candle::bail!(
+                    "fp8 scale of shape {:?} does not split weight shape {:?} in blocks",
+                    scale.shape(),
+                    ws.shape()
+                ),
+            };
+            if size.div_ceil(block_size) != n_blocks {
+                candle::bail!(
+                    "fp8 scale of shape {:?} does not match weight shape {:?} with blocks of {block_size}",
+                    scale.shape(),
+                    ws.shape()
+                )
+            }
+            // Repeat each scale value over its block, the last block can be partial.
+            let mut dims = scale.dims().to_vec();
+            dims.insert(dim + 1, block_size);
+            scale = scale.unsqueeze(dim + 1)?.broadcast_as(dims.as_slice())?;
+            dims.remove(dim + 1);
+            dims[dim] = n_blocks * block_size;
+            scale = scale.reshape(dims)?.narrow(dim, 0, size)?;
+        }
+        ws.broadcast_mul(&scale)
+    }
+}
+
+impl SimpleBackend for Fp8SafeTensors {
+    fn get(
+        &self,
+        s: Shape,
+        name: &str,
+        _: crate::Init,
+        dtype: DType,
+        dev: &Device,
+    ) -> Result<Tensor> {
+        let is_fp8 = matches!(
+            self.tensors.get(name)?.dtype(),
+            safetensors::Dtype::F8_E4M3 | safetensors::Dtype::F8_E5M2
+        );
+        let tensor = if is_fp8 {
+            // The fp8 values are only supported on the cpu.
+            let tensor = self.tensors.load(name, &Device::Cpu)?;
+            let tensor = match self.scale(name)? {
+                Some(scale) => self.dequantize(&tensor, &scale)?,
+                None => tensor,
+            };
+            tensor.to_dtype(dtype)?.to_device(dev)?
+        } else {
+            self.tensors.load(name, dev)?.to_dtype(dtype)?
+        };
+        if tensor.shape() != &s {
+            Err(candle::Error::UnexpectedShape {
+                msg: format!("shape mismatch for {name}"),
+                expected: s,
+                got: tensor.shape().clone(),
+            }
+            .bt())?
+        }
+        Ok(tensor)
+    }
+
+    fn contains_tensor(&self, name: &str) -> bool {
+        self.tensors.get(name).is_ok()
+    }
+}
+
 /// This traits specifies a way to rename the queried names into names that are stored in an inner
 /// VarBuilder.
 pub trait Renamer {
@@ -155,6 +155,22 @@ fn softmax_numerical_stability() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn dropout_with_generator() -> Result<()> {
+    let dev = &Device::Cpu;
+    let xs = Tensor::ones(1000, candle::DType::F32, dev)?;
+    let mut gen = candle::Generator::new(42);
+    let state = gen.get_state();
+    let ys1 = candle_nn::ops::dropout_with_generator(&xs, 0.2, &mut gen)?.to_vec1::<f32>()?;
+    gen.set_state(&state)?;
+    let ys2 = candle_nn::ops::dropout_with_generator(&xs, 0.2, &mut gen)?.to_vec1::<f32>()?;
+    assert_eq!(ys1, ys2);
+    assert!(ys1.iter().all(|&v| v == 0. || v == 1.25));
+    let dropped = ys1.iter().filter(|&&v| v == 0.).count();
+    assert!((150..250).contains(&dropped), "{dropped}");
+    Ok(())
+}
+
 fn ropei(device: &Device) -> Result<()> {
     use rand::{rngs::StdRng, Rng, SeedableRng};
 
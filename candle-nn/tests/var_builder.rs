@@ -0,0 +1,63 @@
+#[cfg(feature = "mkl")]
+extern crate intel_mkl_src;
+
+#[cfg(feature = "accelerate")]
+extern crate accelerate_src;
+
+use candle::{DType, Device, Result, Tensor};
+use candle_nn::var_builder::Fp8SafeTensors;
+use std::collections::HashMap;
+
+#[test]
+fn fp8_var_builder() -> Result<()> {
+    let dev = &Device::Cpu;
+    let fp8 = |t: Tensor| t.to_dtype(DType::F8E4M3);
+    let tensors = HashMap::from([
+        (
+            "a.weight",
+            fp8(Tensor::new(&[[1f32, 2.], [-0.5, 3.]], dev)?)?,
+        ),
+        ("a.weight_scale", Tensor::new(0.5f32, dev)?),
+        ("b.weight", fp8(Tensor::ones((3, 5), DType::F32, dev)?)?),
+        (
+            "b.weight_scale_inv",
+            Tensor::new(&[[1f32, 2., 3.], [4., 5., 6.]], dev)?,
+        ),
+        ("c.weight", fp8(Tensor::new(&[[1f32, 2.], [3., 4.]], dev)?)?),
+        ("c.weight_scale", Tensor::new(&[[2f32], [0.5]], dev)?),
+        ("d.weight", Tensor::new(&[0.1f32, 0.2], dev)?),
+    ]);
+    let path =
+        std::env::temp_dir().join(format!("candle-fp8-vb-{}.safetensors", std::process::id()));
+    candle::safetensors::save(&tensors, &path)?;
+
+    let vb = unsafe { Fp8SafeTensors::var_builder(&[&path], Some((2, 2)), DType::F32, dev)? };
+    let a = vb.get((2, 2), "a.weight")?;
+    assert_eq!(a.to_vec2::<f32>()?, [[0.5, 1.], [-0.25, 1.5]]);
+    let b = vb.get((3, 5), "b.weight")?;
+    assert_eq!(
+        b.to_vec2::<f32>()?,
+        [
+            [1., 1., 2., 2., 3.],
+            [1., 1., 2., 2., 3.],
+            [4., 4., 5., 5., 6.]
+        ]
+    );
+    let c = vb.pp("c").get((2, 2), "weight")?;
+    assert_eq!(c.to_vec2::<f32>()?, [[2., 4.], [1.5, 2.]]);
+    let d = vb.get(2, "d.weight")?;
+    assert_eq!(d.to_vec1::<f32>()?, [0.1, 0.2]);
+    assert!(vb.get((2, 3), "a.weight").is_err());
+
+    // Without a block size, the blocks have to split the weight evenly.
+    let vb = unsafe { Fp8SafeTensors::var_builder(&[&path], None, DType::BF16, dev)? };
+    assert!(vb.get((3, 5), "b.weight").is_err());
+    let c = vb.get((2, 2), "c.weight")?;
+    assert_eq!(c.dtype(), DType::BF16);
+    assert_eq!(
+        c.to_dtype(DType::F32)?.to_vec2::<f32>()?,
+        [[2., 4.], [1.5, 2.]]
+    );
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -9,12 +9,16 @@ pub type Value = Tensor;
 pub fn dtype(dt: DataType) -> Option<DType> {
     match dt {
         DataType::Uint8 => Some(DType::U8),
+        DataType::Uint16 => Some(DType::U16),
         DataType::Uint32 => Some(DType::U32),
+        DataType::Int8 => Some(DType::I8),
+        DataType::Int16 => Some(DType::I16),
+        DataType::Int32 => Some(DType::I32),
         DataType::Int64 => Some(DType::I64),
         DataType::Float16 => Some(DType::F16),
         DataType::Float => Some(DType::F32),
         DataType::Double => Some(DType::F64),
-        DataType::Bool => Some(DType::U8),
+        DataType::Bool => Some(DType::Bool),
         _ => None,
     }
 }
@@ -356,6 +360,18 @@ fn simple_eval_(
                 let output = input0.broadcast_div(input1)?;
                 values.insert(node.output[0].clone(), output);
             }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#Mod
+            "Mod" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                // With fmod=0 the result has the sign of the divisor, otherwise it has the sign
+                // of the dividend.
+                let output = match get_attr_opt::<i64>(node, "fmod")?.copied() {
+                    Some(1) => input0.broadcast_fmod(input1)?,
+                    _ => input0.broadcast_remainder(input1)?,
+                };
+                values.insert(node.output[0].clone(), output);
+            }
             "Pow" => {
                 let input0 = get(&node.input[0])?;
                 let input1 = get(&node.input[1])?;
@@ -385,6 +401,53 @@ fn simple_eval_(
                 let xs = xs.eq(&xs.zeros_like()?)?;
                 values.insert(node.output[0].clone(), xs);
             }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#And
+            "And" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                let output = input0.broadcast_logical_and(input1)?;
+                values.insert(node.output[0].clone(), output);
+            }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#Or
+            "Or" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                let output = input0.broadcast_logical_or(input1)?;
+                values.insert(node.output[0].clone(), output);
+            }
+            "BitwiseAnd" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                let output = input0.broadcast_bitwise_and(input1)?;
+                values.insert(node.output[0].clone(), output);
+            }
+            "BitwiseOr" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                let output = input0.broadcast_bitwise_or(input1)?;
+                values.insert(node.output[0].clone(), output);
+            }
+            "BitwiseXor" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                let output = input0.broadcast_bitwise_xor(input1)?;
+                values.insert(node.output[0].clone(), output);
+            }
+            "BitwiseNot" => {
+                let xs = get(&node.input[0])?;
+                values.insert(node.output[0].clone(), xs.bitwise_not()?);
+            }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#BitShift
+            "BitShift" => {
+                let input0 = get(&node.input[0])?;
+                let input1 = get(&node.input[1])?;
+                let output = match get_attr::<str>(node, "direction")? {
+                    "LEFT" => input0.broadcast_shl(input1)?,
+                    "RIGHT" => input0.broadcast_shr(input1)?,
+                    direction => bail!("unsupported BitShift direction {direction}"),
+                };
+                values.insert(node.output[0].clone(), output);
+            }
             "MatMul" => {
                 let input0 = get(&node.input[0])?;
                 let input1 = get(&node.input[1])?;
@@ -817,6 +880,31 @@ fn simple_eval_(
                 let output = cond.where_cond(&a, &b)?;
                 values.insert(node.output[0].clone(), output);
             }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#Trilu
+            "Trilu" => {
+                let input = get(&node.input[0])?;
+                let upper = get_attr_opt::<i64>(node, "upper")?.copied().unwrap_or(1);
+                let k = match get_opt(1) {
+                    Some(k) => k?
+                        .flatten_all()?
+                        .to_dtype(DType::I64)?
+                        .get(0)?
+                        .to_scalar::<i64>()?,
+                    None => 0,
+                };
+                let output = if upper == 1 {
+                    input.triu(k)?
+                } else {
+                    input.tril(k)?
+                };
+                values.insert(node.output[0].clone(), output);
+            }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#NonZero
+            "NonZero" => {
+                let xs = get(&node.input[0])?;
+                let output = xs.nonzero()?.t()?.to_dtype(DType::I64)?;
+                values.insert(node.output[0].clone(), output);
+            }
             "Conv" => {
                 // https://github.com/onnx/onnx/blob/main/docs/Operators.md#Conv
                 let dilations = get_attr_opt::<[i64]>(node, "dilations")?;
@@ -1075,8 +1163,11 @@ fn simple_eval_(
             }
             // https://github.com/onnx/onnx/blob/main/docs/Operators.md#if
             "If" => {
-                // protobuf encodes boolean false as 0 and true as 1
-                let cond = get(&node.input[0])?.get(0)?.to_scalar::<u8>()?;
+                // The condition is usually a bool tensor, u8 values are also accepted.
+                let cond = get(&node.input[0])?
+                    .get(0)?
+                    .to_dtype(DType::U8)?
+                    .to_scalar::<u8>()?;
                 let attr_name = if cond != 0 {
                     "then_branch"
                 } else {
@@ -1104,54 +1195,58 @@ fn simple_eval_(
                 let mode = get_attr_opt(node, "mode")?.unwrap_or("constant");
                 let data = get(&node.input[0])?;
                 let pads = get(&node.input[1])?;
-                if node.input.len() > 2 {
-                    bail!(
-                        "unsupported number of inputs {} for Pad node {:?}, expected 2",
-                        node.input.len(),
-                        node.name
-                    );
-                }
                 if pads.rank() != 1 {
                     bail!("Pad expects 'pads' input to be 1D vector: {pads:?}");
                 }
-                if pads.dim(0).unwrap() != 2 * data.rank() {
-                    bail!("Pad expects 'pads' input len to be 2 * rank of 'data' input: pads: {}, data rank: {}", pads, data.rank());
+                let rank = data.rank();
+                let axes = match get_opt(3) {
+                    Some(axes) => axes?
+                        .to_vec1::<i64>()?
+                        .into_iter()
+                        .map(|axis| if axis < 0 { axis + rank as i64 } else { axis })
+                        .collect::<Vec<_>>(),
+                    None => (0..rank as i64).collect(),
+                };
+                if pads.dim(0)? != 2 * axes.len() {
+                    bail!("Pad expects 'pads' input len to be 2 * the number of axes: pads: {}, axes: {axes:?}", pads);
                 }
 
-                let pads = pads.to_vec1::<i64>()?;
+                let pads = pads.to_dtype(DType::I64)?.to_vec1::<i64>()?;
                 let (pads_pre, pads_post) = pads.split_at(pads.len() / 2);
-
-                match mode {
-                    "reflect" => {
-                        let mut out = data.clone();
-                        for (i, &dim) in data.dims().iter().enumerate().rev() {
-                            if pads_pre[i] == 0 && pads_post[i] == 0 {
-                                continue;
-                            }
-                            fn zigzag(min: i64, max: i64) -> impl Iterator<Item = i64> {
-                                std::iter::repeat((min..max).chain((min + 1..=max).rev())).flatten()
+                let mut pad = vec![(0, 0); rank];
+                for (i, &axis) in axes.iter().enumerate() {
+                    if axis < 0 || axis >= rank as i64 {
+                        bail!("Pad axis {axis} is out of range for rank {rank}")
+                    }
+                    if pads_pre[i] < 0 || pads_post[i] < 0 {
+                        bail!(
+                            "negative pads are not supported for Pad node {:?}",
+                            node.name
+                        )
+                    }
+                    pad[axis as usize] = (pads_pre[i] as usize, pads_post[i] as usize);
+                }
+                let mode = match mode {
+                    "constant" => {
+                        let value = match get_opt(2) {
+                            Some(value) => {
+                                let value = value?.to_dtype(DType::F64)?.flatten_all()?;
+                                value.to_vec1::<f64>()?.first().copied().unwrap_or(0.)
                             }
-                            let idx = if dim > 1 {
-                                let cycle_len = dim * 2 - 2;
-                                let skip = cycle_len - ((pads_pre[i] as usize) % cycle_len);
-                                let idx = zigzag(0, (dim - 1) as i64)
-                                    .skip(skip)
-                                    .take((pads_pre[i] as usize) + dim + (pads_post[i] as usize));
-                                Tensor::from_iter(idx, out.device())?
-                            } else {
-                                Tensor::full(0i64, (dim,), out.device())?
-                            };
-
-                            out = out.index_select(&idx, i)?;
-                        }
-
-                        values.insert(node.output[0].clone(), out);
+                            None => 0.,
+                        };
+                        candle::pad::PadMode::Constant(value)
                     }
+                    "reflect" => candle::pad::PadMode::Reflect,
+                    "edge" => candle::pad::PadMode::Replicate,
+                    "wrap" => candle::pad::PadMode::Circular,
                     _ => bail!(
                         "unsupported 'mode' value {mode:?} for Pad node {:?}",
                         node.name
                     ),
-                }
+                };
+                let out = data.pad(&pad, mode)?;
+                values.insert(node.output[0].clone(), out);
             }
             // https://github.com/onnx/onnx/blob/main/docs/Operators.md#slice
             "Slice" => {
@@ -1572,6 +1667,39 @@ fn simple_eval_(
 
                 values.insert(node.output[0].clone(), output);
             }
+            // https://github.com/onnx/onnx/blob/main/docs/Operators.md#ReduceProd
+            // Version 18 impl
+            "ReduceProd" => {
+                let input = get(&node.input[0])?;
+                let axes = get_opt(1);
+                let keepdims = get_attr_opt::<i64>(node, "keepdims")?.copied().unwrap_or(1);
+                let noop_with_empty_axes = get_attr_opt::<i64>(node, "noop_with_empty_axes")?
+                    .copied()
+                    .unwrap_or(0);
+
+                let axes = match axes {
+                    Some(Ok(axes)) => axes
+                        .to_vec1::<i64>()?
+                        .into_iter()
+                        .map(|x| input.normalize_axis(x))
+                        .collect::<Result<Vec<_>>>()?,
+                    Some(Err(_)) | None => {
+                        if noop_with_empty_axes == 1 {
+                            vec![]
+                        } else {
+                            (0..input.rank()).collect()
+                        }
+                    }
+                };
+
+                let output = if keepdims == 1 {
+                    input.prod_keepdim(axes)?
+                } else {
+                    input.prod(axes)?
+                };
+
+                values.insert(node.output[0].clone(), output);
+            }
             random_type @ ("RandomUniform" | "RandomNormal") => {
                 let dt: i64 = get_attr_opt(node, "dtype")?.copied().unwrap_or(1); // 1 is float
                                                                                   // type by
@@ -1936,11 +2064,9 @@ fn simple_eval_(
             }
             // https://onnx.ai/onnx/operators/onnx__Xor.html
             "Xor" => {
-                // Since we don't have a `DType::Bool` yet, this ensures that we are working with `0`(False) & `1`(True)
-                let a = get(&node.input[0])?.gt(0_u8)?;
-                let b = get(&node.input[1])?.gt(0_u8)?;
-
-                let out = a.broadcast_add(&b)?.eq(1_u8)?;
+                let a = get(&node.input[0])?.ne(0_u8)?;
+                let b = get(&node.input[1])?.ne(0_u8)?;
+                let out = a.broadcast_ne(&b)?;
 
                 values.insert(node.output[0].clone(), out);
             }
@@ -5837,7 +5837,11 @@ fn test_xor() -> Result<()> {
         let eval = candle_onnx::simple_eval(&manual_graph, inputs)?;
         assert_eq!(eval.len(), 1);
 
-        let z = eval.get(OUTPUT_Z).expect("Output 'z' not found");
+        // The outputs are bool tensors, they are compared as u8 values.
+        let z = eval
+            .get(OUTPUT_Z)
+            .expect("Output 'z' not found")
+            .to_dtype(DType::U8)?;
 
         let expected = Tensor::new(expected, &Device::Cpu)?;
 
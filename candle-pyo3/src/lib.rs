@@ -150,8 +150,13 @@ macro_rules! pydtype {
     };
 }
 
+pydtype!(bool, |v| v);
+pydtype!(i8, |v| v);
+pydtype!(i16, |v| v);
+pydtype!(i32, |v| v);
 pydtype!(i64, |v| v);
 pydtype!(u8, |v| v);
+pydtype!(u16, |v| v);
 pydtype!(u32, |v| v);
 pydtype!(f16, f32::from);
 pydtype!(bf16, f32::from);
@@ -197,13 +202,24 @@ trait MapDType {
 
     fn map(&self, t: &Tensor) -> PyResult<Self::Output> {
         match t.dtype() {
+            DType::Bool => self.f::<bool>(t),
             DType::U8 => self.f::<u8>(t),
+            DType::U16 => self.f::<u16>(t),
             DType::U32 => self.f::<u32>(t),
+            DType::I8 => self.f::<i8>(t),
+            DType::I16 => self.f::<i16>(t),
+            DType::I32 => self.f::<i32>(t),
             DType::I64 => self.f::<i64>(t),
             DType::BF16 => self.f::<bf16>(t),
             DType::F16 => self.f::<f16>(t),
             DType::F32 => self.f::<f32>(t),
             DType::F64 => self.f::<f64>(t),
+            DType::F8E4M3 | DType::F8E5M2 => {
+                self.f::<f32>(&t.to_dtype(DType::F32).map_err(wrap_err)?)
+            }
+            // Complex values are returned as (real, imaginary) pairs.
+            DType::C64 => self.f::<f32>(&t.view_as_real().map_err(wrap_err)?),
+            DType::C128 => self.f::<f64>(&t.view_as_real().map_err(wrap_err)?),
         }
     }
 }
@@ -118,7 +118,16 @@ fn get_extra_padding_for_conv1d(
 fn pad1d(xs: &Tensor, pad_l: usize, pad_r: usize, mode: PadMode) -> Result<Tensor> {
     match mode {
         PadMode::Constant => xs.pad_with_zeros(D::Minus1, pad_l, pad_r),
-        PadMode::Reflect => candle::bail!("pad-mode 'reflect' is not supported"),
+        PadMode::Reflect => {
+            // Same as the PyTorch version: short inputs are extended with zeros so that they can
+            // be reflected, the extra values are removed afterwards.
+            let len = xs.dim(D::Minus1)?;
+            let extra = (pad_l.max(pad_r) + 1).saturating_sub(len);
+            let xs = xs
+                .pad_with_zeros(D::Minus1, 0, extra)?
+                .pad(&[(pad_l, pad_r)], candle::pad::PadMode::Reflect)?;
+            xs.narrow(D::Minus1, 0, pad_l + len + pad_r)
+        }
         PadMode::Replicate => xs.pad_with_same(D::Minus1, pad_l, pad_r),
     }
 }
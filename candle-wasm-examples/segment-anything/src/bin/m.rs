@@ -105,7 +105,11 @@ impl Model {
         )?;
         let iou = iou_predictions.flatten(0, 1)?.to_vec1::<f32>()?[0];
         let mask_shape = mask.dims().to_vec();
-        let mask_data = mask.ge(0f32)?.flatten_all()?.to_vec1::<u8>()?;
+        let mask_data = mask
+            .ge(0f32)?
+            .to_dtype(DType::U8)?
+            .flatten_all()?
+            .to_vec1::<u8>()?;
         let mask = Mask {
             iou,
             mask_shape,